@@ -0,0 +1,57 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use medici_shared::glossary::{annotate, GlossaryIndex};
+use medici_shared::sync::GlossaryTermData;
+
+const QUESTION_COUNT: usize = 10_000;
+const TERM_COUNT: usize = 500;
+
+fn terms() -> Vec<GlossaryTermData> {
+    (0..TERM_COUNT)
+        .map(|index| {
+            GlossaryTermData::new(
+                format!("termino-{index}"),
+                format!("Síndrome número {index}"),
+                "Definición del término.".into(),
+                vec![format!("SN{index}")],
+                vec![],
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+fn question_texts() -> Vec<String> {
+    (0..QUESTION_COUNT)
+        .map(|index| {
+            format!(
+                "Paciente de {} años que consulta por disnea. Se plantea síndrome número {}, \
+                 con SN{} como diagnóstico diferencial. ¿Cuál es la conducta más adecuada?",
+                20 + index % 60,
+                index % TERM_COUNT,
+                (index * 7) % TERM_COUNT
+            )
+        })
+        .collect()
+}
+
+fn bench_annotate(c: &mut Criterion) {
+    let terms = terms();
+    let texts = question_texts();
+
+    c.bench_function("annotate course with index", |b| {
+        b.iter(|| {
+            let index = GlossaryIndex::new(&terms);
+
+            for text in &texts {
+                black_box(index.annotate(black_box(text)));
+            }
+        })
+    });
+
+    c.bench_function("annotate single question", |b| {
+        b.iter(|| annotate(black_box(&texts[0]), black_box(&terms)))
+    });
+}
+
+criterion_group!(benches, bench_annotate);
+criterion_main!(benches);
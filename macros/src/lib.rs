@@ -153,21 +153,7 @@ pub fn derive_table(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let fields = filtered_struct_fields(derive_input.data, Some);
     let primary_key_field = fields
         .iter()
-        .find(|field| {
-            field.attrs.iter().any(|attr| {
-                let Meta::List(meta_list) = &attr.meta else {
-                    return false;
-                };
-
-                meta_list.tokens.clone().into_iter().any(|token| {
-                    let TokenTree::Ident(ident) = token else {
-                        return false;
-                    };
-
-                    ident == "primary_key"
-                })
-            })
-        })
+        .find(|field| field_has_medici_flag(field, "primary_key"))
         .expect("table struct should have a primary key field");
 
     let table_name = opts.table_name;
@@ -218,6 +204,16 @@ pub fn derive_valkey_string(input: proc_macro::TokenStream) -> proc_macro::Token
     expanded.into()
 }
 
+/// How the Hashable derive encodes a field.
+enum FieldHashEncoding {
+    Plain,
+    /// Sorted hashes of the items, for collections whose order doesn't matter.
+    Unordered,
+    /// A tag byte before an `Option`, so `None` isn't just missing bytes and
+    /// adjacent optional fields can't be confused.
+    ExplicitNone,
+}
+
 #[proc_macro_derive(Hashable, attributes(medici))]
 pub fn derive_hashable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
@@ -228,30 +224,63 @@ pub fn derive_hashable(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
 
     let name = derive_input.ident;
     let fields = filtered_struct_fields(derive_input.data, |field| {
-        let Some(ident) = field.ident.clone() else {
+        let ident = field.ident.clone()?;
+
+        if ident == hash_field_ident || field_has_medici_flag(&field, "skip_hash") {
             return None;
-        };
+        }
 
-        if ident == hash_field_ident
-            || field.attrs.iter().any(|attr| {
-                let Meta::List(meta_list) = &attr.meta else {
-                    return false;
-                };
+        let encoding = if field_has_medici_flag(&field, "unordered_hash") {
+            FieldHashEncoding::Unordered
+        } else if field_has_medici_flag(&field, "explicit_none") {
+            FieldHashEncoding::ExplicitNone
+        } else {
+            FieldHashEncoding::Plain
+        };
 
-                meta_list.tokens.clone().into_iter().any(|token| {
-                    let TokenTree::Ident(ident) = token else {
-                        return false;
-                    };
+        Some((ident, encoding))
+    });
 
-                    ident == "skip_hash"
-                })
-            })
-        {
-            return None;
+    let field_bytes = fields.iter().map(|(ident, encoding)| match encoding {
+        FieldHashEncoding::Unordered => {
+            quote! {
+                let mut hashes = ::std::iter::Iterator::collect::<::std::vec::Vec<_>>(
+                    ::std::iter::Iterator::map(
+                        ::std::iter::IntoIterator::into_iter(&self.#ident),
+                        Hashable::compute_hash,
+                    )
+                );
+                hashes.sort_unstable();
+
+                for hash in hashes {
+                    ::std::iter::Extend::extend(
+                        &mut bytes,
+                        ::std::string::String::into_bytes(hash)
+                    );
+                }
+            }
+        }
+        FieldHashEncoding::ExplicitNone => {
+            quote! {
+                match &self.#ident {
+                    ::core::option::Option::Some(value) => {
+                        bytes.push(1);
+                        ::std::iter::Extend::extend(&mut bytes, Hashable::to_bytes(value));
+                    }
+                    ::core::option::Option::None => bytes.push(0),
+                }
+            }
+        }
+        FieldHashEncoding::Plain => {
+            quote! {
+                ::std::iter::Extend::extend(
+                    &mut bytes,
+                    Hashable::to_bytes(&self.#ident)
+                );
+            }
         }
-
-        Some(ident)
     });
+    let field_idents = fields.iter().map(|(ident, _)| ident);
 
     quote! {
         #[automatically_derived]
@@ -262,12 +291,9 @@ pub fn derive_hashable(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
                 #(
                     ::std::iter::Extend::extend(
                         &mut bytes,
-                        ::core::primitive::str::as_bytes(stringify!(#fields))
-                    );
-                    ::std::iter::Extend::extend(
-                        &mut bytes,
-                        Hashable::to_bytes(&self.#fields)
+                        ::core::primitive::str::as_bytes(stringify!(#field_idents))
                     );
+                    #field_bytes
                 )*
 
                 bytes
@@ -288,3 +314,194 @@ pub fn derive_hashable(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
     }
     .into()
 }
+
+#[derive(FromDeriveInput, Debug)]
+#[darling(attributes(medici))]
+struct SyncEntityOpts {
+    pub key: Option<String>,
+    pub key_fn: Option<String>,
+    pub key_type: Option<String>,
+    pub entity: Option<String>,
+}
+
+#[proc_macro_derive(SyncEntity, attributes(medici))]
+pub fn derive_sync_entity(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+
+    let opts = match SyncEntityOpts::from_derive_input(&derive_input) {
+        Ok(opts) => opts,
+        Err(error) => return error.write_errors().into(),
+    };
+
+    let name = derive_input.ident;
+    let fields = filtered_struct_fields(derive_input.data, Some);
+
+    let (key_type, sync_key) = match (opts.key, opts.key_fn) {
+        (Some(key), None) => {
+            let key_field = fields
+                .iter()
+                .find(|field| field.ident.as_ref().is_some_and(|ident| ident == &key))
+                .expect("key should be a field of the sync entity struct");
+            let key_ident = key_field.ident.clone().unwrap();
+            let key_type = key_field.ty.clone();
+
+            (
+                key_type,
+                quote! { ::std::clone::Clone::clone(&self.#key_ident) },
+            )
+        }
+        (None, Some(key_fn)) => {
+            let key_fn_ident = syn::parse_str::<Ident>(&key_fn).unwrap();
+            let key_type = parse_table_struct(
+                opts.key_type
+                    .expect("key_type is required when using key_fn"),
+            );
+
+            (key_type, quote! { self.#key_fn_ident() })
+        }
+        _ => panic!("sync entity should have exactly one of key or key_fn"),
+    };
+
+    // Entities without a stored hash are identified by their content alone.
+    let content_hash = if fields
+        .iter()
+        .any(|field| field.ident.as_ref().is_some_and(|ident| ident == "hash"))
+    {
+        quote! {}
+    } else {
+        quote! {
+            fn content_hash(&self) -> ::std::borrow::Cow<'_, ::std::primitive::str> {
+                ::std::borrow::Cow::Owned(Hashable::compute_hash(self))
+            }
+        }
+    };
+
+    let entity_variant = syn::parse_str::<Ident>(&opts.entity.unwrap_or_else(|| {
+        let name = name.to_string();
+
+        name.strip_suffix("Data").unwrap_or(&name).to_string()
+    }))
+    .unwrap();
+
+    quote! {
+        #[automatically_derived]
+        impl Syncable for #name {
+            type Key = #key_type;
+
+            fn sync_key(&self) -> Self::Key {
+                #sync_key
+            }
+
+            #content_hash
+        }
+
+        #[automatically_derived]
+        impl ::std::convert::From<#name> for ContentEntity {
+            fn from(data: #name) -> Self {
+                ContentEntity::#entity_variant(data)
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro_derive(Builder, attributes(medici))]
+pub fn derive_builder(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+
+    let name = derive_input.ident;
+    let vis = derive_input.vis;
+    let builder_name = quote::format_ident!("{}Builder", name);
+    let fields = filtered_struct_fields(derive_input.data, Some);
+
+    let field_idents = fields
+        .iter()
+        .map(|field| field.ident.clone().unwrap())
+        .collect::<Vec<Ident>>();
+    let field_types = fields
+        .iter()
+        .map(|field| field.ty.clone())
+        .collect::<Vec<Type>>();
+    let field_values = fields.iter().map(|field| {
+        let ident = field.ident.clone().unwrap();
+
+        if ident == "hash"
+            || field_has_medici_flag(field, "builder_default")
+            || type_has_default(&field.ty)
+        {
+            quote! { ::std::option::Option::unwrap_or_default(self.#ident) }
+        } else {
+            let message = format!("missing {} in {}", ident.unraw(), name);
+
+            quote! {
+                match self.#ident {
+                    ::std::option::Option::Some(value) => value,
+                    ::std::option::Option::None => ::anyhow::bail!(#message),
+                }
+            }
+        }
+    });
+
+    quote! {
+        #[derive(::std::default::Default, ::std::clone::Clone, ::std::fmt::Debug)]
+        #vis struct #builder_name {
+            #(#field_idents: ::std::option::Option<#field_types>,)*
+        }
+
+        #[automatically_derived]
+        impl #builder_name {
+            #(
+                pub fn #field_idents(mut self, value: impl ::std::convert::Into<#field_types>) -> Self {
+                    self.#field_idents = ::std::option::Option::Some(value.into());
+                    self
+                }
+            )*
+
+            pub fn build(self) -> ::anyhow::Result<#name> {
+                let mut data = #name {
+                    #(#field_idents: #field_values,)*
+                };
+
+                data.process()?;
+
+                ::anyhow::Result::Ok(data)
+            }
+        }
+
+        #[automatically_derived]
+        impl #name {
+            pub fn builder() -> #builder_name {
+                ::std::default::Default::default()
+            }
+        }
+    }
+    .into()
+}
+
+fn type_has_default(ty: &Type) -> bool {
+    let Type::Path(type_path) = ty else {
+        return false;
+    };
+
+    type_path.path.segments.last().is_some_and(|segment| {
+        ["Option", "Vec", "BTreeMap", "HashMap", "HashSet"]
+            .iter()
+            .any(|name| segment.ident == name)
+    })
+}
+
+fn field_has_medici_flag(field: &Field, flag: &str) -> bool {
+    field.attrs.iter().any(|attr| {
+        let Meta::List(meta_list) = &attr.meta else {
+            return false;
+        };
+
+        meta_list.tokens.clone().into_iter().any(|token| {
+            let TokenTree::Ident(ident) = token else {
+                return false;
+            };
+
+            ident == flag
+        })
+    })
+}
@@ -0,0 +1,254 @@
+//! Question selection for adaptive quizzes. The choice only depends on the quiz
+//! state and the pool, so the quiz engine and the offline simulator agree on it.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::psychometrics::{estimate_ability, AbilityEstimate, ItemParameters};
+
+/// Precision at which the information of questions is compared, so last-bit
+/// differences between platforms' math libraries don't change the choice.
+const INFORMATION_SCALE: f64 = 1e9;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct PoolQuestion {
+    pub question_id: Uuid,
+    pub topic_key: String,
+    pub parameters: ItemParameters,
+    /// Share of past quizzes that showed the question, from 0 to 1.
+    pub exposure_rate: f64,
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct TopicQuota {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct AdaptiveConfig {
+    pub question_count: usize,
+    /// Topics without a quota have no minimum or maximum.
+    pub topic_quotas: BTreeMap<String, TopicQuota>,
+    /// Questions shown more often than this are only picked when no others are left.
+    pub max_exposure_rate: f64,
+    /// The choice is random among this many of the most informative questions,
+    /// so the same answers don't always lead to the same questions.
+    pub randomesque_size: usize,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        Self {
+            question_count: 20,
+            topic_quotas: BTreeMap::new(),
+            max_exposure_rate: 0.3,
+            randomesque_size: 5,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct AdaptiveResponse {
+    pub question_id: Uuid,
+    pub topic_key: String,
+    pub parameters: ItemParameters,
+    pub correct: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct AdaptiveState {
+    pub seed: u64,
+    pub config: AdaptiveConfig,
+    pub responses: Vec<AdaptiveResponse>,
+}
+
+impl AdaptiveState {
+    pub fn new(seed: u64, config: AdaptiveConfig) -> Self {
+        Self {
+            seed,
+            config,
+            responses: vec![],
+        }
+    }
+
+    pub fn record(&mut self, question: &PoolQuestion, correct: bool) {
+        self.responses.push(AdaptiveResponse {
+            question_id: question.question_id,
+            topic_key: question.topic_key.clone(),
+            parameters: question.parameters,
+            correct,
+        });
+    }
+
+    pub fn ability(&self) -> AbilityEstimate {
+        estimate_ability(
+            &self
+                .responses
+                .iter()
+                .map(|response| (response.parameters, response.correct))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.responses.len() >= self.config.question_count
+    }
+
+    fn topic_count(&self, topic_key: &str) -> usize {
+        self.responses
+            .iter()
+            .filter(|response| response.topic_key == topic_key)
+            .count()
+    }
+}
+
+/// The most informative question at the current ability estimate among those
+/// allowed by the topic quotas and exposure control, picked at random among the
+/// best `randomesque_size` with the state's seed. `None` once the quiz is
+/// finished or no question is left.
+pub fn next_question<'a>(
+    state: &AdaptiveState,
+    pool: &'a [PoolQuestion],
+) -> Option<&'a PoolQuestion> {
+    if state.is_finished() {
+        return None;
+    }
+
+    let answered = state
+        .responses
+        .iter()
+        .map(|response| response.question_id)
+        .collect::<HashSet<_>>();
+    let mut candidates = pool
+        .iter()
+        .filter(|question| {
+            !answered.contains(&question.question_id)
+                && state
+                    .config
+                    .topic_quotas
+                    .get(&question.topic_key)
+                    .and_then(|quota| quota.max)
+                    .is_none_or(|max| state.topic_count(&question.topic_key) < max)
+        })
+        .collect::<Vec<_>>();
+
+    // Once the remaining questions are just enough to meet the minimums, only
+    // topics below their minimum are eligible.
+    let below_minimum = state
+        .config
+        .topic_quotas
+        .iter()
+        .filter_map(|(topic_key, quota)| {
+            let missing = quota.min.saturating_sub(state.topic_count(topic_key));
+
+            (missing > 0).then_some((topic_key.as_str(), missing))
+        })
+        .collect::<BTreeMap<_, _>>();
+    let remaining = state.config.question_count - state.responses.len();
+
+    if !below_minimum.is_empty() && below_minimum.values().sum::<usize>() >= remaining {
+        retain_if_any(&mut candidates, |question| {
+            below_minimum.contains_key(question.topic_key.as_str())
+        });
+    }
+
+    retain_if_any(&mut candidates, |question| {
+        question.exposure_rate <= state.config.max_exposure_rate
+    });
+
+    let ability = state.ability().ability;
+
+    candidates.sort_by_cached_key(|question| {
+        (
+            std::cmp::Reverse(
+                (question.parameters.information(ability) * INFORMATION_SCALE).round() as i64,
+            ),
+            question.question_id,
+        )
+    });
+    candidates.truncate(state.config.randomesque_size.max(1));
+
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let hash = blake3::hash(
+        &[
+            state.seed.to_le_bytes(),
+            (state.responses.len() as u64).to_le_bytes(),
+        ]
+        .concat(),
+    );
+    let choice = u64::from_le_bytes(hash.as_bytes()[..8].try_into().unwrap_or_default())
+        % candidates.len() as u64;
+
+    Some(candidates[choice as usize])
+}
+
+fn retain_if_any<T>(candidates: &mut Vec<T>, predicate: impl Fn(&T) -> bool) {
+    if candidates.iter().any(&predicate) {
+        candidates.retain(predicate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_question() {
+        let pool = (0..40)
+            .map(|index| PoolQuestion {
+                question_id: Uuid::new_v4(),
+                topic_key: if index % 4 == 0 { "rare" } else { "common" }.into(),
+                parameters: ItemParameters {
+                    discrimination: 1.0 + (index % 3) as f64 * 0.5,
+                    difficulty: -2.0 + index as f64 * 0.1,
+                },
+                exposure_rate: if index == 20 { 0.9 } else { 0.1 },
+            })
+            .collect::<Vec<_>>();
+        let config = AdaptiveConfig {
+            question_count: 10,
+            topic_quotas: [
+                ("rare".into(), TopicQuota { min: 4, max: None }),
+                (
+                    "common".into(),
+                    TopicQuota {
+                        min: 0,
+                        max: Some(6),
+                    },
+                ),
+            ]
+            .into(),
+            ..Default::default()
+        };
+
+        let run = |seed| {
+            let mut state = AdaptiveState::new(seed, config.clone());
+
+            while let Some(question) = next_question(&state, &pool) {
+                // Answers correctly up to difficulty 0.5.
+                state.record(question, question.parameters.difficulty <= 0.5);
+            }
+
+            state
+        };
+
+        let state = run(1);
+
+        assert_eq!(state.responses.len(), 10);
+        assert!(state.topic_count("rare") >= 4);
+        assert!(state.topic_count("common") <= 6);
+        assert!(!state
+            .responses
+            .iter()
+            .any(|response| response.question_id == pool[20].question_id));
+        assert!((state.ability().ability - 0.5).abs() < 1.0);
+        assert_eq!(run(1), state);
+        assert_ne!(run(2).responses, state.responses);
+    }
+}
@@ -0,0 +1,447 @@
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use super::AnalyticsRow;
+
+/// Destination of flushed analytics rows.
+pub trait AnalyticsSink: Send + Sync + 'static {
+    fn write(&self, rows: &[AnalyticsRow]) -> impl Future<Output = Result<()>> + Send;
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct BatcherConfig {
+    /// Rows written to the sink at once. Reaching it triggers a flush.
+    pub max_batch_size: usize,
+    /// Buffered rows are flushed at least this often.
+    pub flush_interval: Duration,
+    /// Rows accepted but not yet buffered. Pushes wait, or fail with
+    /// `BatcherError::Full`, when it's reached.
+    pub channel_capacity: usize,
+    /// Rows kept for retrying after failed writes; the oldest are dropped beyond it.
+    pub max_pending: usize,
+}
+
+impl Default for BatcherConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_size: 500,
+            flush_interval: Duration::from_secs(10),
+            channel_capacity: 10_000,
+            max_pending: 50_000,
+        }
+    }
+}
+
+impl BatcherConfig {
+    pub fn check(&self) -> Result<()> {
+        if self.max_batch_size == 0 || self.channel_capacity == 0 {
+            bail!("analytics batcher sizes must be positive");
+        }
+
+        if self.flush_interval.is_zero() {
+            bail!("analytics batcher flush interval must be positive");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BatcherError {
+    /// The batcher can't take more rows for now; clients should retry later.
+    Full,
+    Closed,
+}
+
+impl std::fmt::Display for BatcherError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full => write!(f, "analytics batcher is full"),
+            Self::Closed => write!(f, "analytics batcher is closed"),
+        }
+    }
+}
+
+impl std::error::Error for BatcherError {}
+
+#[derive(Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct BatcherStats {
+    pub written: u64,
+    pub dropped: u64,
+    pub failed_writes: u64,
+}
+
+/// Buffers analytics rows and writes them to a sink in batches from a
+/// background task. Clones share the same task.
+#[derive(Clone, Debug)]
+pub struct Batcher {
+    sender: mpsc::Sender<AnalyticsRow>,
+}
+
+impl Batcher {
+    /// Once every clone of the returned batcher is dropped, the task flushes
+    /// what's left and returns its stats.
+    pub fn spawn<S: AnalyticsSink>(
+        sink: S,
+        config: BatcherConfig,
+    ) -> Result<(Self, JoinHandle<BatcherStats>)> {
+        config.check()?;
+
+        let (sender, receiver) = mpsc::channel(config.channel_capacity);
+        let task = tokio::spawn(run(sink, config, receiver));
+
+        Ok((Self { sender }, task))
+    }
+
+    /// Waits for room in the batcher.
+    pub async fn push(&self, row: AnalyticsRow) -> Result<(), BatcherError> {
+        self.sender
+            .send(row)
+            .await
+            .map_err(|_| BatcherError::Closed)
+    }
+
+    /// Takes all of `rows` or none of them, without waiting.
+    pub fn try_push(&self, rows: Vec<AnalyticsRow>) -> Result<(), BatcherError> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let permits = self
+            .sender
+            .try_reserve_many(rows.len())
+            .map_err(|error| match error {
+                mpsc::error::TrySendError::Full(_) => BatcherError::Full,
+                mpsc::error::TrySendError::Closed(_) => BatcherError::Closed,
+            })?;
+
+        for (permit, row) in permits.zip(rows) {
+            permit.send(row);
+        }
+
+        Ok(())
+    }
+
+    /// Rows that can be pushed without waiting.
+    pub fn available_capacity(&self) -> usize {
+        self.sender.capacity()
+    }
+}
+
+async fn run<S: AnalyticsSink>(
+    sink: S,
+    config: BatcherConfig,
+    mut receiver: mpsc::Receiver<AnalyticsRow>,
+) -> BatcherStats {
+    let mut stats = BatcherStats::default();
+    let mut buffer = Vec::with_capacity(config.max_batch_size);
+    let mut interval = tokio::time::interval(config.flush_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // After a failed write, full batches wait for the next tick instead of
+    // hitting the sink again with every row.
+    let mut is_failing = false;
+
+    loop {
+        tokio::select! {
+            row = receiver.recv() => match row {
+                Some(row) => {
+                    buffer.push(row);
+                    drop_overflow(&config, &mut buffer, &mut stats);
+
+                    if buffer.len() >= config.max_batch_size && !is_failing {
+                        is_failing = !flush(&sink, &config, &mut buffer, &mut stats).await;
+                    }
+                }
+                None => break,
+            },
+            _ = interval.tick() => {
+                if !buffer.is_empty() {
+                    is_failing = !flush(&sink, &config, &mut buffer, &mut stats).await;
+                }
+            }
+        }
+    }
+
+    if !buffer.is_empty() {
+        flush(&sink, &config, &mut buffer, &mut stats).await;
+    }
+
+    stats
+}
+
+/// Writes `buffer` in batches. What fails to be written stays for the next
+/// flush. Returns whether every batch was written.
+async fn flush<S: AnalyticsSink>(
+    sink: &S,
+    config: &BatcherConfig,
+    buffer: &mut Vec<AnalyticsRow>,
+    stats: &mut BatcherStats,
+) -> bool {
+    let mut written = 0;
+    let mut is_written = true;
+
+    for batch in buffer.chunks(config.max_batch_size) {
+        if let Err(error) = sink.write(batch).await {
+            warn!("failed to write {} analytics rows: {error:#}", batch.len());
+            stats.failed_writes += 1;
+            is_written = false;
+            break;
+        }
+
+        written += batch.len();
+    }
+
+    buffer.drain(..written);
+    stats.written += written as u64;
+    drop_overflow(config, buffer, stats);
+
+    is_written
+}
+
+/// Drops the oldest rows of `buffer` beyond `max_pending`, so rows keep being
+/// received while the sink is down without growing it unbounded.
+fn drop_overflow(config: &BatcherConfig, buffer: &mut Vec<AnalyticsRow>, stats: &mut BatcherStats) {
+    if buffer.len() > config.max_pending {
+        let dropped = buffer.len() - config.max_pending;
+
+        warn!("dropping {dropped} pending analytics rows");
+        buffer.drain(..dropped);
+        stats.dropped += dropped as u64;
+    }
+}
+
+/// Copies rows into a table with `session_id`, `occurred_at`, `event_type` and
+/// `event` (JSONB) columns.
+#[cfg(feature = "db")]
+pub struct PostgresCopySink {
+    pub pool: sqlx::PgPool,
+    pub table_name: String,
+}
+
+#[cfg(feature = "db")]
+impl AnalyticsSink for PostgresCopySink {
+    async fn write(&self, rows: &[AnalyticsRow]) -> Result<()> {
+        use sqlx::postgres::PgPoolCopyExt;
+
+        let mut data = String::new();
+
+        for row in rows {
+            let event = serde_json::to_string(&row.event)?.replace('"', "\"\"");
+
+            data.push_str(&format!(
+                "{},{},{},\"{event}\"\n",
+                row.session_id,
+                row.occurred_at.to_rfc3339(),
+                row.event.name()
+            ));
+        }
+
+        let mut copy = self
+            .pool
+            .copy_in_raw(&format!(
+                "COPY {} (session_id, occurred_at, event_type, event) FROM STDIN WITH (FORMAT csv)",
+                self.table_name
+            ))
+            .await?;
+        copy.send(data.into_bytes()).await?;
+        copy.finish().await?;
+
+        Ok(())
+    }
+}
+
+/// Writes every batch as an NDJSON object under `prefix`, partitioned by date.
+#[cfg(feature = "analytics_s3")]
+pub struct S3NdjsonSink {
+    pub client: aws_sdk_s3::Client,
+    pub bucket: String,
+    pub prefix: String,
+}
+
+#[cfg(feature = "analytics_s3")]
+impl AnalyticsSink for S3NdjsonSink {
+    async fn write(&self, rows: &[AnalyticsRow]) -> Result<()> {
+        let mut body = Vec::new();
+
+        for row in rows {
+            serde_json::to_writer(&mut body, row)?;
+            body.push(b'\n');
+        }
+
+        let now = chrono::Utc::now();
+        let key = format!(
+            "{}/{}/{}.ndjson",
+            self.prefix.trim_end_matches('/'),
+            now.format("%Y/%m/%d"),
+            uuid::Uuid::new_v4()
+        );
+
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type("application/x-ndjson")
+            .body(body.into())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::analytics::AnalyticsEvent;
+
+    #[derive(Clone, Default)]
+    struct MemorySink {
+        batches: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl AnalyticsSink for MemorySink {
+        async fn write(&self, rows: &[AnalyticsRow]) -> Result<()> {
+            self.batches.lock().unwrap().push(rows.len());
+
+            Ok(())
+        }
+    }
+
+    fn row() -> AnalyticsRow {
+        AnalyticsRow {
+            session_id: Uuid::new_v4(),
+            occurred_at: Utc::now(),
+            event: AnalyticsEvent::ExplanationViewed {
+                question_id: Uuid::new_v4(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batcher() {
+        let sink = MemorySink::default();
+        let (batcher, task) = Batcher::spawn(
+            sink.clone(),
+            BatcherConfig {
+                max_batch_size: 3,
+                flush_interval: Duration::from_secs(3600),
+                channel_capacity: 4,
+                max_pending: 10,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            batcher.try_push((0..5).map(|_| row()).collect()),
+            Err(BatcherError::Full)
+        );
+
+        batcher.try_push((0..4).map(|_| row()).collect()).unwrap();
+
+        for _ in 0..3 {
+            batcher.push(row()).await.unwrap();
+        }
+
+        drop(batcher);
+        let stats = task.await.unwrap();
+
+        assert_eq!(stats.written, 7);
+        assert_eq!(*sink.batches.lock().unwrap(), vec![3, 3, 1]);
+    }
+
+    struct FailingSink {
+        writes: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl AnalyticsSink for FailingSink {
+        async fn write(&self, rows: &[AnalyticsRow]) -> Result<()> {
+            self.writes.lock().unwrap().push(rows.len());
+
+            anyhow::bail!("sink is down")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batcher_waits_after_failure() {
+        let writes = Arc::new(Mutex::new(vec![]));
+        let (batcher, task) = Batcher::spawn(
+            FailingSink {
+                writes: writes.clone(),
+            },
+            BatcherConfig {
+                max_batch_size: 2,
+                flush_interval: Duration::from_secs(3600),
+                channel_capacity: 10,
+                max_pending: 100,
+            },
+        )
+        .unwrap();
+
+        for _ in 0..7 {
+            batcher.push(row()).await.unwrap();
+        }
+
+        drop(batcher);
+        let stats = task.await.unwrap();
+
+        // The first full batch and the final flush.
+        assert_eq!(writes.lock().unwrap().len(), 2);
+        assert_eq!(stats.failed_writes, 2);
+        assert_eq!(stats.written, 0);
+    }
+
+    #[tokio::test]
+    async fn test_batcher_caps_pending_rows() {
+        let writes = Arc::new(Mutex::new(vec![]));
+        let (batcher, task) = Batcher::spawn(
+            FailingSink {
+                writes: writes.clone(),
+            },
+            BatcherConfig {
+                max_batch_size: 5,
+                flush_interval: Duration::from_secs(3600),
+                channel_capacity: 10,
+                max_pending: 3,
+            },
+        )
+        .unwrap();
+
+        for _ in 0..7 {
+            batcher.push(row()).await.unwrap();
+        }
+
+        drop(batcher);
+        let stats = task.await.unwrap();
+
+        // The buffer never reaches a full batch, so only the final flush writes.
+        assert_eq!(*writes.lock().unwrap(), vec![3]);
+        assert_eq!(stats.dropped, 4);
+    }
+
+    #[test]
+    fn test_config_check() {
+        assert!(BatcherConfig::default().check().is_ok());
+        assert!(BatcherConfig {
+            channel_capacity: 0,
+            ..Default::default()
+        }
+        .check()
+        .is_err());
+        assert!(BatcherConfig {
+            flush_interval: Duration::ZERO,
+            ..Default::default()
+        }
+        .check()
+        .is_err());
+    }
+}
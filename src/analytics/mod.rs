@@ -0,0 +1,281 @@
+//! Client analytics events, shared by the app and the ingestion service so
+//! both agree on one schema. Events carry no free text and no account
+//! identifiers, only content keys and a random session ID, so they can't hold
+//! personal data. Payloads with unknown fields are rejected.
+
+#[cfg(feature = "runtime")]
+mod batcher;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "runtime")]
+pub use batcher::*;
+
+pub const ANALYTICS_SCHEMA_VERSION: u16 = 1;
+pub const MAX_BATCH_EVENTS: usize = 100;
+pub const MAX_COURSE_KEY_LENGTH: usize = 64;
+/// How far ahead of the server clock a client clock may be.
+pub const MAX_CLOCK_SKEW: TimeDelta = TimeDelta::minutes(5);
+/// Older events are dropped by clients, so they're rejected here too.
+pub const MAX_EVENT_AGE: TimeDelta = TimeDelta::days(7);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum AnalyticsEvent {
+    QuizStarted {
+        course_key: String,
+        question_count: u16,
+    },
+    QuestionAnswered {
+        question_id: Uuid,
+        correct: bool,
+        duration_ms: u32,
+    },
+    ExplanationViewed {
+        question_id: Uuid,
+    },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[serde(deny_unknown_fields)]
+pub struct AnalyticsRecord {
+    pub occurred_at: DateTime<Utc>,
+    pub event: AnalyticsEvent,
+}
+
+/// Events sent together by a client.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[serde(deny_unknown_fields)]
+pub struct AnalyticsBatch {
+    pub schema_version: u16,
+    /// Random for every app launch and never linked to an account.
+    pub session_id: Uuid,
+    pub events: Vec<AnalyticsRecord>,
+}
+
+/// Event as stored, along with the session it was sent in.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct AnalyticsRow {
+    pub session_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    pub event: AnalyticsEvent,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AnalyticsError {
+    InvalidPayload(String),
+    UnsupportedSchemaVersion(u16),
+    EventCount(usize),
+    InvalidEvent { index: usize, reason: &'static str },
+}
+
+impl std::fmt::Display for AnalyticsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPayload(error) => write!(f, "invalid analytics payload: {error}"),
+            Self::UnsupportedSchemaVersion(version) => {
+                write!(f, "unsupported analytics schema version {version}")
+            }
+            Self::EventCount(count) => write!(
+                f,
+                "analytics batch has {count} events, expected 1 to {MAX_BATCH_EVENTS}"
+            ),
+            Self::InvalidEvent { index, reason } => {
+                write!(f, "invalid analytics event {index}: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AnalyticsError {}
+
+impl AnalyticsEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::QuizStarted { .. } => "quiz_started",
+            Self::QuestionAnswered { .. } => "question_answered",
+            Self::ExplanationViewed { .. } => "explanation_viewed",
+        }
+    }
+}
+
+impl AnalyticsBatch {
+    pub fn new(session_id: Uuid, events: Vec<AnalyticsRecord>) -> Self {
+        Self {
+            schema_version: ANALYTICS_SCHEMA_VERSION,
+            session_id,
+            events,
+        }
+    }
+
+    /// Parses and validates a request body, for rejecting invalid batches at the edge.
+    pub fn parse(body: &[u8], now: DateTime<Utc>) -> Result<Self, AnalyticsError> {
+        let batch: Self = serde_json::from_slice(body)
+            .map_err(|error| AnalyticsError::InvalidPayload(error.to_string()))?;
+
+        batch.validate(now)?;
+
+        Ok(batch)
+    }
+
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<(), AnalyticsError> {
+        if self.schema_version != ANALYTICS_SCHEMA_VERSION {
+            return Err(AnalyticsError::UnsupportedSchemaVersion(
+                self.schema_version,
+            ));
+        }
+
+        if self.events.is_empty() || self.events.len() > MAX_BATCH_EVENTS {
+            return Err(AnalyticsError::EventCount(self.events.len()));
+        }
+
+        for (index, record) in self.events.iter().enumerate() {
+            record
+                .validate(now)
+                .map_err(|reason| AnalyticsError::InvalidEvent { index, reason })?;
+        }
+
+        Ok(())
+    }
+
+    pub fn into_rows(self) -> Vec<AnalyticsRow> {
+        self.events
+            .into_iter()
+            .map(|record| AnalyticsRow {
+                session_id: self.session_id,
+                occurred_at: record.occurred_at,
+                event: record.event,
+            })
+            .collect()
+    }
+}
+
+impl AnalyticsRecord {
+    fn validate(&self, now: DateTime<Utc>) -> Result<(), &'static str> {
+        if self.occurred_at > now + MAX_CLOCK_SKEW {
+            return Err("occurred in the future");
+        }
+
+        if self.occurred_at < now - MAX_EVENT_AGE {
+            return Err("too old");
+        }
+
+        match &self.event {
+            AnalyticsEvent::QuizStarted { course_key, .. } if !is_course_key(course_key) => {
+                Err("invalid course key")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Course keys are short and plain, which keeps arbitrary text out of events.
+fn is_course_key(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_COURSE_KEY_LENGTH
+        && value
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let now = Utc::now();
+        let body = |events: serde_json::Value| {
+            json!({
+                "schema_version": ANALYTICS_SCHEMA_VERSION,
+                "session_id": Uuid::new_v4(),
+                "events": events
+            })
+            .to_string()
+        };
+
+        let batch = AnalyticsBatch::parse(
+            body(json!([
+                {
+                    "occurred_at": now,
+                    "event": { "type": "quiz_started", "course_key": "ANATOMIA", "question_count": 20 }
+                },
+                {
+                    "occurred_at": now,
+                    "event": { "type": "explanation_viewed", "question_id": Uuid::new_v4() }
+                }
+            ]))
+            .as_bytes(),
+            now,
+        )
+        .unwrap();
+
+        assert_eq!(batch.events.len(), 2);
+
+        let result = AnalyticsBatch::parse(
+            body(json!([{
+                "occurred_at": now,
+                "event": { "type": "explanation_viewed", "question_id": Uuid::new_v4(), "email": "ana@example.com" }
+            }]))
+            .as_bytes(),
+            now,
+        );
+
+        assert!(matches!(result, Err(AnalyticsError::InvalidPayload(_))));
+
+        let result = AnalyticsBatch::parse(
+            body(json!([{
+                "occurred_at": now,
+                "event": { "type": "quiz_started", "course_key": "ana@example.com", "question_count": 20 }
+            }]))
+            .as_bytes(),
+            now,
+        );
+
+        assert_eq!(
+            result,
+            Err(AnalyticsError::InvalidEvent {
+                index: 0,
+                reason: "invalid course key"
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let now = Utc::now();
+        let record = |occurred_at| AnalyticsRecord {
+            occurred_at,
+            event: AnalyticsEvent::QuestionAnswered {
+                question_id: Uuid::new_v4(),
+                correct: true,
+                duration_ms: 12_000,
+            },
+        };
+
+        let mut batch = AnalyticsBatch::new(Uuid::new_v4(), vec![record(now)]);
+
+        assert_eq!(batch.validate(now), Ok(()));
+
+        batch.events.push(record(now - TimeDelta::days(8)));
+
+        assert!(matches!(
+            batch.validate(now),
+            Err(AnalyticsError::InvalidEvent { index: 1, .. })
+        ));
+
+        batch.events = vec![];
+
+        assert_eq!(batch.validate(now), Err(AnalyticsError::EventCount(0)));
+    }
+}
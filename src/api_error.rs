@@ -0,0 +1,277 @@
+//! Error envelope shared by the engine, the admin API and the payments
+//! service, so clients get the same JSON whichever service failed.
+
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::analytics::AnalyticsError;
+use crate::precondition::ConflictError;
+use crate::review::ReviewError;
+use crate::sync::{GuardrailError, SourceKeyError, SyncCompatibilityError, ValidationError};
+use crate::webhooks::WebhookError;
+
+#[derive(
+    strum::Display,
+    strum::EnumString,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    Debug,
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ApiErrorCode {
+    BadRequest,
+    Validation,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    PreconditionFailed,
+    RateLimited,
+    Internal,
+    Unavailable,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct FieldError {
+    /// Path of the field in the request body, e.g. `question_options.1.text`.
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct ApiError {
+    pub code: ApiErrorCode,
+    /// For humans; clients should branch on `code`.
+    pub message: String,
+    #[serde(default)]
+    pub field_errors: Vec<FieldError>,
+    /// Seconds to wait before retrying.
+    #[serde(default)]
+    pub retry_after: Option<u64>,
+}
+
+impl ApiErrorCode {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::BadRequest => StatusCode::BAD_REQUEST,
+            Self::Validation => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::Conflict => StatusCode::CONFLICT,
+            Self::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+            Self::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+impl ApiError {
+    pub const INTERNAL_MESSAGE: &'static str = "internal error";
+
+    pub fn new(code: ApiErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            field_errors: vec![],
+            retry_after: None,
+        }
+    }
+
+    /// For the checks of the data model, which fail with plain `anyhow` errors.
+    pub fn validation(error: impl std::fmt::Display) -> Self {
+        Self::new(ApiErrorCode::Validation, error.to_string())
+    }
+
+    pub fn with_field_error(
+        mut self,
+        field: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        self.field_errors.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+
+        self
+    }
+
+    pub fn with_retry_after(mut self, seconds: u64) -> Self {
+        self.retry_after = Some(seconds);
+
+        self
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        self.code.status_code()
+    }
+}
+
+impl From<ConflictError> for ApiError {
+    fn from(error: ConflictError) -> Self {
+        Self::new(ApiErrorCode::PreconditionFailed, error.to_string())
+    }
+}
+
+impl From<ReviewError> for ApiError {
+    fn from(error: ReviewError) -> Self {
+        let code = match error {
+            ReviewError::InvalidTransition { .. } => ApiErrorCode::Conflict,
+            ReviewError::NotReviewer(_) => ApiErrorCode::Forbidden,
+            ReviewError::EmptyComment => ApiErrorCode::Validation,
+        };
+
+        Self::new(code, error.to_string())
+    }
+}
+
+impl From<WebhookError> for ApiError {
+    fn from(error: WebhookError) -> Self {
+        let code = match error {
+            WebhookError::InvalidPayload(_) => ApiErrorCode::BadRequest,
+            _ => ApiErrorCode::Unauthorized,
+        };
+
+        Self::new(code, error.to_string())
+    }
+}
+
+impl From<AnalyticsError> for ApiError {
+    fn from(error: AnalyticsError) -> Self {
+        let code = match error {
+            AnalyticsError::InvalidPayload(_) => ApiErrorCode::BadRequest,
+            _ => ApiErrorCode::Validation,
+        };
+
+        Self::new(code, error.to_string())
+    }
+}
+
+impl From<SourceKeyError> for ApiError {
+    fn from(error: SourceKeyError) -> Self {
+        Self::new(ApiErrorCode::BadRequest, error.to_string())
+    }
+}
+
+impl From<SyncCompatibilityError> for ApiError {
+    fn from(error: SyncCompatibilityError) -> Self {
+        Self::new(ApiErrorCode::Conflict, error.to_string())
+    }
+}
+
+impl From<ValidationError> for ApiError {
+    fn from(error: ValidationError) -> Self {
+        Self::new(ApiErrorCode::Validation, error.to_string())
+    }
+}
+
+impl From<GuardrailError> for ApiError {
+    fn from(error: GuardrailError) -> Self {
+        Self::new(ApiErrorCode::Conflict, error.to_string())
+    }
+}
+
+/// Known error types keep their codes. Anything else is internal and its
+/// message is withheld, as it may expose implementation details.
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<ConflictError>() {
+            Ok(error) => return error.into(),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<ReviewError>() {
+            Ok(error) => return error.into(),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<WebhookError>() {
+            Ok(error) => return error.into(),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<AnalyticsError>() {
+            Ok(error) => return error.into(),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<SourceKeyError>() {
+            Ok(error) => return error.into(),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<SyncCompatibilityError>() {
+            Ok(error) => return error.into(),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<ValidationError>() {
+            Ok(error) => return error.into(),
+            Err(error) => error,
+        };
+
+        match error.downcast::<GuardrailError>() {
+            Ok(error) => error.into(),
+            Err(_) => Self::new(ApiErrorCode::Internal, Self::INTERNAL_MESSAGE),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_from_anyhow() {
+        let error = ApiError::from(anyhow::Error::new(ReviewError::EmptyComment));
+
+        assert_eq!(error.code, ApiErrorCode::Validation);
+        assert_eq!(error.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let error = ApiError::from(ValidationError::wrap(anyhow::anyhow!(
+            "invalid question with ID 1"
+        )));
+
+        assert_eq!(error.code, ApiErrorCode::Validation);
+        assert_eq!(error.message, "invalid question with ID 1");
+
+        let error = ApiError::from(anyhow::anyhow!("connection refused to 10.0.0.3"));
+
+        assert_eq!(error.code, ApiErrorCode::Internal);
+        assert_eq!(error.message, ApiError::INTERNAL_MESSAGE);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let error = ApiError::new(ApiErrorCode::RateLimited, "too many requests")
+            .with_field_error("email", "already used")
+            .with_retry_after(30);
+
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "rate_limited",
+                "message": "too many requests",
+                "field_errors": [{ "field": "email", "message": "already used" }],
+                "retry_after": 30
+            })
+        );
+    }
+}
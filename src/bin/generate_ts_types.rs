@@ -0,0 +1,21 @@
+//! Writes the TypeScript declarations of the shared DTOs, e.g.
+//! `cargo run --features ts_types --bin generate-ts-types -- web/src/medici.d.ts`.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use medici_shared::export::typescript_declarations;
+
+const DEFAULT_OUTPUT_PATH: &str = "medici-shared.d.ts";
+
+fn main() -> Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .map_or_else(|| PathBuf::from(DEFAULT_OUTPUT_PATH), PathBuf::from);
+
+    std::fs::write(&path, typescript_declarations())?;
+
+    println!("wrote {}", path.display());
+
+    Ok(())
+}
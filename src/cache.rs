@@ -0,0 +1,206 @@
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::warn;
+
+/// Key-value cache with expiration, such as Valkey.
+pub trait Cache: Sync {
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<String>>> + Send;
+
+    fn set(
+        &self,
+        key: &str,
+        value: String,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn delete(&self, key: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Values are stored as JSON. A cached value that no longer deserializes,
+    /// e.g. after a schema change, is computed again.
+    fn get_or_compute<T, F, Fut>(
+        &self,
+        key: &str,
+        ttl: Duration,
+        compute: F,
+    ) -> impl Future<Output = Result<T>> + Send
+    where
+        T: Serialize + DeserializeOwned + Send,
+        F: FnOnce() -> Fut + Send,
+        Fut: Future<Output = Result<T>> + Send,
+    {
+        async move {
+            if let Some(cached) = self.get(key).await? {
+                match serde_json::from_str(&cached) {
+                    Ok(value) => return Ok(value),
+                    Err(error) => warn!(key, "discarding undecodable cached value: {error}"),
+                }
+            }
+
+            let value = compute().await?;
+
+            self.set(key, serde_json::to_string(&value)?, ttl).await?;
+
+            Ok(value)
+        }
+    }
+}
+
+/// Commands sent per pipeline round trip.
+#[cfg(feature = "valkey")]
+pub const PIPELINE_CHUNK_SIZE: usize = 500;
+
+/// Keys whose commands failed in a pipeline. Other keys in the same call were
+/// still applied.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PipelineError {
+    pub failures: Vec<PipelineFailure>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PipelineFailure {
+    pub key: String,
+    pub error: String,
+}
+
+impl std::fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} pipelined cache commands failed", self.failures.len())?;
+
+        if let Some(first) = self.failures.first() {
+            write!(f, ", first for {}: {}", first.key, first.error)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+/// Deletes `keys` in pipelines of [`PIPELINE_CHUNK_SIZE`] commands.
+#[cfg(feature = "valkey")]
+pub async fn pipeline_delete(
+    client: &fred::clients::Client,
+    keys: impl IntoIterator<Item = String>,
+) -> Result<(), PipelineError> {
+    run_pipelines(
+        client,
+        keys.into_iter().map(PipelineCommand::Delete).collect(),
+    )
+    .await
+}
+
+/// Sets `entries` with the same `ttl` in pipelines of [`PIPELINE_CHUNK_SIZE`] commands.
+#[cfg(feature = "valkey")]
+pub async fn pipeline_set(
+    client: &fred::clients::Client,
+    entries: impl IntoIterator<Item = (String, String)>,
+    ttl: Duration,
+) -> Result<(), PipelineError> {
+    let ttl_secs = ttl.as_secs().max(1) as i64;
+
+    run_pipelines(
+        client,
+        entries
+            .into_iter()
+            .map(|(key, value)| PipelineCommand::Set(key, value, ttl_secs))
+            .collect(),
+    )
+    .await
+}
+
+#[cfg(feature = "valkey")]
+enum PipelineCommand {
+    Delete(String),
+    Set(String, String, i64),
+}
+
+#[cfg(feature = "valkey")]
+impl PipelineCommand {
+    fn key(&self) -> &str {
+        match self {
+            Self::Delete(key) | Self::Set(key, _, _) => key,
+        }
+    }
+
+    async fn queue(
+        &self,
+        pipeline: &fred::clients::Pipeline<fred::clients::Client>,
+    ) -> Result<(), fred::error::Error> {
+        use fred::interfaces::KeysInterface;
+        use fred::types::Expiration;
+
+        match self {
+            Self::Delete(key) => pipeline.del(key.as_str()).await,
+            Self::Set(key, value, ttl_secs) => {
+                pipeline
+                    .set(
+                        key.as_str(),
+                        value.clone(),
+                        Some(Expiration::EX(*ttl_secs)),
+                        None,
+                        false,
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+/// Sends `commands` in chunks, collecting the keys whose commands failed
+/// instead of stopping at the first error.
+#[cfg(feature = "valkey")]
+async fn run_pipelines(
+    client: &fred::clients::Client,
+    commands: Vec<PipelineCommand>,
+) -> Result<(), PipelineError> {
+    let mut failures = vec![];
+
+    for chunk in commands.chunks(PIPELINE_CHUNK_SIZE) {
+        let pipeline = client.pipeline();
+        let mut queued = vec![];
+
+        for command in chunk {
+            match command.queue(&pipeline).await {
+                Ok(()) => queued.push(command.key()),
+                Err(error) => failures.push(PipelineFailure {
+                    key: command.key().into(),
+                    error: error.to_string(),
+                }),
+            }
+        }
+
+        let results = pipeline.try_all::<fred::types::Value>().await;
+
+        // Without a result per command, none of them can be assumed applied.
+        if results.len() != queued.len() {
+            let error = format!(
+                "pipeline returned {} results for {} commands",
+                results.len(),
+                queued.len()
+            );
+            failures.extend(queued.into_iter().map(|key| PipelineFailure {
+                key: key.into(),
+                error: error.clone(),
+            }));
+
+            continue;
+        }
+
+        failures.extend(queued.into_iter().zip(results).filter_map(|(key, result)| {
+            result.err().map(|error| PipelineFailure {
+                key: key.into(),
+                error: error.to_string(),
+            })
+        }));
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(PipelineError { failures })
+    }
+}
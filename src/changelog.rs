@@ -0,0 +1,438 @@
+use std::collections::{BTreeSet, HashMap};
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use uuid::Uuid;
+
+use crate::sync::{CourseData, LanguageTag, QuestionData};
+
+/// What changed in a course between two content snapshots.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct CourseChangelog {
+    pub course_key: String,
+    pub course_name: String,
+    pub locale: LanguageTag,
+    pub added_questions: Vec<QuestionSummary>,
+    pub removed_questions: Vec<QuestionSummary>,
+    pub edited_questions: Vec<QuestionEdit>,
+    pub added_topics: Vec<String>,
+    pub removed_topics: Vec<String>,
+    pub price_change: Option<PriceChange>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct QuestionSummary {
+    pub id: Uuid,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct QuestionEdit {
+    pub id: Uuid,
+    /// Word-level diff of the question text, empty if the text didn't change.
+    pub text_diff: Vec<TextDiffSegment>,
+    pub options_changed: bool,
+    pub explanation_changed: bool,
+    pub topic_change: Option<TopicChange>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct TopicChange {
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct PriceChange {
+    pub old: Option<Decimal>,
+    pub new: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct TextDiffSegment {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffKind {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+pub fn between(old: &CourseData, new: &CourseData) -> CourseChangelog {
+    let old_questions = old
+        .questions
+        .iter()
+        .map(|question| (question.id, question))
+        .collect::<HashMap<_, _>>();
+    let new_questions = new
+        .questions
+        .iter()
+        .map(|question| (question.id, question))
+        .collect::<HashMap<_, _>>();
+
+    let added_questions = new
+        .questions
+        .iter()
+        .filter(|question| !old_questions.contains_key(&question.id))
+        .map(QuestionSummary::from)
+        .collect();
+    let removed_questions = old
+        .questions
+        .iter()
+        .filter(|question| !new_questions.contains_key(&question.id))
+        .map(QuestionSummary::from)
+        .collect();
+    let edited_questions = new
+        .questions
+        .iter()
+        .filter_map(|question| {
+            let old_question = old_questions.get(&question.id)?;
+
+            (old_question.hash != question.hash).then(|| QuestionEdit::new(old_question, question))
+        })
+        .collect();
+
+    let old_topics = topic_names(old);
+    let new_topics = topic_names(new);
+
+    CourseChangelog {
+        course_key: new.key.clone(),
+        course_name: new.name.clone(),
+        locale: new.locale.clone(),
+        added_questions,
+        removed_questions,
+        edited_questions,
+        added_topics: new_topics.difference(&old_topics).cloned().collect(),
+        removed_topics: old_topics.difference(&new_topics).cloned().collect(),
+        price_change: (old.price_in_uyu != new.price_in_uyu).then_some(PriceChange {
+            old: old.price_in_uyu,
+            new: new.price_in_uyu,
+        }),
+    }
+}
+
+fn topic_names(course: &CourseData) -> BTreeSet<String> {
+    course
+        .questions
+        .iter()
+        .map(|question| question.topic.name.clone())
+        .collect()
+}
+
+impl From<&QuestionData> for QuestionSummary {
+    fn from(question: &QuestionData) -> Self {
+        Self {
+            id: question.id,
+            text: question.text.clone(),
+        }
+    }
+}
+
+impl QuestionEdit {
+    fn new(old: &QuestionData, new: &QuestionData) -> Self {
+        let options = |question: &QuestionData| {
+            question
+                .question_options
+                .iter()
+                .map(|option| (option.text.clone(), option.is_correct))
+                .collect::<Vec<_>>()
+        };
+
+        Self {
+            id: new.id,
+            text_diff: if old.text == new.text {
+                vec![]
+            } else {
+                diff_words(&old.text, &new.text)
+            },
+            options_changed: options(old) != options(new),
+            explanation_changed: old
+                .explanation
+                .as_ref()
+                .map(|explanation| &explanation.text)
+                != new
+                    .explanation
+                    .as_ref()
+                    .map(|explanation| &explanation.text),
+            topic_change: (old.topic.name != new.topic.name).then(|| TopicChange {
+                old: old.topic.name.clone(),
+                new: new.topic.name.clone(),
+            }),
+        }
+    }
+}
+
+fn diff_words(old: &str, new: &str) -> Vec<TextDiffSegment> {
+    let mut segments: Vec<TextDiffSegment> = vec![];
+
+    for change in TextDiff::from_words(old, new).iter_all_changes() {
+        let kind = match change.tag() {
+            ChangeTag::Equal => DiffKind::Unchanged,
+            ChangeTag::Insert => DiffKind::Added,
+            ChangeTag::Delete => DiffKind::Removed,
+        };
+
+        match segments.last_mut() {
+            Some(last) if last.kind == kind => last.text.push_str(change.value()),
+            _ => segments.push(TextDiffSegment {
+                kind,
+                text: change.value().into(),
+            }),
+        }
+    }
+
+    segments
+}
+
+struct Headings {
+    title: &'static str,
+    added_questions: &'static str,
+    removed_questions: &'static str,
+    edited_questions: &'static str,
+    options_changed: &'static str,
+    explanation_changed: &'static str,
+    topic: &'static str,
+    added_topics: &'static str,
+    removed_topics: &'static str,
+    price: &'static str,
+    no_price: &'static str,
+}
+
+impl Headings {
+    fn new(locale: &LanguageTag) -> Self {
+        match locale.language() {
+            "pt" => Self {
+                title: "Novidades",
+                added_questions: "Perguntas novas",
+                removed_questions: "Perguntas removidas",
+                edited_questions: "Perguntas editadas",
+                options_changed: "opções alteradas",
+                explanation_changed: "explicação alterada",
+                topic: "tema",
+                added_topics: "Temas novos",
+                removed_topics: "Temas removidos",
+                price: "Preço",
+                no_price: "grátis",
+            },
+            "en" => Self {
+                title: "What's new",
+                added_questions: "New questions",
+                removed_questions: "Removed questions",
+                edited_questions: "Edited questions",
+                options_changed: "options changed",
+                explanation_changed: "explanation changed",
+                topic: "topic",
+                added_topics: "New topics",
+                removed_topics: "Removed topics",
+                price: "Price",
+                no_price: "free",
+            },
+            _ => Self {
+                title: "Novedades",
+                added_questions: "Preguntas nuevas",
+                removed_questions: "Preguntas eliminadas",
+                edited_questions: "Preguntas editadas",
+                options_changed: "opciones modificadas",
+                explanation_changed: "explicación modificada",
+                topic: "tema",
+                added_topics: "Temas nuevos",
+                removed_topics: "Temas eliminados",
+                price: "Precio",
+                no_price: "gratis",
+            },
+        }
+    }
+}
+
+impl CourseChangelog {
+    pub fn is_empty(&self) -> bool {
+        self.added_questions.is_empty()
+            && self.removed_questions.is_empty()
+            && self.edited_questions.is_empty()
+            && self.added_topics.is_empty()
+            && self.removed_topics.is_empty()
+            && self.price_change.is_none()
+    }
+
+    /// Headings follow the course locale. Edited text shows removed words struck
+    /// through and added words in bold. Edits to fields the changelog doesn't
+    /// describe, like tags, are left out.
+    pub fn to_markdown(&self) -> String {
+        let headings = Headings::new(&self.locale);
+        let mut markdown = format!("# {}: {}\n", self.course_name, headings.title);
+
+        let mut section = |title: &str, items: Vec<String>| {
+            if !items.is_empty() {
+                markdown.push_str(&format!("\n## {title} ({})\n\n", items.len()));
+
+                for item in items {
+                    markdown.push_str(&format!("- {item}\n"));
+                }
+            }
+        };
+
+        section(
+            headings.added_questions,
+            self.added_questions
+                .iter()
+                .map(|question| question.text.clone())
+                .collect(),
+        );
+        section(
+            headings.removed_questions,
+            self.removed_questions
+                .iter()
+                .map(|question| question.text.clone())
+                .collect(),
+        );
+        section(
+            headings.edited_questions,
+            self.edited_questions
+                .iter()
+                .map(|edit| edit.to_markdown(&headings))
+                .filter(|markdown| !markdown.is_empty())
+                .collect(),
+        );
+        section(headings.added_topics, self.added_topics.clone());
+        section(headings.removed_topics, self.removed_topics.clone());
+
+        if let Some(price_change) = &self.price_change {
+            let price = |price: Option<Decimal>| {
+                price.map_or(headings.no_price.to_string(), |price| {
+                    format!("UYU {price}")
+                })
+            };
+
+            markdown.push_str(&format!(
+                "\n## {}\n\n{} → {}\n",
+                headings.price,
+                price(price_change.old),
+                price(price_change.new)
+            ));
+        }
+
+        markdown
+    }
+}
+
+impl QuestionEdit {
+    fn to_markdown(&self, headings: &Headings) -> String {
+        let mut parts = vec![];
+
+        if !self.text_diff.is_empty() {
+            parts.push(
+                self.text_diff
+                    .iter()
+                    .map(TextDiffSegment::to_markdown)
+                    .collect::<String>(),
+            );
+        }
+
+        if let Some(topic_change) = &self.topic_change {
+            parts.push(format!(
+                "{}: {} → {}",
+                headings.topic, topic_change.old, topic_change.new
+            ));
+        }
+
+        if self.options_changed {
+            parts.push(headings.options_changed.into());
+        }
+
+        if self.explanation_changed {
+            parts.push(headings.explanation_changed.into());
+        }
+
+        parts.join("; ")
+    }
+}
+
+impl TextDiffSegment {
+    fn to_markdown(&self) -> String {
+        let marker = match self.kind {
+            DiffKind::Unchanged => return self.text.clone(),
+            DiffKind::Added => "**",
+            DiffKind::Removed => "~~",
+        };
+
+        // Markdown emphasis can't start or end with whitespace.
+        let trimmed = self.text.trim();
+
+        if trimmed.is_empty() {
+            return self.text.clone();
+        }
+
+        let leading = &self.text[..self.text.len() - self.text.trim_start().len()];
+        let trailing = &self.text[self.text.trim_end().len()..];
+
+        format!("{leading}{marker}{trimmed}{marker}{trailing}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::traits::Hashable;
+
+    #[test]
+    fn test_between() {
+        let mut old = Faker.fake::<CourseData>();
+        old.locale = LanguageTag::default();
+        old.price_in_uyu = Some(Decimal::from(100));
+        old.questions = fake::vec![QuestionData; 3];
+
+        for question in &mut old.questions {
+            question.prepare_for_test().unwrap();
+        }
+
+        let mut new = old.clone();
+        let removed = new.questions.remove(0);
+        let mut added = Faker.fake::<QuestionData>();
+        added.prepare_for_test().unwrap();
+        new.questions.push(added.clone());
+        new.questions[0].text = "Cuál es el nervio frénico".into();
+        new.questions[0].refresh_hash();
+        new.questions[1].tags.push("repaso".into());
+        new.questions[1].refresh_hash();
+        new.price_in_uyu = Some(Decimal::from(120));
+
+        let mut edited_old = old.questions[1].clone();
+        edited_old.text = "Cuál es el nervio vago".into();
+        old.questions[1] = edited_old;
+        old.questions[1].refresh_hash();
+
+        let changelog = between(&old, &new);
+
+        assert_eq!(changelog.added_questions, [QuestionSummary::from(&added)]);
+        assert_eq!(
+            changelog.removed_questions,
+            [QuestionSummary::from(&removed)]
+        );
+        assert_eq!(changelog.edited_questions.len(), 2);
+        assert!(!changelog.edited_questions[0].options_changed);
+
+        let markdown = changelog.to_markdown();
+
+        assert!(markdown.contains("## Preguntas editadas (1)"));
+        assert!(!markdown.contains("- \n"));
+
+        assert!(markdown.contains("- Cuál es el nervio ~~vago~~**frénico**"));
+        assert!(markdown.contains("UYU 100 → UYU 120"));
+        assert!(between(&new, &new).is_empty());
+
+        let whitespace = TextDiffSegment {
+            kind: DiffKind::Added,
+            text: " ".into(),
+        };
+
+        assert_eq!(whitespace.to_markdown(), " ");
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::Result;
+use reqwest::header::{HeaderValue, CONTENT_TYPE};
+use reqwest::{Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::http::{check_status, client, send};
+use crate::redact::Redacted;
+use crate::sync::{Catalog, SyncData, SyncMetadata, SyncReport};
+
+pub const SYNC_CONTENT_TYPE: &str = "application/x-medici-sync";
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HealthStatus {
+    pub status: String,
+    pub version: Option<String>,
+}
+
+impl HealthStatus {
+    pub const OK: &'static str = "ok";
+
+    pub fn is_ok(&self) -> bool {
+        self.status == Self::OK
+    }
+}
+
+/// Client for the engine's HTTP API.
+#[derive(Clone, Debug)]
+pub struct EngineClient {
+    base_url: String,
+    token: Redacted<String>,
+    client: reqwest::Client,
+}
+
+impl EngineClient {
+    pub const HEALTH_PATH: &'static str = "/health";
+    pub const CATALOG_PATH: &'static str = "/catalog";
+    pub const SYNC_PATH: &'static str = "/internal/sync";
+    pub const SYNC_METADATA_PATH: &'static str = "/internal/sync/metadata";
+
+    pub fn new(base_url: &str, token: String) -> Result<Self> {
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').into(),
+            token: Redacted(token),
+            client: client()?,
+        })
+    }
+
+    pub async fn health(&self) -> Result<HealthStatus> {
+        let response = send(self.request(Method::GET, Self::HEALTH_PATH)).await?;
+
+        Ok(check_status(response).await?.json().await?)
+    }
+
+    pub async fn get_catalog(&self) -> Result<Catalog> {
+        let response = send(self.request(Method::GET, Self::CATALOG_PATH)).await?;
+
+        Ok(check_status(response).await?.json().await?)
+    }
+
+    pub async fn get_sync_metadata(&self) -> Result<SyncMetadata> {
+        let response = send(self.request(Method::GET, Self::SYNC_METADATA_PATH)).await?;
+
+        Ok(check_status(response).await?.json().await?)
+    }
+
+    /// Uploads the sync data compressed, returning the engine's report.
+    pub async fn upload_sync(&self, sync_data: &SyncData) -> Result<SyncReport> {
+        let body = sync_data.to_compressed_bytes()?;
+
+        debug!(bytes = body.len(), "uploading sync data");
+
+        let request = self
+            .request(Method::POST, Self::SYNC_PATH)
+            .header(CONTENT_TYPE, HeaderValue::from_static(SYNC_CONTENT_TYPE))
+            .body(body);
+
+        Ok(check_status(send(request).await?).await?.json().await?)
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{path}", self.base_url))
+            .bearer_auth(self.token.expose())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let client = EngineClient::new("https://engine.medici.uy/", "s3cr3t".into()).unwrap();
+
+        assert_eq!(client.base_url, "https://engine.medici.uy");
+        assert!(!format!("{client:?}").contains("s3cr3t"));
+    }
+}
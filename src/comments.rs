@@ -0,0 +1,157 @@
+//! Reviewer comments on questions. They are keyed by question ID and aren't
+//! part of the question content, so they never change question hashes.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::sync::format_text;
+#[cfg(feature = "db")]
+use crate::traits::{Changeset, Insertable, Table};
+
+pub const MAX_COMMENT_LENGTH: usize = 4000;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(
+    feature = "db",
+    derive(medici_macros::Table, sqlx::FromRow),
+    medici(table_name = "question_comments")
+)]
+pub struct QuestionCommentData {
+    #[cfg_attr(feature = "db", medici(primary_key))]
+    pub id: Uuid,
+    pub question_id: Uuid,
+    pub author_id: Uuid,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub resolved: bool,
+}
+
+/// Request body for commenting on a question; the author comes from the session.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct NewQuestionComment {
+    pub question_id: Uuid,
+    pub body: String,
+}
+
+/// Columns of a new comment row. Comments start unresolved.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(
+    feature = "db",
+    derive(medici_macros::Insertable),
+    medici(table_struct = "QuestionCommentData")
+)]
+pub struct QuestionCommentInsert {
+    pub id: Uuid,
+    pub question_id: Uuid,
+    pub author_id: Uuid,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request body for editing or resolving a comment.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(
+    feature = "db",
+    derive(medici_macros::Changeset),
+    medici(table_struct = "QuestionCommentData")
+)]
+pub struct QuestionCommentChangeset {
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub resolved: Option<bool>,
+}
+
+impl NewQuestionComment {
+    pub fn into_insert(self, author_id: Uuid, now: DateTime<Utc>) -> Result<QuestionCommentInsert> {
+        Ok(QuestionCommentInsert {
+            id: Uuid::new_v4(),
+            question_id: self.question_id,
+            author_id,
+            body: format_comment_body(&self.body)?,
+            created_at: now,
+        })
+    }
+}
+
+impl QuestionCommentChangeset {
+    pub fn process(&mut self) -> Result<()> {
+        if let Some(body) = &self.body {
+            self.body = Some(format_comment_body(body)?);
+        }
+
+        Ok(())
+    }
+}
+
+fn format_comment_body(body: &str) -> Result<String> {
+    let body = format_text(body);
+
+    if body.is_empty() {
+        bail!("empty comment");
+    }
+
+    if body.chars().count() > MAX_COMMENT_LENGTH {
+        bail!("comment longer than {MAX_COMMENT_LENGTH} characters");
+    }
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_question_comment() {
+        let question_id = Uuid::new_v4();
+        let insert = NewQuestionComment {
+            question_id,
+            body: "  La opción B   está repetida ".into(),
+        }
+        .into_insert(Uuid::new_v4(), Utc::now())
+        .unwrap();
+
+        assert_eq!(insert.body, "La opción B está repetida");
+        assert!(NewQuestionComment {
+            question_id,
+            body: " ".into(),
+        }
+        .into_insert(Uuid::new_v4(), Utc::now())
+        .is_err());
+
+        let mut changeset = QuestionCommentChangeset {
+            body: Some(" Ya está corregida. ".into()),
+            resolved: Some(true),
+        };
+
+        changeset.process().unwrap();
+
+        assert_eq!(changeset.body.as_deref(), Some("Ya está corregida."));
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn test_question_comment_table() {
+        let comment = QuestionCommentData {
+            id: Uuid::new_v4(),
+            question_id: Uuid::new_v4(),
+            author_id: Uuid::new_v4(),
+            body: "Falta la imagen".into(),
+            created_at: Utc::now(),
+            resolved: true,
+        };
+
+        assert_eq!(QuestionCommentData::TABLE_NAME, "question_comments");
+        assert_eq!(comment.primary_key(), &comment.id);
+        assert_eq!(QuestionCommentInsert::COLUMNS[3], "body");
+        assert!(
+            QuestionCommentChangeset {
+                resolved: Some(true),
+                ..Default::default()
+            } == comment
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use toml::{Table, Value};
+
+#[cfg(feature = "aws")]
+use super::secrets::{resolve_secrets, SecretResolver, SecretSource};
+use crate::redact::Redacted;
+use crate::telemetry::TelemetryConfig;
+
+pub const ENV_VAR_PREFIX: &str = "MEDICI__";
+pub const ENV_VAR_SEPARATOR: &str = "__";
+pub const ENVIRONMENT_ENV_VAR: &str = "MEDICI_ENV";
+pub const DEFAULT_ENVIRONMENT: &str = "development";
+
+/// Well-known env vars mapped to config paths, so services can keep their existing names.
+pub const ENV_VAR_ALIASES: [(&str, &str); 4] = [
+    ("DATABASE_URL", "database.url"),
+    ("VALKEY_URL", "cache.url"),
+    ("S3_BUCKET", "storage.bucket"),
+    ("OPENAI_API_KEY", "openai.api_key"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MediciConfig {
+    #[serde(default = "default_environment")]
+    pub environment: String,
+    pub database: DatabaseConfig,
+    pub cache: CacheConfig,
+    pub storage: StorageConfig,
+    pub openai: OpenAiConfig,
+    #[serde(default)]
+    pub email: EmailConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DatabaseConfig {
+    pub url: Redacted<String>,
+    #[serde(
+        default = "DatabaseConfig::default_max_connections",
+        deserialize_with = "from_env_str"
+    )]
+    pub max_connections: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CacheConfig {
+    pub url: Redacted<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StorageConfig {
+    pub bucket: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OpenAiConfig {
+    pub api_key: Redacted<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct EmailConfig {
+    pub from_address: Option<String>,
+    pub configuration_set: Option<String>,
+}
+
+impl DatabaseConfig {
+    pub const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+
+    fn default_max_connections() -> u32 {
+        Self::DEFAULT_MAX_CONNECTIONS
+    }
+}
+
+fn default_environment() -> String {
+    DEFAULT_ENVIRONMENT.into()
+}
+
+impl MediciConfig {
+    /// Loads the optional TOML file, then the `[environments.<name>]` section for
+    /// the environment in `MEDICI_ENV`, then env vars, each overriding the previous.
+    pub fn load(toml_path: Option<&Path>) -> Result<Self> {
+        let toml = toml_path.map(std::fs::read_to_string).transpose()?;
+
+        Self::load_from(toml.as_deref(), std::env::vars())
+    }
+
+    /// Like `load`, resolving `aws-sm://` and `aws-ssm://` references in any value.
+    #[cfg(feature = "aws")]
+    pub async fn load_with_secrets<S: SecretSource>(
+        toml_path: Option<&Path>,
+        resolver: &SecretResolver<S>,
+    ) -> Result<Self> {
+        let toml = toml_path.map(std::fs::read_to_string).transpose()?;
+        let mut table = config_table(toml.as_deref(), std::env::vars())?;
+
+        resolve_secrets(&mut table, resolver).await?;
+
+        Self::from_table(table)
+    }
+
+    pub fn load_from(
+        toml: Option<&str>,
+        env_vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self> {
+        Self::from_table(config_table(toml, env_vars)?)
+    }
+
+    fn from_table(table: Table) -> Result<Self> {
+        let config: Self = Value::Table(table).try_into()?;
+        config.check()?;
+
+        Ok(config)
+    }
+
+    fn check(&self) -> Result<()> {
+        let missing = [
+            ("database.url", self.database.url.expose().as_str()),
+            ("cache.url", self.cache.url.expose().as_str()),
+            ("storage.bucket", self.storage.bucket.as_str()),
+            ("openai.api_key", self.openai.api_key.expose().as_str()),
+        ]
+        .into_iter()
+        .filter(|(_, value)| value.trim().is_empty())
+        .map(|(path, _)| path)
+        .collect::<Vec<&str>>();
+
+        if !missing.is_empty() {
+            bail!("missing config value(s): {}", missing.join(", "));
+        }
+
+        Ok(())
+    }
+}
+
+fn config_table(
+    toml: Option<&str>,
+    env_vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<Table> {
+    let mut table = toml
+        .map(str::parse::<Table>)
+        .transpose()?
+        .unwrap_or_default();
+    let env_vars = env_vars.into_iter().collect::<Vec<(String, String)>>();
+
+    let environment = env_vars
+        .iter()
+        .find(|(name, _)| name == ENVIRONMENT_ENV_VAR)
+        .map(|(_, value)| value.clone())
+        .or_else(|| table.get("environment")?.as_str().map(Into::into))
+        .unwrap_or_else(default_environment);
+
+    if let Some(Value::Table(environments)) = table.remove("environments") {
+        if let Some(Value::Table(overrides)) = environments.get(&environment) {
+            merge_tables(&mut table, overrides.clone());
+        }
+    }
+
+    table.insert("environment".into(), Value::String(environment));
+
+    for (name, value) in &env_vars {
+        let path = match ENV_VAR_ALIASES.iter().find(|(alias, _)| alias == name) {
+            Some((_, path)) => path.split('.').map(str::to_lowercase).collect(),
+            None => match name.strip_prefix(ENV_VAR_PREFIX) {
+                Some(path) => path
+                    .split(ENV_VAR_SEPARATOR)
+                    .map(str::to_lowercase)
+                    .collect::<Vec<String>>(),
+                None => continue,
+            },
+        };
+
+        set_path(&mut table, &path, Value::String(value.clone()));
+    }
+
+    Ok(table)
+}
+
+fn merge_tables(base: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(overrides)) => merge_tables(base, overrides),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn set_path(table: &mut Table, path: &[String], value: Value) {
+    let Some((key, parents)) = path.split_last() else {
+        return;
+    };
+
+    let mut table = table;
+
+    for parent in parents {
+        let entry = table
+            .entry(parent.clone())
+            .or_insert_with(|| Value::Table(Table::new()));
+
+        if !entry.is_table() {
+            *entry = Value::Table(Table::new());
+        }
+
+        table = entry.as_table_mut().unwrap();
+    }
+
+    table.insert(key.clone(), value);
+}
+
+/// For non-string fields, which env vars can only set as strings.
+pub(crate) fn from_env_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr + Deserialize<'de>,
+    T::Err: Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOr<T> {
+        String(String),
+        Value(T),
+    }
+
+    match StringOr::<T>::deserialize(deserializer)? {
+        StringOr::String(value) => value.parse().map_err(D::Error::custom),
+        StringOr::Value(value) => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+        [database]
+        url = "postgres://localhost/medici"
+
+        [cache]
+        url = "redis://localhost"
+
+        [storage]
+        bucket = "medici-dev"
+
+        [environments.production.storage]
+        bucket = "medici-prod"
+    "#;
+
+    fn env_vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_load_from() {
+        let config = MediciConfig::load_from(
+            Some(TOML),
+            env_vars(&[
+                ("MEDICI_ENV", "production"),
+                ("OPENAI_API_KEY", "sk-test"),
+                ("MEDICI__DATABASE__MAX_CONNECTIONS", "25"),
+                ("MEDICI__EMAIL__CONFIGURATION_SET", "2024"),
+                ("MEDICI__TELEMETRY__FILTER", "debug"),
+                ("MEDICI__TELEMETRY__JSON", "false"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(config.environment, "production");
+        assert_eq!(config.storage.bucket, "medici-prod");
+        assert_eq!(config.openai.api_key.expose(), "sk-test");
+        assert_eq!(config.database.max_connections, 25);
+        assert_eq!(config.email.configuration_set.as_deref(), Some("2024"));
+        assert!(!config.telemetry.json);
+    }
+
+    #[test]
+    fn test_missing_values() {
+        let error = MediciConfig::load_from(
+            Some(TOML),
+            env_vars(&[("OPENAI_API_KEY", ""), ("VALKEY_URL", " ")]),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "missing config value(s): cache.url, openai.api_key"
+        );
+    }
+}
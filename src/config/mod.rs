@@ -0,0 +1,7 @@
+mod loader;
+#[cfg(feature = "aws")]
+mod secrets;
+
+pub use loader::*;
+#[cfg(feature = "aws")]
+pub use secrets::*;
@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Result};
+use tokio::sync::Mutex;
+use toml::{Table, Value};
+use tracing::warn;
+
+pub const SECRETS_MANAGER_SCHEME: &str = "aws-sm://";
+pub const PARAMETER_STORE_SCHEME: &str = "aws-ssm://";
+pub const JSON_KEY_SEPARATOR: char = '#';
+pub const DEFAULT_SECRET_TTL: Duration = Duration::from_secs(300);
+
+/// `aws-sm://<secret id>[#<JSON key>]` or `aws-ssm://<parameter name>`.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum SecretReference {
+    SecretsManager {
+        secret_id: String,
+        json_key: Option<String>,
+    },
+    ParameterStore {
+        name: String,
+    },
+}
+
+impl SecretReference {
+    pub fn parse(value: &str) -> Option<Self> {
+        if let Some(rest) = value.strip_prefix(SECRETS_MANAGER_SCHEME) {
+            let (secret_id, json_key) = match rest.split_once(JSON_KEY_SEPARATOR) {
+                Some((secret_id, json_key)) => (secret_id, Some(json_key.into())),
+                None => (rest, None),
+            };
+
+            return Some(Self::SecretsManager {
+                secret_id: secret_id.into(),
+                json_key,
+            });
+        }
+
+        value
+            .strip_prefix(PARAMETER_STORE_SCHEME)
+            .map(|name| Self::ParameterStore { name: name.into() })
+    }
+}
+
+impl std::fmt::Display for SecretReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SecretsManager {
+                secret_id,
+                json_key: Some(json_key),
+            } => write!(
+                f,
+                "{SECRETS_MANAGER_SCHEME}{secret_id}{JSON_KEY_SEPARATOR}{json_key}"
+            ),
+            Self::SecretsManager { secret_id, .. } => {
+                write!(f, "{SECRETS_MANAGER_SCHEME}{secret_id}")
+            }
+            Self::ParameterStore { name } => write!(f, "{PARAMETER_STORE_SCHEME}{name}"),
+        }
+    }
+}
+
+pub trait SecretSource {
+    fn fetch(&self, reference: &SecretReference) -> impl Future<Output = Result<String>> + Send;
+}
+
+#[derive(Clone, Debug)]
+pub struct AwsSecretSource {
+    pub secrets_manager: aws_sdk_secretsmanager::Client,
+    pub ssm: aws_sdk_ssm::Client,
+}
+
+impl AwsSecretSource {
+    pub fn new(secrets_manager: aws_sdk_secretsmanager::Client, ssm: aws_sdk_ssm::Client) -> Self {
+        Self {
+            secrets_manager,
+            ssm,
+        }
+    }
+}
+
+impl SecretSource for AwsSecretSource {
+    async fn fetch(&self, reference: &SecretReference) -> Result<String> {
+        match reference {
+            SecretReference::SecretsManager {
+                secret_id,
+                json_key,
+            } => {
+                let output = self
+                    .secrets_manager
+                    .get_secret_value()
+                    .secret_id(secret_id)
+                    .send()
+                    .await?;
+
+                let Some(secret) = output.secret_string() else {
+                    bail!("secret {reference} has no string value");
+                };
+
+                match json_key {
+                    Some(json_key) => json_secret_value(secret, json_key)
+                        .ok_or_else(|| anyhow!("secret {reference} has no string {json_key}")),
+                    None => Ok(secret.into()),
+                }
+            }
+            SecretReference::ParameterStore { name } => {
+                let output = self
+                    .ssm
+                    .get_parameter()
+                    .name(name)
+                    .with_decryption(true)
+                    .send()
+                    .await?;
+
+                output
+                    .parameter()
+                    .and_then(|parameter| parameter.value())
+                    .map(Into::into)
+                    .ok_or_else(|| anyhow!("parameter {reference} has no value"))
+            }
+        }
+    }
+}
+
+fn json_secret_value(secret: &str, json_key: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(secret).ok()?;
+
+    value.get(json_key)?.as_str().map(Into::into)
+}
+
+/// Caches resolved secrets for a TTL so rotated values are picked up without
+/// fetching on every use. If a refresh fails, the last value is kept. Fetches
+/// don't hold the cache lock, so concurrent misses may fetch the same secret.
+pub struct SecretResolver<S> {
+    source: S,
+    ttl: Duration,
+    cache: Mutex<HashMap<SecretReference, CachedSecret>>,
+}
+
+struct CachedSecret {
+    value: String,
+    fetched_at: Instant,
+}
+
+impl CachedSecret {
+    fn is_expired(&self, ttl: Duration) -> bool {
+        self.fetched_at.elapsed() >= ttl
+    }
+}
+
+impl<S: SecretSource> SecretResolver<S> {
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            ttl: DEFAULT_SECRET_TTL,
+            cache: Default::default(),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub async fn resolve(&self, reference: &SecretReference) -> Result<String> {
+        let stale_value = match self.cache.lock().await.get(reference) {
+            Some(cached) if !cached.is_expired(self.ttl) => return Ok(cached.value.clone()),
+            cached => cached.map(|cached| cached.value.clone()),
+        };
+
+        match self.source.fetch(reference).await {
+            Ok(value) => {
+                self.cache.lock().await.insert(
+                    reference.clone(),
+                    CachedSecret {
+                        value: value.clone(),
+                        fetched_at: Instant::now(),
+                    },
+                );
+
+                Ok(value)
+            }
+            Err(error) => match stale_value {
+                Some(value) => {
+                    warn!("failed to refresh secret {reference}, keeping cached value: {error}");
+
+                    Ok(value)
+                }
+                None => Err(error),
+            },
+        }
+    }
+
+    /// Forces the next `resolve` to fetch, e.g. after credentials were rejected.
+    pub async fn invalidate(&self, reference: &SecretReference) {
+        self.cache.lock().await.remove(reference);
+    }
+}
+
+/// Replaces every string value in the table that's a secret reference with the secret.
+pub async fn resolve_secrets<S: SecretSource>(
+    table: &mut Table,
+    resolver: &SecretResolver<S>,
+) -> Result<()> {
+    let mut values = table
+        .iter_mut()
+        .map(|(_, value)| value)
+        .collect::<Vec<&mut Value>>();
+
+    while let Some(value) = values.pop() {
+        match value {
+            Value::String(string) => {
+                if let Some(reference) = SecretReference::parse(string) {
+                    *string = resolver.resolve(&reference).await?;
+                }
+            }
+            Value::Table(table) => values.extend(table.iter_mut().map(|(_, value)| value)),
+            Value::Array(array) => values.extend(array.iter_mut()),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct CountingSource(AtomicUsize);
+
+    impl SecretSource for CountingSource {
+        async fn fetch(&self, reference: &SecretReference) -> Result<String> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+
+            Ok(format!("secret for {reference}"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_secrets() {
+        let mut table: Table = r#"
+            [database]
+            url = "aws-ssm:///medici/database-url"
+
+            [openai]
+            api_key = "aws-sm://medici#openai"
+            fallback_key = "aws-sm://medici#openai"
+        "#
+        .parse()
+        .unwrap();
+
+        let resolver = SecretResolver::new(CountingSource::default());
+        resolve_secrets(&mut table, &resolver).await.unwrap();
+
+        assert_eq!(
+            table["database"]["url"].as_str(),
+            Some("secret for aws-ssm:///medici/database-url")
+        );
+        assert_eq!(
+            table["openai"]["api_key"].as_str(),
+            Some("secret for aws-sm://medici#openai")
+        );
+        assert_eq!(resolver.source.0.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_ttl() {
+        let reference = SecretReference::parse("aws-ssm:///medici/database-url").unwrap();
+
+        let resolver = SecretResolver::new(CountingSource::default());
+        resolver.resolve(&reference).await.unwrap();
+        resolver.resolve(&reference).await.unwrap();
+
+        assert_eq!(resolver.source.0.load(Ordering::SeqCst), 1);
+
+        let resolver = SecretResolver::new(CountingSource::default()).with_ttl(Duration::ZERO);
+        resolver.resolve(&reference).await.unwrap();
+        resolver.resolve(&reference).await.unwrap();
+
+        assert_eq!(resolver.source.0.load(Ordering::SeqCst), 2);
+    }
+
+    /// Never answers for `aws-ssm://slow`.
+    struct SlowSource;
+
+    impl SecretSource for SlowSource {
+        async fn fetch(&self, reference: &SecretReference) -> Result<String> {
+            if reference.to_string() == "aws-ssm://slow" {
+                std::future::pending::<()>().await;
+            }
+
+            Ok(reference.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_fetch_doesnt_block() {
+        let resolver = SecretResolver::new(SlowSource);
+        let slow = SecretReference::parse("aws-ssm://slow").unwrap();
+        let fast = SecretReference::parse("aws-ssm://fast").unwrap();
+
+        let value = tokio::time::timeout(Duration::from_secs(1), async {
+            tokio::select! {
+                biased;
+                _ = resolver.resolve(&slow) => unreachable!(),
+                value = resolver.resolve(&fast) => value,
+            }
+        })
+        .await
+        .expect("resolving was blocked by the slow fetch");
+
+        assert_eq!(value.unwrap(), "aws-ssm://fast");
+    }
+
+    #[test]
+    fn test_json_secret_value() {
+        assert_eq!(
+            json_secret_value(r#"{"openai": "sk-test"}"#, "openai"),
+            Some("sk-test".into())
+        );
+        assert_eq!(json_secret_value("sk-test", "openai"), None);
+    }
+}
@@ -0,0 +1,163 @@
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, bail, Result};
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub const KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 24;
+
+static DEFAULT_KEY: OnceLock<EncryptionKey> = OnceLock::new();
+
+#[derive(Clone)]
+pub struct EncryptionKey([u8; KEY_LEN]);
+
+impl EncryptionKey {
+    pub fn new(bytes: [u8; KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let bytes = base64::engine::general_purpose::STANDARD.decode(encoded.trim())?;
+
+        let Ok(bytes) = <[u8; KEY_LEN]>::try_from(bytes) else {
+            bail!("encryption key must be {KEY_LEN} bytes long");
+        };
+
+        Ok(Self(bytes))
+    }
+
+    pub fn generate() -> Self {
+        Self(XChaCha20Poly1305::generate_key(&mut OsRng).into())
+    }
+
+    /// Sets the key used by `SensitiveString`, once per process.
+    pub fn set_default(self) -> Result<()> {
+        DEFAULT_KEY
+            .set(self)
+            .map_err(|_| anyhow!("default encryption key already set"))
+    }
+
+    pub fn default_key() -> Result<&'static Self> {
+        DEFAULT_KEY
+            .get()
+            .ok_or_else(|| anyhow!("default encryption key not set"))
+    }
+}
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EncryptionKey(..)")
+    }
+}
+
+/// Encrypts with XChaCha20-Poly1305, prefixing the random nonce to the ciphertext.
+pub fn seal(plaintext: &[u8], key: &EncryptionKey) -> Result<Vec<u8>> {
+    let cipher = XChaCha20Poly1305::new(&key.0.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow!("failed to encrypt"))?;
+
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+pub fn open(sealed: &[u8], key: &EncryptionKey) -> Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        bail!("sealed data is too short");
+    }
+
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let cipher = XChaCha20Poly1305::new(&key.0.into());
+
+    cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("failed to decrypt"))
+}
+
+/// String encrypted with the default key whenever it's serialized, so structs
+/// cached as JSON (e.g. through `ValkeyString`) don't store it in plaintext.
+#[derive(PartialEq, Eq, Clone)]
+pub struct SensitiveString(String);
+
+impl SensitiveString {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl From<String> for SensitiveString {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl std::fmt::Debug for SensitiveString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SensitiveString(..)")
+    }
+}
+
+impl Serialize for SensitiveString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let sealed = EncryptionKey::default_key()
+            .and_then(|key| seal(self.0.as_bytes(), key))
+            .map_err(serde::ser::Error::custom)?;
+
+        serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(sealed))
+    }
+}
+
+impl<'de> Deserialize<'de> for SensitiveString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+
+        let value = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(anyhow::Error::from)
+            .and_then(|sealed| open(&sealed, EncryptionKey::default_key()?))
+            .and_then(|plaintext| Ok(String::from_utf8(plaintext)?))
+            .map_err(serde::de::Error::custom)?;
+
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let key = EncryptionKey::generate();
+        let sealed = seal(b"user@medici.uy", &key).unwrap();
+
+        assert_eq!(open(&sealed, &key).unwrap(), b"user@medici.uy");
+        assert!(open(&sealed, &EncryptionKey::generate()).is_err());
+    }
+
+    #[test]
+    fn test_sensitive_string() {
+        EncryptionKey::generate().set_default().unwrap();
+
+        let value = SensitiveString::new("user@medici.uy".into());
+        let json = serde_json::to_string(&value).unwrap();
+
+        assert!(!json.contains("medici"));
+        assert_eq!(
+            serde_json::from_str::<SensitiveString>(&json).unwrap(),
+            value
+        );
+    }
+}
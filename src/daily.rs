@@ -0,0 +1,115 @@
+//! Question of the day. The pick only depends on its inputs, so every service
+//! selects the same question for a course and date without coordinating.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::sync::{CourseData, QuestionData};
+
+/// Questions seen within this many days aren't picked again, unless every
+/// question was.
+pub const RECENTLY_SEEN_DAYS: i64 = 30;
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+pub struct UserHistory {
+    /// Last date each question was answered or shown to the user.
+    pub last_seen: HashMap<Uuid, NaiveDate>,
+    /// Questions no longer offered, e.g. after errata.
+    pub retired: HashSet<Uuid>,
+}
+
+impl UserHistory {
+    /// Seen before `date` within `RECENTLY_SEEN_DAYS`. Seeing the question of the
+    /// day on its own date doesn't count, so picking it again gives the same one.
+    pub fn was_seen_recently(&self, question_id: Uuid, date: NaiveDate) -> bool {
+        self.last_seen.get(&question_id).is_some_and(|seen_on| {
+            *seen_on < date && date - *seen_on < TimeDelta::days(RECENTLY_SEEN_DAYS)
+        })
+    }
+}
+
+/// Picks the question of the day, preferring one the user hasn't seen recently
+/// and falling back to the one seen longest ago. Retired questions are never picked.
+pub fn pick<'a>(
+    course: &'a CourseData,
+    date: NaiveDate,
+    user_history: &UserHistory,
+) -> Option<&'a QuestionData> {
+    let mut candidates = course
+        .questions
+        .iter()
+        .filter(|question| !user_history.retired.contains(&question.id))
+        .collect::<Vec<_>>();
+
+    candidates.sort_by_cached_key(|question| seeded_hash(&course.key, date, question.id));
+
+    candidates
+        .iter()
+        .find(|question| !user_history.was_seen_recently(question.id, date))
+        .or_else(|| {
+            candidates
+                .iter()
+                .min_by_key(|question| user_history.last_seen.get(&question.id))
+        })
+        .copied()
+}
+
+fn seeded_hash(course_key: &str, date: NaiveDate, question_id: Uuid) -> [u8; blake3::OUT_LEN] {
+    blake3::hash(
+        &[
+            course_key.as_bytes(),
+            date.to_string().as_bytes(),
+            question_id.as_bytes(),
+        ]
+        .concat(),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+
+    #[test]
+    fn test_pick() {
+        let mut course = Faker.fake::<CourseData>();
+        course.questions = fake::vec![QuestionData; 5];
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let mut history = UserHistory::default();
+
+        let picked = pick(&course, date, &history).unwrap().id;
+
+        assert_eq!(pick(&course, date, &history).unwrap().id, picked);
+
+        history.last_seen.insert(picked, date);
+
+        assert_eq!(pick(&course, date, &history).unwrap().id, picked);
+
+        history.last_seen.insert(picked, date - TimeDelta::days(3));
+
+        let next = pick(&course, date, &history).unwrap().id;
+
+        assert_ne!(next, picked);
+
+        for question in &course.questions {
+            history
+                .last_seen
+                .insert(question.id, date - TimeDelta::days(1));
+        }
+        history.last_seen.insert(next, date - TimeDelta::days(10));
+        history.retired.insert(picked);
+
+        assert_eq!(pick(&course, date, &history).unwrap().id, next);
+
+        history
+            .retired
+            .extend(course.questions.iter().map(|question| question.id));
+
+        assert!(pick(&course, date, &history).is_none());
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::sync::{CourseData, LanguageTag};
+use crate::traits::EmailTemplate;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct UserProgress {
+    pub user_id: Uuid,
+    pub locale: LanguageTag,
+    pub course_keys: Vec<String>,
+    pub streak_days: u16,
+    pub last_study_date: Option<NaiveDate>,
+    /// Reviews due by course key.
+    pub due_reviews: BTreeMap<String, usize>,
+    pub last_digest: Option<DigestSnapshot>,
+}
+
+/// Question hashes by ID as of the last digest, used to find what changed since.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Default, Clone, Debug)]
+pub struct DigestSnapshot {
+    pub sent_at: Option<DateTime<Utc>>,
+    pub question_hashes: HashMap<Uuid, String>,
+}
+
+#[derive(strum::Display, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum DigestPeriod {
+    Daily,
+    Weekly,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum StreakStatus {
+    /// Studied today.
+    Active {
+        days: u16,
+    },
+    /// Last studied yesterday, so the streak ends unless the user studies today.
+    AtRisk {
+        days: u16,
+    },
+    None,
+}
+
+impl StreakStatus {
+    pub fn new(streak_days: u16, last_study_date: Option<NaiveDate>, today: NaiveDate) -> Self {
+        match last_study_date {
+            _ if streak_days == 0 => Self::None,
+            Some(date) if date >= today => Self::Active { days: streak_days },
+            Some(date) if date + TimeDelta::days(1) == today => Self::AtRisk { days: streak_days },
+            _ => Self::None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct DigestCourse {
+    pub course_key: String,
+    pub course_name: String,
+    pub due_reviews: usize,
+    pub new_question_count: usize,
+    pub updated_question_count: usize,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct StudyDigest {
+    pub period: DigestPeriod,
+    pub locale: LanguageTag,
+    pub streak: StreakStatus,
+    pub due_review_count: usize,
+    pub new_question_count: usize,
+    pub courses: Vec<DigestCourse>,
+    /// To be stored as the user's `last_digest` once the email is sent.
+    #[serde(skip)]
+    pub snapshot: DigestSnapshot,
+}
+
+impl StudyDigest {
+    /// Whether there is nothing worth sending.
+    pub fn is_empty(&self) -> bool {
+        self.due_review_count == 0
+            && self.new_question_count == 0
+            && self
+                .courses
+                .iter()
+                .all(|course| course.updated_question_count == 0)
+            && !matches!(self.streak, StreakStatus::AtRisk { .. })
+    }
+}
+
+impl EmailTemplate for StudyDigest {
+    const TEMPLATE_NAME: &'static str = "study_digest";
+}
+
+pub fn build_daily(progress: &UserProgress, courses: &[CourseData]) -> StudyDigest {
+    build_at(DigestPeriod::Daily, progress, courses, Utc::now())
+}
+
+pub fn build_weekly(progress: &UserProgress, courses: &[CourseData]) -> StudyDigest {
+    build_at(DigestPeriod::Weekly, progress, courses, Utc::now())
+}
+
+/// Only the user's courses are included. Without a previous digest, no
+/// questions count as new, so the first digest doesn't list whole courses.
+pub fn build_at(
+    period: DigestPeriod,
+    progress: &UserProgress,
+    courses: &[CourseData],
+    now: DateTime<Utc>,
+) -> StudyDigest {
+    let mut snapshot = DigestSnapshot {
+        sent_at: Some(now),
+        question_hashes: HashMap::new(),
+    };
+
+    let digest_courses = courses
+        .iter()
+        .filter(|course| progress.course_keys.contains(&course.key))
+        .map(|course| {
+            let mut digest_course = DigestCourse {
+                course_key: course.key.clone(),
+                course_name: course.name.clone(),
+                due_reviews: progress
+                    .due_reviews
+                    .get(&course.key)
+                    .copied()
+                    .unwrap_or_default(),
+                new_question_count: 0,
+                updated_question_count: 0,
+            };
+
+            for question in &course.questions {
+                if let Some(last_digest) = &progress.last_digest {
+                    match last_digest.question_hashes.get(&question.id) {
+                        None => digest_course.new_question_count += 1,
+                        Some(hash) if *hash != question.hash => {
+                            digest_course.updated_question_count += 1
+                        }
+                        Some(_) => {}
+                    }
+                }
+
+                snapshot
+                    .question_hashes
+                    .insert(question.id, question.hash.clone());
+            }
+
+            digest_course
+        })
+        .collect::<Vec<_>>();
+
+    StudyDigest {
+        period,
+        locale: progress.locale.clone(),
+        streak: StreakStatus::new(
+            progress.streak_days,
+            progress.last_study_date,
+            now.date_naive(),
+        ),
+        due_review_count: digest_courses.iter().map(|course| course.due_reviews).sum(),
+        new_question_count: digest_courses
+            .iter()
+            .map(|course| course.new_question_count)
+            .sum(),
+        courses: digest_courses,
+        snapshot,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+
+    #[test]
+    fn test_build_at() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![_; 3];
+
+        let now = Utc::now();
+        let mut progress = UserProgress {
+            user_id: Uuid::new_v4(),
+            locale: LanguageTag::default(),
+            course_keys: vec![course.key.clone()],
+            streak_days: 4,
+            last_study_date: Some(now.date_naive() - TimeDelta::days(1)),
+            due_reviews: BTreeMap::from([(course.key.clone(), 5)]),
+            last_digest: None,
+        };
+
+        let digest = build_at(DigestPeriod::Weekly, &progress, &[course.clone()], now);
+
+        assert_eq!(digest.new_question_count, 0);
+        assert_eq!(digest.due_review_count, 5);
+        assert_eq!(digest.streak, StreakStatus::AtRisk { days: 4 });
+
+        progress.last_digest = Some(digest.snapshot);
+        course.questions[0].hash = "changed".into();
+        course.questions.push(Faker.fake());
+
+        let digest = build_at(DigestPeriod::Weekly, &progress, &[course], now);
+
+        assert_eq!(digest.new_question_count, 1);
+        assert_eq!(digest.courses[0].updated_question_count, 1);
+    }
+}
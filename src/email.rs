@@ -0,0 +1,71 @@
+use std::future::Future;
+
+#[cfg(feature = "aws")]
+use anyhow::anyhow;
+use anyhow::Result;
+#[cfg(feature = "aws")]
+use aws_sdk_sesv2::types::Destination;
+#[cfg(feature = "aws")]
+use tracing::debug;
+
+#[cfg(feature = "aws")]
+use crate::config::EmailConfig;
+use crate::redact::Email;
+use crate::traits::EmailTemplate;
+
+pub trait EmailSender: Sync {
+    fn send<T: EmailTemplate + Send>(
+        &self,
+        to: &Email,
+        template: T,
+    ) -> impl Future<Output = Result<()>> + Send;
+}
+
+#[cfg(feature = "aws")]
+#[derive(Clone, Debug)]
+pub struct SesEmailSender {
+    client: aws_sdk_sesv2::Client,
+    from_address: String,
+    configuration_set: Option<String>,
+}
+
+#[cfg(feature = "aws")]
+impl SesEmailSender {
+    pub fn new(client: aws_sdk_sesv2::Client, config: &EmailConfig) -> Result<Self> {
+        Ok(Self {
+            client,
+            from_address: config
+                .from_address
+                .clone()
+                .ok_or_else(|| anyhow!("missing email from address"))?,
+            configuration_set: config.configuration_set.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "aws")]
+impl EmailSender for SesEmailSender {
+    async fn send<T: EmailTemplate + Send>(&self, to: &Email, template: T) -> Result<()> {
+        let mut request = self
+            .client
+            .send_email()
+            .from_email_address(&self.from_address)
+            .destination(Destination::builder().to_addresses(to.expose()).build())
+            .content(template.email_content());
+
+        if let Some(configuration_set) = &self.configuration_set {
+            request = request.configuration_set_name(configuration_set);
+        }
+
+        let output = request.send().await?;
+
+        debug!(
+            to = %to,
+            template = T::TEMPLATE_NAME,
+            message_id = output.message_id(),
+            "sent email"
+        );
+
+        Ok(())
+    }
+}
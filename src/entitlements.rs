@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Access to a course granted to a user, either through a payment or by hand.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+pub struct CourseEntitlement {
+    pub user_id: Uuid,
+    pub course_key: String,
+    /// `None` for manual grants, such as scholarships or support credits.
+    pub provider_payment_id: Option<String>,
+    pub granted_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+}
+
+impl CourseEntitlement {
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    pub fn grants_access_at(&self, now: DateTime<Utc>) -> bool {
+        self.is_active && !self.is_expired_at(now)
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::sync::format_text;
+
+pub const MAX_ERRATA_TEXT_LENGTH: usize = 2000;
+
+#[derive(
+    strum::Display,
+    strum::EnumString,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    Debug,
+)]
+#[cfg_attr(
+    feature = "db",
+    derive(sqlx::Type),
+    sqlx(type_name = "text", rename_all = "snake_case")
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ErrataCategory {
+    WrongAnswer,
+    Typo,
+    Outdated,
+    ImageBroken,
+}
+
+impl ErrataCategory {
+    /// Weight of a report when ranking questions for triage; a wrong answer
+    /// misleads students, a typo doesn't.
+    pub fn severity(&self) -> usize {
+        match self {
+            Self::WrongAnswer => 3,
+            Self::Outdated | Self::ImageBroken => 2,
+            Self::Typo => 1,
+        }
+    }
+}
+
+#[derive(
+    strum::Display,
+    strum::EnumString,
+    Serialize,
+    Deserialize,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    Debug,
+)]
+#[cfg_attr(
+    feature = "db",
+    derive(sqlx::Type),
+    sqlx(type_name = "text", rename_all = "snake_case")
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ErrataStatus {
+    #[default]
+    Open,
+    Confirmed,
+    Dismissed,
+    Fixed,
+}
+
+/// A problem with a question reported by a user.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
+pub struct ErrataReport {
+    pub id: Uuid,
+    pub question_id: Uuid,
+    pub user_id: Uuid,
+    pub category: ErrataCategory,
+    pub text: Option<String>,
+    pub status: ErrataStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ErrataReport {
+    pub fn new(
+        question_id: Uuid,
+        user_id: Uuid,
+        category: ErrataCategory,
+        text: Option<String>,
+        created_at: DateTime<Utc>,
+    ) -> Result<Self> {
+        let mut data = Self {
+            id: Uuid::new_v4(),
+            question_id,
+            user_id,
+            category,
+            text,
+            status: ErrataStatus::Open,
+            created_at,
+        };
+
+        data.process()?;
+
+        Ok(data)
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        self.text = self
+            .text
+            .as_deref()
+            .map(format_text)
+            .filter(|text| !text.is_empty());
+
+        if let Some(text) = &self.text {
+            if text.chars().count() > MAX_ERRATA_TEXT_LENGTH {
+                bail!("errata text longer than {MAX_ERRATA_TEXT_LENGTH} characters");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Open and confirmed reports still need work.
+    pub fn is_pending(&self) -> bool {
+        matches!(self.status, ErrataStatus::Open | ErrataStatus::Confirmed)
+    }
+}
+
+/// Pending reports grouped by question, most urgent first.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+pub struct ErrataSummary {
+    pub questions: Vec<QuestionErrata>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct QuestionErrata {
+    pub question_id: Uuid,
+    /// Distinct users per category, so repeated reports by one user count once.
+    pub reporters_by_category: BTreeMap<ErrataCategory, usize>,
+    pub report_count: usize,
+    pub first_reported_at: DateTime<Utc>,
+    pub last_reported_at: DateTime<Utc>,
+}
+
+impl QuestionErrata {
+    pub fn reporter_count(&self) -> usize {
+        self.reporters_by_category.values().sum()
+    }
+
+    pub fn priority(&self) -> usize {
+        self.reporters_by_category
+            .iter()
+            .map(|(category, reporters)| category.severity() * reporters)
+            .sum()
+    }
+}
+
+impl ErrataSummary {
+    /// Ignores dismissed and fixed reports.
+    pub fn aggregate<'a>(reports: impl IntoIterator<Item = &'a ErrataReport>) -> Self {
+        let mut by_question = HashMap::<Uuid, Vec<&ErrataReport>>::new();
+
+        for report in reports.into_iter().filter(|report| report.is_pending()) {
+            by_question
+                .entry(report.question_id)
+                .or_default()
+                .push(report);
+        }
+
+        let mut questions = by_question
+            .into_iter()
+            .map(|(question_id, reports)| {
+                let mut reporters = HashMap::<ErrataCategory, HashSet<Uuid>>::new();
+
+                for report in &reports {
+                    reporters
+                        .entry(report.category)
+                        .or_default()
+                        .insert(report.user_id);
+                }
+
+                QuestionErrata {
+                    question_id,
+                    reporters_by_category: reporters
+                        .into_iter()
+                        .map(|(category, users)| (category, users.len()))
+                        .collect(),
+                    report_count: reports.len(),
+                    first_reported_at: reports
+                        .iter()
+                        .map(|report| report.created_at)
+                        .min()
+                        .unwrap(),
+                    last_reported_at: reports
+                        .iter()
+                        .map(|report| report.created_at)
+                        .max()
+                        .unwrap(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        questions.sort_by(|a, b| {
+            b.priority()
+                .cmp(&a.priority())
+                .then(a.first_reported_at.cmp(&b.first_reported_at))
+                .then(a.question_id.cmp(&b.question_id))
+        });
+
+        Self { questions }
+    }
+
+    /// Questions whose reports reach `min_priority`, for the triage queue.
+    pub fn needing_triage(&self, min_priority: usize) -> Vec<&QuestionErrata> {
+        self.questions
+            .iter()
+            .filter(|question| question.priority() >= min_priority)
+            .collect()
+    }
+
+    pub fn question(&self, question_id: Uuid) -> Option<&QuestionErrata> {
+        self.questions
+            .iter()
+            .find(|question| question.question_id == question_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_aggregate() {
+        let now = Utc::now();
+        let (typo_question, wrong_question) = (Uuid::new_v4(), Uuid::new_v4());
+        let (user_a, user_b) = (Uuid::new_v4(), Uuid::new_v4());
+        let report = |question_id, user_id, category, minutes| {
+            ErrataReport::new(
+                question_id,
+                user_id,
+                category,
+                Some("  La respuesta  correcta es la C ".into()),
+                now - Duration::minutes(minutes),
+            )
+            .unwrap()
+        };
+
+        let mut dismissed = report(wrong_question, user_b, ErrataCategory::Typo, 1);
+        dismissed.status = ErrataStatus::Dismissed;
+
+        let reports = [
+            report(typo_question, user_a, ErrataCategory::Typo, 30),
+            report(typo_question, user_b, ErrataCategory::Typo, 20),
+            report(wrong_question, user_a, ErrataCategory::WrongAnswer, 10),
+            report(wrong_question, user_a, ErrataCategory::WrongAnswer, 5),
+            dismissed,
+        ];
+
+        assert_eq!(
+            reports[0].text.as_deref(),
+            Some("La respuesta correcta es la C")
+        );
+
+        let summary = ErrataSummary::aggregate(&reports);
+
+        assert_eq!(summary.questions.len(), 2);
+        assert_eq!(summary.questions[0].question_id, wrong_question);
+        assert_eq!(summary.questions[0].report_count, 2);
+        assert_eq!(summary.questions[0].reporter_count(), 1);
+        assert_eq!(summary.question(typo_question).unwrap().priority(), 2);
+        assert_eq!(summary.needing_triage(3).len(), 1);
+    }
+}
@@ -0,0 +1,13 @@
+#[cfg(all(feature = "compression", feature = "crypto"))]
+pub mod offline;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+mod sitemap;
+#[cfg(feature = "ts_types")]
+mod typescript;
+
+#[cfg(all(feature = "compression", feature = "crypto"))]
+pub use offline::{offline_package, verify_package, verify_signed_package};
+pub use sitemap::*;
+#[cfg(feature = "ts_types")]
+pub use typescript::*;
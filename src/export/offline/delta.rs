@@ -0,0 +1,229 @@
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+    from_sealed_bytes, sign_manifest, to_sealed_bytes, ManifestSignature, ManifestSigner,
+    OfflinePackage, OfflineQuestion, PackageManifest, OFFLINE_PACKAGE_FORMAT_VERSION,
+};
+use crate::crypto::EncryptionKey;
+use crate::sync::CourseData;
+
+pub const DELTA_PACKAGE_MAGIC: [u8; 4] = *b"MODL";
+
+/// Changes from a package to the one for a newer version of its course, so
+/// the app only downloads what changed.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct DeltaPackage {
+    /// Content hash of the package the delta applies to.
+    pub base_content_hash: String,
+    pub manifest: PackageManifest,
+    /// New questions and those whose source question's hash changed, by ID.
+    pub questions: Vec<OfflineQuestion>,
+    pub removed_question_ids: BTreeSet<Uuid>,
+    pub added_image_paths: BTreeSet<String>,
+    pub removed_image_paths: BTreeSet<String>,
+    #[serde(default)]
+    pub added_media_paths: BTreeSet<String>,
+    #[serde(default)]
+    pub removed_media_paths: BTreeSet<String>,
+    /// Signature of the new manifest, carried over to the updated package.
+    #[serde(default)]
+    pub signature: Option<ManifestSignature>,
+}
+
+/// Delta from the package with `old_manifest` to a package of `new_course`
+/// with the same options.
+pub fn delta(old_manifest: &PackageManifest, new_course: &CourseData) -> Result<DeltaPackage> {
+    if old_manifest.format_version != OFFLINE_PACKAGE_FORMAT_VERSION {
+        bail!(
+            "can't compute a delta from offline package format version {}",
+            old_manifest.format_version
+        );
+    }
+
+    if old_manifest.course_key != new_course.key {
+        bail!(
+            "can't compute a delta from course {} to {}",
+            old_manifest.course_key,
+            new_course.key
+        );
+    }
+
+    let new_package = OfflinePackage::new(new_course, old_manifest.includes_answers)?;
+    let new_manifest = new_package.manifest;
+
+    Ok(DeltaPackage {
+        base_content_hash: old_manifest.content_hash.clone(),
+        questions: new_package
+            .questions
+            .into_iter()
+            .filter(|question| {
+                old_manifest.question_hashes.get(&question.id)
+                    != new_manifest.question_hashes.get(&question.id)
+            })
+            .collect(),
+        removed_question_ids: old_manifest
+            .question_hashes
+            .keys()
+            .filter(|id| !new_manifest.question_hashes.contains_key(id))
+            .copied()
+            .collect(),
+        added_image_paths: new_manifest
+            .image_paths
+            .difference(&old_manifest.image_paths)
+            .cloned()
+            .collect(),
+        removed_image_paths: old_manifest
+            .image_paths
+            .difference(&new_manifest.image_paths)
+            .cloned()
+            .collect(),
+        added_media_paths: new_manifest
+            .media_paths
+            .difference(&old_manifest.media_paths)
+            .cloned()
+            .collect(),
+        removed_media_paths: old_manifest
+            .media_paths
+            .difference(&new_manifest.media_paths)
+            .cloned()
+            .collect(),
+        manifest: new_manifest,
+        signature: None,
+    })
+}
+
+impl DeltaPackage {
+    pub fn is_empty(&self) -> bool {
+        self.questions.is_empty()
+            && self.removed_question_ids.is_empty()
+            && self.added_image_paths.is_empty()
+            && self.removed_image_paths.is_empty()
+            && self.added_media_paths.is_empty()
+            && self.removed_media_paths.is_empty()
+    }
+
+    pub fn sign(&mut self, signer: &ManifestSigner) -> Result<()> {
+        self.signature = Some(sign_manifest(&self.manifest, signer)?);
+
+        Ok(())
+    }
+
+    /// Checks that the changes agree with the new manifest.
+    pub fn verify(&self) -> Result<()> {
+        if self.manifest.format_version != OFFLINE_PACKAGE_FORMAT_VERSION {
+            bail!(
+                "unsupported offline package format version {}",
+                self.manifest.format_version
+            );
+        }
+
+        if self.questions.iter().any(|question| {
+            !self.manifest.question_hashes.contains_key(&question.id)
+                || self.removed_question_ids.contains(&question.id)
+        }) || self
+            .removed_question_ids
+            .iter()
+            .any(|id| self.manifest.question_hashes.contains_key(id))
+        {
+            bail!("offline package delta doesn't match its manifest");
+        }
+
+        Ok(())
+    }
+
+    /// Applies the delta to `package`, checking the result against the new manifest.
+    pub fn apply(&self, package: &OfflinePackage) -> Result<OfflinePackage> {
+        if package.manifest.content_hash != self.base_content_hash {
+            bail!("offline package delta doesn't apply to this package");
+        }
+
+        let changed_ids = self
+            .questions
+            .iter()
+            .map(|question| question.id)
+            .collect::<BTreeSet<_>>();
+        let mut questions = package
+            .questions
+            .iter()
+            .filter(|question| {
+                !changed_ids.contains(&question.id)
+                    && !self.removed_question_ids.contains(&question.id)
+            })
+            .cloned()
+            .chain(self.questions.iter().cloned())
+            .collect::<Vec<_>>();
+        questions.sort_by_key(|question| question.id);
+
+        let updated = OfflinePackage {
+            manifest: self.manifest.clone(),
+            questions,
+            signature: self.signature.clone(),
+        };
+        updated.verify()?;
+
+        Ok(updated)
+    }
+
+    pub fn to_bytes(&self, key: &EncryptionKey) -> Result<Vec<u8>> {
+        to_sealed_bytes(self, DELTA_PACKAGE_MAGIC, key)
+    }
+
+    pub fn from_bytes(bytes: &[u8], key: &EncryptionKey) -> Result<Self> {
+        from_sealed_bytes(bytes, DELTA_PACKAGE_MAGIC, key)
+    }
+}
+
+/// Opens a delta, rejecting it unless its changes match its manifest.
+pub fn verify_delta(bytes: &[u8], key: &EncryptionKey) -> Result<DeltaPackage> {
+    let delta = DeltaPackage::from_bytes(bytes, key)?;
+    delta.verify()?;
+
+    Ok(delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::sync::QuestionData;
+    use crate::traits::Hashable;
+
+    #[test]
+    fn test_delta() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![QuestionData; 4];
+
+        for question in &mut course.questions {
+            question.course_key = course.key.clone();
+        }
+
+        let package = OfflinePackage::new(&course, true).unwrap();
+
+        assert!(delta(&package.manifest, &course).unwrap().is_empty());
+
+        let removed = course.questions.remove(0);
+        course.questions[0].text = "Texto corregido".into();
+        course.questions[0].refresh_hash();
+        course.questions.push(Faker.fake());
+        course.questions[3].course_key = course.key.clone();
+
+        let delta = delta(&package.manifest, &course).unwrap();
+        let key = EncryptionKey::generate();
+        let delta = verify_delta(&delta.to_bytes(&key).unwrap(), &key).unwrap();
+
+        assert_eq!(delta.questions.len(), 2);
+        assert_eq!(delta.removed_question_ids, [removed.id].into());
+        assert_eq!(
+            delta.apply(&package).unwrap(),
+            OfflinePackage::new(&course, true).unwrap()
+        );
+        assert!(delta
+            .apply(&OfflinePackage::new(&course, false).unwrap())
+            .is_err());
+    }
+}
@@ -0,0 +1,650 @@
+//! Courses packaged for offline use in the mobile app. A package is a header
+//! (magic, format version) followed by the MessagePack encoding of an
+//! `OfflinePackage`, compressed with zstd and sealed with the package key.
+//! Deltas between packages are encoded the same way.
+
+mod delta;
+mod signing;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::crypto::{open, seal, EncryptionKey};
+use crate::sync::{
+    CaseData, CourseData, LicenseData, MediaKind, NumericAnswer, QuestionData, QuestionKind,
+};
+
+pub use delta::*;
+pub use signing::*;
+
+pub const OFFLINE_PACKAGE_MAGIC: [u8; 4] = *b"MOFF";
+pub const OFFLINE_PACKAGE_FORMAT_VERSION: u8 = 1;
+pub const OFFLINE_PACKAGE_COMPRESSION_LEVEL: i32 = 19;
+
+const HEADER_LEN: usize = 4 + 1;
+
+#[derive(Clone, Debug)]
+pub struct OfflinePackageOptions {
+    pub key: EncryptionKey,
+    /// Whether options say if they're correct and questions have explanations.
+    /// Left out for exam simulations that are graded online, so the answers
+    /// can't be read from the package.
+    pub include_answers: bool,
+    pub signer: Option<ManifestSigner>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct OfflinePackage {
+    pub manifest: PackageManifest,
+    /// By ID.
+    pub questions: Vec<OfflineQuestion>,
+    #[serde(default)]
+    pub signature: Option<ManifestSignature>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct PackageManifest {
+    pub format_version: u8,
+    pub course_key: String,
+    pub course_hash: String,
+    pub includes_answers: bool,
+    /// Hash of the source question of each question in the package, combined
+    /// with the hash of its case for questions in a case.
+    pub question_hashes: BTreeMap<Uuid, String>,
+    /// Paths of the images of the course, its questions and its cases, which
+    /// are downloaded separately.
+    pub image_paths: BTreeSet<String>,
+    /// Paths of the audio and video files of the questions, also downloaded
+    /// separately.
+    #[serde(default)]
+    pub media_paths: BTreeSet<String>,
+    /// BLAKE3 hash of the encoded questions of the package.
+    pub content_hash: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct OfflineQuestion {
+    pub id: Uuid,
+    pub topic: String,
+    pub text: String,
+    pub image_path: Option<String>,
+    pub alt_text: Option<String>,
+    /// By reference.
+    pub options: Vec<OfflineOption>,
+    #[serde(default)]
+    pub kind: OfflineKind,
+    pub explanation: Option<String>,
+    #[serde(default)]
+    pub media: Vec<OfflineMedia>,
+    /// Whether the app has to load its math renderer for the question.
+    #[serde(default)]
+    pub contains_math: bool,
+    #[serde(default)]
+    pub time_limit_seconds: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case: Option<OfflineCase>,
+    /// The question's license, or its course's when it has none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<LicenseData>,
+}
+
+/// Clinical case of a question, repeated in each of its questions so they can
+/// be shown on their own.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct OfflineCase {
+    pub id: Uuid,
+    pub text: String,
+    pub image_paths: Vec<String>,
+    /// Position of the question in the case, from 0.
+    pub position: usize,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct OfflineOption {
+    pub id: Uuid,
+    pub text: String,
+    /// `None` when the package doesn't include answers.
+    pub is_correct: Option<bool>,
+    pub explanation: Option<String>,
+}
+
+/// How the question is answered. Multiple choice questions are answered with
+/// their options; the other kinds have none.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OfflineKind {
+    #[default]
+    MultipleChoice,
+    Numeric {
+        unit: Option<String>,
+        /// `None` when the package doesn't include answers.
+        answer: Option<NumericAnswer>,
+    },
+    /// Left items in their order and right items in the order they're shown,
+    /// which comes from the question ID.
+    Matching {
+        left: Vec<String>,
+        right: Vec<String>,
+        /// Index in `right` of the match of each left item, or `None` when the
+        /// package doesn't include answers.
+        answer: Option<Vec<usize>>,
+    },
+    /// Items in the order they're shown, which comes from the question ID.
+    Ordering {
+        items: Vec<String>,
+        /// Indices in `items` in their correct sequence, or `None` when the
+        /// package doesn't include answers.
+        answer: Option<Vec<usize>>,
+    },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct OfflineMedia {
+    pub kind: MediaKind,
+    pub path: String,
+    pub duration_seconds: u32,
+    pub transcript: Option<String>,
+}
+
+impl OfflineQuestion {
+    /// `course` is the course of `question`, whose case and license it takes.
+    pub fn new(question: &QuestionData, course: &CourseData, include_answers: bool) -> Self {
+        let mut options = question.question_options.iter().collect::<Vec<_>>();
+        options.sort_by_key(|option| option.reference);
+
+        Self {
+            id: question.id,
+            topic: question.topic.name.clone(),
+            text: question.text.clone(),
+            image_path: question.full_image_path(),
+            alt_text: question.alt_text.clone(),
+            options: options
+                .into_iter()
+                .map(|option| OfflineOption {
+                    id: option.id,
+                    text: option.text.clone(),
+                    is_correct: include_answers.then_some(option.is_correct),
+                    explanation: option.explanation.clone().filter(|_| include_answers),
+                })
+                .collect(),
+            kind: OfflineKind::new(question, include_answers),
+            explanation: question
+                .explanation
+                .as_ref()
+                .filter(|_| include_answers)
+                .map(|explanation| explanation.text.clone()),
+            media: question
+                .media
+                .iter()
+                .map(|attachment| OfflineMedia {
+                    kind: attachment.kind,
+                    path: attachment.full_path(&question.course_key),
+                    duration_seconds: attachment.duration_seconds,
+                    transcript: attachment.transcript.clone(),
+                })
+                .collect(),
+            contains_math: question.contains_math(),
+            time_limit_seconds: question.time_limit_seconds,
+            case: course.case_of(question.id).map(|case| OfflineCase {
+                id: case.id,
+                text: case.text.clone(),
+                image_paths: case.full_image_paths(),
+                position: case
+                    .question_ids
+                    .iter()
+                    .position(|id| *id == question.id)
+                    .unwrap_or_default(),
+            }),
+            license: question.license.clone().or_else(|| course.license.clone()),
+        }
+    }
+}
+
+impl OfflineKind {
+    pub fn new(question: &QuestionData, include_answers: bool) -> Self {
+        let seed = question.id.as_bytes();
+
+        match &question.kind {
+            QuestionKind::MultipleChoice => Self::MultipleChoice,
+            QuestionKind::Numeric(answer) => Self::Numeric {
+                unit: answer.unit.clone(),
+                answer: include_answers.then(|| answer.clone()),
+            },
+            QuestionKind::Matching(answer) => {
+                let order = answer.right_order(seed);
+
+                Self::Matching {
+                    left: answer.pairs.iter().map(|pair| pair.left.clone()).collect(),
+                    right: order
+                        .iter()
+                        .map(|&pair| answer.pairs[pair].right.clone())
+                        .collect(),
+                    answer: include_answers.then(|| positions(&order, answer.pairs.len())),
+                }
+            }
+            QuestionKind::Ordering(answer) => {
+                let order = answer.presented_order(seed);
+
+                Self::Ordering {
+                    items: order
+                        .iter()
+                        .map(|&item| answer.items[item].clone())
+                        .collect(),
+                    answer: include_answers.then(|| positions(&order, answer.items.len())),
+                }
+            }
+        }
+    }
+}
+
+/// Position in `order` of each of the `count` indices it shuffles.
+fn positions(order: &[usize], count: usize) -> Vec<usize> {
+    (0..count)
+        .filter_map(|index| order.iter().position(|&item| item == index))
+        .collect()
+}
+
+impl OfflinePackage {
+    pub fn new(course: &CourseData, include_answers: bool) -> Result<Self> {
+        let mut questions = course.questions.iter().collect::<Vec<_>>();
+        questions.sort_by_key(|question| question.id);
+
+        let image_paths = std::iter::once(course.full_image_path())
+            .chain(
+                questions
+                    .iter()
+                    .filter_map(|question| question.full_image_path()),
+            )
+            .chain(course.cases.iter().flat_map(CaseData::full_image_paths))
+            .collect();
+        let media_paths = questions
+            .iter()
+            .flat_map(|question| {
+                question
+                    .media
+                    .iter()
+                    .map(|attachment| attachment.full_path(&question.course_key))
+            })
+            .collect();
+        let offline_questions = questions
+            .iter()
+            .map(|question| OfflineQuestion::new(question, course, include_answers))
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            manifest: PackageManifest {
+                format_version: OFFLINE_PACKAGE_FORMAT_VERSION,
+                course_key: course.key.clone(),
+                course_hash: course.hash.clone(),
+                includes_answers: include_answers,
+                question_hashes: questions
+                    .iter()
+                    .map(|question| {
+                        (
+                            question.id,
+                            source_hash(question, course.case_of(question.id)),
+                        )
+                    })
+                    .collect(),
+                image_paths,
+                media_paths,
+                content_hash: content_hash(&offline_questions)?,
+            },
+            questions: offline_questions,
+            signature: None,
+        })
+    }
+
+    pub fn sign(&mut self, signer: &ManifestSigner) -> Result<()> {
+        self.signature = Some(sign_manifest(&self.manifest, signer)?);
+
+        Ok(())
+    }
+
+    /// Checks that the questions match the manifest. The signature is checked
+    /// separately, with `verify_manifest`.
+    pub fn verify(&self) -> Result<()> {
+        if self.manifest.format_version != OFFLINE_PACKAGE_FORMAT_VERSION {
+            bail!(
+                "unsupported offline package format version {}",
+                self.manifest.format_version
+            );
+        }
+
+        if !self
+            .questions
+            .iter()
+            .map(|question| &question.id)
+            .eq(self.manifest.question_hashes.keys())
+        {
+            bail!("offline package questions don't match its manifest");
+        }
+
+        if content_hash(&self.questions)? != self.manifest.content_hash {
+            bail!("offline package content hash mismatch");
+        }
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self, key: &EncryptionKey) -> Result<Vec<u8>> {
+        to_sealed_bytes(self, OFFLINE_PACKAGE_MAGIC, key)
+    }
+
+    pub fn from_bytes(bytes: &[u8], key: &EncryptionKey) -> Result<Self> {
+        from_sealed_bytes(bytes, OFFLINE_PACKAGE_MAGIC, key)
+    }
+}
+
+pub fn offline_package(course: &CourseData, options: &OfflinePackageOptions) -> Result<Vec<u8>> {
+    let mut package = OfflinePackage::new(course, options.include_answers)?;
+
+    if let Some(signer) = &options.signer {
+        package.sign(signer)?;
+    }
+
+    package.to_bytes(&options.key)
+}
+
+/// Opens a package, rejecting it unless its contents match its manifest.
+pub fn verify_package(bytes: &[u8], key: &EncryptionKey) -> Result<OfflinePackage> {
+    let package = OfflinePackage::from_bytes(bytes, key)?;
+    package.verify()?;
+
+    Ok(package)
+}
+
+/// Like `verify_package`, also requiring a valid signature for `channel`.
+pub fn verify_signed_package(
+    bytes: &[u8],
+    key: &EncryptionKey,
+    channel: ReleaseChannel,
+) -> Result<OfflinePackage> {
+    let package = verify_package(bytes, key)?;
+
+    let Some(signature) = &package.signature else {
+        bail!("offline package isn't signed");
+    };
+
+    verify_manifest(&package.manifest, signature, channel)?;
+
+    Ok(package)
+}
+
+fn to_sealed_bytes<T: Serialize>(
+    value: &T,
+    magic: [u8; 4],
+    key: &EncryptionKey,
+) -> Result<Vec<u8>> {
+    let encoded = rmp_serde::to_vec_named(value)?;
+    let compressed = zstd::encode_all(encoded.as_slice(), OFFLINE_PACKAGE_COMPRESSION_LEVEL)?;
+
+    let mut bytes = Vec::with_capacity(HEADER_LEN + compressed.len());
+    bytes.extend(magic);
+    bytes.push(OFFLINE_PACKAGE_FORMAT_VERSION);
+    bytes.extend(seal(&compressed, key)?);
+
+    Ok(bytes)
+}
+
+fn from_sealed_bytes<T: DeserializeOwned>(
+    bytes: &[u8],
+    magic: [u8; 4],
+    key: &EncryptionKey,
+) -> Result<T> {
+    if bytes.len() < HEADER_LEN || bytes[..4] != magic {
+        bail!("invalid offline package header");
+    }
+
+    let version = bytes[4];
+
+    if version != OFFLINE_PACKAGE_FORMAT_VERSION {
+        bail!("unsupported offline package format version {version}");
+    }
+
+    let compressed = open(&bytes[HEADER_LEN..], key)?;
+    let encoded = zstd::decode_all(compressed.as_slice())?;
+
+    Ok(rmp_serde::from_slice(&encoded)?)
+}
+
+/// Hash of `question`, which changes along with its case's.
+fn source_hash(question: &QuestionData, case: Option<&CaseData>) -> String {
+    match case {
+        Some(case) => blake3::hash(format!("{}{}", question.hash, case.hash).as_bytes())
+            .to_hex()
+            .to_string(),
+        None => question.hash.clone(),
+    }
+}
+
+fn content_hash(questions: &[OfflineQuestion]) -> Result<String> {
+    Ok(blake3::hash(&rmp_serde::to_vec_named(questions)?)
+        .to_hex()
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::sync::{LicenseKind, MatchingAnswer, MatchingPair, MediaAttachment, OrderingAnswer};
+    use crate::traits::Hashable;
+
+    #[test]
+    fn test_offline_package() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![QuestionData; 3];
+
+        for question in &mut course.questions {
+            question.course_key = course.key.clone();
+
+            for option in &mut question.question_options {
+                option.is_correct = true;
+            }
+        }
+
+        course.questions[0].media =
+            vec![MediaAttachment::new(MediaKind::Audio, "soplo.mp3".into(), 12, None).unwrap()];
+
+        let answer =
+            NumericAnswer::new(Decimal::from(5), Some("mg".into()), Default::default()).unwrap();
+        course.questions[1].question_options.clear();
+        course.questions[1].kind = QuestionKind::Numeric(answer.clone());
+
+        let key = EncryptionKey::generate();
+        let options = OfflinePackageOptions {
+            key: key.clone(),
+            include_answers: false,
+            signer: None,
+        };
+        let bytes = offline_package(&course, &options).unwrap();
+        let package = verify_package(&bytes, &key).unwrap();
+
+        assert_eq!(package.questions.len(), 3);
+        assert_eq!(package.manifest.course_key, course.key);
+        assert!(package
+            .manifest
+            .image_paths
+            .contains(&course.full_image_path()));
+        assert_eq!(
+            package.manifest.media_paths,
+            [format!("{}/soplo.mp3", course.key)].into()
+        );
+        assert!(package
+            .questions
+            .iter()
+            .flat_map(|question| &question.options)
+            .all(|option| option.is_correct.is_none()));
+        assert!(package.questions.contains(&OfflineQuestion::new(
+            &course.questions[1],
+            &course,
+            false
+        )));
+        assert_eq!(
+            OfflineQuestion::new(&course.questions[1], &course, false).kind,
+            OfflineKind::Numeric {
+                unit: Some("mg".into()),
+                answer: None,
+            }
+        );
+        assert_eq!(
+            OfflineKind::new(&course.questions[1], true),
+            OfflineKind::Numeric {
+                unit: Some("mg".into()),
+                answer: Some(answer),
+            }
+        );
+        assert!(verify_package(&bytes, &EncryptionKey::generate()).is_err());
+
+        let mut tampered = package.clone();
+        tampered
+            .questions
+            .iter_mut()
+            .flat_map(|question| &mut question.options)
+            .next()
+            .unwrap()
+            .is_correct = Some(true);
+
+        assert!(tampered.verify().is_err());
+        assert!(verify_package(&tampered.to_bytes(&key).unwrap(), &key).is_err());
+    }
+
+    #[test]
+    fn test_cases() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![QuestionData; 3];
+
+        let topic = course.questions[0].topic.clone();
+
+        for question in &mut course.questions {
+            question.course_key = course.key.clone();
+            question.topic = topic.clone();
+        }
+
+        let case = CaseData::from_questions(
+            Uuid::new_v4(),
+            "Mujer de 40 años con disnea.".into(),
+            vec!["rx.png".into()],
+            &[&course.questions[2], &course.questions[0]],
+        )
+        .unwrap();
+        course.cases = vec![case.clone()];
+        course.license = Some(
+            LicenseData::new(
+                "Facultad de Medicina".into(),
+                LicenseKind::CcBy,
+                "Material cedido".into(),
+                None,
+            )
+            .unwrap(),
+        );
+
+        let package = OfflinePackage::new(&course, true).unwrap();
+        let offline_question = |id| {
+            package
+                .questions
+                .iter()
+                .find(|question| question.id == id)
+                .unwrap()
+        };
+
+        assert_eq!(
+            offline_question(course.questions[0].id).case,
+            Some(OfflineCase {
+                id: case.id,
+                text: case.text.clone(),
+                image_paths: vec![format!("{}/rx.png", course.key)],
+                position: 1,
+            })
+        );
+        assert!(offline_question(course.questions[1].id).case.is_none());
+        assert_eq!(
+            offline_question(course.questions[1].id).license,
+            course.license
+        );
+        assert!(package
+            .manifest
+            .image_paths
+            .contains(&format!("{}/rx.png", course.key)));
+        assert_eq!(
+            package.manifest.question_hashes[&course.questions[1].id],
+            course.questions[1].hash
+        );
+
+        let mut edited = course.clone();
+        edited.cases[0].text = "Mujer de 41 años con disnea.".into();
+        edited.cases[0].refresh_hash();
+        let edited_package = OfflinePackage::new(&edited, true).unwrap();
+
+        assert_ne!(
+            edited_package.manifest.question_hashes[&course.questions[2].id],
+            package.manifest.question_hashes[&course.questions[2].id]
+        );
+    }
+
+    #[test]
+    fn test_matching_and_ordering_kinds() {
+        let mut question: QuestionData = Faker.fake();
+        question.question_options.clear();
+        question.kind = QuestionKind::Matching(
+            MatchingAnswer::new(
+                [
+                    ("Furosemida", "Asa de Henle"),
+                    ("Tiazida", "Túbulo distal"),
+                    ("Amilorida", "Túbulo colector"),
+                ]
+                .into_iter()
+                .map(|(left, right)| MatchingPair {
+                    left: left.into(),
+                    right: right.into(),
+                })
+                .collect(),
+            )
+            .unwrap(),
+        );
+
+        let OfflineKind::Matching {
+            left,
+            right,
+            answer: Some(answer),
+        } = OfflineKind::new(&question, true)
+        else {
+            panic!("expected a matching kind with its answer");
+        };
+
+        assert_eq!(left, ["Furosemida", "Tiazida", "Amilorida"]);
+        assert_eq!(right[answer[1]], "Túbulo distal");
+        assert!(matches!(
+            OfflineKind::new(&question, false),
+            OfflineKind::Matching { answer: None, .. }
+        ));
+
+        question.kind = QuestionKind::Ordering(
+            OrderingAnswer::new(vec!["A".into(), "B".into(), "C".into(), "D".into()]).unwrap(),
+        );
+
+        let OfflineKind::Ordering {
+            items,
+            answer: Some(answer),
+        } = OfflineKind::new(&question, true)
+        else {
+            panic!("expected an ordering kind with its answer");
+        };
+
+        assert_ne!(items, ["A", "B", "C", "D"]);
+        assert_eq!(
+            answer
+                .iter()
+                .map(|&index| items[index].as_str())
+                .collect::<Vec<_>>(),
+            ["A", "B", "C", "D"]
+        );
+    }
+}
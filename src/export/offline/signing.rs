@@ -0,0 +1,185 @@
+//! Ed25519 signatures of package manifests. The manifest commits to the
+//! contents of the package through its content hash, so a package with altered
+//! answers doesn't verify even if it's sealed again with the app's key.
+
+use anyhow::{anyhow, bail, Result};
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use super::PackageManifest;
+
+/// Prefix of signed messages, so manifest signatures can't be reused elsewhere.
+const SIGNATURE_CONTEXT: &[u8] = b"medici-offline-manifest-v1";
+
+#[derive(
+    strum::Display, strum::EnumString, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ReleaseChannel {
+    Stable,
+    Beta,
+}
+
+impl ReleaseChannel {
+    /// Public key trusted for the channel, embedded at build time from
+    /// `MEDICI_STABLE_MANIFEST_PUBLIC_KEY` or `MEDICI_BETA_MANIFEST_PUBLIC_KEY`
+    /// (base64), so packages can't bring their own.
+    pub fn public_key(&self) -> Result<VerifyingKey> {
+        let encoded = match self {
+            Self::Stable => option_env!("MEDICI_STABLE_MANIFEST_PUBLIC_KEY"),
+            Self::Beta => option_env!("MEDICI_BETA_MANIFEST_PUBLIC_KEY"),
+        }
+        .ok_or_else(|| anyhow!("no manifest public key embedded for the {self} channel"))?;
+
+        decode_public_key(encoded)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ManifestSignature {
+    pub channel: ReleaseChannel,
+    /// Base64.
+    pub signature: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct ManifestSigner {
+    pub channel: ReleaseChannel,
+    pub key: SigningKey,
+}
+
+impl ManifestSigner {
+    pub fn from_base64(channel: ReleaseChannel, encoded: &str) -> Result<Self> {
+        let bytes = base64::engine::general_purpose::STANDARD.decode(encoded.trim())?;
+
+        let Ok(bytes) = <[u8; ed25519_dalek::SECRET_KEY_LENGTH]>::try_from(bytes) else {
+            bail!(
+                "manifest signing key must be {} bytes long",
+                ed25519_dalek::SECRET_KEY_LENGTH
+            );
+        };
+
+        Ok(Self {
+            channel,
+            key: SigningKey::from_bytes(&bytes),
+        })
+    }
+}
+
+pub fn sign_manifest(
+    manifest: &PackageManifest,
+    signer: &ManifestSigner,
+) -> Result<ManifestSignature> {
+    let signature = signer.key.sign(&signed_message(manifest, signer.channel)?);
+
+    Ok(ManifestSignature {
+        channel: signer.channel,
+        signature: base64::engine::general_purpose::STANDARD.encode(signature.to_bytes()),
+    })
+}
+
+/// Verifies the signature with the public key embedded for `channel`, which
+/// must be the channel of the signature.
+pub fn verify_manifest(
+    manifest: &PackageManifest,
+    signature: &ManifestSignature,
+    channel: ReleaseChannel,
+) -> Result<()> {
+    if signature.channel != channel {
+        bail!(
+            "manifest signed for the {} channel instead of {channel}",
+            signature.channel
+        );
+    }
+
+    verify_manifest_with_key(manifest, signature, &channel.public_key()?)
+}
+
+pub fn verify_manifest_with_key(
+    manifest: &PackageManifest,
+    signature: &ManifestSignature,
+    key: &VerifyingKey,
+) -> Result<()> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(&signature.signature)?;
+    let signature_bytes = <[u8; ed25519_dalek::SIGNATURE_LENGTH]>::try_from(bytes)
+        .map_err(|_| anyhow!("invalid manifest signature length"))?;
+
+    key.verify_strict(
+        &signed_message(manifest, signature.channel)?,
+        &Signature::from_bytes(&signature_bytes),
+    )
+    .map_err(|_| anyhow!("invalid manifest signature"))
+}
+
+pub fn decode_public_key(encoded: &str) -> Result<VerifyingKey> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(encoded.trim())?;
+
+    let Ok(bytes) = <[u8; ed25519_dalek::PUBLIC_KEY_LENGTH]>::try_from(bytes) else {
+        bail!(
+            "manifest public key must be {} bytes long",
+            ed25519_dalek::PUBLIC_KEY_LENGTH
+        );
+    };
+
+    Ok(VerifyingKey::from_bytes(&bytes)?)
+}
+
+fn signed_message(manifest: &PackageManifest, channel: ReleaseChannel) -> Result<Vec<u8>> {
+    Ok([
+        SIGNATURE_CONTEXT,
+        channel.to_string().as_bytes(),
+        rmp_serde::to_vec_named(manifest)?.as_slice(),
+    ]
+    .join(&0))
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::crypto::EncryptionKey;
+    use crate::export::offline::{
+        content_hash, offline_package, OfflinePackage, OfflinePackageOptions,
+    };
+    use crate::sync::{CourseData, QuestionData};
+
+    #[test]
+    fn test_sign_manifest() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![QuestionData; 2];
+
+        let signer = ManifestSigner::from_base64(
+            ReleaseChannel::Stable,
+            &base64::engine::general_purpose::STANDARD.encode([7; 32]),
+        )
+        .unwrap();
+        let public_key = signer.key.verifying_key();
+        let key = EncryptionKey::generate();
+        let bytes = offline_package(
+            &course,
+            &OfflinePackageOptions {
+                key: key.clone(),
+                include_answers: true,
+                signer: Some(signer.clone()),
+            },
+        )
+        .unwrap();
+        let package = OfflinePackage::from_bytes(&bytes, &key).unwrap();
+        let signature = package.signature.as_ref().unwrap();
+
+        assert!(verify_manifest_with_key(&package.manifest, signature, &public_key).is_ok());
+        assert!(verify_manifest(&package.manifest, signature, ReleaseChannel::Beta).is_err());
+
+        // Resealing a package with a flipped answer and a matching content hash.
+        let mut tampered = package.clone();
+        let option = &mut tampered.questions[0].options[0];
+        option.is_correct = option.is_correct.map(|is_correct| !is_correct);
+        tampered.manifest.content_hash = content_hash(&tampered.questions).unwrap();
+
+        assert!(tampered.verify().is_ok());
+        assert!(verify_manifest_with_key(&tampered.manifest, signature, &public_key).is_err());
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use printpdf::image_crate::{self, DynamicImage, GenericImageView};
+use printpdf::{
+    BuiltinFont, Image, ImageTransform, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference,
+};
+use uuid::Uuid;
+
+use crate::render::{self, RenderOptions};
+use crate::sync::{CaseData, CourseData, ExamBlueprint, QuestionData};
+
+const PAGE_WIDTH: Mm = Mm(210.0);
+const PAGE_HEIGHT: Mm = Mm(297.0);
+const MARGIN: f32 = 20.0;
+const FONT_SIZE: f32 = 11.0;
+const TITLE_FONT_SIZE: f32 = 16.0;
+const LINE_HEIGHT: f32 = 5.5;
+/// Approximate number of Helvetica characters that fit in a line at [`FONT_SIZE`].
+const LINE_CHARS: usize = 85;
+const MAX_IMAGE_HEIGHT: f32 = 80.0;
+const LAYER_NAME: &str = "Questions";
+
+pub enum QuestionSelection<'a> {
+    Blueprint(&'a ExamBlueprint),
+    Questions(&'a [Uuid]),
+}
+
+impl QuestionSelection<'_> {
+    fn select<'a>(&self, course: &'a CourseData) -> Result<Vec<&'a QuestionData>> {
+        match self {
+            Self::Blueprint(blueprint) => blueprint.select(course),
+            Self::Questions(ids) => ids
+                .iter()
+                .map(
+                    |id| match course.questions.iter().find(|question| question.id == *id) {
+                        Some(question) => Ok(question),
+                        None => bail!("question with ID {id} not found in course {}", course.key),
+                    },
+                )
+                .collect(),
+        }
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct PdfOptions {
+    pub title: String,
+    pub render: RenderOptions,
+}
+
+/// Renders the selected questions into an A4 PDF. Questions in a case are
+/// preceded by its vignette, printed once. Images are looked up by file name,
+/// and those missing are left out. Each question ends with the attribution of
+/// its license, or of the course's when it has none.
+pub fn render_pdf(
+    course: &CourseData,
+    selection: &QuestionSelection,
+    options: &PdfOptions,
+    images: &HashMap<PathBuf, Vec<u8>>,
+) -> Result<Vec<u8>> {
+    let questions = selection.select(course)?;
+
+    let (document, page, layer) =
+        PdfDocument::new(&options.title, PAGE_WIDTH, PAGE_HEIGHT, LAYER_NAME);
+    let font = document.add_builtin_font(BuiltinFont::Helvetica)?;
+    let bold_font = document.add_builtin_font(BuiltinFont::HelveticaBold)?;
+
+    let mut writer = PageWriter {
+        layer: document.get_page(page).get_layer(layer),
+        document: &document,
+        y: PAGE_HEIGHT.0 - MARGIN,
+    };
+
+    writer.line(&options.title, TITLE_FONT_SIZE, &bold_font);
+    writer.space(LINE_HEIGHT);
+
+    let render_options = RenderOptions {
+        course_license: course.license.clone(),
+        ..options.render.clone()
+    };
+    let mut printed_case_ids = HashSet::new();
+
+    for (index, question) in questions.into_iter().enumerate() {
+        if let Some(case) = course.case_of(question.id) {
+            if printed_case_ids.insert(case.id) {
+                writer.case(case, images, &bold_font)?;
+            }
+        }
+
+        let text = render::to_text(question, &render_options);
+        let (stem, rest) = text.split_once("\n\n").unwrap_or((&text, ""));
+
+        writer.paragraph(&format!("{}. {stem}", index + 1), &font);
+
+        if let Some(image) = question
+            .image_file_name
+            .as_ref()
+            .and_then(|image_file_name| images.get(image_file_name))
+        {
+            writer.image(&image_crate::load_from_memory(image)?);
+        }
+
+        for paragraph in rest.lines() {
+            writer.paragraph(paragraph, &font);
+        }
+
+        writer.space(LINE_HEIGHT);
+    }
+
+    Ok(document.save_to_bytes()?)
+}
+
+struct PageWriter<'a> {
+    document: &'a PdfDocumentReference,
+    layer: PdfLayerReference,
+    y: f32,
+}
+
+impl PageWriter<'_> {
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height >= MARGIN {
+            return;
+        }
+
+        let (page, layer) = self.document.add_page(PAGE_WIDTH, PAGE_HEIGHT, LAYER_NAME);
+        self.layer = self.document.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT.0 - MARGIN;
+    }
+
+    fn space(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn line(&mut self, text: &str, font_size: f32, font: &IndirectFontRef) {
+        self.ensure_space(LINE_HEIGHT);
+        self.y -= LINE_HEIGHT;
+        self.layer
+            .use_text(text, font_size, Mm(MARGIN), Mm(self.y), font);
+    }
+
+    fn paragraph(&mut self, text: &str, font: &IndirectFontRef) {
+        for line in wrap(text, LINE_CHARS) {
+            self.line(&line, FONT_SIZE, font);
+        }
+    }
+
+    fn case(
+        &mut self,
+        case: &CaseData,
+        images: &HashMap<PathBuf, Vec<u8>>,
+        font: &IndirectFontRef,
+    ) -> Result<()> {
+        self.paragraph(&case.text, font);
+
+        for image in case
+            .image_file_names
+            .iter()
+            .filter_map(|image_file_name| images.get(image_file_name))
+        {
+            self.image(&image_crate::load_from_memory(image)?);
+        }
+
+        self.space(LINE_HEIGHT);
+
+        Ok(())
+    }
+
+    /// Scales the image down to fit the text width and [`MAX_IMAGE_HEIGHT`].
+    fn image(&mut self, image: &DynamicImage) {
+        let (width, height) = image.dimensions();
+        let max_width = PAGE_WIDTH.0 - 2.0 * MARGIN;
+
+        let dpi = [
+            300.0,
+            width as f32 * 25.4 / max_width,
+            height as f32 * 25.4 / MAX_IMAGE_HEIGHT,
+        ]
+        .into_iter()
+        .fold(0.0, f32::max);
+        let image_height = height as f32 * 25.4 / dpi;
+
+        self.ensure_space(image_height + LINE_HEIGHT);
+        self.y -= image_height + LINE_HEIGHT / 2.0;
+
+        Image::from_dynamic_image(&DynamicImage::ImageRgb8(image.to_rgb8())).add_to_layer(
+            self.layer.clone(),
+            ImageTransform {
+                translate_x: Some(Mm(MARGIN)),
+                translate_y: Some(Mm(self.y)),
+                dpi: Some(dpi),
+                ..Default::default()
+            },
+        );
+    }
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + word.chars().count() + 1 > width {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+
+        line.push_str(word);
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+
+    #[test]
+    fn test_render_pdf() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![QuestionData; 3];
+
+        let ids = course
+            .questions
+            .iter()
+            .map(|question| question.id)
+            .collect::<Vec<_>>();
+
+        let pdf = render_pdf(
+            &course,
+            &QuestionSelection::Questions(&ids),
+            &PdfOptions {
+                title: "Simulacro".into(),
+                ..Default::default()
+            },
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert!(pdf.starts_with(b"%PDF"));
+
+        let topic = course.questions[0].topic.clone();
+
+        for question in &mut course.questions {
+            question.course_key = course.key.clone();
+            question.topic = topic.clone();
+        }
+
+        course.cases = vec![CaseData::from_questions(
+            Uuid::new_v4(),
+            "Varón de 70 años con hematuria.".into(),
+            vec!["eco.png".into()],
+            &course.questions[..2].iter().collect::<Vec<_>>(),
+        )
+        .unwrap()];
+
+        assert!(render_pdf(
+            &course,
+            &QuestionSelection::Questions(&ids),
+            &PdfOptions::default(),
+            &HashMap::new(),
+        )
+        .unwrap()
+        .starts_with(b"%PDF"));
+        assert!(render_pdf(
+            &course,
+            &QuestionSelection::Questions(&[Uuid::new_v4()]),
+            &PdfOptions::default(),
+            &HashMap::new(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("uno dos tres", 7), ["uno dos", "tres"]);
+        assert_eq!(wrap("", 7), [""]);
+    }
+}
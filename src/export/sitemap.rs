@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::links::DeepLink;
+use crate::sync::Catalog;
+
+/// First time each content hash was seen, used as the last-modified date of its page.
+pub type HashDates = HashMap<String, DateTime<Utc>>;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct SitemapEntry {
+    pub url: String,
+    pub last_modified: DateTime<Utc>,
+    pub hash: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct CatalogFeed {
+    pub generated_at: DateTime<Utc>,
+    pub items: Vec<CatalogFeedItem>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct CatalogFeedItem {
+    pub kind: CatalogFeedItemKind,
+    pub key: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub url: String,
+    pub image_path: String,
+    pub price_in_uyu: Option<Decimal>,
+    pub last_modified: DateTime<Utc>,
+    pub hash: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogFeedItemKind {
+    Course,
+    Bundle,
+}
+
+/// Items of the courses and bundles visible at `now`, so drafts and scheduled
+/// content stay out of public pages until they're live.
+pub fn catalog_feed(
+    catalog: &Catalog,
+    base_url: &str,
+    hash_dates: &HashDates,
+    now: DateTime<Utc>,
+) -> CatalogFeed {
+    let catalog = catalog.visible_at(now);
+    let last_modified = |hash: &str| hash_dates.get(hash).copied().unwrap_or(now);
+
+    let courses = catalog.courses.iter().map(|course| CatalogFeedItem {
+        kind: CatalogFeedItemKind::Course,
+        key: course.key.clone(),
+        name: course.name.clone(),
+        description: course.description.clone(),
+        url: DeepLink::from(course).url(base_url),
+        image_path: course.full_image_path(),
+        price_in_uyu: course.price_in_uyu,
+        last_modified: last_modified(&course.hash),
+        hash: course.hash.clone(),
+    });
+
+    let bundles = catalog.bundles.iter().map(|bundle| CatalogFeedItem {
+        kind: CatalogFeedItemKind::Bundle,
+        key: bundle.key.clone(),
+        name: bundle.name.clone(),
+        description: Some(bundle.description.clone()),
+        url: DeepLink::from(bundle).url(base_url),
+        image_path: bundle.full_image_path(),
+        price_in_uyu: Some(bundle.price_in_uyu(&catalog.courses)),
+        last_modified: last_modified(&bundle.hash),
+        hash: bundle.hash.clone(),
+    });
+
+    CatalogFeed {
+        generated_at: now,
+        items: courses.chain(bundles).collect(),
+    }
+}
+
+pub fn sitemap_entries(
+    catalog: &Catalog,
+    base_url: &str,
+    hash_dates: &HashDates,
+    now: DateTime<Utc>,
+) -> Vec<SitemapEntry> {
+    catalog_feed(catalog, base_url, hash_dates, now)
+        .items
+        .into_iter()
+        .map(|item| SitemapEntry {
+            url: item.url,
+            last_modified: item.last_modified,
+            hash: item.hash,
+        })
+        .collect()
+}
+
+pub fn sitemap(
+    catalog: &Catalog,
+    base_url: &str,
+    hash_dates: &HashDates,
+    now: DateTime<Utc>,
+) -> String {
+    let urls = sitemap_entries(catalog, base_url, hash_dates, now)
+        .into_iter()
+        .fold(String::new(), |acc, entry| {
+            format!(
+                "{acc}  <url>\n    <loc>{}</loc>\n    <lastmod>{}</lastmod>\n  </url>\n",
+                escape_xml(&entry.url),
+                entry.last_modified.format("%Y-%m-%d")
+            )
+        });
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n{urls}</urlset>\n"
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::links::UNIVERSAL_LINK_BASE_URL;
+    use crate::sync::{CourseData, PublishState};
+
+    fn live_course(name: &str) -> CourseData {
+        let mut course: CourseData = Faker.fake();
+        course.name = name.into();
+        course.publish_state = PublishState::Published;
+        course.publish_at = None;
+        course.unpublish_at = None;
+        course.questions.clear();
+        course.process().unwrap();
+
+        course
+    }
+
+    #[test]
+    fn test_sitemap() {
+        let course = live_course("Cirugía & Trauma");
+
+        let known_date = DateTime::parse_from_rfc3339("2024-03-01T00:00:00Z")
+            .unwrap()
+            .to_utc();
+        let hash_dates = HashDates::from([(course.hash.clone(), known_date)]);
+
+        let catalog = Catalog::new(vec![course.clone()], vec![]);
+        let sitemap = sitemap(&catalog, "https://medici.uy/", &hash_dates, Utc::now());
+
+        assert!(sitemap.contains(&format!(
+            "<loc>https://medici.uy/courses/{}</loc>",
+            escape_xml(&course.key)
+        )));
+        assert!(sitemap.contains("<lastmod>2024-03-01</lastmod>"));
+    }
+
+    #[test]
+    fn test_urls_are_deep_links() {
+        let course = live_course("Pediatría");
+        let catalog = Catalog::new(vec![course.clone()], vec![]);
+        let feed = catalog_feed(
+            &catalog,
+            UNIVERSAL_LINK_BASE_URL,
+            &HashDates::new(),
+            Utc::now(),
+        );
+
+        assert_eq!(
+            DeepLink::parse(&feed.items[0].url).unwrap(),
+            DeepLink::from(&course)
+        );
+    }
+
+    #[test]
+    fn test_scheduled_courses_are_left_out() {
+        let now = Utc::now();
+        let live = live_course("Pediatría");
+        let mut scheduled = live_course("Neonatología");
+        scheduled.publish_at = Some(now + Duration::days(7));
+        let mut draft = live_course("Geriatría");
+        draft.publish_state = PublishState::Draft;
+
+        let catalog = Catalog::new(vec![live.clone(), scheduled.clone(), draft], vec![]);
+        let feed = catalog_feed(&catalog, "https://medici.uy", &HashDates::new(), now);
+
+        assert_eq!(
+            feed.items
+                .iter()
+                .map(|item| item.key.as_str())
+                .collect::<Vec<_>>(),
+            [live.key.as_str()]
+        );
+        assert!(
+            !sitemap(&catalog, "https://medici.uy", &HashDates::new(), now)
+                .contains(&escape_xml(&scheduled.key))
+        );
+        assert_eq!(
+            catalog_feed(
+                &catalog,
+                "https://medici.uy",
+                &HashDates::new(),
+                now + Duration::days(8)
+            )
+            .items
+            .len(),
+            2
+        );
+    }
+}
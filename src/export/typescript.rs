@@ -0,0 +1,92 @@
+use ts_rs::TS;
+
+use crate::analytics::{AnalyticsBatch, AnalyticsEvent, AnalyticsRecord};
+use crate::api_error::{ApiError, ApiErrorCode, FieldError};
+use crate::glossary::{AnnotatedText, TermSpan};
+use crate::precondition::IfMatch;
+use crate::search::{SearchFilters, SearchHit, Suggestion, SuggestionKind};
+use crate::status::engine::{CacheStatus, DbStatus, EngineStatus};
+use crate::sync::{
+    ContentEntityType, CourseRelation, CourseRelationKind, ExamPeriod, ExplanationData,
+    LanguageTag, LicenseData, LicenseKind, MatchingAnswer, MatchingPair, MediaAttachment,
+    MediaKind, MilestoneData, NumericAnswer, OptionCountRange, OrderingAnswer, QuestionKind,
+    QuestionSourceType, RawCaseData, RawCourseData, RawGlossaryTermData, RawLearningPathData,
+    RawQuestionData, RawQuestionOptionData, RawQuestionSourceData, SyncCounts, SyncReport,
+    Tolerance, TranslatedQuestion,
+};
+
+/// TypeScript declarations of the DTOs shared with the admin web UI, as a `.d.ts` bundle.
+pub fn typescript_declarations() -> String {
+    let declarations = [
+        RawCourseData::decl(),
+        RawCaseData::decl(),
+        CourseRelation::decl(),
+        CourseRelationKind::decl(),
+        RawLearningPathData::decl(),
+        MilestoneData::decl(),
+        RawGlossaryTermData::decl(),
+        AnnotatedText::decl(),
+        TermSpan::decl(),
+        SearchFilters::decl(),
+        SearchHit::decl(),
+        Suggestion::decl(),
+        SuggestionKind::decl(),
+        RawQuestionData::decl(),
+        RawQuestionOptionData::decl(),
+        RawQuestionSourceData::decl(),
+        QuestionSourceType::decl(),
+        ExamPeriod::decl(),
+        ExplanationData::decl(),
+        LanguageTag::decl(),
+        LicenseData::decl(),
+        LicenseKind::decl(),
+        MediaAttachment::decl(),
+        MediaKind::decl(),
+        OptionCountRange::decl(),
+        TranslatedQuestion::decl(),
+        QuestionKind::decl(),
+        NumericAnswer::decl(),
+        MatchingAnswer::decl(),
+        MatchingPair::decl(),
+        OrderingAnswer::decl(),
+        Tolerance::decl(),
+        EngineStatus::decl(),
+        DbStatus::decl(),
+        CacheStatus::decl(),
+        SyncReport::decl(),
+        SyncCounts::decl(),
+        ContentEntityType::decl(),
+        IfMatch::decl(),
+        ApiError::decl(),
+        ApiErrorCode::decl(),
+        FieldError::decl(),
+        AnalyticsBatch::decl(),
+        AnalyticsEvent::decl(),
+        AnalyticsRecord::decl(),
+    ];
+
+    let mut output = String::from("// Generated by medici-shared. Do not edit.\n");
+
+    for declaration in declarations {
+        output.push_str(&format!("\nexport {declaration}\n"));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typescript_declarations() {
+        let declarations = typescript_declarations();
+
+        assert!(declarations.contains("export type RawCourseData = {"));
+        assert!(declarations.contains("export type RawCaseData = {"));
+        assert!(declarations.contains("cases: Array<RawCaseData>"));
+        assert!(declarations.contains("price_in_uyu: string | null"));
+        assert!(declarations.contains("export type LanguageTag = string;"));
+        assert!(declarations.contains("duration_ms: number"));
+    }
+}
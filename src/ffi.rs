@@ -0,0 +1,501 @@
+//! Bindings for the mobile app, generated with uniffi. Records are flattened
+//! copies of the data model using only types every binding language has;
+//! parsing and validation always go through the Rust model.
+//!
+//! The shared library the bindings load is built on demand, so dependents
+//! keep a plain `lib`:
+//! `cargo rustc --release --lib --features ffi --crate-type cdylib`.
+
+use crate::sync::{
+    BundleData, CaseData, Catalog, CourseData, LicenseData, OptionCountRange, QuestionData,
+    QuestionKind, QuestionOptionData, RawQuestionData,
+};
+
+#[derive(uniffi::Error, PartialEq, Eq, Clone, Debug)]
+pub enum FfiError {
+    Parse { message: String },
+    Invalid { message: String },
+}
+
+impl std::fmt::Display for FfiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse { message } => write!(f, "parse error: {message}"),
+            Self::Invalid { message } => write!(f, "invalid data: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for FfiError {}
+
+impl FfiError {
+    fn parse(error: impl std::fmt::Display) -> Self {
+        Self::Parse {
+            message: error.to_string(),
+        }
+    }
+
+    fn invalid(error: impl std::fmt::Display) -> Self {
+        Self::Invalid {
+            message: error.to_string(),
+        }
+    }
+}
+
+#[derive(uniffi::Record, PartialEq, Eq, Clone, Debug)]
+pub struct FfiCatalog {
+    pub courses: Vec<FfiCourse>,
+    pub bundles: Vec<FfiBundle>,
+}
+
+#[derive(uniffi::Record, PartialEq, Eq, Clone, Debug)]
+pub struct FfiCourse {
+    pub key: String,
+    pub name: String,
+    pub slug: String,
+    pub short_name: String,
+    pub description: Option<String>,
+    /// Decimal as a string, to avoid float rounding.
+    pub price_in_uyu: Option<String>,
+    pub tags: Vec<String>,
+    pub image_path: String,
+    pub alt_text: Option<String>,
+    pub year: Option<u16>,
+    pub order: Option<u16>,
+    pub locale: String,
+    pub relations: Vec<FfiCourseRelation>,
+    pub preview_question_ids: Vec<String>,
+    pub license: Option<FfiLicense>,
+    pub hash: String,
+}
+
+#[derive(uniffi::Record, PartialEq, Eq, Clone, Debug)]
+pub struct FfiLicense {
+    pub source_institution: String,
+    /// `cc_by`, `public_domain`, etc.
+    pub kind: String,
+    pub attribution_text: String,
+    pub url: Option<String>,
+    /// Line to show with the content, as rendered by this crate.
+    pub attribution: String,
+}
+
+#[derive(uniffi::Record, PartialEq, Eq, Clone, Debug)]
+pub struct FfiCourseRelation {
+    /// `prerequisite`, `recommended_next` or `related`.
+    pub kind: String,
+    pub course_key: String,
+}
+
+#[derive(uniffi::Record, PartialEq, Eq, Clone, Debug)]
+pub struct FfiBundle {
+    pub key: String,
+    pub name: String,
+    pub slug: String,
+    pub description: String,
+    pub course_keys: Vec<String>,
+    pub discount: String,
+    pub image_path: String,
+    pub alt_text: Option<String>,
+    pub hash: String,
+}
+
+#[derive(uniffi::Record, PartialEq, Eq, Clone, Debug)]
+pub struct FfiQuestion {
+    pub id: String,
+    pub course_key: String,
+    pub text: String,
+    pub explanation: Option<String>,
+    pub topic: String,
+    pub source_key: String,
+    pub tags: Vec<String>,
+    pub image_path: Option<String>,
+    pub alt_text: Option<String>,
+    pub media: Vec<FfiMediaAttachment>,
+    pub options: Vec<FfiQuestionOption>,
+    pub kind: FfiQuestionKind,
+    pub contains_math: bool,
+    pub time_limit_seconds: Option<u32>,
+    /// `None` when the question uses the license of its course.
+    pub license: Option<FfiLicense>,
+    pub hash: String,
+}
+
+/// Multiple choice questions are answered with their options; the other kinds
+/// have none.
+#[derive(uniffi::Enum, PartialEq, Eq, Clone, Debug)]
+pub enum FfiQuestionKind {
+    MultipleChoice,
+    /// Decimals are strings, as in `FfiCourse`.
+    Numeric {
+        value: String,
+        unit: Option<String>,
+        /// Largest accepted distance from `value`.
+        margin: String,
+    },
+    Matching {
+        /// Pairs in their correct matching.
+        pairs: Vec<FfiMatchingPair>,
+    },
+    Ordering {
+        /// Items in their correct sequence.
+        items: Vec<String>,
+    },
+}
+
+#[derive(uniffi::Record, PartialEq, Eq, Clone, Debug)]
+pub struct FfiMatchingPair {
+    pub left: String,
+    pub right: String,
+}
+
+#[derive(uniffi::Record, PartialEq, Eq, Clone, Debug)]
+pub struct FfiMediaAttachment {
+    pub kind: String,
+    pub path: String,
+    pub duration_seconds: u32,
+    pub transcript: Option<String>,
+}
+
+#[derive(uniffi::Record, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct FfiOptionCountRange {
+    pub min: u16,
+    pub max: u16,
+}
+
+#[derive(uniffi::Record, PartialEq, Eq, Clone, Debug)]
+pub struct FfiQuestionOption {
+    pub id: String,
+    pub text: String,
+    pub is_correct: bool,
+    pub reference: u16,
+    pub explanation: Option<String>,
+}
+
+#[derive(uniffi::Record, PartialEq, Eq, Clone, Debug)]
+pub struct FfiCase {
+    pub id: String,
+    pub course_key: String,
+    pub topic: String,
+    pub text: String,
+    pub image_paths: Vec<String>,
+    /// In the order they're asked.
+    pub question_ids: Vec<String>,
+    pub hash: String,
+}
+
+impl From<&Catalog> for FfiCatalog {
+    fn from(catalog: &Catalog) -> Self {
+        Self {
+            courses: catalog.courses.iter().map(Into::into).collect(),
+            bundles: catalog.bundles.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<&CourseData> for FfiCourse {
+    fn from(course: &CourseData) -> Self {
+        Self {
+            key: course.key.clone(),
+            name: course.name.clone(),
+            slug: course.slug().into(),
+            short_name: course.short_name.clone(),
+            description: course.description.clone(),
+            price_in_uyu: course.price_in_uyu.map(|price| price.to_string()),
+            tags: course.tags.clone(),
+            image_path: course.full_image_path(),
+            alt_text: course.alt_text.clone(),
+            year: course.year,
+            order: course.order,
+            locale: course.locale.to_string(),
+            relations: course
+                .relations
+                .iter()
+                .map(|relation| FfiCourseRelation {
+                    kind: relation.kind.to_string(),
+                    course_key: relation.course_key.clone(),
+                })
+                .collect(),
+            preview_question_ids: course
+                .preview_question_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            license: course.license.as_ref().map(Into::into),
+            hash: course.hash.clone(),
+        }
+    }
+}
+
+impl From<&LicenseData> for FfiLicense {
+    fn from(license: &LicenseData) -> Self {
+        Self {
+            source_institution: license.source_institution.clone(),
+            kind: license.kind.to_string(),
+            attribution_text: license.attribution_text.clone(),
+            url: license.url.clone(),
+            attribution: license.attribution(),
+        }
+    }
+}
+
+impl From<&BundleData> for FfiBundle {
+    fn from(bundle: &BundleData) -> Self {
+        Self {
+            key: bundle.key.clone(),
+            name: bundle.name.clone(),
+            slug: bundle.slug().into(),
+            description: bundle.description.clone(),
+            course_keys: bundle.course_keys.clone(),
+            discount: bundle.discount.to_string(),
+            image_path: bundle.full_image_path(),
+            alt_text: bundle.alt_text.clone(),
+            hash: bundle.hash.clone(),
+        }
+    }
+}
+
+impl From<&QuestionData> for FfiQuestion {
+    fn from(question: &QuestionData) -> Self {
+        Self {
+            id: question.id.to_string(),
+            course_key: question.course_key.clone(),
+            text: question.text.clone(),
+            explanation: question
+                .explanation
+                .as_ref()
+                .map(|explanation| explanation.text.clone()),
+            topic: question.topic.name.clone(),
+            source_key: question.source_key(),
+            tags: question.tags.clone(),
+            image_path: question.full_image_path(),
+            alt_text: question.alt_text.clone(),
+            media: question
+                .media
+                .iter()
+                .map(|attachment| FfiMediaAttachment {
+                    kind: attachment.kind.to_string(),
+                    path: attachment.full_path(&question.course_key),
+                    duration_seconds: attachment.duration_seconds,
+                    transcript: attachment.transcript.clone(),
+                })
+                .collect(),
+            options: question.question_options.iter().map(Into::into).collect(),
+            kind: (&question.kind).into(),
+            contains_math: question.contains_math(),
+            time_limit_seconds: question.time_limit_seconds,
+            license: question.license.as_ref().map(Into::into),
+            hash: question.hash.clone(),
+        }
+    }
+}
+
+impl From<&QuestionKind> for FfiQuestionKind {
+    fn from(kind: &QuestionKind) -> Self {
+        match kind {
+            QuestionKind::MultipleChoice => Self::MultipleChoice,
+            QuestionKind::Numeric(answer) => Self::Numeric {
+                value: answer.value.to_string(),
+                unit: answer.unit.clone(),
+                margin: answer.margin().normalize().to_string(),
+            },
+            QuestionKind::Matching(answer) => Self::Matching {
+                pairs: answer
+                    .pairs
+                    .iter()
+                    .map(|pair| FfiMatchingPair {
+                        left: pair.left.clone(),
+                        right: pair.right.clone(),
+                    })
+                    .collect(),
+            },
+            QuestionKind::Ordering(answer) => Self::Ordering {
+                items: answer.items.clone(),
+            },
+        }
+    }
+}
+
+impl From<&QuestionOptionData> for FfiQuestionOption {
+    fn from(option: &QuestionOptionData) -> Self {
+        Self {
+            id: option.id.to_string(),
+            text: option.text.clone(),
+            is_correct: option.is_correct,
+            reference: option.reference,
+            explanation: option.explanation.clone(),
+        }
+    }
+}
+
+impl From<&CaseData> for FfiCase {
+    fn from(case: &CaseData) -> Self {
+        Self {
+            id: case.id.to_string(),
+            course_key: case.course_key.clone(),
+            topic: case.topic.clone(),
+            text: case.text.clone(),
+            image_paths: case.full_image_paths(),
+            question_ids: case.question_ids.iter().map(ToString::to_string).collect(),
+            hash: case.hash.clone(),
+        }
+    }
+}
+
+/// Parses the catalog as served by the engine.
+#[uniffi::export]
+pub fn parse_catalog(json: String) -> Result<FfiCatalog, FfiError> {
+    let catalog: Catalog = serde_json::from_str(&json).map_err(FfiError::parse)?;
+
+    Ok((&catalog).into())
+}
+
+/// Parses a question in the authoring format, formatting and checking it like the engine does.
+/// `option_count_range` is that of the course, the default one if `None`.
+#[uniffi::export]
+pub fn parse_question(
+    course_key: String,
+    option_count_range: Option<FfiOptionCountRange>,
+    json: String,
+) -> Result<FfiQuestion, FfiError> {
+    Ok((&question_from_json(&course_key, option_count_range, &json)?).into())
+}
+
+/// Like `parse_question`, also applying the stricter checks required for publishing.
+#[uniffi::export]
+pub fn validate_question(
+    course_key: String,
+    option_count_range: Option<FfiOptionCountRange>,
+    json: String,
+) -> Result<(), FfiError> {
+    question_from_json(&course_key, option_count_range, &json)?
+        .check_strict()
+        .map_err(FfiError::invalid)
+}
+
+/// Parses a case as served by the engine, checking it like the engine does.
+#[uniffi::export]
+pub fn parse_case(json: String) -> Result<FfiCase, FfiError> {
+    let mut case: CaseData = serde_json::from_str(&json).map_err(FfiError::parse)?;
+    case.process().map_err(FfiError::invalid)?;
+
+    Ok((&case).into())
+}
+
+fn question_from_json(
+    course_key: &str,
+    option_count_range: Option<FfiOptionCountRange>,
+    json: &str,
+) -> Result<QuestionData, FfiError> {
+    let option_count_range = match option_count_range {
+        Some(range) => OptionCountRange::new(range.min, range.max).map_err(FfiError::invalid)?,
+        None => Default::default(),
+    };
+    let raw: RawQuestionData = serde_json::from_str(json).map_err(FfiError::parse)?;
+
+    raw.into_question_data(course_key, option_count_range, None)
+        .map_err(FfiError::invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUESTION: &str = r#"{
+        "id": "0f8fad5b-d9cb-469f-a165-70867728950e",
+        "text": "  ¿Cuál es el  nervio del diafragma? ",
+        "topic": "Anatomía",
+        "question_options": [
+            {"id": "7c9e6679-7425-40de-944b-e07fc1f90ae7", "text": "frénico", "correct": true},
+            {"id": "9b2d3b3e-2c1a-4f5e-8f83-9a4e1f2b7c11", "text": "vago", "correct": false}
+        ],
+        "source": {"type": "other"},
+        "image": "diafragma.png",
+        "media": [{"kind": "audio", "file_name": "hipo.mp3", "duration_seconds": 12}],
+        "license": {"source_institution": "Facultad de Medicina", "kind": "cc_by", "attribution_text": "Material cedido", "url": null}
+    }"#;
+
+    #[test]
+    fn test_parse_question() {
+        let question = parse_question("anatomia".into(), None, QUESTION.into()).unwrap();
+
+        assert_eq!(question.text, "¿Cuál es el nervio del diafragma?");
+        assert_eq!(question.options.len(), 2);
+        assert!(question.options[0].is_correct);
+        assert_eq!(question.kind, FfiQuestionKind::MultipleChoice);
+        assert_eq!(
+            question.media,
+            vec![FfiMediaAttachment {
+                kind: "audio".into(),
+                path: "anatomia/hipo.mp3".into(),
+                duration_seconds: 12,
+                transcript: None,
+            }]
+        );
+        assert_eq!(
+            question.license.map(|license| license.attribution),
+            Some("Material cedido — Facultad de Medicina, CC BY".into())
+        );
+        assert!(matches!(
+            validate_question("anatomia".into(), None, QUESTION.into()),
+            Err(FfiError::Invalid { .. })
+        ));
+        assert!(matches!(
+            parse_question("anatomia".into(), None, "{}".into()),
+            Err(FfiError::Parse { .. })
+        ));
+
+        let numeric = parse_question(
+            "anatomia".into(),
+            None,
+            r#"{
+                "id": "0f8fad5b-d9cb-469f-a165-70867728950e",
+                "text": "¿Cuántos mL de amoxicilina se administran?",
+                "topic": "Pediatría",
+                "question_options": [],
+                "source": {"type": "other"},
+                "kind": {"type": "numeric", "value": "2.5", "unit": "mL", "tolerance": {"relative": "0.04"}}
+            }"#
+            .into(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            numeric.kind,
+            FfiQuestionKind::Numeric {
+                value: "2.5".into(),
+                unit: Some("mL".into()),
+                margin: "0.1".into(),
+            }
+        );
+
+        let ordering = parse_question(
+            "anatomia".into(),
+            None,
+            r#"{
+                "id": "0f8fad5b-d9cb-469f-a165-70867728950e",
+                "text": "Ordene los pasos de la intubación.",
+                "topic": "Urgencias",
+                "question_options": [],
+                "source": {"type": "other"},
+                "kind": {"type": "ordering", "items": ["Preoxigenar", "Inducir", "Intubar"]}
+            }"#
+            .into(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            ordering.kind,
+            FfiQuestionKind::Ordering {
+                items: vec!["Preoxigenar".into(), "Inducir".into(), "Intubar".into()],
+            }
+        );
+
+        let range = FfiOptionCountRange { min: 3, max: 5 };
+
+        assert!(matches!(
+            parse_question("anatomia".into(), Some(range), QUESTION.into()),
+            Err(FfiError::Invalid { .. })
+        ));
+    }
+}
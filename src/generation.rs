@@ -0,0 +1,261 @@
+use anyhow::{bail, Result};
+use async_openai::types::{
+    ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
+    CreateChatCompletionRequestArgs, ResponseFormat,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::helpers::LlmClient;
+use crate::sync::{
+    unit_formatter, ExplanationData, OptionCountRange, QuestionSourceType, RawQuestionData,
+    RawQuestionOptionData, RawQuestionSourceData, UnitFormatter, UnitRules,
+};
+
+pub const GENERATION_MODEL: &str = "gpt-4o";
+pub const MAX_DRAFTS_PER_REQUEST: usize = 20;
+/// Source name of generated questions, so they can be told apart after review.
+pub const GENERATED_SOURCE_NAME: &str = "generated";
+
+/// A generated question waiting for human review.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct QuestionDraft {
+    pub question: RawQuestionData,
+    pub status: DraftStatus,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum DraftStatus {
+    /// Passed validation, ready for review.
+    Draft,
+    /// Failed validation; kept so reviewers can fix it instead of regenerating.
+    Invalid { reason: String },
+}
+
+impl QuestionDraft {
+    pub fn is_valid(&self) -> bool {
+        self.status == DraftStatus::Draft
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct GenerationPayload {
+    questions: Vec<GeneratedQuestion>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct GeneratedQuestion {
+    text: String,
+    question_options: Vec<String>,
+    /// Zero-based index into `question_options`.
+    correct_option: usize,
+    explanation: Option<String>,
+}
+
+impl GeneratedQuestion {
+    /// A question with an invalid explanation is kept without it, as an invalid draft.
+    /// `formatter` is that of `unit_rules`.
+    fn into_draft(
+        self,
+        course_key: &str,
+        option_count_range: OptionCountRange,
+        unit_rules: Option<&UnitRules>,
+        formatter: &UnitFormatter,
+        topic: &str,
+    ) -> QuestionDraft {
+        let (explanation, explanation_error) = match self
+            .explanation
+            .map(|text| {
+                let mut explanation = ExplanationData {
+                    text,
+                    by: GENERATION_MODEL.into(),
+                    date: Utc::now(),
+                    hash: Default::default(),
+                };
+                explanation.process_with(formatter)?;
+
+                Ok::<_, anyhow::Error>(explanation)
+            })
+            .transpose()
+        {
+            Ok(explanation) => (explanation, None),
+            Err(error) => (None, Some(error)),
+        };
+
+        let question = RawQuestionData {
+            id: Uuid::new_v4(),
+            text: self.text,
+            explanation,
+            topic: topic.into(),
+            topic_by: Some(GENERATION_MODEL.into()),
+            tags: vec![],
+            image_file_name: None,
+            alt_text: None,
+            question_options: self
+                .question_options
+                .into_iter()
+                .enumerate()
+                .map(|(index, text)| RawQuestionOptionData {
+                    id: Uuid::new_v4(),
+                    text,
+                    is_correct: index == self.correct_option,
+                    preserve_case: false,
+                    explanation: None,
+                })
+                .collect(),
+            source: RawQuestionSourceData {
+                r#type: QuestionSourceType::Other,
+                name: Some(GENERATED_SOURCE_NAME.into()),
+                date: None,
+                period: None,
+            },
+            translations: Default::default(),
+            license: None,
+            media: vec![],
+            kind: Default::default(),
+            time_limit_seconds: None,
+        };
+
+        let result = match explanation_error {
+            Some(error) => Err(error.context("invalid explanation")),
+            None => question
+                .clone()
+                .into_question_data(course_key, option_count_range, unit_rules.cloned())
+                .map(|_| ()),
+        };
+        let status = match result {
+            Ok(()) => DraftStatus::Draft,
+            Err(error) => DraftStatus::Invalid {
+                reason: format!("{error:#}"),
+            },
+        };
+
+        QuestionDraft { question, status }
+    }
+}
+
+/// Asks the model for `count` questions about `topic` based on `source_text`,
+/// e.g. an explanation or a chapter of a guideline. Every draft is validated
+/// like an imported question of `course_key`, whose options must fit in
+/// `option_count_range` and whose text is formatted with `unit_rules`, and
+/// invalid drafts are returned along with the rest.
+pub async fn draft_questions(
+    course_key: &str,
+    option_count_range: OptionCountRange,
+    unit_rules: Option<&UnitRules>,
+    source_text: &str,
+    topic: &str,
+    count: usize,
+    client: &impl LlmClient,
+) -> Result<Vec<QuestionDraft>> {
+    if count == 0 || count > MAX_DRAFTS_PER_REQUEST {
+        bail!("can draft between 1 and {MAX_DRAFTS_PER_REQUEST} questions at a time");
+    }
+
+    let formatter = unit_formatter(unit_rules)?;
+
+    let request = CreateChatCompletionRequestArgs::default()
+        .model(GENERATION_MODEL)
+        .response_format(ResponseFormat::JsonObject)
+        .messages([
+            ChatCompletionRequestSystemMessageArgs::default()
+                .content(system_prompt(topic, count))
+                .build()?
+                .into(),
+            ChatCompletionRequestUserMessageArgs::default()
+                .content(source_text)
+                .build()?
+                .into(),
+        ])
+        .build()?;
+
+    let response = client.chat_completion(request).await?;
+    let payload: GenerationPayload = serde_json::from_str(&response)?;
+
+    Ok(payload
+        .questions
+        .into_iter()
+        .take(count)
+        .map(|generated| {
+            generated.into_draft(
+                course_key,
+                option_count_range,
+                unit_rules,
+                &formatter,
+                topic,
+            )
+        })
+        .collect())
+}
+
+fn system_prompt(topic: &str, count: usize) -> String {
+    format!(
+        "Write {count} multiple choice medical exam questions about {topic}, based only on the \
+        text sent by the user and in its language. Each question has between 4 and 5 options and \
+        exactly one correct option. Reply with a JSON object with a questions key, holding a list \
+        of objects with the keys text, question_options (a list of strings), correct_option (the \
+        zero-based index of the correct option) and explanation (why that option is correct)."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::FakeLlmClient;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_draft_questions() {
+        let client = FakeLlmClient::new([serde_json::json!({
+            "questions": [
+                {
+                    "text": "¿Cuál es el principal agente causal de la neumonía aguda comunitaria?",
+                    "question_options": ["Neumococo", "Estafilococo", "Klebsiella", "Legionella"],
+                    "correct_option": 0,
+                    "explanation": "El neumococo es el agente más frecuente."
+                },
+                {
+                    "text": "¿Qué opción es correcta?",
+                    "question_options": ["A", "A", "B", "C"],
+                    "correct_option": 7,
+                    "explanation": null
+                },
+                {
+                    "text": "¿Cuál es el tratamiento de elección?",
+                    "question_options": ["Amoxicilina", "Vancomicina", "Aciclovir", "Fluconazol"],
+                    "correct_option": 0,
+                    "explanation": " "
+                }
+            ]
+        })
+        .to_string()]);
+
+        let drafts = draft_questions(
+            "MI",
+            Default::default(),
+            None,
+            "La neumonía...",
+            "Neumología",
+            3,
+            &client,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(drafts.len(), 3);
+        assert!(drafts[0].is_valid());
+        assert!(drafts[0].question.question_options[0].is_correct);
+        assert_eq!(drafts[0].question.topic, "Neumología");
+        assert!(!drafts[1].is_valid());
+        assert!(!drafts[2].is_valid());
+        assert!(drafts[2].question.explanation.is_none());
+        assert_eq!(client.requests().len(), 1);
+        assert!(
+            draft_questions("MI", Default::default(), None, "", "Neumología", 0, &client)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Cross-linking of glossary terms in question text, so clients can show the
+//! definition of a term in a tooltip.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::slug::fold_accent;
+use crate::sync::GlossaryTermData;
+
+/// Occurrence of a glossary term. Offsets are in characters (Unicode scalar
+/// values) of the annotated text, not bytes, with `end` exclusive.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct TermSpan {
+    pub start: usize,
+    pub end: usize,
+    pub term_key: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct AnnotatedText {
+    pub text: String,
+    /// In order and without overlaps.
+    pub spans: Vec<TermSpan>,
+}
+
+/// Names of glossary terms, normalized and grouped by their first word so
+/// annotating only looks at the names that could start at each word.
+#[derive(Default, Debug)]
+pub struct GlossaryIndex {
+    names_by_first_word: HashMap<Vec<char>, Vec<IndexedName>>,
+}
+
+#[derive(Debug)]
+struct IndexedName {
+    chars: Vec<char>,
+    term_key: String,
+}
+
+impl GlossaryIndex {
+    pub fn new(terms: &[GlossaryTermData]) -> Self {
+        let mut index = Self::default();
+
+        for term in terms {
+            for name in term.names() {
+                let chars = normalize(&name.split_whitespace().collect::<Vec<_>>().join(" "));
+                let first_word_len = word_len(&chars, 0);
+
+                if first_word_len == 0 {
+                    continue;
+                }
+
+                index
+                    .names_by_first_word
+                    .entry(chars[..first_word_len].to_vec())
+                    .or_default()
+                    .push(IndexedName {
+                        chars,
+                        term_key: term.key.clone(),
+                    });
+            }
+        }
+
+        // Longest names first, so the longest match wins. The sort is stable, so
+        // for repeated names the first term keeps them.
+        for names in index.names_by_first_word.values_mut() {
+            names.sort_by_key(|name| std::cmp::Reverse(name.chars.len()));
+        }
+
+        index
+    }
+
+    /// Finds whole-word occurrences of the term names in `text`, ignoring case
+    /// and accents.
+    pub fn annotate(&self, text: &str) -> AnnotatedText {
+        let chars = normalize(text);
+        let mut spans = vec![];
+        let mut position = 0;
+
+        while position < chars.len() {
+            let word_len = word_len(&chars, position);
+
+            if word_len == 0 {
+                position += 1;
+                continue;
+            }
+
+            let name = self
+                .names_by_first_word
+                .get(&chars[position..position + word_len])
+                .and_then(|names| {
+                    names
+                        .iter()
+                        .find(|name| matches_at(&chars, position, &name.chars))
+                });
+
+            match name {
+                Some(name) => {
+                    let end = position + name.chars.len();
+
+                    spans.push(TermSpan {
+                        start: position,
+                        end,
+                        term_key: name.term_key.clone(),
+                    });
+                    position = end;
+                }
+                None => position += word_len,
+            }
+        }
+
+        AnnotatedText {
+            text: text.into(),
+            spans,
+        }
+    }
+}
+
+/// To annotate many texts, build a `GlossaryIndex` once instead.
+pub fn annotate(text: &str, terms: &[GlossaryTermData]) -> AnnotatedText {
+    GlossaryIndex::new(terms).annotate(text)
+}
+
+/// One character per character of `text`, so offsets carry over.
+fn normalize(text: &str) -> Vec<char> {
+    text.chars()
+        .map(|char| {
+            let char = fold_accent(char);
+
+            char.to_lowercase().next().unwrap_or(char)
+        })
+        .collect()
+}
+
+fn word_len(chars: &[char], start: usize) -> usize {
+    chars[start..]
+        .iter()
+        .take_while(|char| char.is_alphanumeric())
+        .count()
+}
+
+fn matches_at(chars: &[char], start: usize, name: &[char]) -> bool {
+    let end = start + name.len();
+
+    chars.get(start..end) == Some(name) && chars.get(end).is_none_or(|char| !char.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate() {
+        let term = |key: &str, term: &str, aliases: &[&str]| {
+            GlossaryTermData::new(
+                key.into(),
+                term.into(),
+                "Definición.".into(),
+                aliases.iter().map(ToString::to_string).collect(),
+                vec![],
+            )
+            .unwrap()
+        };
+        let terms = [
+            term("insuficiencia", "Insuficiencia", &[]),
+            term("ic", "Insuficiencia cardíaca", &["IC"]),
+            term("ecg", "Electrocardiograma", &["ECG"]),
+        ];
+
+        let annotated = annotate(
+            "Paciente con INSUFICIENCIA CARDIACA, ICC e insuficiencia renal. Se pide un ECG.",
+            &terms,
+        );
+        let span = |start, end, term_key: &str| TermSpan {
+            start,
+            end,
+            term_key: term_key.into(),
+        };
+
+        assert_eq!(
+            annotated.spans,
+            vec![
+                span(13, 35, "ic"),
+                span(43, 56, "insuficiencia"),
+                span(75, 78, "ecg"),
+            ]
+        );
+
+        let annotated = annotate("Evolución: ictericia", &terms);
+
+        assert!(annotated.spans.is_empty());
+    }
+}
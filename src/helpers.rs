@@ -1,7 +1,50 @@
-use anyhow::Result;
+use std::future::Future;
+
+use anyhow::{bail, Result};
+use async_openai::types::{
+    AudioInput, CreateChatCompletionRequest, CreateTranscriptionRequest,
+    CreateTranscriptionRequestArgs,
+};
+
+use crate::sync::LanguageTag;
+
+pub const TRANSCRIPTION_MODEL: &str = "whisper-1";
+/// Under the 25 MB upload limit of the audio API.
+pub const MAX_TRANSCRIPTION_CHUNK_LEN: usize = 24 * 1024 * 1024;
+/// Length of the end of the previous chunk's transcript sent as the prompt of
+/// the next one, so sentences split between chunks are transcribed consistently.
+const TRANSCRIPTION_PROMPT_CHARS: usize = 200;
+
+/// Chat completions backend, implemented by the OpenAI client.
+pub trait LlmClient: Sync {
+    fn chat_completion(
+        &self,
+        request: CreateChatCompletionRequest,
+    ) -> impl Future<Output = Result<String>> + Send;
+}
+
+impl LlmClient for async_openai::Client<async_openai::config::OpenAIConfig> {
+    async fn chat_completion(&self, request: CreateChatCompletionRequest) -> Result<String> {
+        send_chat_completion(request, self).await
+    }
+}
+
+/// Speech to text backend, implemented by the OpenAI client.
+pub trait TranscriptionClient: Sync {
+    fn transcription(
+        &self,
+        request: CreateTranscriptionRequest,
+    ) -> impl Future<Output = Result<String>> + Send;
+}
+
+impl TranscriptionClient for async_openai::Client<async_openai::config::OpenAIConfig> {
+    async fn transcription(&self, request: CreateTranscriptionRequest) -> Result<String> {
+        Ok(self.audio().transcribe(request).await?.text)
+    }
+}
 
 pub async fn send_chat_completion(
-    request: async_openai::types::CreateChatCompletionRequest,
+    request: CreateChatCompletionRequest,
     client: &async_openai::Client<async_openai::config::OpenAIConfig>,
 ) -> Result<String> {
     let response = client
@@ -17,3 +60,130 @@ pub async fn send_chat_completion(
 
     Ok(response)
 }
+
+/// Transcribes an audio or video file, e.g. a media attachment of a question.
+/// `file_name` tells the API the format. MP3 files over the upload limit are
+/// split at frame boundaries and transcribed in order; other formats have to
+/// fit in one request.
+pub async fn transcribe_audio(
+    bytes: &[u8],
+    file_name: &str,
+    language: Option<&LanguageTag>,
+    client: &impl TranscriptionClient,
+) -> Result<String> {
+    transcribe_chunks(
+        bytes,
+        file_name,
+        language,
+        MAX_TRANSCRIPTION_CHUNK_LEN,
+        client,
+    )
+    .await
+}
+
+async fn transcribe_chunks(
+    bytes: &[u8],
+    file_name: &str,
+    language: Option<&LanguageTag>,
+    max_chunk_len: usize,
+    client: &impl TranscriptionClient,
+) -> Result<String> {
+    let chunks = if bytes.len() <= max_chunk_len {
+        vec![bytes]
+    } else if file_name.to_lowercase().ends_with(".mp3") {
+        split_mp3_frames(bytes, max_chunk_len)?
+    } else {
+        bail!("{file_name} is too large to transcribe, convert it to MP3 first");
+    };
+
+    let mut transcript = String::new();
+
+    for chunk in chunks {
+        let mut request = CreateTranscriptionRequestArgs::default();
+        request
+            .file(AudioInput::from_vec_u8(file_name.into(), chunk.to_vec()))
+            .model(TRANSCRIPTION_MODEL);
+
+        if let Some(language) = language {
+            request.language(language.language());
+        }
+
+        if !transcript.is_empty() {
+            let skip = transcript
+                .chars()
+                .count()
+                .saturating_sub(TRANSCRIPTION_PROMPT_CHARS);
+            request.prompt(transcript.chars().skip(skip).collect::<String>());
+        }
+
+        let text = client.transcription(request.build()?).await?;
+
+        if !transcript.is_empty() && !text.trim().is_empty() {
+            transcript.push(' ');
+        }
+
+        transcript.push_str(text.trim());
+    }
+
+    Ok(transcript)
+}
+
+/// Splits an MP3 file in chunks of at most `max_len` bytes, each starting at a
+/// frame sync word so it decodes on its own.
+fn split_mp3_frames(bytes: &[u8], max_len: usize) -> Result<Vec<&[u8]>> {
+    let mut chunks = vec![];
+    let mut rest = bytes;
+
+    while rest.len() > max_len {
+        let Some(end) = (1..=max_len).rev().find(|&index| {
+            rest[index] == 0xFF && rest.get(index + 1).is_some_and(|byte| byte & 0xE0 == 0xE0)
+        }) else {
+            bail!("no MP3 frame boundary to split the file at");
+        };
+
+        let (chunk, next) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = next;
+    }
+
+    chunks.push(rest);
+
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::FakeTranscriptionClient;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_transcribe_audio() {
+        let frame = |fill| [[0xFF, 0xFB].as_slice(), &[fill; 6]].concat();
+        let bytes = [frame(1), frame(2), frame(3)].concat();
+        let client = FakeTranscriptionClient::new([" Primera parte", "segunda.", "Fin."]);
+
+        let transcript = transcribe_chunks(
+            &bytes,
+            "soplo.mp3",
+            Some(&LanguageTag::new("es-UY").unwrap()),
+            10,
+            &client,
+        )
+        .await
+        .unwrap();
+        let requests = client.requests();
+
+        assert_eq!(transcript, "Primera parte segunda. Fin.");
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].language.as_deref(), Some("es"));
+        assert_eq!(requests[0].prompt, None);
+        assert_eq!(
+            requests[2].prompt.as_deref(),
+            Some("Primera parte segunda.")
+        );
+        assert!(transcribe_chunks(&bytes, "soplo.wav", None, 10, &client)
+            .await
+            .is_err());
+    }
+}
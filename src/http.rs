@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use reqwest::header::HeaderMap;
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use tracing::warn;
+use uuid::Uuid;
+
+pub const USER_AGENT: &str = concat!("medici-shared/", env!("CARGO_PKG_VERSION"));
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+pub const MAX_RETRIES: u32 = 3;
+pub const RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+pub fn client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()?)
+}
+
+/// Sends the request with a request ID header, retrying idempotent requests that
+/// fail to connect, time out, or get a 429 or 5xx response.
+pub async fn send(request: RequestBuilder) -> Result<Response> {
+    let (client, request) = request
+        .header(REQUEST_ID_HEADER, Uuid::new_v4().to_string())
+        .headers(trace_headers())
+        .build_split();
+    let request = request?;
+
+    let retries = if is_idempotent(request.method()) {
+        MAX_RETRIES
+    } else {
+        0
+    };
+
+    let mut attempt = 0;
+
+    loop {
+        let Some(attempt_request) = request.try_clone() else {
+            return Ok(client.execute(request).await?);
+        };
+
+        let result = client.execute(attempt_request).await;
+
+        let retryable = match &result {
+            Ok(response) => is_retryable_status(response.status()),
+            Err(error) => error.is_connect() || error.is_timeout(),
+        };
+
+        if !retryable || attempt >= retries {
+            return Ok(result?);
+        }
+
+        attempt += 1;
+        let delay = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+
+        warn!(
+            method = %request.method(),
+            url = %request.url(),
+            attempt,
+            "retrying HTTP request in {delay:?}"
+        );
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+pub fn is_idempotent(method: &Method) -> bool {
+    [
+        Method::GET,
+        Method::HEAD,
+        Method::PUT,
+        Method::DELETE,
+        Method::OPTIONS,
+    ]
+    .contains(method)
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// W3C trace context of the current span, when exporting to OpenTelemetry.
+fn trace_headers() -> HeaderMap {
+    #[allow(unused_mut)]
+    let mut headers = HeaderMap::new();
+
+    #[cfg(feature = "otlp")]
+    {
+        use opentelemetry::propagation::TextMapPropagator;
+        use opentelemetry_sdk::propagation::TraceContextPropagator;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let context = tracing::Span::current().context();
+        let mut carrier = std::collections::HashMap::new();
+
+        TraceContextPropagator::new().inject_context(&context, &mut carrier);
+
+        for (name, value) in carrier {
+            if let (Ok(name), Ok(value)) = (
+                reqwest::header::HeaderName::try_from(name),
+                reqwest::header::HeaderValue::try_from(value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+    }
+
+    headers
+}
+
+/// Fails with the response body when the status isn't successful.
+pub async fn check_status(response: Response) -> Result<Response> {
+    let status = response.status();
+
+    if !status.is_success() {
+        let url = response.url().clone();
+        let body = response.text().await.unwrap_or_default();
+
+        bail!("request to {url} failed with status {status}: {body}");
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_rules() {
+        assert!(is_idempotent(&Method::PUT));
+        assert!(!is_idempotent(&Method::POST));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+    }
+}
@@ -0,0 +1,112 @@
+use std::future::Future;
+
+#[cfg(feature = "openai")]
+use anyhow::bail;
+use anyhow::Result;
+#[cfg(feature = "openai")]
+use async_openai::types::{
+    ChatCompletionRequestMessageContentPartImageArgs,
+    ChatCompletionRequestMessageContentPartTextArgs, ChatCompletionRequestUserMessageArgs,
+    ChatCompletionRequestUserMessageContent, CreateChatCompletionRequestArgs, ImageDetail,
+    ImageUrlArgs,
+};
+#[cfg(feature = "openai")]
+use base64::Engine;
+
+#[cfg(feature = "openai")]
+use crate::helpers::LlmClient;
+#[cfg(feature = "openai")]
+use crate::sync::{format_text, LanguageTag};
+
+#[cfg(feature = "openai")]
+pub const ALT_TEXT_MODEL: &str = "gpt-4o";
+
+/// Object storage for images, keyed by their full path.
+pub trait ImageStorage: Sync {
+    fn put(
+        &self,
+        path: &str,
+        bytes: Vec<u8>,
+        content_type: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn get(&self, path: &str) -> impl Future<Output = Result<Option<Vec<u8>>>> + Send;
+
+    fn delete(&self, path: &str) -> impl Future<Output = Result<()>> + Send;
+}
+
+pub fn image_media_type(image_bytes: &[u8]) -> Option<&'static str> {
+    match image_bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "openai")]
+pub async fn generate_alt_text(
+    image_bytes: &[u8],
+    locale: &LanguageTag,
+    client: &impl LlmClient,
+) -> Result<String> {
+    let Some(media_type) = image_media_type(image_bytes) else {
+        bail!("unsupported image format");
+    };
+
+    let image_url = format!(
+        "data:{media_type};base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(image_bytes)
+    );
+
+    let content = ChatCompletionRequestUserMessageContent::Array(vec![
+        ChatCompletionRequestMessageContentPartTextArgs::default()
+            .text(format!(
+                "Write a concise alt text (one or two sentences) for this image from a medical \
+                exam question, in the language with tag {locale}. Reply with the alt text only."
+            ))
+            .build()?
+            .into(),
+        ChatCompletionRequestMessageContentPartImageArgs::default()
+            .image_url(
+                ImageUrlArgs::default()
+                    .url(image_url)
+                    .detail(ImageDetail::Low)
+                    .build()?,
+            )
+            .build()?
+            .into(),
+    ]);
+
+    let request = CreateChatCompletionRequestArgs::default()
+        .model(ALT_TEXT_MODEL)
+        .messages([ChatCompletionRequestUserMessageArgs::default()
+            .content(content)
+            .build()?
+            .into()])
+        .build()?;
+
+    let alt_text = format_text(&client.chat_completion(request).await?);
+
+    if alt_text.is_empty() {
+        bail!("empty alt text generated");
+    }
+
+    Ok(alt_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_media_type() {
+        assert_eq!(
+            image_media_type(&[0x89, b'P', b'N', b'G', 0x0D]),
+            Some("image/png")
+        );
+        assert_eq!(image_media_type(&[0xFF, 0xD8, 0xFF]), Some("image/jpeg"));
+        assert_eq!(image_media_type(b"text"), None);
+    }
+}
@@ -0,0 +1,328 @@
+//! First stage of the import pipeline: scanned exams to raw questions. An
+//! [`OcrBackend`] extracts the text of each page, and the LLM structures it
+//! into questions that go through the usual validation and review afterwards.
+
+use std::future::Future;
+
+use anyhow::Result;
+use async_openai::types::{
+    ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
+    CreateChatCompletionRequestArgs, ResponseFormat,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::helpers::LlmClient;
+use crate::sync::{RawQuestionData, RawQuestionOptionData, RawQuestionSourceData};
+
+pub const STRUCTURING_MODEL: &str = "gpt-4o";
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct OcrPage {
+    /// One-based.
+    pub number: u32,
+    pub text: String,
+}
+
+/// Text recognition backend, implemented by the Textract client and by `TesseractOcr`.
+pub trait OcrBackend: Sync {
+    /// Recorded in the provenance of extracted questions.
+    fn name(&self) -> &str;
+
+    fn extract_pages(&self, document: &[u8]) -> impl Future<Output = Result<Vec<OcrPage>>> + Send;
+}
+
+/// Only single-page PDFs and images are supported, as with Textract's synchronous API.
+#[cfg(feature = "ocr_textract")]
+impl OcrBackend for aws_sdk_textract::Client {
+    fn name(&self) -> &str {
+        "textract"
+    }
+
+    async fn extract_pages(&self, document: &[u8]) -> Result<Vec<OcrPage>> {
+        use std::collections::BTreeMap;
+
+        use aws_sdk_textract::primitives::Blob;
+        use aws_sdk_textract::types::{BlockType, Document};
+
+        let output = self
+            .detect_document_text()
+            .document(Document::builder().bytes(Blob::new(document)).build())
+            .send()
+            .await?;
+
+        let mut pages = BTreeMap::<u32, Vec<&str>>::new();
+
+        for block in output
+            .blocks()
+            .iter()
+            .filter(|block| block.block_type() == Some(&BlockType::Line))
+        {
+            pages
+                .entry(block.page().unwrap_or(1).max(1) as u32)
+                .or_default()
+                .extend(block.text());
+        }
+
+        Ok(pages
+            .into_iter()
+            .map(|(number, lines)| OcrPage {
+                number,
+                text: lines.join("\n"),
+            })
+            .collect())
+    }
+}
+
+/// Local OCR with Tesseract. Only images are supported, so PDFs must be
+/// rendered to one image per page first.
+#[cfg(feature = "ocr_tesseract")]
+#[derive(Clone, Debug)]
+pub struct TesseractOcr {
+    pub datapath: Option<String>,
+    /// Tesseract language code, e.g. `spa`.
+    pub language: String,
+}
+
+#[cfg(feature = "ocr_tesseract")]
+impl Default for TesseractOcr {
+    fn default() -> Self {
+        Self {
+            datapath: None,
+            language: "spa".into(),
+        }
+    }
+}
+
+#[cfg(feature = "ocr_tesseract")]
+impl OcrBackend for TesseractOcr {
+    fn name(&self) -> &str {
+        "tesseract"
+    }
+
+    async fn extract_pages(&self, document: &[u8]) -> Result<Vec<OcrPage>> {
+        if document.starts_with(b"%PDF") {
+            anyhow::bail!("tesseract can't read PDFs, render their pages to images first");
+        }
+
+        let text = tesseract::Tesseract::new(self.datapath.as_deref(), Some(&self.language))?
+            .set_image_from_mem(document)?
+            .recognize()?
+            .get_text()?;
+
+        Ok(vec![OcrPage { number: 1, text }])
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OcrOptions {
+    /// File name or other identifier of the scanned document.
+    pub document_name: String,
+    /// Shared by all questions in the document, e.g. the exam and its date.
+    pub source: RawQuestionSourceData,
+    /// Topics of the course; the model picks one for each question.
+    pub topics: Vec<String>,
+}
+
+/// A question read from a scanned document, with where it came from so
+/// reviewers can compare it to the original.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OcrDraft {
+    pub question: RawQuestionData,
+    pub provenance: OcrProvenance,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct OcrProvenance {
+    pub document_name: String,
+    pub page: u32,
+    pub ocr_backend: String,
+    pub model: String,
+    /// Recognized text of the whole page.
+    pub page_text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct StructuringPayload {
+    questions: Vec<StructuredQuestion>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct StructuredQuestion {
+    text: String,
+    question_options: Vec<String>,
+    /// Zero-based, `None` when the document doesn't mark the answer.
+    correct_option: Option<usize>,
+    topic: Option<String>,
+}
+
+/// Extracts the questions of a scanned exam, one page at a time. Questions
+/// split across pages come out truncated and are caught in review.
+pub async fn extract_questions(
+    document: &[u8],
+    options: &OcrOptions,
+    backend: &impl OcrBackend,
+    client: &impl LlmClient,
+) -> Result<Vec<OcrDraft>> {
+    let mut drafts = vec![];
+
+    for page in backend.extract_pages(document).await? {
+        if page.text.trim().is_empty() {
+            continue;
+        }
+
+        let payload = structure_page(&page, &options.topics, client).await?;
+
+        drafts.extend(payload.questions.into_iter().map(|structured| {
+            OcrDraft {
+                question: RawQuestionData {
+                    id: Uuid::new_v4(),
+                    text: structured.text,
+                    explanation: None,
+                    topic: structured.topic.clone().unwrap_or_default(),
+                    topic_by: structured.topic.is_some().then(|| STRUCTURING_MODEL.into()),
+                    tags: vec![],
+                    image_file_name: None,
+                    alt_text: None,
+                    question_options: structured
+                        .question_options
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, text)| RawQuestionOptionData {
+                            id: Uuid::new_v4(),
+                            text,
+                            is_correct: structured.correct_option == Some(index),
+                            preserve_case: false,
+                            explanation: None,
+                        })
+                        .collect(),
+                    source: options.source.clone(),
+                    translations: Default::default(),
+                    license: None,
+                    media: vec![],
+                    kind: Default::default(),
+                    time_limit_seconds: None,
+                },
+                provenance: OcrProvenance {
+                    document_name: options.document_name.clone(),
+                    page: page.number,
+                    ocr_backend: backend.name().into(),
+                    model: STRUCTURING_MODEL.into(),
+                    page_text: page.text.clone(),
+                },
+            }
+        }));
+    }
+
+    Ok(drafts)
+}
+
+async fn structure_page(
+    page: &OcrPage,
+    topics: &[String],
+    client: &impl LlmClient,
+) -> Result<StructuringPayload> {
+    let mut prompt = String::from(
+        "The user sends the OCR text of a page of a medical exam. Extract its multiple choice \
+        questions, fixing obvious recognition errors but otherwise keeping the original wording. \
+        Reply with a JSON object with a questions key, holding a list of objects with the keys \
+        text, question_options (a list of strings without their letters), correct_option (the \
+        zero-based index of the option marked as correct, or null if none is marked) and topic.",
+    );
+
+    if topics.is_empty() {
+        prompt.push_str(" Set topic to null.");
+    } else {
+        prompt.push_str(&format!(
+            " Set topic to the best match among: {}.",
+            topics.join("; ")
+        ));
+    }
+
+    let request = CreateChatCompletionRequestArgs::default()
+        .model(STRUCTURING_MODEL)
+        .response_format(ResponseFormat::JsonObject)
+        .messages([
+            ChatCompletionRequestSystemMessageArgs::default()
+                .content(prompt)
+                .build()?
+                .into(),
+            ChatCompletionRequestUserMessageArgs::default()
+                .content(page.text.as_str())
+                .build()?
+                .into(),
+        ])
+        .build()?;
+
+    let response = client.chat_completion(request).await?;
+
+    Ok(serde_json::from_str(&response)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::sync::QuestionSourceType;
+    use crate::test_support::FakeLlmClient;
+
+    struct FakeOcr;
+
+    impl OcrBackend for FakeOcr {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        async fn extract_pages(&self, _document: &[u8]) -> Result<Vec<OcrPage>> {
+            Ok(vec![
+                OcrPage {
+                    number: 1,
+                    text: "1. ¿Cuál es la causa más frecuente de...? a) ... b) ...".into(),
+                },
+                OcrPage {
+                    number: 2,
+                    text: "  ".into(),
+                },
+            ])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_questions() {
+        let client = FakeLlmClient::new([serde_json::json!({
+            "questions": [{
+                "text": "¿Cuál es la causa más frecuente de hipotiroidismo?",
+                "question_options": ["Tiroiditis de Hashimoto", "Déficit de yodo", "Fármacos"],
+                "correct_option": null,
+                "topic": "Endocrinología"
+            }]
+        })
+        .to_string()]);
+        let options = OcrOptions {
+            document_name: "examen-2023-12.pdf".into(),
+            source: RawQuestionSourceData {
+                r#type: QuestionSourceType::Exam,
+                name: None,
+                date: NaiveDate::from_ymd_opt(2023, 12, 11),
+                period: None,
+            },
+            topics: vec!["Endocrinología".into()],
+        };
+
+        let drafts = extract_questions(b"%PDF", &options, &FakeOcr, &client)
+            .await
+            .unwrap();
+
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(client.requests().len(), 1);
+        assert_eq!(drafts[0].provenance.page, 1);
+        assert_eq!(drafts[0].provenance.ocr_backend, "fake");
+        assert_eq!(drafts[0].question.topic, "Endocrinología");
+        assert!(drafts[0]
+            .question
+            .question_options
+            .iter()
+            .all(|question_option| !question_option.is_correct));
+    }
+}
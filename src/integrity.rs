@@ -0,0 +1,261 @@
+//! Heuristics flagging implausible answer patterns in quiz results, e.g. in
+//! scholarship exams. Flags are meant for human review, not as proof of cheating.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct AnswerRecord {
+    pub user_id: Uuid,
+    pub question_id: Uuid,
+    /// Length of the question's text in characters.
+    pub question_length: usize,
+    pub answered_at: DateTime<Utc>,
+    /// Time from showing the question to answering it.
+    pub duration_ms: u64,
+    pub correct: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct IntegrityThresholds {
+    /// Answers faster than this to questions of at least `long_question_length`
+    /// characters are too fast to have read the question.
+    pub fast_answer_ms: u64,
+    pub long_question_length: usize,
+    /// Fast answers a user needs to be flagged, so a single lucky guess isn't.
+    pub min_fast_answers: usize,
+    /// Durations closer than this to each other count as matching.
+    pub timing_tolerance_ms: u64,
+    /// Questions two users must have both answered to compare their timings.
+    pub min_shared_answers: usize,
+    /// Share of the shared questions, from 0 to 1, with matching durations for
+    /// two users to be flagged.
+    pub min_matching_share: f64,
+}
+
+impl Default for IntegrityThresholds {
+    fn default() -> Self {
+        Self {
+            fast_answer_ms: 1_000,
+            long_question_length: 300,
+            min_fast_answers: 3,
+            timing_tolerance_ms: 100,
+            min_shared_answers: 10,
+            min_matching_share: 0.9,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IntegrityFlag {
+    FastAnswers {
+        user_id: Uuid,
+        question_ids: Vec<Uuid>,
+    },
+    /// Two accounts answered the same questions taking the same times, e.g.
+    /// because they're scripted or one person is using both.
+    MatchingTimings {
+        user_ids: [Uuid; 2],
+        shared_answers: usize,
+        matching_answers: usize,
+    },
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
+pub struct IntegrityReport {
+    pub flags: Vec<IntegrityFlag>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.flags.is_empty()
+    }
+
+    pub fn flagged_users(&self) -> BTreeSet<Uuid> {
+        self.flags
+            .iter()
+            .flat_map(|flag| match flag {
+                IntegrityFlag::FastAnswers { user_id, .. } => vec![*user_id],
+                IntegrityFlag::MatchingTimings { user_ids, .. } => user_ids.to_vec(),
+            })
+            .collect()
+    }
+}
+
+pub fn analyze(answers: &[AnswerRecord]) -> IntegrityReport {
+    analyze_with(answers, &IntegrityThresholds::default())
+}
+
+pub fn analyze_with(answers: &[AnswerRecord], thresholds: &IntegrityThresholds) -> IntegrityReport {
+    let mut flags = fast_answer_flags(answers, thresholds);
+    flags.extend(matching_timing_flags(answers, thresholds));
+
+    IntegrityReport { flags }
+}
+
+fn fast_answer_flags(
+    answers: &[AnswerRecord],
+    thresholds: &IntegrityThresholds,
+) -> Vec<IntegrityFlag> {
+    let mut fast_answers = BTreeMap::<Uuid, Vec<&AnswerRecord>>::new();
+
+    for answer in answers {
+        if answer.duration_ms < thresholds.fast_answer_ms
+            && answer.question_length >= thresholds.long_question_length
+        {
+            fast_answers.entry(answer.user_id).or_default().push(answer);
+        }
+    }
+
+    fast_answers
+        .into_iter()
+        .filter(|(_, answers)| answers.len() >= thresholds.min_fast_answers.max(1))
+        .map(|(user_id, mut answers)| {
+            answers.sort_by_key(|answer| answer.answered_at);
+
+            IntegrityFlag::FastAnswers {
+                user_id,
+                question_ids: answers.iter().map(|answer| answer.question_id).collect(),
+            }
+        })
+        .collect()
+}
+
+/// Only pairs with durations matching on some question are compared, so this
+/// stays close to linear when timings are spread out.
+fn matching_timing_flags(
+    answers: &[AnswerRecord],
+    thresholds: &IntegrityThresholds,
+) -> Vec<IntegrityFlag> {
+    // The first answer of each user to each question.
+    let mut answers_by_question = HashMap::<Uuid, BTreeMap<Uuid, &AnswerRecord>>::new();
+
+    for answer in answers {
+        let previous = answers_by_question
+            .entry(answer.question_id)
+            .or_default()
+            .entry(answer.user_id)
+            .or_insert(answer);
+
+        if answer.answered_at < previous.answered_at {
+            *previous = answer;
+        }
+    }
+
+    let mut questions_by_user = HashMap::<Uuid, HashSet<Uuid>>::new();
+    let mut matching_counts = BTreeMap::<(Uuid, Uuid), usize>::new();
+
+    for (question_id, answers) in &answers_by_question {
+        let mut answers = answers.values().collect::<Vec<_>>();
+        answers.sort_by_key(|answer| answer.duration_ms);
+
+        for (index, answer) in answers.iter().enumerate() {
+            questions_by_user
+                .entry(answer.user_id)
+                .or_default()
+                .insert(*question_id);
+
+            for other in answers[index + 1..].iter().take_while(|other| {
+                other.duration_ms - answer.duration_ms <= thresholds.timing_tolerance_ms
+            }) {
+                let pair = if answer.user_id < other.user_id {
+                    (answer.user_id, other.user_id)
+                } else {
+                    (other.user_id, answer.user_id)
+                };
+
+                *matching_counts.entry(pair).or_default() += 1;
+            }
+        }
+    }
+
+    matching_counts
+        .into_iter()
+        .filter_map(|((first, second), matching_answers)| {
+            let shared_answers = questions_by_user[&first]
+                .intersection(&questions_by_user[&second])
+                .count();
+
+            (shared_answers >= thresholds.min_shared_answers.max(1)
+                && matching_answers as f64 / shared_answers as f64 >= thresholds.min_matching_share)
+                .then_some(IntegrityFlag::MatchingTimings {
+                    user_ids: [first, second],
+                    shared_answers,
+                    matching_answers,
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    #[test]
+    fn test_analyze() {
+        let start = Utc::now();
+        let users = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let questions = (0..12).map(|_| Uuid::new_v4()).collect::<Vec<_>>();
+        let answer = |user: usize, question: usize, duration_ms| AnswerRecord {
+            user_id: users[user],
+            question_id: questions[question],
+            question_length: if question < 4 { 500 } else { 100 },
+            answered_at: start + TimeDelta::minutes(question as i64),
+            duration_ms,
+            correct: true,
+        };
+
+        let mut answers = vec![];
+
+        for question in 0..12 {
+            let duration_ms = 20_000 + question as u64 * 1_000;
+
+            // The first two users take almost the same time on every question.
+            answers.push(answer(0, question, duration_ms));
+            answers.push(answer(1, question, duration_ms + 50));
+            // The third skims the long questions.
+            answers.push(answer(
+                2,
+                question,
+                if question < 4 { 600 } else { duration_ms + 400 },
+            ));
+        }
+
+        let report = analyze(&answers);
+        let mut first_pair = [users[0], users[1]];
+        first_pair.sort();
+
+        assert_eq!(
+            report.flags,
+            vec![
+                IntegrityFlag::FastAnswers {
+                    user_id: users[2],
+                    question_ids: questions[..4].to_vec(),
+                },
+                IntegrityFlag::MatchingTimings {
+                    user_ids: first_pair,
+                    shared_answers: 12,
+                    matching_answers: 12,
+                },
+            ]
+        );
+        assert_eq!(report.flagged_users(), users.into_iter().collect());
+
+        let report = analyze_with(
+            &answers,
+            &IntegrityThresholds {
+                min_fast_answers: 5,
+                timing_tolerance_ms: 10,
+                ..Default::default()
+            },
+        );
+
+        assert!(report.is_clean());
+    }
+}
@@ -1,4 +1,67 @@
+pub mod adaptive;
+pub mod analytics;
+pub mod api_error;
+pub mod cache;
+pub mod changelog;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod comments;
+pub mod config;
+#[cfg(feature = "crypto")]
+pub mod crypto;
+pub mod daily;
+pub mod digests;
+pub mod email;
+pub mod entitlements;
+pub mod errata;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "openai")]
+pub mod generation;
+pub mod glossary;
+#[cfg(feature = "openai")]
 pub mod helpers;
+#[cfg(feature = "client")]
+pub mod http;
+pub mod images;
+#[cfg(feature = "ocr")]
+pub mod ingest;
+pub mod integrity;
+pub mod links;
+pub mod money;
+pub mod notifications;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod overlap;
+pub mod payments;
+pub mod precondition;
+pub mod psychometrics;
+pub mod rankings;
+pub mod recommend;
+pub mod reconciliation;
+pub mod redact;
+pub mod render;
+pub mod review;
+#[cfg(feature = "runtime")]
+pub mod runtime;
+pub mod search;
+pub mod slug;
+pub mod stats;
 pub mod status;
+pub mod streaks;
 pub mod sync;
+pub mod telemetry;
+#[cfg(any(test, feature = "test_support"))]
+pub mod test_support;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod traits;
+#[cfg(feature = "openai")]
+pub mod translate;
+#[cfg(feature = "openai")]
+pub mod verification;
+pub mod webhooks;
+
+#[cfg(feature = "ffi")]
+uniffi::setup_scaffolding!();
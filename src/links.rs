@@ -0,0 +1,153 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::sync::{BundleData, CourseData, QuestionData};
+
+pub const APP_SCHEME: &str = "medici://";
+pub const UNIVERSAL_LINK_BASE_URL: &str = "https://medici.uy";
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DeepLink {
+    Course { key: String },
+    Question { id: Uuid },
+    Bundle { key: String },
+    QuizSession { id: Uuid },
+}
+
+impl DeepLink {
+    const COURSES_PATH: &'static str = "courses";
+    const QUESTIONS_PATH: &'static str = "questions";
+    const BUNDLES_PATH: &'static str = "bundles";
+    const QUIZ_SESSIONS_PATH: &'static str = "quiz-sessions";
+
+    pub fn path(&self) -> String {
+        match self {
+            Self::Course { key } => format!("{}/{key}", Self::COURSES_PATH),
+            Self::Question { id } => format!("{}/{id}", Self::QUESTIONS_PATH),
+            Self::Bundle { key } => format!("{}/{key}", Self::BUNDLES_PATH),
+            Self::QuizSession { id } => format!("{}/{id}", Self::QUIZ_SESSIONS_PATH),
+        }
+    }
+
+    pub fn app_link(&self) -> String {
+        format!("{APP_SCHEME}{}", self.path())
+    }
+
+    pub fn universal_link(&self) -> String {
+        self.url(UNIVERSAL_LINK_BASE_URL)
+    }
+
+    /// The page of the link on a site other than the universal link one, such as
+    /// a staging deployment.
+    pub fn url(&self, base_url: &str) -> String {
+        format!("{}/{}", base_url.trim_end_matches('/'), self.path())
+    }
+
+    pub fn parse(link: &str) -> Result<Self> {
+        let Some(path) = link.strip_prefix(APP_SCHEME).or_else(|| {
+            link.strip_prefix(UNIVERSAL_LINK_BASE_URL)?
+                .strip_prefix('/')
+        }) else {
+            bail!("unknown link {link}");
+        };
+
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+
+        let Some((entity, value)) = path.trim_end_matches('/').split_once('/') else {
+            bail!("invalid link {link}");
+        };
+
+        if value.is_empty() || value.contains('/') {
+            bail!("invalid link {link}");
+        }
+
+        Ok(match entity {
+            Self::COURSES_PATH => Self::Course { key: value.into() },
+            Self::QUESTIONS_PATH => Self::Question {
+                id: Uuid::parse_str(value)?,
+            },
+            Self::BUNDLES_PATH => Self::Bundle { key: value.into() },
+            Self::QUIZ_SESSIONS_PATH => Self::QuizSession {
+                id: Uuid::parse_str(value)?,
+            },
+            _ => bail!("unknown link entity {entity}"),
+        })
+    }
+}
+
+impl FromStr for DeepLink {
+    type Err = anyhow::Error;
+
+    fn from_str(link: &str) -> Result<Self> {
+        Self::parse(link)
+    }
+}
+
+impl std::fmt::Display for DeepLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.app_link())
+    }
+}
+
+impl From<&CourseData> for DeepLink {
+    fn from(course: &CourseData) -> Self {
+        Self::Course {
+            key: course.key.clone(),
+        }
+    }
+}
+
+impl From<&QuestionData> for DeepLink {
+    fn from(question: &QuestionData) -> Self {
+        Self::Question { id: question.id }
+    }
+}
+
+impl From<&BundleData> for DeepLink {
+    fn from(bundle: &BundleData) -> Self {
+        Self::Bundle {
+            key: bundle.key.clone(),
+        }
+    }
+}
+
+pub fn deep_link(entity: impl Into<DeepLink>) -> DeepLink {
+    entity.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let links = [
+            DeepLink::Course {
+                key: "anatomia".into(),
+            },
+            DeepLink::Question { id: Uuid::new_v4() },
+            DeepLink::Bundle { key: "pack".into() },
+            DeepLink::QuizSession { id: Uuid::new_v4() },
+        ];
+
+        for link in links {
+            assert_eq!(DeepLink::parse(&link.app_link()).unwrap(), link);
+            assert_eq!(DeepLink::parse(&link.universal_link()).unwrap(), link);
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(DeepLink::parse("https://example.com/courses/a").is_err());
+        assert!(DeepLink::parse("medici://questions/not-a-uuid").is_err());
+        assert!(DeepLink::parse("medici://courses/").is_err());
+        assert_eq!(
+            DeepLink::parse("https://medici.uy/courses/a?utm=x").unwrap(),
+            DeepLink::Course { key: "a".into() }
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::{bail, Result};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    strum::Display,
+    strum::EnumString,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    Debug,
+)]
+#[cfg_attr(
+    feature = "db",
+    derive(sqlx::Type),
+    sqlx(type_name = "text", rename_all = "UPPERCASE")
+)]
+#[serde(rename_all = "UPPERCASE")]
+#[strum(serialize_all = "UPPERCASE", ascii_case_insensitive)]
+pub enum Currency {
+    Uyu,
+    Usd,
+    Ars,
+    Brl,
+    Clp,
+}
+
+impl Currency {
+    pub fn minor_units(&self) -> u32 {
+        match self {
+            Self::Clp => 0,
+            _ => 2,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Money {
+    pub amount: Decimal,
+    pub currency: Currency,
+}
+
+impl Money {
+    /// Rounds the amount to the currency's minor units.
+    pub fn new(amount: Decimal, currency: Currency) -> Self {
+        Self {
+            amount: amount.round_dp_with_strategy(
+                currency.minor_units(),
+                RoundingStrategy::MidpointAwayFromZero,
+            ),
+            currency,
+        }
+    }
+
+    pub fn uyu(amount: Decimal) -> Self {
+        Self::new(amount, Currency::Uyu)
+    }
+
+    pub fn from_minor_units(minor_units: i64, currency: Currency) -> Self {
+        Self {
+            amount: Decimal::new(minor_units, currency.minor_units()),
+            currency,
+        }
+    }
+
+    pub fn to_minor_units(&self) -> Result<i64> {
+        let scale = Decimal::from(10i64.pow(self.currency.minor_units()));
+
+        let Some(minor_units) = (self.amount * scale).trunc().to_i64() else {
+            bail!("amount {self} out of range");
+        };
+
+        Ok(minor_units)
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.amount > Decimal::ZERO
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Result<Self> {
+        if self.currency != other.currency {
+            bail!("cannot add {other} to {self}");
+        }
+
+        Ok(Self::new(self.amount + other.amount, self.currency))
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Result<Self> {
+        if self.currency != other.currency {
+            bail!("cannot subtract {other} from {self}");
+        }
+
+        Ok(Self::new(self.amount - other.amount, self.currency))
+    }
+}
+
+impl std::fmt::Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {:.*}",
+            self.currency,
+            self.currency.minor_units() as usize,
+            self.amount
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_minor_units() {
+        let money = Money::new(Decimal::from_str("1234.565").unwrap(), Currency::Uyu);
+
+        assert_eq!(money.to_string(), "UYU 1234.57");
+        assert_eq!(money.to_minor_units().unwrap(), 123_457);
+        assert_eq!(Money::from_minor_units(123_457, Currency::Uyu), money);
+        assert_eq!(
+            Money::from_minor_units(1500, Currency::Clp).to_string(),
+            "CLP 1500"
+        );
+        assert!(money.checked_add(&Money::uyu(Decimal::ONE)).is_ok());
+        assert!(money
+            .checked_add(&Money::new(Decimal::ONE, Currency::Usd))
+            .is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+use crate::links::DeepLink;
+use crate::sync::LanguageTag;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationPayload {
+    StreakReminder {
+        streak_days: u16,
+    },
+    NewContentAvailable {
+        course_key: String,
+        course_name: String,
+    },
+    SubscriptionExpiring {
+        days_left: u16,
+    },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    pub payload: NotificationPayload,
+}
+
+impl NotificationPayload {
+    pub fn render(self, locale: &LanguageTag) -> Notification {
+        let (title, body) = match locale.language() {
+            "pt" => self.texts_pt(),
+            "en" => self.texts_en(),
+            _ => self.texts_es(),
+        };
+
+        Notification {
+            title,
+            body,
+            payload: self,
+        }
+    }
+
+    pub fn deep_link(&self) -> Option<DeepLink> {
+        match self {
+            Self::NewContentAvailable { course_key, .. } => Some(DeepLink::Course {
+                key: course_key.clone(),
+            }),
+            Self::StreakReminder { .. } | Self::SubscriptionExpiring { .. } => None,
+        }
+    }
+
+    fn texts_es(&self) -> (String, String) {
+        match self {
+            Self::StreakReminder { streak_days } => (
+                "¡No pierdas tu racha!".into(),
+                format!(
+                    "Llevás {streak_days} días seguidos practicando. Respondé una pregunta hoy."
+                ),
+            ),
+            Self::NewContentAvailable { course_name, .. } => (
+                "Nuevo contenido".into(),
+                format!("Hay preguntas nuevas en {course_name}."),
+            ),
+            Self::SubscriptionExpiring { days_left } => (
+                "Tu suscripción está por vencer".into(),
+                format!("Tu suscripción vence en {days_left} días."),
+            ),
+        }
+    }
+
+    fn texts_pt(&self) -> (String, String) {
+        match self {
+            Self::StreakReminder { streak_days } => (
+                "Não perca sua sequência!".into(),
+                format!("Você pratica há {streak_days} dias seguidos. Responda uma pergunta hoje."),
+            ),
+            Self::NewContentAvailable { course_name, .. } => (
+                "Novo conteúdo".into(),
+                format!("Há novas perguntas em {course_name}."),
+            ),
+            Self::SubscriptionExpiring { days_left } => (
+                "Sua assinatura está perto de vencer".into(),
+                format!("Sua assinatura vence em {days_left} dias."),
+            ),
+        }
+    }
+
+    fn texts_en(&self) -> (String, String) {
+        match self {
+            Self::StreakReminder { streak_days } => (
+                "Don't lose your streak!".into(),
+                format!("You've practiced {streak_days} days in a row. Answer a question today."),
+            ),
+            Self::NewContentAvailable { course_name, .. } => (
+                "New content".into(),
+                format!("There are new questions in {course_name}."),
+            ),
+            Self::SubscriptionExpiring { days_left } => (
+                "Your subscription is expiring".into(),
+                format!("Your subscription expires in {days_left} days."),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let notification = NotificationPayload::SubscriptionExpiring { days_left: 3 }
+            .render(&LanguageTag::new("pt-BR").unwrap());
+
+        assert_eq!(notification.body, "Sua assinatura vence em 3 dias.");
+
+        let json = serde_json::to_value(&notification.payload).unwrap();
+
+        assert_eq!(json["type"], "subscription_expiring");
+        assert_eq!(json["days_left"], 3);
+    }
+}
@@ -0,0 +1,74 @@
+//! Schemas of the shared DTOs, for merging into a service's OpenAPI document:
+//! `ApiDoc::openapi().merge_from(SharedSchemas::openapi())`.
+
+use utoipa::OpenApi;
+
+use crate::analytics::{AnalyticsBatch, AnalyticsEvent, AnalyticsRecord};
+use crate::api_error::{ApiError, ApiErrorCode, FieldError};
+use crate::glossary::{AnnotatedText, TermSpan};
+use crate::precondition::{IfMatch, QuestionPatchRequest};
+use crate::search::{SearchFilters, SearchHit, Suggestion, SuggestionKind};
+use crate::status::engine::{CacheStatus, DbStatus, EngineStatus};
+use crate::sync::{
+    BundleData, CaseData, Catalog, ContentEntityType, CourseData, CourseRelation,
+    CourseRelationKind, DateRange, GlossaryTermData, LanguageTag, LearningPathData, LicenseData,
+    LicenseKind, MilestoneData, OptionCountRange, SyncCounts, SyncMetadata, SyncReport,
+    ValidationIssue, ValidationReport,
+};
+
+#[derive(OpenApi)]
+#[openapi(components(schemas(
+    AnalyticsBatch,
+    AnalyticsEvent,
+    AnalyticsRecord,
+    AnnotatedText,
+    ApiError,
+    ApiErrorCode,
+    BundleData,
+    CacheStatus,
+    CaseData,
+    Catalog,
+    ContentEntityType,
+    CourseData,
+    CourseRelation,
+    CourseRelationKind,
+    DateRange,
+    DbStatus,
+    EngineStatus,
+    FieldError,
+    GlossaryTermData,
+    IfMatch,
+    LanguageTag,
+    LearningPathData,
+    LicenseData,
+    LicenseKind,
+    MilestoneData,
+    OptionCountRange,
+    QuestionPatchRequest,
+    SearchFilters,
+    SearchHit,
+    Suggestion,
+    SuggestionKind,
+    SyncCounts,
+    SyncMetadata,
+    SyncReport,
+    TermSpan,
+    ValidationIssue,
+    ValidationReport,
+)))]
+pub struct SharedSchemas;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_schemas() {
+        let openapi = SharedSchemas::openapi();
+        let schemas = openapi.components.unwrap().schemas;
+
+        assert!(schemas.contains_key("Catalog"));
+        assert!(schemas.contains_key("EngineStatus"));
+        assert!(schemas.contains_key("ValidationReport"));
+    }
+}
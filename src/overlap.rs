@@ -0,0 +1,211 @@
+//! Detection of the same question in several courses, e.g. partner content
+//! copied into another course. Questions are compared by the Jaccard
+//! similarity of their word shingles, so small edits and reordered options
+//! still match.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::slug::fold_accent;
+use crate::sync::{CourseData, QuestionData};
+
+/// Words per shingle.
+pub const SHINGLE_SIZE: usize = 3;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+pub struct QuestionRef {
+    pub course_key: String,
+    pub question_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct OverlapMatch {
+    pub first: QuestionRef,
+    pub second: QuestionRef,
+    /// From 0 to 1, where 1 means the same normalized text.
+    pub similarity: f64,
+}
+
+/// Pairs of questions in different courses with a similarity of at least
+/// `threshold`, most similar first.
+pub fn find_cross_course_duplicates(courses: &[CourseData], threshold: f64) -> Vec<OverlapMatch> {
+    let questions = courses
+        .iter()
+        .flat_map(|course| {
+            course
+                .questions
+                .iter()
+                .map(move |question| (course, question))
+        })
+        .filter(|(_, question)| !question.is_blank())
+        .map(|(course, question)| (course.key.as_str(), question.id, shingles(question)))
+        .filter(|(_, _, shingles)| !shingles.is_empty())
+        .collect::<Vec<_>>();
+
+    let mut index = HashMap::<u64, Vec<usize>>::new();
+
+    for (position, (_, _, shingles)) in questions.iter().enumerate() {
+        for shingle in shingles {
+            index.entry(*shingle).or_default().push(position);
+        }
+    }
+
+    let mut matches = vec![];
+
+    for (position, (course_key, question_id, shingles)) in questions.iter().enumerate() {
+        let mut shared_counts = HashMap::<usize, usize>::new();
+
+        for shingle in shingles {
+            for &other in &index[shingle] {
+                if other > position && questions[other].0 != *course_key {
+                    *shared_counts.entry(other).or_default() += 1;
+                }
+            }
+        }
+
+        for (other, shared_count) in shared_counts {
+            let (other_course_key, other_question_id, other_shingles) = &questions[other];
+            let similarity =
+                shared_count as f64 / (shingles.len() + other_shingles.len() - shared_count) as f64;
+
+            if similarity >= threshold {
+                matches.push(OverlapMatch {
+                    first: QuestionRef {
+                        course_key: course_key.to_string(),
+                        question_id: *question_id,
+                    },
+                    second: QuestionRef {
+                        course_key: other_course_key.to_string(),
+                        question_id: *other_question_id,
+                    },
+                    similarity,
+                });
+            }
+        }
+    }
+
+    matches.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then_with(|| a.first.question_id.cmp(&b.first.question_id))
+            .then_with(|| a.second.question_id.cmp(&b.second.question_id))
+    });
+
+    matches
+}
+
+/// Hashed word shingles of the question's text and options, ignoring case,
+/// accents, punctuation and option order.
+fn shingles(question: &QuestionData) -> HashSet<u64> {
+    let mut option_texts = question
+        .question_options
+        .iter()
+        .map(|question_option| normalize(&question_option.text))
+        .collect::<Vec<_>>();
+    option_texts.sort_unstable();
+
+    let text = normalize(&question.text);
+    let words = text
+        .split(' ')
+        .chain(
+            option_texts
+                .iter()
+                .flat_map(|option_text| option_text.split(' ')),
+        )
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>();
+
+    words
+        .windows(SHINGLE_SIZE.min(words.len()).max(1))
+        .map(|shingle| {
+            let mut hasher = DefaultHasher::new();
+            shingle.hash(&mut hasher);
+
+            hasher.finish()
+        })
+        .collect()
+}
+
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(fold_accent)
+        .map(|char| {
+            if char.is_alphanumeric() {
+                char.to_ascii_lowercase()
+            } else {
+                ' '
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::sync::QuestionOptionData;
+
+    #[test]
+    fn test_find_cross_course_duplicates() {
+        let question = |course_key: &str, text: &str, options: [&str; 3]| {
+            let mut question = Faker.fake::<QuestionData>();
+            question.course_key = course_key.into();
+            question.text = text.into();
+            question.question_options = options
+                .iter()
+                .map(|option_text| {
+                    let mut question_option = Faker.fake::<QuestionOptionData>();
+                    question_option.text = option_text.to_string();
+
+                    question_option
+                })
+                .collect();
+
+            question
+        };
+        let course = |key: &str, questions| {
+            let mut course = Faker.fake::<CourseData>();
+            course.key = key.into();
+            course.questions = questions;
+
+            course
+        };
+
+        let text = "¿Cuál es el tratamiento de primera línea de la hipertensión arterial?";
+        let options = ["Diuréticos tiazídicos", "Betabloqueantes", "Nitratos"];
+        let courses = [
+            course(
+                "MI",
+                vec![
+                    question("MI", text, options),
+                    question("MI", text, options),
+                    question(
+                        "MI",
+                        "¿Qué es la insulina?",
+                        ["Una hormona", "Una enzima", "Un lípido"],
+                    ),
+                ],
+            ),
+            course(
+                "CARDIO",
+                vec![question(
+                    "CARDIO",
+                    "¿Cuál es el tratamiento de primera linea de la HIPERTENSIÓN arterial?",
+                    ["Nitratos", "Betabloqueantes", "Diuréticos tiazídicos."],
+                )],
+            ),
+        ];
+
+        let matches = find_cross_course_duplicates(&courses, 0.8);
+
+        assert_eq!(matches.len(), 2);
+        assert!(matches.iter().all(|overlap| overlap.similarity == 1.0
+            && overlap.first.course_key == "MI"
+            && overlap.second.course_key == "CARDIO"));
+        assert!(find_cross_course_duplicates(&courses[..1], 0.0).is_empty());
+    }
+}
@@ -0,0 +1,249 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::money::{Currency, Money};
+use crate::redact::Email;
+use crate::webhooks::{MercadoPagoPayment, MercadoPagoPaymentStatus};
+
+#[derive(
+    strum::Display,
+    strum::EnumString,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    Debug,
+)]
+#[cfg_attr(
+    feature = "db",
+    derive(sqlx::Type),
+    sqlx(type_name = "text", rename_all = "snake_case")
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum PaymentProvider {
+    MercadoPago,
+    DLocal,
+}
+
+#[derive(
+    strum::Display,
+    strum::EnumString,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    Debug,
+)]
+#[cfg_attr(
+    feature = "db",
+    derive(sqlx::Type),
+    sqlx(type_name = "text", rename_all = "snake_case")
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum PaymentStatus {
+    Pending,
+    Authorized,
+    Approved,
+    Rejected,
+    Cancelled,
+    Expired,
+    Refunded,
+    ChargedBack,
+}
+
+impl PaymentStatus {
+    pub fn is_paid(&self) -> bool {
+        *self == Self::Approved
+    }
+
+    /// Statuses that undo a previous approval.
+    pub fn is_reversal(&self) -> bool {
+        matches!(self, Self::Refunded | Self::ChargedBack)
+    }
+
+    /// Whether the provider can still move the payment to another status.
+    pub fn is_final(&self) -> bool {
+        !matches!(self, Self::Pending | Self::Authorized | Self::Approved)
+    }
+}
+
+/// A payment as seen by any provider, keyed by the provider's payment ID.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct PaymentIntent {
+    pub provider: PaymentProvider,
+    pub provider_payment_id: String,
+    /// Our order reference, sent to the provider when creating the checkout.
+    pub external_reference: Option<String>,
+    pub amount: Money,
+    pub status: PaymentStatus,
+    pub status_detail: Option<String>,
+    pub payer_email: Option<Email>,
+    pub live_mode: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub paid_at: Option<DateTime<Utc>>,
+}
+
+impl PaymentIntent {
+    pub fn from_mercadopago(payment: &MercadoPagoPayment) -> Result<Self> {
+        Ok(Self {
+            provider: PaymentProvider::MercadoPago,
+            provider_payment_id: payment.id.to_string(),
+            external_reference: payment.external_reference.clone(),
+            amount: Money::new(
+                payment.transaction_amount,
+                parse_currency(&payment.currency_id)?,
+            ),
+            status: payment.status.into(),
+            status_detail: payment.status_detail.clone(),
+            payer_email: payment
+                .payer
+                .as_ref()
+                .and_then(|payer| payer.email.as_deref())
+                .and_then(|email| Email::new(email).ok()),
+            live_mode: payment.live_mode,
+            created_at: payment.date_created,
+            paid_at: payment.date_approved,
+        })
+    }
+
+    pub fn from_dlocal(payment: &DLocalPayment) -> Result<Self> {
+        Ok(Self {
+            provider: PaymentProvider::DLocal,
+            provider_payment_id: payment.id.clone(),
+            external_reference: payment.order_id.clone(),
+            amount: Money::new(payment.amount, parse_currency(&payment.currency)?),
+            status: payment.status.into(),
+            status_detail: payment.status_detail.clone(),
+            payer_email: payment
+                .payer
+                .as_ref()
+                .and_then(|payer| payer.email.as_deref())
+                .and_then(|email| Email::new(email).ok()),
+            live_mode: payment.live_mode,
+            created_at: payment.created_date,
+            paid_at: payment.approved_date,
+        })
+    }
+}
+
+fn parse_currency(currency: &str) -> Result<Currency> {
+    Currency::from_str(currency).map_err(|_| anyhow!("unsupported payment currency {currency}"))
+}
+
+impl From<MercadoPagoPaymentStatus> for PaymentStatus {
+    fn from(value: MercadoPagoPaymentStatus) -> Self {
+        match value {
+            MercadoPagoPaymentStatus::Pending
+            | MercadoPagoPaymentStatus::InProcess
+            | MercadoPagoPaymentStatus::InMediation => Self::Pending,
+            MercadoPagoPaymentStatus::Authorized => Self::Authorized,
+            MercadoPagoPaymentStatus::Approved => Self::Approved,
+            MercadoPagoPaymentStatus::Rejected => Self::Rejected,
+            MercadoPagoPaymentStatus::Cancelled => Self::Cancelled,
+            MercadoPagoPaymentStatus::Refunded => Self::Refunded,
+            MercadoPagoPaymentStatus::ChargedBack => Self::ChargedBack,
+        }
+    }
+}
+
+/// Payment resource returned by dLocal's `/payments/{id}` and sent in its notifications.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct DLocalPayment {
+    pub id: String,
+    pub amount: Decimal,
+    pub currency: String,
+    pub status: DLocalPaymentStatus,
+    pub status_detail: Option<String>,
+    pub order_id: Option<String>,
+    pub created_date: Option<DateTime<Utc>>,
+    pub approved_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub live_mode: bool,
+    pub payer: Option<DLocalPayer>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct DLocalPayer {
+    pub email: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DLocalPaymentStatus {
+    Pending,
+    Authorized,
+    Verified,
+    Paid,
+    Rejected,
+    Cancelled,
+    Expired,
+    Refunded,
+    Chargeback,
+}
+
+impl From<DLocalPaymentStatus> for PaymentStatus {
+    fn from(value: DLocalPaymentStatus) -> Self {
+        match value {
+            DLocalPaymentStatus::Pending | DLocalPaymentStatus::Verified => Self::Pending,
+            DLocalPaymentStatus::Authorized => Self::Authorized,
+            DLocalPaymentStatus::Paid => Self::Approved,
+            DLocalPaymentStatus::Rejected => Self::Rejected,
+            DLocalPaymentStatus::Cancelled => Self::Cancelled,
+            DLocalPaymentStatus::Expired => Self::Expired,
+            DLocalPaymentStatus::Refunded => Self::Refunded,
+            DLocalPaymentStatus::Chargeback => Self::ChargedBack,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_mercadopago() {
+        let payment: MercadoPagoPayment = serde_json::from_str(
+            r#"{"id":123456,"status":"in_process","status_detail":"pending_contingency","transaction_amount":490.5,"currency_id":"UYU","external_reference":"order-1","date_created":"2024-01-10T17:33:30Z","date_approved":null,"live_mode":true,"payer":{"email":"Ana@Example.com"}}"#,
+        )
+        .unwrap();
+
+        let intent = PaymentIntent::from_mercadopago(&payment).unwrap();
+
+        assert_eq!(intent.provider_payment_id, "123456");
+        assert_eq!(intent.status, PaymentStatus::Pending);
+        assert_eq!(intent.amount.to_string(), "UYU 490.50");
+        assert_eq!(intent.payer_email.unwrap().expose(), "ana@example.com");
+    }
+
+    #[test]
+    fn test_from_dlocal() {
+        let payment: DLocalPayment = serde_json::from_str(
+            r#"{"id":"D-4-1","amount":12.0,"currency":"USD","status":"PAID","status_detail":null,"order_id":"order-2","created_date":null,"approved_date":null,"payer":null}"#,
+        )
+        .unwrap();
+
+        let intent = PaymentIntent::from_dlocal(&payment).unwrap();
+
+        assert!(intent.status.is_paid());
+        assert_eq!(intent.amount.currency, Currency::Usd);
+
+        let payment = DLocalPayment {
+            currency: "EUR".into(),
+            ..payment
+        };
+
+        assert!(PaymentIntent::from_dlocal(&payment).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Optimistic concurrency for admin mutations. Clients send the hash of the
+//! entity they edited, and the mutation is rejected if the entity changed
+//! since, instead of silently overwriting someone else's edit.
+
+use http::{header, HeaderMap, StatusCode};
+use json_patch::Patch;
+use serde::{Deserialize, Serialize};
+
+use crate::traits::Hashable;
+
+/// Expected content hash of the entity a mutation applies to.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct IfMatch {
+    pub expected_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct QuestionPatchRequest {
+    #[serde(flatten)]
+    pub if_match: IfMatch,
+    /// RFC 6902 operations, see `QuestionData::apply_patch`.
+    #[cfg_attr(feature = "openapi", schema(value_type = Vec<Object>))]
+    pub patch: Patch,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ConflictError {
+    pub expected_hash: String,
+    pub current_hash: String,
+}
+
+impl IfMatch {
+    pub fn new(expected_hash: impl Into<String>) -> Self {
+        Self {
+            expected_hash: expected_hash.into(),
+        }
+    }
+
+    /// From an `If-Match` header holding a single strong entity tag.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = headers.get(header::IF_MATCH)?.to_str().ok()?.trim();
+        let expected_hash = value.strip_prefix('"')?.strip_suffix('"')?;
+
+        Some(Self::new(expected_hash))
+    }
+
+    /// Entity tag for the `ETag` header of responses, so clients can send it back.
+    pub fn etag(current: &impl Hashable) -> String {
+        format!("\"{}\"", current.compute_hash())
+    }
+
+    pub fn check(&self, current: &impl Hashable) -> Result<(), ConflictError> {
+        check_precondition(current, &self.expected_hash)
+    }
+}
+
+/// Fails if `current` no longer has the hash the client read.
+pub fn check_precondition(current: &impl Hashable, expected: &str) -> Result<(), ConflictError> {
+    let current_hash = current.compute_hash();
+
+    if current_hash == expected {
+        Ok(())
+    } else {
+        Err(ConflictError {
+            expected_hash: expected.into(),
+            current_hash,
+        })
+    }
+}
+
+impl ConflictError {
+    pub fn status_code(&self) -> StatusCode {
+        StatusCode::PRECONDITION_FAILED
+    }
+}
+
+impl std::fmt::Display for ConflictError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "entity changed since it was read: expected hash {}, found {}",
+            self.expected_hash, self.current_hash
+        )
+    }
+}
+
+impl std::error::Error for ConflictError {}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+    use http::HeaderValue;
+
+    use super::*;
+    use crate::sync::QuestionData;
+
+    #[test]
+    fn test_check_precondition() {
+        let mut question: QuestionData = Faker.fake();
+        question.prepare_for_test().unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_MATCH,
+            HeaderValue::from_str(&IfMatch::etag(&question)).unwrap(),
+        );
+        let if_match = IfMatch::from_headers(&headers).unwrap();
+
+        assert_eq!(if_match.check(&question), Ok(()));
+
+        let read_hash = question.hash.clone();
+        question.text.push_str(" Editado");
+        question.process().unwrap();
+
+        let error = check_precondition(&question, &read_hash).unwrap_err();
+
+        assert_eq!(error.current_hash, question.hash);
+        assert_eq!(error.status_code(), StatusCode::PRECONDITION_FAILED);
+    }
+}
@@ -0,0 +1,410 @@
+//! Two-parameter logistic (2PL) item response theory, where the probability of
+//! answering a question correctly is `1 / (1 + e^(-a(θ - b)))` for ability θ,
+//! discrimination `a` and difficulty `b`. Abilities are on a standard normal
+//! scale.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::first_answers;
+use crate::integrity::AnswerRecord;
+
+pub const MIN_DISCRIMINATION: f64 = 0.05;
+pub const MAX_DISCRIMINATION: f64 = 5.0;
+pub const MAX_ABS_DIFFICULTY: f64 = 6.0;
+
+/// Precision of the normal priors on the discrimination (around 1) and the
+/// intercept (around 0) of items, which keep the parameters of items everyone
+/// answers correctly, or incorrectly, finite.
+const ITEM_PRIOR_PRECISION: f64 = 0.1;
+
+/// Abilities at which integrals over the ability distribution are evaluated.
+const ABILITY_RANGE: f64 = 4.0;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct ItemParameters {
+    /// How sharply the probability of a correct answer rises with ability.
+    pub discrimination: f64,
+    /// Ability with even odds of answering correctly.
+    pub difficulty: f64,
+}
+
+impl Default for ItemParameters {
+    fn default() -> Self {
+        Self {
+            discrimination: 1.0,
+            difficulty: 0.0,
+        }
+    }
+}
+
+impl ItemParameters {
+    pub fn probability(&self, ability: f64) -> f64 {
+        logistic(self.discrimination * (ability - self.difficulty))
+    }
+
+    /// Fisher information of an answer at `ability`; answers to questions with
+    /// more information narrow down the ability more.
+    pub fn information(&self, ability: f64) -> f64 {
+        let probability = self.probability(ability);
+
+        self.discrimination.powi(2) * probability * (1.0 - probability)
+    }
+}
+
+/// Answers of examinees without identifying them, with a row per examinee and
+/// a column per question.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+pub struct ResponseMatrix {
+    pub question_ids: Vec<Uuid>,
+    /// Whether each question was answered correctly, `None` if it wasn't answered.
+    pub rows: Vec<Vec<Option<bool>>>,
+}
+
+impl ResponseMatrix {
+    /// Only the first answer of each user to each question counts.
+    pub fn from_answers(answers: &[AnswerRecord]) -> Self {
+        let first_answers = first_answers(answers);
+        let question_ids = first_answers
+            .keys()
+            .map(|(_, question_id)| *question_id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let mut rows = BTreeMap::<Uuid, Vec<Option<bool>>>::new();
+
+        for ((user_id, question_id), answer) in first_answers {
+            let column = question_ids.binary_search(&question_id).unwrap_or_default();
+
+            rows.entry(user_id)
+                .or_insert_with(|| vec![None; question_ids.len()])[column] = Some(answer.correct);
+        }
+
+        Self {
+            question_ids,
+            rows: rows.into_values().collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct IrtFitConfig {
+    pub max_iterations: usize,
+    /// Fitting stops once no parameter changes more than this in an iteration.
+    pub tolerance: f64,
+    /// Abilities at which the ability distribution is evaluated.
+    pub quadrature_points: usize,
+}
+
+impl Default for IrtFitConfig {
+    fn default() -> Self {
+        Self {
+            max_iterations: 200,
+            tolerance: 1e-4,
+            quadrature_points: 41,
+        }
+    }
+}
+
+/// Marginal maximum likelihood estimates of the parameters of the questions
+/// with answers, by expectation-maximization over a standard normal ability
+/// distribution (Bock-Aitkin).
+pub fn fit_item_parameters(
+    matrix: &ResponseMatrix,
+    config: &IrtFitConfig,
+) -> BTreeMap<Uuid, ItemParameters> {
+    let nodes = quadrature(config.quadrature_points);
+    let answered = (0..matrix.question_ids.len())
+        .filter(|&column| matrix.rows.iter().any(|row| row[column].is_some()))
+        .collect::<Vec<_>>();
+    let mut items = vec![ItemParameters::default(); matrix.question_ids.len()];
+
+    for _ in 0..config.max_iterations {
+        // Expected answers and correct answers to each question at each node.
+        let mut expected = vec![vec![(0.0, 0.0); nodes.len()]; items.len()];
+
+        for row in &matrix.rows {
+            let posterior = posterior(
+                &nodes,
+                row.iter()
+                    .zip(&items)
+                    .filter_map(|(correct, item)| correct.map(|correct| (item, correct))),
+            );
+
+            for (column, correct) in row.iter().enumerate() {
+                let Some(correct) = correct else {
+                    continue;
+                };
+
+                for (counts, weight) in expected[column].iter_mut().zip(&posterior) {
+                    counts.0 += weight;
+
+                    if *correct {
+                        counts.1 += weight;
+                    }
+                }
+            }
+        }
+
+        let mut change = 0.0_f64;
+
+        for &column in &answered {
+            let updated = maximize_item(items[column], &expected[column], &nodes);
+
+            change = change
+                .max((updated.discrimination - items[column].discrimination).abs())
+                .max((updated.difficulty - items[column].difficulty).abs());
+            items[column] = updated;
+        }
+
+        if change < config.tolerance {
+            break;
+        }
+    }
+
+    answered
+        .into_iter()
+        .map(|column| (matrix.question_ids[column], items[column]))
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct AbilityEstimate {
+    pub ability: f64,
+    pub standard_error: f64,
+}
+
+/// Expected a posteriori estimate under a standard normal prior, so it's finite
+/// even when all answers are correct. Without answers, it's the prior's mean.
+pub fn estimate_ability(responses: &[(ItemParameters, bool)]) -> AbilityEstimate {
+    let nodes = quadrature(81);
+    let posterior = posterior(
+        &nodes,
+        responses.iter().map(|(item, correct)| (item, *correct)),
+    );
+
+    let ability = nodes
+        .iter()
+        .zip(&posterior)
+        .map(|((node, _), weight)| node * weight)
+        .sum::<f64>();
+    let variance = nodes
+        .iter()
+        .zip(&posterior)
+        .map(|((node, _), weight)| (node - ability).powi(2) * weight)
+        .sum::<f64>();
+
+    AbilityEstimate {
+        ability,
+        standard_error: variance.sqrt(),
+    }
+}
+
+/// Evenly spaced abilities with standard normal weights adding up to 1.
+fn quadrature(points: usize) -> Vec<(f64, f64)> {
+    let points = points.max(2);
+    let step = 2.0 * ABILITY_RANGE / (points - 1) as f64;
+    let nodes = (0..points)
+        .map(|index| {
+            let ability = -ABILITY_RANGE + step * index as f64;
+
+            (ability, (-ability * ability / 2.0).exp())
+        })
+        .collect::<Vec<_>>();
+    let total = nodes.iter().map(|(_, weight)| weight).sum::<f64>();
+
+    nodes
+        .into_iter()
+        .map(|(ability, weight)| (ability, weight / total))
+        .collect()
+}
+
+/// Weight of each node given `responses`, adding up to 1.
+fn posterior<'a>(
+    nodes: &[(f64, f64)],
+    responses: impl Iterator<Item = (&'a ItemParameters, bool)> + Clone,
+) -> Vec<f64> {
+    let log_weights = nodes
+        .iter()
+        .map(|(ability, weight)| {
+            weight.ln()
+                + responses
+                    .clone()
+                    .map(|(item, correct)| {
+                        let probability = item.probability(*ability);
+
+                        if correct {
+                            probability.max(f64::MIN_POSITIVE).ln()
+                        } else {
+                            (1.0 - probability).max(f64::MIN_POSITIVE).ln()
+                        }
+                    })
+                    .sum::<f64>()
+        })
+        .collect::<Vec<_>>();
+    let max = log_weights
+        .iter()
+        .copied()
+        .fold(f64::NEG_INFINITY, f64::max);
+    let weights = log_weights
+        .iter()
+        .map(|log_weight| (log_weight - max).exp())
+        .collect::<Vec<_>>();
+    let total = weights.iter().sum::<f64>();
+
+    weights.into_iter().map(|weight| weight / total).collect()
+}
+
+/// Newton-Raphson on the slope `a` and intercept `c = -ab` of the item, given
+/// the expected answers and correct answers at each node.
+fn maximize_item(
+    item: ItemParameters,
+    expected: &[(f64, f64)],
+    nodes: &[(f64, f64)],
+) -> ItemParameters {
+    let mut slope = item.discrimination;
+    let mut intercept = -slope * item.difficulty;
+
+    for _ in 0..20 {
+        let mut gradient = (
+            -ITEM_PRIOR_PRECISION * (slope - 1.0),
+            -ITEM_PRIOR_PRECISION * intercept,
+        );
+        let mut hessian = (-ITEM_PRIOR_PRECISION, 0.0, -ITEM_PRIOR_PRECISION);
+
+        for ((count, correct_count), (ability, _)) in expected.iter().zip(nodes) {
+            let probability = logistic(slope * ability + intercept);
+            let residual = correct_count - count * probability;
+            let weight = count * probability * (1.0 - probability);
+
+            gradient.0 += residual * ability;
+            gradient.1 += residual;
+            hessian.0 -= weight * ability * ability;
+            hessian.1 -= weight * ability;
+            hessian.2 -= weight;
+        }
+
+        let determinant = hessian.0 * hessian.2 - hessian.1 * hessian.1;
+
+        if determinant <= 0.0 {
+            break;
+        }
+
+        let slope_step = -(hessian.2 * gradient.0 - hessian.1 * gradient.1) / determinant;
+        let intercept_step = -(hessian.0 * gradient.1 - hessian.1 * gradient.0) / determinant;
+
+        slope = (slope + slope_step).clamp(MIN_DISCRIMINATION, MAX_DISCRIMINATION);
+        intercept += intercept_step;
+
+        if slope_step.abs() < 1e-6 && intercept_step.abs() < 1e-6 {
+            break;
+        }
+    }
+
+    ItemParameters {
+        discrimination: slope,
+        difficulty: (-intercept / slope).clamp(-MAX_ABS_DIFFICULTY, MAX_ABS_DIFFICULTY),
+    }
+}
+
+fn logistic(value: f64) -> f64 {
+    1.0 / (1.0 + (-value).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn test_fit_item_parameters() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let truth = [(1.0, -1.5), (1.5, -0.5), (1.0, 0.0), (2.0, 0.5), (0.8, 1.5)].map(
+            |(discrimination, difficulty)| ItemParameters {
+                discrimination,
+                difficulty,
+            },
+        );
+        let rows = (0..2_000)
+            .map(|_| {
+                // Box-Muller transform of two uniform samples.
+                let ability = (-2.0 * rng.gen::<f64>().max(f64::MIN_POSITIVE).ln()).sqrt()
+                    * (2.0 * std::f64::consts::PI * rng.gen::<f64>()).cos();
+
+                truth
+                    .iter()
+                    .map(|item| Some(rng.gen::<f64>() < item.probability(ability)))
+                    .collect()
+            })
+            .collect();
+        let matrix = ResponseMatrix {
+            question_ids: (0..truth.len()).map(|_| Uuid::new_v4()).collect(),
+            rows,
+        };
+
+        let fitted = fit_item_parameters(
+            &matrix,
+            &IrtFitConfig {
+                tolerance: 1e-3,
+                ..Default::default()
+            },
+        );
+
+        for (question_id, expected) in matrix.question_ids.iter().zip(&truth) {
+            let item = fitted[question_id];
+
+            assert!(
+                (item.difficulty - expected.difficulty).abs() < 0.3,
+                "{item:?} {expected:?}"
+            );
+            assert!(
+                (item.discrimination - expected.discrimination).abs() < 0.5,
+                "{item:?} {expected:?}"
+            );
+        }
+
+        let items = matrix.question_ids.iter().map(|id| fitted[id]);
+        let high = estimate_ability(&items.clone().map(|item| (item, true)).collect::<Vec<_>>());
+        let low = estimate_ability(&items.map(|item| (item, false)).collect::<Vec<_>>());
+
+        assert!(high.ability > 1.0 && low.ability < -1.0);
+        assert!(estimate_ability(&[]).ability.abs() < 1e-9);
+    }
+
+    fn item_strategy() -> impl Strategy<Value = ItemParameters> {
+        (0.2..3.0, -3.0..3.0).prop_map(|(discrimination, difficulty)| ItemParameters {
+            discrimination,
+            difficulty,
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_estimate_ability_monotonic(
+            responses in proptest::collection::vec((item_strategy(), any::<bool>()), 1..15),
+            flipped in any::<prop::sample::Index>(),
+        ) {
+            let estimate = estimate_ability(&responses);
+
+            let mut improved = responses.clone();
+            improved[flipped.index(responses.len())].1 = true;
+            let improved_estimate = estimate_ability(&improved);
+
+            prop_assert!(improved_estimate.ability >= estimate.ability - 1e-9);
+            prop_assert!(estimate.standard_error > 0.0 && estimate.standard_error <= 1.0 + 1e-9);
+
+            for (item, _) in &responses {
+                let probability = item.probability(estimate.ability);
+
+                prop_assert!((0.0..=1.0).contains(&probability));
+                prop_assert!(item.probability(estimate.ability + 0.5) >= probability);
+                prop_assert!(item.information(estimate.ability) >= 0.0);
+            }
+        }
+    }
+}
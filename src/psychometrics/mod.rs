@@ -0,0 +1,223 @@
+//! Estimation of question difficulty and user ability from the answers users
+//! gave, with classical item analysis and item response theory.
+
+mod irt;
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::integrity::AnswerRecord;
+
+pub use irt::*;
+
+/// Share of the users, by score, in each of the upper and lower groups
+/// compared by the discrimination index.
+pub const DISCRIMINATION_GROUP_SHARE: f64 = 0.27;
+
+#[derive(
+    strum::Display, strum::EnumString, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct ItemAnalysis {
+    pub question_id: Uuid,
+    /// Users who answered the question. Only their first answer counts.
+    pub answer_count: usize,
+    /// Share of correct answers, from 0 to 1. Higher is easier.
+    pub p_value: f64,
+    /// Share of correct answers in the upper scoring group minus that of the
+    /// lower one, from -1 to 1. `None` without enough users with other answers.
+    pub discrimination: Option<f64>,
+    /// Correlation between answering correctly and the score in the other
+    /// questions. `None` when either doesn't vary.
+    pub point_biserial: Option<f64>,
+}
+
+impl ItemAnalysis {
+    pub fn difficulty(&self) -> Difficulty {
+        if self.p_value > 0.7 {
+            Difficulty::Easy
+        } else if self.p_value < 0.3 {
+            Difficulty::Hard
+        } else {
+            Difficulty::Medium
+        }
+    }
+}
+
+/// Scores are each user's share of correct answers to the other questions in
+/// `answers`, so the question doesn't correlate with itself. `None` when no
+/// user answered the question.
+pub fn estimate_difficulty(question_id: Uuid, answers: &[AnswerRecord]) -> Option<ItemAnalysis> {
+    let mut users = BTreeMap::<Uuid, UserResults>::new();
+
+    for ((user_id, answer_question_id), answer) in first_answers(answers) {
+        let user = users.entry(user_id).or_default();
+
+        if answer_question_id == question_id {
+            user.item_correct = Some(answer.correct);
+        } else {
+            user.rest_count += 1;
+            user.rest_correct += usize::from(answer.correct);
+        }
+    }
+
+    let examinees = users
+        .into_values()
+        .filter_map(|user| {
+            let item_correct = user.item_correct?;
+            let rest_score =
+                (user.rest_count > 0).then(|| user.rest_correct as f64 / user.rest_count as f64);
+
+            Some((item_correct, rest_score))
+        })
+        .collect::<Vec<_>>();
+
+    if examinees.is_empty() {
+        return None;
+    }
+
+    let p_value = share_correct(examinees.iter().map(|(correct, _)| *correct));
+
+    let mut scored = examinees
+        .iter()
+        .filter_map(|(correct, rest_score)| Some((*correct, (*rest_score)?)))
+        .collect::<Vec<_>>();
+    scored.sort_by(|(_, first), (_, second)| first.total_cmp(second));
+
+    Some(ItemAnalysis {
+        question_id,
+        answer_count: examinees.len(),
+        p_value,
+        discrimination: discrimination(&scored),
+        point_biserial: point_biserial(&scored),
+    })
+}
+
+/// By user ID and question ID.
+fn first_answers(answers: &[AnswerRecord]) -> HashMap<(Uuid, Uuid), &AnswerRecord> {
+    let mut first_answers = HashMap::<(Uuid, Uuid), &AnswerRecord>::new();
+
+    for answer in answers {
+        let first = first_answers
+            .entry((answer.user_id, answer.question_id))
+            .or_insert(answer);
+
+        if answer.answered_at < first.answered_at {
+            *first = answer;
+        }
+    }
+
+    first_answers
+}
+
+#[derive(Default)]
+struct UserResults {
+    item_correct: Option<bool>,
+    rest_count: usize,
+    rest_correct: usize,
+}
+
+/// `scored` is sorted by score, lowest first.
+fn discrimination(scored: &[(bool, f64)]) -> Option<f64> {
+    let group_size = ((scored.len() as f64 * DISCRIMINATION_GROUP_SHARE).round() as usize).max(1);
+
+    if group_size * 2 > scored.len() {
+        return None;
+    }
+
+    let lower = share_correct(scored[..group_size].iter().map(|(correct, _)| *correct));
+    let upper = share_correct(
+        scored[scored.len() - group_size..]
+            .iter()
+            .map(|(correct, _)| *correct),
+    );
+
+    Some(upper - lower)
+}
+
+fn point_biserial(scored: &[(bool, f64)]) -> Option<f64> {
+    let count = scored.len() as f64;
+    let mean = scored.iter().map(|(_, score)| score).sum::<f64>() / count;
+    let std_dev = (scored
+        .iter()
+        .map(|(_, score)| (score - mean).powi(2))
+        .sum::<f64>()
+        / count)
+        .sqrt();
+
+    let (correct, incorrect): (Vec<_>, Vec<_>) = scored.iter().partition(|(correct, _)| *correct);
+
+    if std_dev == 0.0 || correct.is_empty() || incorrect.is_empty() {
+        return None;
+    }
+
+    let mean_of = |group: &[&(bool, f64)]| {
+        group.iter().map(|(_, score)| score).sum::<f64>() / group.len() as f64
+    };
+    let p = correct.len() as f64 / count;
+
+    Some((mean_of(&correct) - mean_of(&incorrect)) / std_dev * (p * (1.0 - p)).sqrt())
+}
+
+fn share_correct(correct: impl Iterator<Item = bool>) -> f64 {
+    let (count, correct_count) = correct.fold((0, 0), |(count, correct_count), correct| {
+        (count + 1, correct_count + usize::from(correct))
+    });
+
+    correct_count as f64 / count as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    #[test]
+    fn test_estimate_difficulty() {
+        let item = Uuid::new_v4();
+        let others = (0..4).map(|_| Uuid::new_v4()).collect::<Vec<_>>();
+        let answer = |user_id, question_id, correct| AnswerRecord {
+            user_id,
+            question_id,
+            question_length: 100,
+            answered_at: Utc::now(),
+            duration_ms: 30_000,
+            correct,
+        };
+
+        let mut answers = vec![];
+
+        // User `n` answers `n` of the other questions correctly, and the
+        // question only when `n` is at least 2.
+        for correct_count in 0..=4 {
+            let user_id = Uuid::new_v4();
+
+            answers.push(answer(user_id, item, correct_count >= 2));
+
+            for (index, question_id) in others.iter().enumerate() {
+                answers.push(answer(user_id, *question_id, index < correct_count));
+            }
+        }
+
+        let analysis = estimate_difficulty(item, &answers).unwrap();
+
+        assert_eq!(analysis.answer_count, 5);
+        assert_eq!(analysis.p_value, 0.6);
+        assert_eq!(analysis.difficulty(), Difficulty::Medium);
+        assert_eq!(analysis.discrimination, Some(1.0));
+        assert!(analysis.point_biserial.unwrap() > 0.8);
+
+        assert_eq!(estimate_difficulty(Uuid::new_v4(), &answers), None);
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Utc};
+use chrono_tz::Tz;
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::stats::percentile_table;
+
+/// Points earned by a user at a point in time, e.g. for a correct answer.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ScoreEvent {
+    pub user_id: Uuid,
+    pub points: u32,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(
+    strum::Display, strum::EnumString, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum RankingWindow {
+    AllTime,
+    /// ISO weeks, starting on Monday.
+    Weekly,
+    Monthly,
+}
+
+impl RankingWindow {
+    /// Key of the bucket containing `at` in `timezone`, e.g. `2024-W07` or `2024-02`,
+    /// for use in cache keys.
+    pub fn bucket(&self, at: DateTime<Utc>, timezone: Tz) -> String {
+        let local = at.with_timezone(&timezone);
+
+        match self {
+            Self::AllTime => "all".into(),
+            Self::Weekly => {
+                let week = local.iso_week();
+
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Self::Monthly => format!("{}-{:02}", local.year(), local.month()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    /// Equal totals share a rank, and the next rank is skipped (1, 1, 3).
+    Shared,
+    /// Among equal totals, whoever reached the total first ranks higher.
+    FirstToReach,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct RankingConfig {
+    pub window: RankingWindow,
+    /// Selects the window's bucket; usually now.
+    pub at: DateTime<Utc>,
+    pub timezone: Tz,
+    pub tie_break: TieBreak,
+    pub limit: Option<usize>,
+}
+
+impl RankingConfig {
+    pub fn new(window: RankingWindow, at: DateTime<Utc>) -> Self {
+        Self {
+            window,
+            at,
+            timezone: chrono_tz::America::Montevideo,
+            tie_break: TieBreak::Shared,
+            limit: None,
+        }
+    }
+
+    pub fn bucket(&self) -> String {
+        self.window.bucket(self.at, self.timezone)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "valkey", derive(medici_macros::ValkeyString))]
+pub struct LeaderboardEntry {
+    pub user_id: Uuid,
+    pub rank: u32,
+    pub points: u64,
+    /// Share of the ranked users with fewer points, from 0 to 100, as in
+    /// `PercentileTable::better_than`. A user ranked alone is better than 0%.
+    pub percentile: u8,
+    pub reached_at: DateTime<Utc>,
+}
+
+/// Ranks users by their points within the configured window. Users without
+/// points in the window aren't ranked.
+pub fn compute(scores: &[ScoreEvent], config: &RankingConfig) -> Vec<LeaderboardEntry> {
+    let bucket = config.bucket();
+    let mut totals = HashMap::<Uuid, (u64, DateTime<Utc>)>::new();
+
+    for score in scores
+        .iter()
+        .filter(|score| config.window.bucket(score.at, config.timezone) == bucket)
+    {
+        let (points, reached_at) = totals.entry(score.user_id).or_insert((0, score.at));
+
+        *points += u64::from(score.points);
+        *reached_at = (*reached_at).max(score.at);
+    }
+
+    let mut totals = totals
+        .into_iter()
+        .filter(|(_, (points, _))| *points > 0)
+        .collect::<Vec<_>>();
+
+    totals.sort_by(|(a_id, (a_points, a_at)), (b_id, (b_points, b_at))| {
+        b_points.cmp(a_points).then_with(|| match config.tie_break {
+            TieBreak::FirstToReach => a_at.cmp(b_at).then(a_id.cmp(b_id)),
+            TieBreak::Shared => a_id.cmp(b_id),
+        })
+    });
+
+    let table = percentile_table(
+        &totals
+            .iter()
+            .map(|(_, (points, _))| Decimal::from(*points))
+            .collect::<Vec<_>>(),
+    );
+    let mut entries: Vec<LeaderboardEntry> = Vec::with_capacity(totals.len());
+
+    for (index, (user_id, (points, reached_at))) in totals.iter().enumerate() {
+        let rank = match (config.tie_break, entries.last()) {
+            (TieBreak::Shared, Some(previous)) if previous.points == *points => previous.rank,
+            _ => index as u32 + 1,
+        };
+        let percentile = table
+            .better_than(Decimal::from(*points))
+            .floor()
+            .to_u8()
+            .unwrap_or(100);
+
+        entries.push(LeaderboardEntry {
+            user_id: *user_id,
+            rank,
+            points: *points,
+            percentile,
+            reached_at: *reached_at,
+        });
+    }
+
+    if let Some(limit) = config.limit {
+        entries.truncate(limit);
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    #[test]
+    fn test_compute() {
+        // Monday 2024-02-12 00:30 in Montevideo (UTC-3).
+        let monday = Utc.with_ymd_and_hms(2024, 2, 12, 3, 30, 0).unwrap();
+        let users = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let score = |user: usize, points, at| ScoreEvent {
+            user_id: users[user],
+            points,
+            at,
+        };
+
+        let scores = [
+            score(0, 10, monday),
+            score(1, 4, monday + Duration::hours(1)),
+            score(1, 6, monday + Duration::hours(2)),
+            score(2, 3, monday + Duration::hours(3)),
+            // Sunday night local time, so in the previous week.
+            score(2, 50, monday - Duration::hours(1)),
+        ];
+
+        let mut config = RankingConfig::new(RankingWindow::Weekly, monday + Duration::days(2));
+
+        assert_eq!(config.bucket(), "2024-W07");
+
+        let entries = compute(&scores, &config);
+
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.rank, entry.points, entry.percentile))
+                .collect::<Vec<_>>(),
+            [(1, 10, 33), (1, 10, 33), (3, 3, 0)]
+        );
+
+        config.tie_break = TieBreak::FirstToReach;
+        config.limit = Some(2);
+
+        let entries = compute(&scores, &config);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].user_id, entries[0].rank), (users[0], 1));
+        assert_eq!((entries[1].user_id, entries[1].rank), (users[1], 2));
+        assert_eq!(compute(&scores[..1], &config)[0].percentile, 0);
+        assert_eq!(
+            RankingWindow::Monthly.bucket(monday, chrono_tz::America::Montevideo),
+            "2024-02"
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sync::CourseStats;
+
+/// A user's answers in one course, by topic name.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+pub struct UserTopicProgress {
+    pub by_topic: BTreeMap<String, TopicProgress>,
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct TopicProgress {
+    pub answered: usize,
+    pub correct: usize,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct RecommendationWeights {
+    /// Exponent of `1 - mastery`; higher values favor weak topics more.
+    pub weakness: f64,
+    /// Exponent of the topic's share of the course's questions.
+    pub frequency: f64,
+    /// Pseudo-answers at 50% mastery added to every topic, so a couple of
+    /// lucky answers don't make a topic look mastered.
+    pub prior_answers: f64,
+}
+
+impl Default for RecommendationWeights {
+    fn default() -> Self {
+        Self {
+            weakness: 1.0,
+            frequency: 1.0,
+            prior_answers: 4.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct TopicRecommendation {
+    pub topic: String,
+    pub score: f64,
+    /// Smoothed share of correct answers, from 0 to 1.
+    pub mastery: f64,
+    /// Share of the course's questions in this topic, from 0 to 1.
+    pub frequency: f64,
+    pub answered: usize,
+}
+
+/// Topics of the course ranked by weakness-weighted frequency, best recommendation first.
+pub fn score_topics(
+    user_progress: &UserTopicProgress,
+    course_stats: &CourseStats,
+    weights: &RecommendationWeights,
+) -> Vec<TopicRecommendation> {
+    if course_stats.question_count == 0 {
+        return vec![];
+    }
+
+    let mut recommendations = course_stats
+        .by_topic
+        .iter()
+        .map(|(topic, question_count)| {
+            let progress = user_progress
+                .by_topic
+                .get(topic)
+                .copied()
+                .unwrap_or_default();
+            let mastery = (progress.correct as f64 + weights.prior_answers / 2.0)
+                / (progress.answered as f64 + weights.prior_answers).max(1.0);
+            let frequency = *question_count as f64 / course_stats.question_count as f64;
+
+            TopicRecommendation {
+                topic: topic.clone(),
+                score: (1.0 - mastery).max(0.0).powf(weights.weakness)
+                    * frequency.powf(weights.frequency),
+                mastery,
+                frequency,
+                answered: progress.answered,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    recommendations.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.topic.cmp(&b.topic))
+    });
+
+    recommendations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_topics() {
+        let course_stats = CourseStats {
+            question_count: 100,
+            by_topic: BTreeMap::from([
+                ("Cardiología".into(), 50),
+                ("Nefrología".into(), 30),
+                ("Dermatología".into(), 20),
+            ]),
+            ..Default::default()
+        };
+        let user_progress = UserTopicProgress {
+            by_topic: BTreeMap::from([
+                (
+                    "Cardiología".into(),
+                    TopicProgress {
+                        answered: 40,
+                        correct: 38,
+                    },
+                ),
+                (
+                    "Nefrología".into(),
+                    TopicProgress {
+                        answered: 20,
+                        correct: 6,
+                    },
+                ),
+            ]),
+        };
+
+        let recommendations = score_topics(
+            &user_progress,
+            &course_stats,
+            &RecommendationWeights::default(),
+        );
+        let topics = recommendations
+            .iter()
+            .map(|recommendation| recommendation.topic.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(topics, ["Nefrología", "Dermatología", "Cardiología"]);
+        assert_eq!(recommendations[1].mastery, 0.5);
+
+        let frequency_only = RecommendationWeights {
+            weakness: 0.0,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            score_topics(&user_progress, &course_stats, &frequency_only)[0].topic,
+            "Cardiología"
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entitlements::CourseEntitlement;
+use crate::payments::PaymentIntent;
+use crate::traits::EmailTemplate;
+
+/// A payment together with the user and course it pays for. Bundle payments
+/// have one record per course.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct PaymentRecord {
+    pub user_id: Uuid,
+    pub course_key: String,
+    pub intent: PaymentIntent,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct MissingGrant {
+    pub user_id: Uuid,
+    pub course_key: String,
+    pub provider_payment_id: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct EntitlementDiff {
+    pub generated_at: DateTime<Utc>,
+    /// Paid courses the user has no access to.
+    pub missing_grants: Vec<MissingGrant>,
+    /// Active grants referencing a payment that isn't paid, e.g. after a refund.
+    pub orphan_grants: Vec<CourseEntitlement>,
+    pub expired_but_active: Vec<CourseEntitlement>,
+}
+
+impl EntitlementDiff {
+    pub fn compute(
+        payments: &[PaymentRecord],
+        entitlements: &[CourseEntitlement],
+        now: DateTime<Utc>,
+    ) -> Self {
+        let paid = payments
+            .iter()
+            .filter(|payment| payment.intent.status.is_paid())
+            .collect::<Vec<_>>();
+
+        let granted = entitlements
+            .iter()
+            .filter(|entitlement| entitlement.grants_access_at(now))
+            .map(|entitlement| (entitlement.user_id, entitlement.course_key.as_str()))
+            .collect::<HashSet<_>>();
+
+        let paid_ids = paid
+            .iter()
+            .map(|payment| {
+                (
+                    payment.user_id,
+                    payment.course_key.as_str(),
+                    payment.intent.provider_payment_id.as_str(),
+                )
+            })
+            .collect::<HashSet<_>>();
+
+        let missing_grants = paid
+            .iter()
+            .filter(|payment| !granted.contains(&(payment.user_id, payment.course_key.as_str())))
+            .map(|payment| MissingGrant {
+                user_id: payment.user_id,
+                course_key: payment.course_key.clone(),
+                provider_payment_id: payment.intent.provider_payment_id.clone(),
+            })
+            .collect();
+
+        let orphan_grants = entitlements
+            .iter()
+            .filter(|entitlement| entitlement.is_active)
+            .filter(|entitlement| {
+                entitlement
+                    .provider_payment_id
+                    .as_deref()
+                    .is_some_and(|payment_id| {
+                        !paid_ids.contains(&(
+                            entitlement.user_id,
+                            entitlement.course_key.as_str(),
+                            payment_id,
+                        ))
+                    })
+            })
+            .cloned()
+            .collect();
+
+        let expired_but_active = entitlements
+            .iter()
+            .filter(|entitlement| entitlement.is_active && entitlement.is_expired_at(now))
+            .cloned()
+            .collect();
+
+        Self {
+            generated_at: now,
+            missing_grants,
+            orphan_grants,
+            expired_but_active,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.missing_grants.is_empty()
+            && self.orphan_grants.is_empty()
+            && self.expired_but_active.is_empty()
+    }
+
+    pub fn email(&self) -> ReconciliationEmail {
+        let missing_grants = self.missing_grants.iter().map(|grant| ReconciliationRow {
+            kind: ReconciliationIssue::MissingGrant,
+            user_id: grant.user_id,
+            course_key: grant.course_key.clone(),
+            provider_payment_id: Some(grant.provider_payment_id.clone()),
+        });
+
+        let entitlement_row = |kind: ReconciliationIssue| {
+            move |entitlement: &CourseEntitlement| ReconciliationRow {
+                kind,
+                user_id: entitlement.user_id,
+                course_key: entitlement.course_key.clone(),
+                provider_payment_id: entitlement.provider_payment_id.clone(),
+            }
+        };
+
+        let orphan_grants = self
+            .orphan_grants
+            .iter()
+            .map(entitlement_row(ReconciliationIssue::OrphanGrant));
+        let expired_but_active = self
+            .expired_but_active
+            .iter()
+            .map(entitlement_row(ReconciliationIssue::ExpiredButActive));
+
+        ReconciliationEmail {
+            generated_at: self.generated_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+            missing_grant_count: self.missing_grants.len(),
+            orphan_grant_count: self.orphan_grants.len(),
+            expired_but_active_count: self.expired_but_active.len(),
+            rows: missing_grants
+                .chain(orphan_grants)
+                .chain(expired_but_active)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconciliationIssue {
+    MissingGrant,
+    OrphanGrant,
+    ExpiredButActive,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ReconciliationRow {
+    pub kind: ReconciliationIssue,
+    pub user_id: Uuid,
+    pub course_key: String,
+    pub provider_payment_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ReconciliationEmail {
+    pub generated_at: String,
+    pub missing_grant_count: usize,
+    pub orphan_grant_count: usize,
+    pub expired_but_active_count: usize,
+    pub rows: Vec<ReconciliationRow>,
+}
+
+impl EmailTemplate for ReconciliationEmail {
+    const TEMPLATE_NAME: &'static str = "entitlement_reconciliation";
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::money::Money;
+    use crate::payments::{PaymentProvider, PaymentStatus};
+
+    fn payment(user_id: Uuid, id: &str, status: PaymentStatus) -> PaymentRecord {
+        PaymentRecord {
+            user_id,
+            course_key: "cardiologia".into(),
+            intent: PaymentIntent {
+                provider: PaymentProvider::MercadoPago,
+                provider_payment_id: id.into(),
+                external_reference: None,
+                amount: Money::uyu(Decimal::ONE_HUNDRED),
+                status,
+                status_detail: None,
+                payer_email: None,
+                live_mode: true,
+                created_at: None,
+                paid_at: None,
+            },
+        }
+    }
+
+    fn entitlement(user_id: Uuid, id: Option<&str>, now: DateTime<Utc>) -> CourseEntitlement {
+        CourseEntitlement {
+            user_id,
+            course_key: "cardiologia".into(),
+            provider_payment_id: id.map(Into::into),
+            granted_at: now - TimeDelta::days(30),
+            expires_at: None,
+            is_active: true,
+        }
+    }
+
+    #[test]
+    fn test_compute() {
+        let now = Utc::now();
+        let [paid_user, refunded_user, manual_user] = [(); 3].map(|_| Uuid::new_v4());
+
+        let payments = [
+            payment(paid_user, "1", PaymentStatus::Approved),
+            payment(refunded_user, "2", PaymentStatus::Refunded),
+        ];
+
+        let mut expired = entitlement(manual_user, None, now);
+        expired.expires_at = Some(now - TimeDelta::days(1));
+
+        let entitlements = [entitlement(refunded_user, Some("2"), now), expired];
+
+        let diff = EntitlementDiff::compute(&payments, &entitlements, now);
+
+        assert_eq!(diff.missing_grants[0].user_id, paid_user);
+        assert_eq!(diff.orphan_grants[0].user_id, refunded_user);
+        assert_eq!(diff.expired_but_active[0].user_id, manual_user);
+        assert_eq!(diff.email().rows.len(), 3);
+
+        let diff = EntitlementDiff::compute(
+            &payments[..1],
+            &[entitlement(paid_user, Some("1"), now)],
+            now,
+        );
+
+        assert!(diff.is_empty());
+    }
+}
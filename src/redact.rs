@@ -0,0 +1,221 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "db")]
+use sqlx::encode::IsNull;
+#[cfg(feature = "db")]
+use sqlx::error::BoxDynError;
+#[cfg(feature = "db")]
+use sqlx::postgres::{PgTypeInfo, PgValueRef};
+#[cfg(feature = "db")]
+use sqlx::Postgres;
+
+pub const REDACTED: &str = "[redacted]";
+
+/// Wrapper hiding its value from `Debug` and `Display`, e.g. in tracing output.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(transparent)]
+pub struct Redacted<T>(pub T);
+
+impl<T> Redacted<T> {
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Redacted<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> std::fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{REDACTED}")
+    }
+}
+
+impl<T> std::fmt::Display for Redacted<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{REDACTED}")
+    }
+}
+
+#[cfg(feature = "db")]
+impl<T: sqlx::Type<Postgres>> sqlx::Type<Postgres> for Redacted<T> {
+    fn type_info() -> PgTypeInfo {
+        T::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        T::compatible(ty)
+    }
+}
+
+#[cfg(feature = "db")]
+impl<'q, T: sqlx::Encode<'q, Postgres>> sqlx::Encode<'q, Postgres> for Redacted<T> {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Postgres as sqlx::Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        self.0.encode_by_ref(buf)
+    }
+}
+
+#[cfg(feature = "db")]
+impl<'r, T: sqlx::Decode<'r, Postgres>> sqlx::Decode<'r, Postgres> for Redacted<T> {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        T::decode(value).map(Self)
+    }
+}
+
+/// Email address shown as `u***@medici.uy` in `Debug` and `Display`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "db", derive(sqlx::Type), sqlx(transparent))]
+#[serde(try_from = "String", into = "String")]
+pub struct Email(String);
+
+impl Email {
+    pub fn new(value: &str) -> Result<Self> {
+        let value = value.trim().to_lowercase();
+
+        match value.split_once('@') {
+            Some((local, domain))
+                if !local.is_empty() && domain.contains('.') && !domain.contains('@') =>
+            {
+                Ok(Self(value))
+            }
+            _ => bail!("invalid email"),
+        }
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn masked(&self) -> String {
+        let (local, domain) = self.0.split_once('@').unwrap_or_default();
+
+        format!("{}***@{domain}", local.chars().next().unwrap_or_default())
+    }
+}
+
+impl TryFrom<String> for Email {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::new(&value)
+    }
+}
+
+impl From<Email> for String {
+    fn from(value: Email) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Debug for Email {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Email({})", self.masked())
+    }
+}
+
+impl std::fmt::Display for Email {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.masked())
+    }
+}
+
+/// Phone number shown with only its last digits in `Debug` and `Display`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "db", derive(sqlx::Type), sqlx(transparent))]
+#[serde(try_from = "String", into = "String")]
+pub struct PhoneNumber(String);
+
+impl PhoneNumber {
+    pub const VISIBLE_DIGITS: usize = 3;
+
+    pub fn new(value: &str) -> Result<Self> {
+        let digits = value
+            .chars()
+            .filter(|char| !matches!(char, ' ' | '-' | '(' | ')'))
+            .collect::<String>();
+        let number = digits.strip_prefix('+').unwrap_or(&digits);
+
+        if !(6..=15).contains(&number.len()) || !number.chars().all(|char| char.is_ascii_digit()) {
+            bail!("invalid phone number");
+        }
+
+        Ok(Self(digits))
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn masked(&self) -> String {
+        let visible = &self.0[self.0.len() - Self::VISIBLE_DIGITS..];
+
+        format!("***{visible}")
+    }
+}
+
+impl TryFrom<String> for PhoneNumber {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::new(&value)
+    }
+}
+
+impl From<PhoneNumber> for String {
+    fn from(value: PhoneNumber) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Debug for PhoneNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PhoneNumber({})", self.masked())
+    }
+}
+
+impl std::fmt::Display for PhoneNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.masked())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masking() {
+        let email = Email::new(" User@Medici.uy ").unwrap();
+
+        assert_eq!(email.expose(), "user@medici.uy");
+        assert_eq!(format!("{email:?}"), "Email(u***@medici.uy)");
+        assert!(Email::new("user@").is_err());
+
+        let phone_number = PhoneNumber::new("+598 99 123 456").unwrap();
+
+        assert_eq!(phone_number.to_string(), "***456");
+        assert_eq!(format!("{:?}", Redacted("token")), REDACTED);
+    }
+
+    #[test]
+    fn test_serde_passthrough() {
+        let email: Email = serde_json::from_str(r#""user@medici.uy""#).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&email).unwrap(),
+            r#""user@medici.uy""#
+        );
+        assert_eq!(serde_json::to_string(&Redacted(1)).unwrap(), "1");
+        assert!(serde_json::from_str::<Email>(r#""user""#).is_err());
+    }
+}
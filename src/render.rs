@@ -0,0 +1,454 @@
+use crate::sync::{LanguageTag, LicenseData, QuestionData, QuestionKind};
+
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub enum OptionLabels {
+    LowerAlpha,
+    #[default]
+    UpperAlpha,
+    Numeric,
+}
+
+impl OptionLabels {
+    pub fn label(&self, reference: u16) -> String {
+        let reference = reference.min(QuestionData::MAX_OPTION_REFERENCE);
+
+        match self {
+            Self::LowerAlpha => char::from(b'a' + reference as u8).into(),
+            Self::UpperAlpha => char::from(b'A' + reference as u8).into(),
+            Self::Numeric => (reference + 1).to_string(),
+        }
+    }
+}
+
+/// The answer is hidden unless `reveal_answer` is set. With a `locale`, the
+/// question's translation is used when it has one, and headings are localized.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct RenderOptions {
+    pub reveal_answer: bool,
+    pub include_explanation: bool,
+    pub labels: OptionLabels,
+    pub locale: Option<LanguageTag>,
+    /// License of the question's course, attributed when the question has none.
+    pub course_license: Option<LicenseData>,
+}
+
+struct RenderedOption<'a> {
+    label: String,
+    text: &'a str,
+    is_correct: bool,
+    explanation: Option<&'a str>,
+}
+
+struct RenderedQuestion<'a> {
+    text: &'a str,
+    /// Items of matching and ordering questions, labeled and in the order they're shown.
+    items: Vec<String>,
+    /// Expected answer of questions that aren't multiple choice.
+    answer: Option<String>,
+    options: Vec<RenderedOption<'a>>,
+    explanation: Option<&'a str>,
+    attribution: Option<String>,
+    headings: Headings,
+}
+
+impl<'a> RenderedQuestion<'a> {
+    fn new(question: &'a QuestionData, options: &RenderOptions) -> Self {
+        let translation = options
+            .locale
+            .as_ref()
+            .and_then(|locale| question.translations.get(locale));
+
+        let mut question_options = question.question_options.iter().collect::<Vec<_>>();
+        question_options.sort_by_key(|question_option| question_option.reference);
+
+        let (items, answer) = kind_items(question, options.labels);
+
+        Self {
+            text: translation.map_or(&question.text, |translation| &translation.text),
+            options: question_options
+                .into_iter()
+                .map(|question_option| RenderedOption {
+                    label: options.labels.label(question_option.reference),
+                    text: translation
+                        .and_then(|translation| {
+                            translation
+                                .question_options
+                                .get(question_option.reference as usize)
+                        })
+                        .unwrap_or(&question_option.text),
+                    is_correct: question_option.is_correct,
+                    explanation: question_option.explanation.as_deref(),
+                })
+                .collect(),
+            explanation: translation
+                .and_then(|translation| translation.explanation.as_deref())
+                .or(question
+                    .explanation
+                    .as_ref()
+                    .map(|explanation| explanation.text.as_str())),
+            attribution: question
+                .license
+                .as_ref()
+                .or(options.course_license.as_ref())
+                .map(LicenseData::attribution),
+            headings: Headings::new(options.locale.as_ref()),
+            items,
+            answer,
+        }
+    }
+
+    /// Labels of the correct options, or the expected answer of other kinds.
+    fn correct_answer(&self) -> String {
+        if let Some(answer) = &self.answer {
+            return answer.clone();
+        }
+
+        self.options
+            .iter()
+            .filter(|option| option.is_correct)
+            .map(|option| option.label.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Matching questions list their left items by number and their right items
+/// by label, and are answered like "1-b, 2-a". Ordering questions list their
+/// items by label and are answered with the labels in order. The order of the
+/// items comes from the question ID, so it's the same everywhere.
+fn kind_items(question: &QuestionData, labels: OptionLabels) -> (Vec<String>, Option<String>) {
+    let seed = question.id.as_bytes();
+
+    match &question.kind {
+        QuestionKind::MultipleChoice => (vec![], None),
+        QuestionKind::Numeric(answer) => (vec![], Some(answer.to_string())),
+        QuestionKind::Matching(answer) => {
+            let order = answer.right_order(seed);
+            let position = |pair: usize| order.iter().position(|&index| index == pair);
+            let items = answer
+                .pairs
+                .iter()
+                .enumerate()
+                .map(|(index, pair)| format!("{}. {}", index + 1, pair.left))
+                .chain(order.iter().enumerate().map(|(position, &pair)| {
+                    format!(
+                        "{}. {}",
+                        labels.label(position as u16),
+                        answer.pairs[pair].right
+                    )
+                }))
+                .collect();
+            let matches = (0..answer.pairs.len())
+                .filter_map(|pair| {
+                    position(pair)
+                        .map(|position| format!("{}-{}", pair + 1, labels.label(position as u16)))
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            (items, Some(matches))
+        }
+        QuestionKind::Ordering(answer) => {
+            let order = answer.presented_order(seed);
+            let items = order
+                .iter()
+                .enumerate()
+                .map(|(position, &item)| {
+                    format!("{}. {}", labels.label(position as u16), answer.items[item])
+                })
+                .collect();
+            let sequence = (0..answer.items.len())
+                .filter_map(|item| order.iter().position(|&index| index == item))
+                .map(|position| labels.label(position as u16))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            (items, Some(sequence))
+        }
+    }
+}
+
+struct Headings {
+    answer: &'static str,
+    explanation: &'static str,
+}
+
+impl Headings {
+    fn new(locale: Option<&LanguageTag>) -> Self {
+        match locale.map(LanguageTag::language) {
+            Some("pt") => Self {
+                answer: "Resposta correta",
+                explanation: "Explicação",
+            },
+            Some("en") => Self {
+                answer: "Correct answer",
+                explanation: "Explanation",
+            },
+            _ => Self {
+                answer: "Respuesta correcta",
+                explanation: "Explicación",
+            },
+        }
+    }
+}
+
+pub fn to_text(question: &QuestionData, options: &RenderOptions) -> String {
+    let rendered = RenderedQuestion::new(question, options);
+
+    let mut text = format!("{}\n", rendered.text);
+
+    for item in &rendered.items {
+        text.push_str(&format!("\n{item}"));
+    }
+
+    for option in &rendered.options {
+        text.push_str(&format!("\n{}. {}", option.label, option.text));
+    }
+
+    if options.reveal_answer {
+        text.push_str(&format!(
+            "\n\n{}: {}",
+            rendered.headings.answer,
+            rendered.correct_answer()
+        ));
+    }
+
+    if options.include_explanation {
+        if let Some(explanation) = rendered.explanation {
+            text.push_str(&format!(
+                "\n\n{}: {explanation}",
+                rendered.headings.explanation
+            ));
+        }
+
+        for option in &rendered.options {
+            if let Some(explanation) = option.explanation {
+                text.push_str(&format!("\n{}. {explanation}", option.label));
+            }
+        }
+    }
+
+    if let Some(attribution) = &rendered.attribution {
+        text.push_str(&format!("\n\n{attribution}"));
+    }
+
+    text
+}
+
+pub fn to_html(question: &QuestionData, options: &RenderOptions) -> String {
+    let rendered = RenderedQuestion::new(question, options);
+
+    // Pages load a math renderer when a question has this class.
+    let class = if question.contains_math() {
+        "question math"
+    } else {
+        "question"
+    };
+    let mut html = format!(
+        "<div class=\"{class}\">\n<p class=\"question-text\">{}</p>\n",
+        escape_html(rendered.text)
+    );
+
+    if !rendered.items.is_empty() {
+        html.push_str("<ul class=\"question-items\">\n");
+
+        for item in &rendered.items {
+            html.push_str(&format!("<li>{}</li>\n", escape_html(item)));
+        }
+
+        html.push_str("</ul>\n");
+    }
+
+    html.push_str("<ol class=\"question-options\">\n");
+
+    for option in &rendered.options {
+        let class = if options.reveal_answer && option.is_correct {
+            "question-option correct"
+        } else {
+            "question-option"
+        };
+
+        html.push_str(&format!(
+            "<li class=\"{class}\"><span class=\"option-label\">{}.</span> {}",
+            escape_html(&option.label),
+            escape_html(option.text)
+        ));
+
+        if let Some(explanation) = option.explanation.filter(|_| options.include_explanation) {
+            html.push_str(&format!(
+                " <span class=\"option-explanation\">{}</span>",
+                escape_html(explanation)
+            ));
+        }
+
+        html.push_str("</li>\n");
+    }
+
+    html.push_str("</ol>\n");
+
+    if options.reveal_answer {
+        html.push_str(&format!(
+            "<p class=\"answer\"><strong>{}:</strong> {}</p>\n",
+            rendered.headings.answer,
+            escape_html(&rendered.correct_answer())
+        ));
+    }
+
+    if let Some(explanation) = rendered.explanation.filter(|_| options.include_explanation) {
+        html.push_str(&format!(
+            "<div class=\"explanation\"><strong>{}:</strong> {}</div>\n",
+            rendered.headings.explanation,
+            escape_html(explanation)
+        ));
+    }
+
+    if let Some(attribution) = &rendered.attribution {
+        html.push_str(&format!(
+            "<p class=\"attribution\">{}</p>\n",
+            escape_html(attribution)
+        ));
+    }
+
+    html.push_str("</div>\n");
+
+    html
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::sync::{LicenseKind, OrderingAnswer};
+
+    fn question() -> QuestionData {
+        let mut data: QuestionData = Faker.fake();
+        data.question_options = fake::vec![_; 3];
+
+        // The first option is made correct, and sorted first, by `prepare_for_test`.
+        for (index, question_option) in data.question_options.iter_mut().enumerate() {
+            question_option.reference = 2 - index as u16;
+            question_option.text = format!("Option <{}>", question_option.reference);
+            question_option.is_correct = false;
+            question_option.explanation = None;
+        }
+
+        data.prepare_for_test().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_to_text() {
+        let data = question();
+
+        let text = to_text(&data, &RenderOptions::default());
+
+        assert!(text.contains("A. Option <0>.\nB. Option <1>.\nC. Option <2>."));
+        assert!(!text.contains("Respuesta correcta"));
+
+        let text = to_text(
+            &data,
+            &RenderOptions {
+                reveal_answer: true,
+                labels: OptionLabels::Numeric,
+                locale: Some(LanguageTag::new("en").unwrap()),
+                ..Default::default()
+            },
+        );
+
+        assert!(text.ends_with("Correct answer: 3"));
+    }
+
+    #[test]
+    fn test_attribution() {
+        let mut data = question();
+        let license = |institution: &str| {
+            LicenseData::new(
+                institution.into(),
+                LicenseKind::CcBy,
+                "Material cedido".into(),
+                None,
+            )
+            .unwrap()
+        };
+        let options = RenderOptions {
+            course_license: Some(license("Facultad de Medicina")),
+            ..Default::default()
+        };
+
+        assert!(
+            to_text(&data, &options).ends_with("\n\nMaterial cedido — Facultad de Medicina, CC BY")
+        );
+        assert!(!to_text(&data, &RenderOptions::default()).contains("Material cedido"));
+
+        data.license = Some(license("Hospital de Clínicas"));
+
+        assert!(to_text(&data, &options).ends_with("Material cedido — Hospital de Clínicas, CC BY"));
+        assert!(to_html(&data, &options).contains(
+            "<p class=\"attribution\">Material cedido — Hospital de Clínicas, CC BY</p>"
+        ));
+    }
+
+    #[test]
+    fn test_to_text_ordering() {
+        let mut data = question();
+        data.question_options.clear();
+        data.kind = QuestionKind::Ordering(
+            OrderingAnswer::new(vec!["Primero".into(), "Segundo".into(), "Tercero".into()])
+                .unwrap(),
+        );
+
+        let text = to_text(
+            &data,
+            &RenderOptions {
+                reveal_answer: true,
+                ..Default::default()
+            },
+        );
+        let label = |item: &str| {
+            text.lines()
+                .find_map(|line| line.strip_suffix(&format!(". {item}")))
+                .unwrap()
+                .to_string()
+        };
+
+        assert!(text.ends_with(&format!(
+            "Respuesta correcta: {}, {}, {}",
+            label("Primero"),
+            label("Segundo"),
+            label("Tercero")
+        )));
+    }
+
+    #[test]
+    fn test_to_html() {
+        let mut data = question();
+
+        let html = to_html(
+            &data,
+            &RenderOptions {
+                reveal_answer: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(html.contains(
+            "<li class=\"question-option correct\"><span class=\"option-label\">C.</span> Option &lt;2&gt;.</li>"
+        ));
+        assert!(html.starts_with("<div class=\"question\">"));
+
+        data.text = r"¿Cuál es el valor de $\bar{x}$?".into();
+
+        assert!(
+            to_html(&data, &RenderOptions::default()).starts_with("<div class=\"question math\">")
+        );
+    }
+}
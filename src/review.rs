@@ -0,0 +1,237 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(
+    strum::Display,
+    strum::EnumString,
+    Serialize,
+    Deserialize,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    Debug,
+)]
+#[cfg_attr(
+    feature = "db",
+    derive(sqlx::Type),
+    sqlx(type_name = "text", rename_all = "snake_case")
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ReviewState {
+    #[default]
+    Pending,
+    Approved,
+    ChangesRequested,
+}
+
+impl ReviewState {
+    /// Approved and changes-requested reviews go back to pending once the
+    /// question is edited.
+    pub fn can_transition_to(&self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Pending, Self::Approved)
+                | (Self::Pending, Self::ChangesRequested)
+                | (Self::Approved, Self::Pending)
+                | (Self::ChangesRequested, Self::Pending)
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ReviewComment {
+    pub author_id: Uuid,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Content QA of a single question by an assigned reviewer.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "db", derive(sqlx::FromRow))]
+pub struct ReviewTask {
+    pub id: Uuid,
+    pub question_id: Uuid,
+    pub reviewer_id: Uuid,
+    pub state: ReviewState,
+    #[cfg_attr(feature = "db", sqlx(json))]
+    pub comments: Vec<ReviewComment>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum ReviewError {
+    InvalidTransition { from: ReviewState, to: ReviewState },
+    NotReviewer(Uuid),
+    EmptyComment,
+}
+
+impl std::fmt::Display for ReviewError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidTransition { from, to } => {
+                write!(f, "invalid review transition from {from} to {to}")
+            }
+            Self::NotReviewer(user_id) => {
+                write!(f, "user {user_id} isn't the reviewer of this question")
+            }
+            Self::EmptyComment => write!(f, "empty review comment"),
+        }
+    }
+}
+
+impl std::error::Error for ReviewError {}
+
+impl ReviewTask {
+    pub fn new(question_id: Uuid, reviewer_id: Uuid, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            question_id,
+            reviewer_id,
+            state: ReviewState::Pending,
+            comments: vec![],
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn approve(&mut self, by: Uuid, now: DateTime<Utc>) -> Result<(), ReviewError> {
+        self.check_reviewer(by)?;
+        self.transition(ReviewState::Approved, now)
+    }
+
+    /// Changes must be explained, so the comment is required.
+    pub fn request_changes(
+        &mut self,
+        by: Uuid,
+        comment: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), ReviewError> {
+        self.check_reviewer(by)?;
+
+        let comment = new_comment(by, comment, now)?;
+
+        self.transition(ReviewState::ChangesRequested, now)?;
+        self.comments.push(comment);
+
+        Ok(())
+    }
+
+    /// Sends the question back to review, e.g. after it was edited.
+    pub fn reopen(&mut self, now: DateTime<Utc>) -> Result<(), ReviewError> {
+        self.transition(ReviewState::Pending, now)
+    }
+
+    pub fn reassign(&mut self, reviewer_id: Uuid, now: DateTime<Utc>) -> Result<(), ReviewError> {
+        if self.state != ReviewState::Pending {
+            return Err(ReviewError::InvalidTransition {
+                from: self.state,
+                to: ReviewState::Pending,
+            });
+        }
+
+        self.reviewer_id = reviewer_id;
+        self.updated_at = now;
+
+        Ok(())
+    }
+
+    /// Anyone can comment, in any state.
+    pub fn comment(
+        &mut self,
+        author_id: Uuid,
+        text: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), ReviewError> {
+        self.comments.push(new_comment(author_id, text, now)?);
+        self.updated_at = now;
+
+        Ok(())
+    }
+
+    fn check_reviewer(&self, user_id: Uuid) -> Result<(), ReviewError> {
+        if user_id != self.reviewer_id {
+            return Err(ReviewError::NotReviewer(user_id));
+        }
+
+        Ok(())
+    }
+
+    fn transition(&mut self, next: ReviewState, now: DateTime<Utc>) -> Result<(), ReviewError> {
+        if !self.state.can_transition_to(next) {
+            return Err(ReviewError::InvalidTransition {
+                from: self.state,
+                to: next,
+            });
+        }
+
+        self.state = next;
+        self.updated_at = now;
+
+        Ok(())
+    }
+}
+
+fn new_comment(
+    author_id: Uuid,
+    text: &str,
+    now: DateTime<Utc>,
+) -> Result<ReviewComment, ReviewError> {
+    let text = text.trim();
+
+    if text.is_empty() {
+        return Err(ReviewError::EmptyComment);
+    }
+
+    Ok(ReviewComment {
+        author_id,
+        text: text.into(),
+        created_at: now,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_review_transitions() {
+        let reviewer_id = Uuid::new_v4();
+        let other_id = Uuid::new_v4();
+        let now = Utc::now();
+        let mut task = ReviewTask::new(Uuid::new_v4(), reviewer_id, now);
+
+        assert_eq!(
+            task.approve(other_id, now),
+            Err(ReviewError::NotReviewer(other_id))
+        );
+        assert_eq!(
+            task.request_changes(reviewer_id, "  ", now),
+            Err(ReviewError::EmptyComment)
+        );
+
+        task.request_changes(reviewer_id, "La opción C también es correcta.", now)
+            .unwrap();
+
+        assert_eq!(task.state, ReviewState::ChangesRequested);
+        assert_eq!(task.comments.len(), 1);
+        assert_eq!(
+            task.approve(reviewer_id, now),
+            Err(ReviewError::InvalidTransition {
+                from: ReviewState::ChangesRequested,
+                to: ReviewState::Approved,
+            })
+        );
+
+        task.reopen(now).unwrap();
+        task.approve(reviewer_id, now).unwrap();
+
+        assert_eq!(task.state, ReviewState::Approved);
+        assert!(task.reassign(other_id, now).is_err());
+    }
+}
@@ -0,0 +1,331 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::sync::broadcast;
+use tokio::task::{JoinError, JoinSet};
+use tracing::{error, info, warn};
+
+/// How long a task may take to stop on its own after the shutdown is triggered.
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// Cancellation token shared by all tasks of a process.
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    sender: broadcast::Sender<()>,
+    triggered: Arc<AtomicBool>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(1);
+
+        Self {
+            sender,
+            triggered: Default::default(),
+        }
+    }
+
+    pub fn trigger(&self) {
+        if !self.triggered.swap(true, Ordering::SeqCst) {
+            let _ = self.sender.send(());
+        }
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.triggered.load(Ordering::SeqCst)
+    }
+
+    pub async fn cancelled(&self) {
+        let mut receiver = self.sender.subscribe();
+
+        if self.is_triggered() {
+            return;
+        }
+
+        let _ = receiver.recv().await;
+    }
+
+    /// Waits for Ctrl-C or SIGTERM, then triggers the shutdown.
+    pub async fn wait_for_signal(&self) -> Result<()> {
+        #[cfg(unix)]
+        {
+            let mut terminate =
+                tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+
+            tokio::select! {
+                result = tokio::signal::ctrl_c() => result?,
+                _ = terminate.recv() => {}
+                _ = self.cancelled() => return Ok(()),
+            }
+        }
+
+        #[cfg(not(unix))]
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result?,
+            _ = self.cancelled() => return Ok(()),
+        }
+
+        info!("shutdown signal received");
+        self.trigger();
+
+        Ok(())
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RestartPolicy {
+    Never,
+    /// Restarts after errors and panics, up to `max_restarts` times.
+    OnFailure {
+        max_restarts: u32,
+        backoff: Duration,
+    },
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum TaskOutcome {
+    Completed,
+    Failed(String),
+    Panicked(String),
+    Cancelled,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct TaskReport {
+    pub name: String,
+    pub outcome: TaskOutcome,
+    pub restarts: u32,
+}
+
+pub struct TaskSet {
+    shutdown: Shutdown,
+    grace_period: Duration,
+    tasks: JoinSet<TaskReport>,
+}
+
+impl TaskSet {
+    pub fn new(shutdown: Shutdown) -> Self {
+        Self {
+            shutdown,
+            grace_period: DEFAULT_GRACE_PERIOD,
+            tasks: JoinSet::new(),
+        }
+    }
+
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Runs the future built by `make_task` until it completes or the shutdown is
+    /// triggered, logging errors and panics and restarting it per `policy`. After
+    /// the shutdown, a task that hasn't stopped within the grace period is aborted.
+    pub fn spawn_supervised<F, Fut>(&mut self, name: &str, policy: RestartPolicy, mut make_task: F)
+    where
+        F: FnMut(Shutdown) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let name = name.to_string();
+        let shutdown = self.shutdown.clone();
+        let grace_period = self.grace_period;
+
+        self.tasks.spawn(async move {
+            let mut restarts = 0;
+
+            loop {
+                let mut task = tokio::spawn(make_task(shutdown.clone()));
+
+                let outcome = tokio::select! {
+                    result = &mut task => task_outcome(result),
+                    _ = shutdown.cancelled() => {
+                        match tokio::time::timeout(grace_period, &mut task).await {
+                            Ok(result) => task_outcome(result),
+                            Err(_) => {
+                                warn!(task = name, "supervised task ignored the shutdown, aborting");
+                                task.abort();
+                                let _ = task.await;
+
+                                TaskOutcome::Cancelled
+                            }
+                        }
+                    }
+                };
+
+                let (max_restarts, backoff) = match (&outcome, policy) {
+                    (
+                        TaskOutcome::Failed(_) | TaskOutcome::Panicked(_),
+                        RestartPolicy::OnFailure {
+                            max_restarts,
+                            backoff,
+                        },
+                    ) => (max_restarts, backoff),
+                    _ => {
+                        return TaskReport {
+                            name,
+                            outcome,
+                            restarts,
+                        }
+                    }
+                };
+
+                error!(task = name, ?outcome, "supervised task failed");
+
+                if restarts >= max_restarts || shutdown.is_triggered() {
+                    return TaskReport {
+                        name,
+                        outcome,
+                        restarts,
+                    };
+                }
+
+                restarts += 1;
+                warn!(task = name, restarts, "restarting supervised task");
+
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = shutdown.cancelled() => {
+                        return TaskReport { name, outcome: TaskOutcome::Cancelled, restarts };
+                    }
+                }
+            }
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    pub async fn join_all(mut self) -> Vec<TaskReport> {
+        let mut reports = vec![];
+
+        while let Some(result) = self.tasks.join_next().await {
+            match result {
+                Ok(report) => reports.push(report),
+                Err(error) => error!("supervisor task failed: {error}"),
+            }
+        }
+
+        reports
+    }
+}
+
+fn task_outcome(result: Result<Result<()>, JoinError>) -> TaskOutcome {
+    match result {
+        Ok(Ok(())) => TaskOutcome::Completed,
+        Ok(Err(error)) => TaskOutcome::Failed(format!("{error:#}")),
+        Err(error) if error.is_panic() => TaskOutcome::Panicked(panic_message(error.into_panic())),
+        Err(_) => TaskOutcome::Cancelled,
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| "unknown panic".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_restarts_after_panic() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let mut tasks = TaskSet::new(Shutdown::new());
+
+        let task_attempts = attempts.clone();
+        tasks.spawn_supervised(
+            "panicky",
+            RestartPolicy::OnFailure {
+                max_restarts: 2,
+                backoff: Duration::ZERO,
+            },
+            move |_| {
+                task_attempts.fetch_add(1, Ordering::SeqCst);
+
+                async { panic!("boom") }
+            },
+        );
+
+        let reports = tasks.join_all().await;
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(reports[0].outcome, TaskOutcome::Panicked("boom".into()));
+        assert_eq!(reports[0].restarts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let shutdown = Shutdown::new();
+        let mut tasks = TaskSet::new(shutdown.clone());
+
+        tasks.spawn_supervised("worker", RestartPolicy::Never, |shutdown| async move {
+            shutdown.cancelled().await;
+
+            Ok(())
+        });
+
+        shutdown.trigger();
+
+        let reports = tasks.join_all().await;
+
+        assert!(matches!(
+            reports[0].outcome,
+            TaskOutcome::Completed | TaskOutcome::Cancelled
+        ));
+    }
+
+    /// Sets its flag when dropped.
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_aborts_task_ignoring_shutdown() {
+        let shutdown = Shutdown::new();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let mut tasks = TaskSet::new(shutdown.clone()).with_grace_period(Duration::from_millis(10));
+
+        let task_dropped = dropped.clone();
+        tasks.spawn_supervised("stubborn", RestartPolicy::Never, move |_| {
+            let flag = DropFlag(task_dropped.clone());
+
+            async move {
+                let _flag = flag;
+                std::future::pending::<()>().await;
+
+                Ok(())
+            }
+        });
+
+        shutdown.trigger();
+
+        let reports = tasks.join_all().await;
+
+        assert_eq!(reports[0].outcome, TaskOutcome::Cancelled);
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,309 @@
+//! In-memory full-text search over the questions of a course, instead of
+//! `ILIKE` queries that time out on big courses. Words are stemmed as Spanish
+//! and folded to ASCII, so "fracturas" finds "fractura" and "cardiaca" finds
+//! "cardíaca".
+
+mod suggest;
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::slug::fold_accent;
+use crate::sync::QuestionData;
+
+pub use suggest::*;
+
+/// Too frequent in Spanish to tell questions apart.
+const STOP_WORDS: &[&str] = &[
+    "a", "al", "con", "cual", "de", "del", "el", "en", "es", "la", "las", "lo", "los", "mas", "o",
+    "para", "por", "que", "se", "su", "un", "una", "y",
+];
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Text,
+    Topic,
+    Explanation,
+}
+
+impl SearchField {
+    /// A match in the topic says more about a question than one in its explanation.
+    pub fn weight(&self) -> f64 {
+        match self {
+            Self::Text => 1.0,
+            Self::Topic => 2.0,
+            Self::Explanation => 0.5,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct SearchFilters {
+    /// Only questions in one of these topics, by topic key. All if empty.
+    #[serde(default)]
+    pub topic_keys: Vec<String>,
+    /// Only questions with all of these tags.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct SearchHit {
+    pub question_id: Uuid,
+    pub score: f64,
+}
+
+/// Inverted index of the questions of a course, built when the course syncs.
+pub struct SearchIndex {
+    documents: Vec<Document>,
+    postings: HashMap<String, Vec<Posting>>,
+    stemmer: Stemmer,
+    suggestions: SuggestionIndex,
+}
+
+struct Document {
+    question_id: Uuid,
+    topic_key: String,
+    tags: Vec<String>,
+}
+
+struct Posting {
+    document: usize,
+    field: SearchField,
+    term_frequency: u32,
+}
+
+impl SearchIndex {
+    pub fn new(questions: &[QuestionData]) -> Self {
+        let mut index = Self {
+            documents: Vec::with_capacity(questions.len()),
+            postings: HashMap::new(),
+            stemmer: Stemmer::create(Algorithm::Spanish),
+            suggestions: SuggestionIndex::new(questions),
+        };
+
+        for question in questions {
+            index.add(question);
+        }
+
+        index
+    }
+
+    fn add(&mut self, question: &QuestionData) {
+        let document = self.documents.len();
+
+        self.documents.push(Document {
+            question_id: question.id,
+            topic_key: question.topic_key(),
+            tags: question.tags.clone(),
+        });
+
+        let fields = [
+            (SearchField::Text, Some(question.text.as_str())),
+            (SearchField::Topic, Some(question.topic.name.as_str())),
+            (
+                SearchField::Explanation,
+                question
+                    .explanation
+                    .as_ref()
+                    .map(|explanation| explanation.text.as_str()),
+            ),
+        ];
+
+        for (field, text) in fields {
+            let Some(text) = text else {
+                continue;
+            };
+
+            let mut term_frequencies = HashMap::<String, u32>::new();
+
+            for term in self.terms(text) {
+                *term_frequencies.entry(term).or_default() += 1;
+            }
+
+            for (term, term_frequency) in term_frequencies {
+                self.postings.entry(term).or_default().push(Posting {
+                    document,
+                    field,
+                    term_frequency,
+                });
+            }
+        }
+    }
+
+    /// Stems of the words of `text`, without stop words. Words are folded
+    /// before stemming, so accented and unaccented spellings get the same stem.
+    fn terms(&self, text: &str) -> Vec<String> {
+        text.split(|char: char| !char.is_alphanumeric())
+            .map(|word| {
+                word.to_lowercase()
+                    .chars()
+                    .map(fold_accent)
+                    .collect::<String>()
+            })
+            .filter(|word| !word.is_empty() && !STOP_WORDS.contains(&word.as_str()))
+            .map(|word| self.stemmer.stem(&word).into_owned())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Questions matching any word of `query`, best first. Scores add up the
+    /// TF-IDF of each matched word, weighted by the field it matched in.
+    pub fn search(&self, query: &str, filters: &SearchFilters) -> Vec<SearchHit> {
+        let mut scores = HashMap::<usize, f64>::new();
+        let mut query_terms = self.terms(query);
+        query_terms.sort_unstable();
+        query_terms.dedup();
+
+        for term in &query_terms {
+            let Some(postings) = self.postings.get(term) else {
+                continue;
+            };
+
+            let inverse_document_frequency =
+                (1.0 + self.documents.len() as f64 / postings.len() as f64).ln();
+
+            for posting in postings {
+                let term_frequency = posting.term_frequency as f64;
+
+                *scores.entry(posting.document).or_default() +=
+                    posting.field.weight() * inverse_document_frequency * term_frequency
+                        / (term_frequency + 1.0);
+            }
+        }
+
+        let mut hits = scores
+            .into_iter()
+            .filter(|(document, _)| self.documents[*document].matches(filters))
+            .map(|(document, score)| SearchHit {
+                question_id: self.documents[document].question_id,
+                score,
+            })
+            .collect::<Vec<_>>();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then(a.question_id.cmp(&b.question_id))
+        });
+
+        if let Some(limit) = filters.limit {
+            hits.truncate(limit);
+        }
+
+        hits
+    }
+
+    /// Topics and tags to complete a partially typed query with.
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Vec<Suggestion> {
+        self.suggestions.suggest(prefix, limit)
+    }
+}
+
+impl Document {
+    fn matches(&self, filters: &SearchFilters) -> bool {
+        (filters.topic_keys.is_empty() || filters.topic_keys.contains(&self.topic_key))
+            && filters.tags.iter().all(|tag| self.tags.contains(tag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::sync::QuestionTopicData;
+
+    fn question(text: &str, topic: &str, tags: &[&str]) -> QuestionData {
+        let mut question: QuestionData = Faker.fake();
+        question.course_key = "TRAUMATOLOGIA".into();
+        question.text = text.into();
+        question.explanation = None;
+        question.tags = tags.iter().map(ToString::to_string).collect();
+        question.topic = QuestionTopicData::new(question.course_key.clone(), topic.into()).unwrap();
+        question.prepare_for_test().unwrap();
+
+        question
+    }
+
+    #[test]
+    fn test_search() {
+        let questions = [
+            question(
+                "¿Cuál es la fractura más frecuente de la muñeca?",
+                "Miembro superior",
+                &[],
+            ),
+            question(
+                "Paciente con fracturas costales múltiples.",
+                "Tórax",
+                &["urgencias"],
+            ),
+            question(
+                "Manejo de la insuficiencia cardíaca aguda.",
+                "Fracturas",
+                &[],
+            ),
+        ];
+        let index = SearchIndex::new(&questions);
+
+        let hits = index.search("FRACTURAS", &SearchFilters::default());
+
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].question_id, questions[2].id);
+
+        let hits = index.search(
+            "fractura cardiaca",
+            &SearchFilters {
+                tags: vec!["urgencias".into()],
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].question_id, questions[1].id);
+        assert!(index.search("de la", &SearchFilters::default()).is_empty());
+    }
+
+    #[test]
+    fn test_search_folds_before_stemming() {
+        let questions = [
+            question("Profilaxis de la infección urinaria.", "Urología", &[]),
+            question("Infecciones de piel y partes blandas.", "Dermatología", &[]),
+            question("Manejo de la hipertensión arterial.", "Cardiología", &[]),
+        ];
+        let index = SearchIndex::new(&questions);
+
+        for query in ["infección", "infeccion", "INFECCIONES"] {
+            let hits = index.search(query, &SearchFilters::default());
+
+            assert_eq!(hits.len(), 2, "{query}");
+        }
+
+        for query in ["cardiología", "cardiologia", "cardiologías"] {
+            let hits = index.search(query, &SearchFilters::default());
+
+            assert_eq!(hits.len(), 1, "{query}");
+            assert_eq!(hits[0].question_id, questions[2].id);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::slug::fold_accent;
+use crate::sync::QuestionData;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    Topic,
+    Tag,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct Suggestion {
+    pub text: String,
+    pub kind: SuggestionKind,
+    pub question_count: usize,
+}
+
+/// Completions for the search box from the topics and tags of a course,
+/// tolerating typos through trigram similarity.
+#[derive(Default, Debug)]
+pub struct SuggestionIndex {
+    suggestions: Vec<Suggestion>,
+    normalized: Vec<String>,
+    by_trigram: HashMap<[char; 3], Vec<usize>>,
+}
+
+impl SuggestionIndex {
+    /// Share of the trigrams of a prefix that a suggestion must have to be
+    /// suggested despite not starting with it.
+    pub const MIN_SIMILARITY: f64 = 0.7;
+
+    pub fn new(questions: &[QuestionData]) -> Self {
+        let mut counts = BTreeMap::<(SuggestionKind, &str), usize>::new();
+
+        for question in questions {
+            if !question.topic.is_default() {
+                *counts
+                    .entry((SuggestionKind::Topic, question.topic.name.as_str()))
+                    .or_default() += 1;
+            }
+
+            for tag in &question.tags {
+                *counts
+                    .entry((SuggestionKind::Tag, tag.as_str()))
+                    .or_default() += 1;
+            }
+        }
+
+        let mut index = Self::default();
+
+        for ((kind, text), question_count) in counts {
+            let position = index.suggestions.len();
+            let normalized = normalize(text);
+
+            for trigram in trigrams(&normalized).into_iter().collect::<HashSet<_>>() {
+                index.by_trigram.entry(trigram).or_default().push(position);
+            }
+
+            index.suggestions.push(Suggestion {
+                text: text.into(),
+                kind,
+                question_count,
+            });
+            index.normalized.push(normalized);
+        }
+
+        index
+    }
+
+    /// Suggestions with a word starting with `prefix` come first, then those
+    /// similar enough to it, each by question count.
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Vec<Suggestion> {
+        let prefix = normalize(prefix);
+
+        if prefix.is_empty() {
+            return vec![];
+        }
+
+        let mut prefix_matches = (0..self.suggestions.len())
+            .filter(|&position| {
+                self.normalized[position]
+                    .split(' ')
+                    .any(|word| word.starts_with(&prefix))
+                    || self.normalized[position].starts_with(&prefix)
+            })
+            .collect::<Vec<_>>();
+        prefix_matches.sort_by_key(|&position| self.rank(position));
+
+        let prefix_trigrams = trigrams(&prefix);
+        let mut shared_counts = HashMap::<usize, usize>::new();
+
+        for trigram in &prefix_trigrams {
+            for &position in self.by_trigram.get(trigram).into_iter().flatten() {
+                *shared_counts.entry(position).or_default() += 1;
+            }
+        }
+
+        let mut fuzzy_matches = shared_counts
+            .into_iter()
+            .filter(|(position, shared_count)| {
+                !prefix_matches.contains(position)
+                    && *shared_count as f64 / prefix_trigrams.len() as f64 >= Self::MIN_SIMILARITY
+            })
+            .map(|(position, shared_count)| (std::cmp::Reverse(shared_count), position))
+            .collect::<Vec<_>>();
+        fuzzy_matches.sort_by_key(|&(shared_count, position)| (shared_count, self.rank(position)));
+
+        prefix_matches
+            .into_iter()
+            .chain(fuzzy_matches.into_iter().map(|(_, position)| position))
+            .take(limit)
+            .map(|position| self.suggestions[position].clone())
+            .collect()
+    }
+
+    fn rank(&self, position: usize) -> (std::cmp::Reverse<usize>, &str) {
+        let suggestion = &self.suggestions[position];
+
+        (
+            std::cmp::Reverse(suggestion.question_count),
+            &suggestion.text,
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.suggestions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.suggestions.is_empty()
+    }
+}
+
+/// Lowercase ASCII-folded words separated by single spaces.
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|char| fold_accent(char).to_lowercase().next().unwrap_or(char))
+        .map(|char| if char.is_alphanumeric() { char } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Padded at the start, so the first letters weigh as much as the rest.
+fn trigrams(text: &str) -> Vec<[char; 3]> {
+    let chars = [' ', ' ']
+        .into_iter()
+        .chain(text.chars())
+        .collect::<Vec<_>>();
+
+    chars
+        .windows(3)
+        .map(|window| [window[0], window[1], window[2]])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::sync::QuestionTopicData;
+
+    #[test]
+    fn test_suggest() {
+        let questions = [
+            ("Cardiología", vec!["ECG"]),
+            ("Cardiología", vec!["Insuficiencia cardíaca"]),
+            ("Cardiopatías congénitas", vec![]),
+            ("Neumología", vec!["Cardio-respiratorio"]),
+        ]
+        .into_iter()
+        .map(|(topic, tags)| {
+            let mut question: QuestionData = Faker.fake();
+            question.topic = QuestionTopicData::new("MEDICINA".into(), topic.into()).unwrap();
+            question.tags = tags.into_iter().map(Into::into).collect();
+
+            question
+        })
+        .collect::<Vec<_>>();
+        let index = SuggestionIndex::new(&questions);
+
+        let texts = |suggestions: Vec<Suggestion>| {
+            suggestions
+                .into_iter()
+                .map(|suggestion| suggestion.text)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            texts(index.suggest("CARDI", 3)),
+            vec![
+                "Cardiología",
+                "Cardio-respiratorio",
+                "Cardiopatías congénitas"
+            ]
+        );
+        assert_eq!(
+            texts(index.suggest("cardiaca", 10)),
+            vec!["Insuficiencia cardíaca"]
+        );
+        assert_eq!(texts(index.suggest("neumolgia", 10)), vec!["Neumología"]);
+    }
+}
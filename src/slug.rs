@@ -0,0 +1,77 @@
+use std::collections::HashSet;
+
+pub fn fold_accent(char: char) -> char {
+    match char {
+        'á' | 'à' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+        'Á' | 'À' | 'Â' | 'Ã' | 'Ä' | 'Å' => 'A',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'É' | 'È' | 'Ê' | 'Ë' => 'E',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+        'Ó' | 'Ò' | 'Ô' | 'Õ' | 'Ö' => 'O',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+        'ñ' => 'n',
+        'Ñ' => 'N',
+        'ç' => 'c',
+        'Ç' => 'C',
+        char => char,
+    }
+}
+
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+
+    for char in name.chars().map(fold_accent) {
+        if char.is_ascii_alphanumeric() {
+            slug.push(char.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    slug.trim_end_matches('-').to_string()
+}
+
+pub fn is_slug(text: &str) -> bool {
+    !text.is_empty() && slugify(text) == text
+}
+
+pub fn unique_slug(name: &str, taken: &HashSet<String>) -> String {
+    let slug = slugify(name);
+
+    if !taken.contains(&slug) {
+        return slug;
+    }
+
+    (2..)
+        .map(|suffix| format!("{slug}-{suffix}"))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("slug suffixes should be unbounded")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(
+            slugify("  Anatomía  & Fisiología II "),
+            "anatomia-fisiologia-ii"
+        );
+        assert_eq!(slugify("Ñandú--Çedilla!"), "nandu-cedilla");
+        assert_eq!(slugify("¿?"), "");
+        assert!(is_slug("pediatria-2024"));
+        assert!(!is_slug("Pediatría"));
+    }
+
+    #[test]
+    fn test_unique_slug() {
+        let taken = HashSet::from(["cardiologia".to_string(), "cardiologia-2".to_string()]);
+
+        assert_eq!(unique_slug("Cardiología", &taken), "cardiologia-3");
+        assert_eq!(unique_slug("Neurología", &taken), "neurologia");
+    }
+}
@@ -0,0 +1,368 @@
+//! Statistics of exam simulation scores, shared so every client shows the same
+//! "you scored better than X% of users". Values are rounded to 2 decimal places.
+
+use anyhow::{bail, Result};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Distribution of the scores of an exam, small enough to be cached as JSON.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+pub struct PercentileTable {
+    pub total: usize,
+    /// Distinct scores, lowest first.
+    pub entries: Vec<PercentileEntry>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct PercentileEntry {
+    pub score: Decimal,
+    pub count: usize,
+    /// Scores lower than this one.
+    pub below: usize,
+}
+
+pub fn percentile_table(scores: &[Decimal]) -> PercentileTable {
+    let mut sorted = scores
+        .iter()
+        .map(|score| score.normalize())
+        .collect::<Vec<_>>();
+    sorted.sort();
+
+    let mut entries = Vec::<PercentileEntry>::new();
+
+    for (index, score) in sorted.into_iter().enumerate() {
+        match entries.last_mut() {
+            Some(entry) if entry.score == score => entry.count += 1,
+            _ => entries.push(PercentileEntry {
+                score,
+                count: 1,
+                below: index,
+            }),
+        }
+    }
+
+    PercentileTable {
+        total: scores.len(),
+        entries,
+    }
+}
+
+impl PercentileTable {
+    /// Percentage of scores strictly lower than `score`, from 0 to 100. Ties
+    /// don't count, so the best score of a single attempt is better than 0%.
+    pub fn better_than(&self, score: Decimal) -> Decimal {
+        if self.total == 0 {
+            return Decimal::ZERO;
+        }
+
+        let below = match self
+            .entries
+            .binary_search_by(|entry| entry.score.cmp(&score))
+        {
+            Ok(position) => self.entries[position].below,
+            Err(position) => self
+                .entries
+                .get(position)
+                .map_or(self.total, |entry| entry.below),
+        };
+
+        percentage(below, self.total)
+    }
+
+    /// Lowest score with at least `percentile` percent of the scores at or
+    /// below it (nearest rank).
+    pub fn score_at(&self, percentile: Decimal) -> Option<Decimal> {
+        let percentile = percentile.clamp(Decimal::ZERO, Decimal::ONE_HUNDRED);
+        let rank = (percentile * Decimal::from(self.total) / Decimal::ONE_HUNDRED)
+            .ceil()
+            .to_usize()?
+            .max(1);
+
+        self.entries
+            .iter()
+            .find(|entry| entry.below + entry.count >= rank)
+            .map(|entry| entry.score)
+    }
+}
+
+/// How answers add up to a score, which universities set differently. Every
+/// correct answer is worth a point and blank answers are worth nothing; schemes
+/// differ in what wrong answers cost. Answers with partial credit earn that
+/// share of a point and pay the rest of the penalty.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringScheme {
+    #[default]
+    NoNegative,
+    /// Each wrong answer takes away this fraction of a point, from 0 to 1.
+    MinusFraction(Decimal),
+    /// Each wrong answer takes away 1 / (options - 1) points, so guessing at
+    /// random scores 0 on average. Questions without options, like numeric
+    /// ones, can't be guessed, so their wrong answers cost nothing.
+    Formula,
+}
+
+impl ScoringScheme {
+    pub fn check(&self) -> Result<()> {
+        if let Self::MinusFraction(fraction) = self {
+            if fraction.is_sign_negative() || *fraction > Decimal::ONE {
+                bail!("invalid penalty fraction {fraction}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Points taken away by a wrong answer to a question with `option_count`
+    /// options, which is 0 for kinds without options.
+    pub fn penalty(&self, option_count: u16) -> Decimal {
+        match self {
+            Self::NoNegative => Decimal::ZERO,
+            Self::MinusFraction(fraction) => *fraction,
+            Self::Formula if option_count > 1 => Decimal::ONE / Decimal::from(option_count - 1),
+            Self::Formula => Decimal::ZERO,
+        }
+    }
+
+    /// Net points of `answers` as a percentage of the points of answering
+    /// every question correctly. Negative when penalties outweigh the correct
+    /// answers.
+    pub fn score(&self, answers: &[ExamAnswer]) -> Decimal {
+        if answers.is_empty() {
+            return Decimal::ZERO;
+        }
+
+        let points = answers
+            .iter()
+            .map(|answer| match answer.credit {
+                Some(credit) => {
+                    let credit = credit.clamp(Decimal::ZERO, Decimal::ONE);
+
+                    credit - (Decimal::ONE - credit) * self.penalty(answer.option_count)
+                }
+                None => Decimal::ZERO,
+            })
+            .sum::<Decimal>();
+
+        (points * Decimal::ONE_HUNDRED / Decimal::from(answers.len())).round_dp(2)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ExamAnswer {
+    /// Share of the answer that is correct, from 0 to 1, as graded by its
+    /// question kind, or `None` when left blank.
+    pub credit: Option<Decimal>,
+    pub option_count: u16,
+}
+
+impl ExamAnswer {
+    /// Answer that is either fully correct or wrong, like a chosen option.
+    pub fn from_correct(correct: Option<bool>, option_count: u16) -> Self {
+        Self {
+            credit: correct.map(|correct| if correct { Decimal::ONE } else { Decimal::ZERO }),
+            option_count,
+        }
+    }
+}
+
+/// Summary of a set of scores, to normalize scores against.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ScoreSummary {
+    pub count: usize,
+    pub mean: Decimal,
+    /// Population standard deviation.
+    pub std_dev: Decimal,
+    pub min: Decimal,
+    pub max: Decimal,
+}
+
+impl ScoreSummary {
+    pub fn new(scores: &[Decimal]) -> Self {
+        let (Some(min), Some(max)) = (scores.iter().min(), scores.iter().max()) else {
+            return Self::default();
+        };
+
+        let count = Decimal::from(scores.len());
+        let mean = scores.iter().sum::<Decimal>() / count;
+        let variance = scores
+            .iter()
+            .map(|score| (score - mean) * (score - mean))
+            .sum::<Decimal>()
+            / count;
+
+        Self {
+            count: scores.len(),
+            mean: mean.round_dp(2),
+            std_dev: variance.sqrt().unwrap_or_default().round_dp(2),
+            min: *min,
+            max: *max,
+        }
+    }
+
+    /// Summary of the scores of `attempts`, each the answers of an exam.
+    pub fn from_answers(attempts: &[Vec<ExamAnswer>], scheme: &ScoringScheme) -> Self {
+        Self::new(
+            &attempts
+                .iter()
+                .map(|answers| scheme.score(answers))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Standard deviations above the mean; zero when all scores are equal.
+    pub fn z_score(&self, score: Decimal) -> Decimal {
+        if self.std_dev.is_zero() {
+            return Decimal::ZERO;
+        }
+
+        ((score - self.mean) / self.std_dev).round_dp(2)
+    }
+
+    /// `score` mapped linearly from the range of scores to 0–100, clamped for
+    /// scores outside it. When all scores are equal, they're scaled to 50.
+    pub fn scaled(&self, score: Decimal) -> Decimal {
+        if self.max <= self.min {
+            return Decimal::from(50);
+        }
+
+        ((score - self.min) * Decimal::ONE_HUNDRED / (self.max - self.min))
+            .clamp(Decimal::ZERO, Decimal::ONE_HUNDRED)
+            .round_dp(2)
+    }
+}
+
+fn percentage(part: usize, total: usize) -> Decimal {
+    (Decimal::from(part) * Decimal::ONE_HUNDRED / Decimal::from(total)).round_dp(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_table() {
+        let scores = [60, 70, 70, 80, 90, 40, 70, 100]
+            .into_iter()
+            .map(Decimal::from)
+            .collect::<Vec<_>>();
+        let table = percentile_table(&scores);
+
+        assert_eq!(table.entries.len(), 6);
+        assert_eq!(table.better_than(Decimal::from(70)), Decimal::from(25));
+        assert_eq!(table.better_than(Decimal::from(75)), Decimal::new(625, 1));
+        assert_eq!(table.better_than(Decimal::from(40)), Decimal::ZERO);
+        assert_eq!(table.better_than(Decimal::from(101)), Decimal::ONE_HUNDRED);
+        assert_eq!(table.score_at(Decimal::from(50)), Some(Decimal::from(70)));
+        assert_eq!(table.score_at(Decimal::ZERO), Some(Decimal::from(40)));
+        assert_eq!(
+            serde_json::from_str::<PercentileTable>(&serde_json::to_string(&table).unwrap())
+                .unwrap(),
+            table
+        );
+        assert_eq!(
+            percentile_table(&[]).better_than(Decimal::ONE),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_score_summary() {
+        let scores = [2, 4, 4, 4, 5, 5, 7, 9]
+            .into_iter()
+            .map(Decimal::from)
+            .collect::<Vec<_>>();
+        let summary = ScoreSummary::new(&scores);
+
+        assert_eq!(summary.mean, Decimal::from(5));
+        assert_eq!(summary.std_dev, Decimal::from(2));
+        assert_eq!(summary.z_score(Decimal::from(9)), Decimal::from(2));
+        assert_eq!(summary.scaled(Decimal::from(4)), Decimal::new(2857, 2));
+        assert_eq!(summary.scaled(Decimal::from(12)), Decimal::ONE_HUNDRED);
+        assert_eq!(
+            ScoreSummary::new(&[Decimal::ONE]).scaled(Decimal::ONE),
+            Decimal::from(50)
+        );
+    }
+
+    #[test]
+    fn test_scoring_scheme() {
+        let answer = ExamAnswer::from_correct;
+        let answers = [
+            answer(Some(true), 4),
+            answer(Some(true), 4),
+            answer(Some(false), 4),
+            answer(Some(false), 5),
+            answer(None, 4),
+        ];
+        let third = Decimal::ONE / Decimal::from(3);
+
+        assert_eq!(ScoringScheme::NoNegative.score(&answers), Decimal::from(40));
+        assert_eq!(
+            ScoringScheme::MinusFraction(Decimal::new(25, 2)).score(&answers),
+            Decimal::from(30)
+        );
+        assert_eq!(
+            ScoringScheme::MinusFraction(third).score(&answers),
+            Decimal::new(2667, 2)
+        );
+        assert_eq!(
+            ScoringScheme::MinusFraction(Decimal::ONE).score(&answers),
+            Decimal::ZERO
+        );
+        // 2 - 1/3 - 1/4 points out of 5.
+        assert_eq!(
+            ScoringScheme::Formula.score(&answers),
+            Decimal::new(2833, 2)
+        );
+        assert_eq!(
+            ScoringScheme::Formula.score(&[answer(Some(false), 2), answer(Some(false), 1)]),
+            Decimal::from(-50)
+        );
+        assert_eq!(ScoringScheme::Formula.score(&[]), Decimal::ZERO);
+
+        let partial = |credit, option_count| ExamAnswer {
+            credit: Some(credit),
+            option_count,
+        };
+
+        // Half a point, minus half of the 1/4 penalty.
+        assert_eq!(
+            ScoringScheme::MinusFraction(Decimal::new(25, 2))
+                .score(&[partial(Decimal::new(5, 1), 0)]),
+            Decimal::new(375, 1)
+        );
+        assert_eq!(
+            ScoringScheme::Formula.score(&[partial(Decimal::ZERO, 0), partial(Decimal::TWO, 0)]),
+            Decimal::from(50)
+        );
+
+        assert!(ScoringScheme::MinusFraction(Decimal::ZERO).check().is_ok());
+        assert!(ScoringScheme::MinusFraction(Decimal::NEGATIVE_ONE)
+            .check()
+            .is_err());
+        assert!(ScoringScheme::MinusFraction(Decimal::TWO).check().is_err());
+        assert!(ScoringScheme::Formula.check().is_ok());
+
+        for scheme in [
+            ScoringScheme::NoNegative,
+            ScoringScheme::MinusFraction(third),
+            ScoringScheme::Formula,
+        ] {
+            assert_eq!(
+                serde_json::from_value::<ScoringScheme>(serde_json::to_value(scheme).unwrap())
+                    .unwrap(),
+                scheme
+            );
+        }
+
+        let summary = ScoreSummary::from_answers(
+            &[answers.to_vec(), vec![answer(Some(true), 4)]],
+            &ScoringScheme::MinusFraction(Decimal::new(25, 2)),
+        );
+
+        assert_eq!(summary.count, 2);
+        assert_eq!(summary.mean, Decimal::from(65));
+        assert_eq!(summary.min, Decimal::from(30));
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
 pub struct EngineStatus {
     pub commit: Option<String>,
     pub db: DbStatus,
@@ -17,6 +19,8 @@ impl EngineStatus {
 }
 
 #[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
 pub struct DbStatus {
     pub healthy: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -28,6 +32,8 @@ pub struct DbStatus {
 }
 
 #[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
 pub struct CacheStatus {
     pub healthy: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -0,0 +1,227 @@
+//! Study streaks counted in the user's local days. Day boundaries come from
+//! converting each activity to a local date, so DST changes never shift them.
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::digests::StreakStatus;
+
+/// Activity this many hours after local midnight still counts for the previous
+/// day, if that day would otherwise break the streak.
+pub const GRACE_PERIOD_HOURS: u32 = 2;
+pub const MAX_FREEZE_TOKENS: u8 = 3;
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Streak {
+    pub days: u16,
+    pub longest_days: u16,
+    pub last_study_date: Option<NaiveDate>,
+    /// Each token covers one missed day.
+    pub freeze_tokens: u8,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum StreakUpdate {
+    /// The day already counted, or the activity is older than the last one.
+    Unchanged,
+    Extended,
+    /// Counted for the previous day thanks to the grace period.
+    ExtendedInGracePeriod,
+    /// Missed days were covered by freeze tokens.
+    Frozen {
+        tokens_used: u8,
+    },
+    /// Missed days broke the streak, which starts again at one day.
+    Reset {
+        previous_days: u16,
+    },
+}
+
+pub fn local_date(at: DateTime<Utc>, timezone: Tz) -> NaiveDate {
+    at.with_timezone(&timezone).date_naive()
+}
+
+/// Records an activity at `activity_at`, returning how the streak changed.
+pub fn update(streak: &mut Streak, activity_at: DateTime<Utc>, timezone: Tz) -> StreakUpdate {
+    let local = activity_at.with_timezone(&timezone);
+    let date = local.date_naive();
+
+    let Some(last_date) = streak.last_study_date else {
+        streak.extend(date);
+
+        return StreakUpdate::Extended;
+    };
+
+    let missed_days = (date - last_date).num_days() - 1;
+
+    match missed_days {
+        ..0 => StreakUpdate::Unchanged,
+        0 => {
+            streak.extend(date);
+
+            StreakUpdate::Extended
+        }
+        1 if local.hour() < GRACE_PERIOD_HOURS => {
+            streak.extend(date - TimeDelta::days(1));
+
+            StreakUpdate::ExtendedInGracePeriod
+        }
+        missed_days if missed_days <= i64::from(streak.freeze_tokens) => {
+            let tokens_used = missed_days as u8;
+
+            streak.freeze_tokens -= tokens_used;
+            streak.extend(date);
+
+            StreakUpdate::Frozen { tokens_used }
+        }
+        _ => {
+            let previous_days = streak.days;
+
+            streak.days = 0;
+            streak.extend(date);
+
+            StreakUpdate::Reset { previous_days }
+        }
+    }
+}
+
+impl Streak {
+    fn extend(&mut self, date: NaiveDate) {
+        self.days = self.days.saturating_add(1);
+        self.longest_days = self.longest_days.max(self.days);
+        self.last_study_date = Some(date);
+    }
+
+    pub fn add_freeze_tokens(&mut self, count: u8) {
+        self.freeze_tokens = self
+            .freeze_tokens
+            .saturating_add(count)
+            .min(MAX_FREEZE_TOKENS);
+    }
+
+    pub fn status_at(&self, now: DateTime<Utc>, timezone: Tz) -> StreakStatus {
+        StreakStatus::new(self.days, self.last_study_date, local_date(now, timezone))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use chrono_tz::America::Montevideo;
+
+    use super::*;
+
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Montevideo
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_update_around_midnight() {
+        let mut streak = Streak::default();
+
+        assert_eq!(
+            update(&mut streak, local(2024, 5, 1, 23, 59), Montevideo),
+            StreakUpdate::Extended
+        );
+        // 02:59:59 UTC is still May 1st in Montevideo.
+        assert_eq!(
+            update(
+                &mut streak,
+                local(2024, 5, 1, 23, 59) + TimeDelta::seconds(59),
+                Montevideo
+            ),
+            StreakUpdate::Unchanged
+        );
+        assert_eq!(
+            update(&mut streak, local(2024, 5, 2, 0, 0), Montevideo),
+            StreakUpdate::Extended
+        );
+        assert_eq!(
+            update(&mut streak, local(2024, 5, 2, 12, 0), Montevideo),
+            StreakUpdate::Unchanged
+        );
+        // May 3rd was missed, but 01:30 on the 4th is within the grace period.
+        assert_eq!(
+            update(&mut streak, local(2024, 5, 4, 1, 30), Montevideo),
+            StreakUpdate::ExtendedInGracePeriod
+        );
+        assert_eq!(streak.last_study_date, NaiveDate::from_ymd_opt(2024, 5, 3));
+        assert_eq!(
+            update(&mut streak, local(2024, 5, 4, 9, 0), Montevideo),
+            StreakUpdate::Extended
+        );
+        assert_eq!(streak.days, 4);
+
+        streak.add_freeze_tokens(5);
+
+        assert_eq!(streak.freeze_tokens, MAX_FREEZE_TOKENS);
+        assert_eq!(
+            update(&mut streak, local(2024, 5, 7, 10, 0), Montevideo),
+            StreakUpdate::Frozen { tokens_used: 2 }
+        );
+        assert_eq!(
+            update(&mut streak, local(2024, 5, 12, 10, 0), Montevideo),
+            StreakUpdate::Reset { previous_days: 5 }
+        );
+        assert_eq!((streak.days, streak.longest_days), (1, 5));
+    }
+
+    #[test]
+    fn test_update_across_dst_transitions() {
+        // Uruguay last observed DST from 2014-10-05 (02:00 -> 03:00) to
+        // 2015-03-08 (02:00 -> 01:00).
+        let mut streak = Streak::default();
+
+        update(&mut streak, local(2014, 10, 4, 23, 30), Montevideo);
+
+        // The 23-hour day still counts once.
+        assert_eq!(
+            update(&mut streak, local(2014, 10, 5, 0, 30), Montevideo),
+            StreakUpdate::Extended
+        );
+        assert_eq!(
+            update(&mut streak, local(2014, 10, 5, 23, 30), Montevideo),
+            StreakUpdate::Unchanged
+        );
+        assert_eq!(
+            update(&mut streak, local(2014, 10, 6, 0, 15), Montevideo),
+            StreakUpdate::Extended
+        );
+
+        let mut streak = Streak::default();
+
+        update(&mut streak, local(2015, 3, 7, 23, 30), Montevideo);
+
+        // Both 01:30s of the 25-hour day are on March 8th.
+        let first = local(2015, 3, 8, 1, 30);
+        let second = first + TimeDelta::hours(1);
+
+        assert_eq!(
+            local_date(second, Montevideo),
+            local_date(first, Montevideo)
+        );
+        assert_eq!(
+            update(&mut streak, first, Montevideo),
+            StreakUpdate::Extended
+        );
+        assert_eq!(
+            update(&mut streak, second, Montevideo),
+            StreakUpdate::Unchanged
+        );
+        assert_eq!(
+            update(&mut streak, local(2015, 3, 9, 0, 0), Montevideo),
+            StreakUpdate::Extended
+        );
+        assert_eq!(streak.days, 3);
+        assert_eq!(
+            streak.status_at(local(2015, 3, 10, 12, 0), Montevideo),
+            StreakStatus::AtRisk { days: 3 }
+        );
+    }
+}
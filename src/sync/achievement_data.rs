@@ -0,0 +1,154 @@
+use anyhow::{bail, Result};
+#[cfg(any(test, feature = "testing"))]
+use fake::Dummy;
+use serde::{Deserialize, Serialize};
+
+use super::content_entity::ContentEntity;
+use super::helpers::format_text;
+use super::validation_report::ValidationError;
+use crate::traits::{Hashable, Syncable};
+
+#[non_exhaustive]
+#[derive(
+    medici_macros::Hashable,
+    medici_macros::SyncEntity,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[medici(key = "key")]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
+pub struct AchievementData {
+    pub key: String,
+
+    pub name: String,
+    pub description: String,
+    pub icon_key: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        dummy(expr = "AchievementCriteria::StreakDays(7)")
+    )]
+    pub criteria: AchievementCriteria,
+    #[cfg_attr(any(test, feature = "testing"), dummy(faker = "1..100"))]
+    pub points: u16,
+
+    pub hash: String,
+}
+
+impl AchievementData {
+    pub fn new(
+        key: String,
+        name: String,
+        description: String,
+        icon_key: String,
+        criteria: AchievementCriteria,
+        points: u16,
+    ) -> Result<Self> {
+        let mut data = Self {
+            key,
+            name,
+            description,
+            icon_key,
+            criteria,
+            points,
+            hash: Default::default(),
+        };
+
+        data.process()?;
+
+        Ok(data)
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        self.format();
+        self.check().map_err(ValidationError::wrap)?;
+
+        self.refresh_hash();
+
+        Ok(())
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.key.is_empty() || self.name.is_empty() || self.icon_key.is_empty() {
+            bail!("invalid achievement with key {}", self.key);
+        }
+
+        if self.points == 0 {
+            bail!("achievement with key {} has no points", self.key);
+        }
+
+        match &self.criteria {
+            AchievementCriteria::QuestionsAnswered(0)
+            | AchievementCriteria::CorrectAnswers(0)
+            | AchievementCriteria::StreakDays(0) => {
+                bail!("achievement with key {} has an empty criteria", self.key)
+            }
+            AchievementCriteria::CourseCompletion(course_key) if course_key.is_empty() => {
+                bail!("achievement with key {} has an invalid course", self.key)
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn format(&mut self) {
+        self.key = self.key.trim().to_string();
+        self.name = self.name.trim().to_string();
+        self.description = format_text(&self.description);
+        self.icon_key = self.icon_key.trim().to_string();
+
+        if let AchievementCriteria::CourseCompletion(course_key) = &mut self.criteria {
+            *course_key = course_key.trim().to_string();
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AchievementCriteria {
+    QuestionsAnswered(u32),
+    CorrectAnswers(u32),
+    StreakDays(u16),
+    CourseCompletion(String),
+    PerfectExam,
+}
+
+impl Hashable for AchievementCriteria {
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::QuestionsAnswered(count) => {
+                [b"questions_answered".to_vec(), count.to_bytes()].concat()
+            }
+            Self::CorrectAnswers(count) => [b"correct_answers".to_vec(), count.to_bytes()].concat(),
+            Self::StreakDays(days) => [b"streak_days".to_vec(), days.to_bytes()].concat(),
+            Self::CourseCompletion(course_key) => {
+                [b"course_completion".to_vec(), course_key.to_bytes()].concat()
+            }
+            Self::PerfectExam => b"perfect_exam".to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+
+    #[test]
+    fn test_process() {
+        let mut data: AchievementData = Faker.fake();
+
+        data.process().unwrap();
+
+        data.criteria = AchievementCriteria::CorrectAnswers(0);
+
+        assert!(data.process().is_err());
+    }
+}
@@ -0,0 +1,312 @@
+//! Progress of applying a sync, so a job that crashed halfway resumes with the
+//! batches it hadn't applied. A batch is one operation on one entity type, and
+//! applying a batch again is harmless, so a batch interrupted midway is rerun.
+
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::content_entity::with_content_entities;
+use super::{ContentEntityType, Environment, SyncData};
+#[cfg(feature = "db")]
+use crate::traits::{Changeset, Insertable, Table};
+
+#[derive(
+    strum::Display, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Debug,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum SyncOperation {
+    Sync,
+    Delete,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct SyncBatch {
+    pub entity_type: ContentEntityType,
+    pub operation: SyncOperation,
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+#[serde(transparent)]
+pub struct AppliedBatches(pub BTreeSet<SyncBatch>);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(
+    feature = "db",
+    derive(medici_macros::Table, sqlx::FromRow),
+    medici(table_name = "sync_apply_states")
+)]
+pub struct SyncApplyState {
+    #[cfg_attr(feature = "db", medici(primary_key))]
+    pub id: Uuid,
+    /// `SyncData::plan_hash` of the sync being applied.
+    pub plan_hash: String,
+    pub environment: Environment,
+    pub applied_batches: AppliedBatches,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(
+    feature = "db",
+    derive(medici_macros::Insertable),
+    medici(table_struct = "SyncApplyState")
+)]
+pub struct SyncApplyStateInsert {
+    pub id: Uuid,
+    pub plan_hash: String,
+    pub environment: Environment,
+    pub applied_batches: AppliedBatches,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(
+    feature = "db",
+    derive(medici_macros::Changeset),
+    medici(table_struct = "SyncApplyState")
+)]
+pub struct SyncApplyStateChangeset {
+    pub applied_batches: Option<AppliedBatches>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl SyncApplyState {
+    pub fn new(sync_data: &SyncData, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            plan_hash: sync_data.plan_hash(),
+            environment: sync_data.environment,
+            applied_batches: Default::default(),
+            started_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn to_insert(&self) -> SyncApplyStateInsert {
+        SyncApplyStateInsert {
+            id: self.id,
+            plan_hash: self.plan_hash.clone(),
+            environment: self.environment,
+            applied_batches: self.applied_batches.clone(),
+            started_at: self.started_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// Records `batch` as applied, returning the changes to persist.
+    pub fn mark_applied(
+        &mut self,
+        batch: SyncBatch,
+        now: DateTime<Utc>,
+    ) -> SyncApplyStateChangeset {
+        self.applied_batches.0.insert(batch);
+        self.updated_at = now;
+
+        SyncApplyStateChangeset {
+            applied_batches: Some(self.applied_batches.clone()),
+            updated_at: Some(now),
+        }
+    }
+
+    pub fn is_applied(&self, batch: SyncBatch) -> bool {
+        self.applied_batches.0.contains(&batch)
+    }
+
+    pub fn is_complete(&self, sync_data: &SyncData) -> bool {
+        sync_data.remaining(self).is_empty()
+    }
+}
+
+impl SyncData {
+    /// Non-empty batches in the order they should be applied.
+    pub fn batches(&self) -> Vec<SyncBatch> {
+        // Deletions go in reverse insertion order.
+        let syncs = ContentEntityType::ALL.iter().map(|entity_type| SyncBatch {
+            entity_type: *entity_type,
+            operation: SyncOperation::Sync,
+        });
+        let deletions = ContentEntityType::ALL
+            .iter()
+            .rev()
+            .map(|entity_type| SyncBatch {
+                entity_type: *entity_type,
+                operation: SyncOperation::Delete,
+            });
+
+        syncs
+            .chain(deletions)
+            .filter(|batch| self.batch_len(*batch) > 0)
+            .collect()
+    }
+
+    /// What's left to apply. If `state` belongs to a different sync, that's everything.
+    pub fn remaining(&self, state: &SyncApplyState) -> Self {
+        let mut remaining = self.clone();
+
+        if state.plan_hash != self.plan_hash() {
+            return remaining;
+        }
+
+        for batch in &state.applied_batches.0 {
+            remaining.clear_batch(*batch);
+        }
+
+        remaining
+    }
+
+    /// Fingerprint of the environment, schema version, and every key and hash
+    /// to sync or delete, independent of set iteration order.
+    pub fn plan_hash(&self) -> String {
+        let mut lines = self
+            .entities_for_sync()
+            .iter()
+            .map(|entity| {
+                format!(
+                    "sync {} {} {}",
+                    entity.entity_type(),
+                    entity.key_string(),
+                    entity.hash()
+                )
+            })
+            .chain(self.deletion_lines())
+            .collect::<Vec<_>>();
+        lines.sort_unstable();
+
+        let header = format!("{} {}", self.environment, self.schema_version);
+
+        blake3::hash([header, lines.join("\n")].join("\n").as_bytes()).to_string()
+    }
+}
+
+macro_rules! batches {
+    ($($(#[$attr:meta])* $variant:ident($data:ty) => $field:ident: $index:ty,)*) => {
+        impl SyncData {
+            /// Entities in `batch`.
+            pub fn batch_len(&self, batch: SyncBatch) -> usize {
+                match (batch.entity_type, batch.operation) {
+                    $(
+                        (ContentEntityType::$variant, SyncOperation::Sync) => self.$field.for_sync.len(),
+                        (ContentEntityType::$variant, SyncOperation::Delete) => {
+                            self.$field.for_deletion.len()
+                        }
+                    )*
+                }
+            }
+
+            fn clear_batch(&mut self, batch: SyncBatch) {
+                match (batch.entity_type, batch.operation) {
+                    $(
+                        (ContentEntityType::$variant, SyncOperation::Sync) => self.$field.for_sync.clear(),
+                        (ContentEntityType::$variant, SyncOperation::Delete) => {
+                            self.$field.for_deletion.clear()
+                        }
+                    )*
+                }
+            }
+
+            fn deletion_lines(&self) -> Vec<String> {
+                let mut lines = vec![];
+
+                $(lines.extend(
+                    self.$field
+                        .for_deletion
+                        .iter()
+                        .map(|key| format!("delete {} {key}", ContentEntityType::$variant)),
+                );)*
+
+                lines
+            }
+        }
+    };
+}
+
+with_content_entities!(batches);
+
+#[cfg(feature = "db")]
+impl sqlx::Type<sqlx::Postgres> for AppliedBatches {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <sqlx::types::Json<BTreeSet<SyncBatch>> as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <sqlx::types::Json<BTreeSet<SyncBatch>> as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+    }
+}
+
+#[cfg(feature = "db")]
+impl<'q> sqlx::Encode<'q, sqlx::Postgres> for AppliedBatches {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <sqlx::Postgres as sqlx::Database>::ArgumentBuffer<'q>,
+    ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        sqlx::Encode::<sqlx::Postgres>::encode_by_ref(&sqlx::types::Json(&self.0), buf)
+    }
+}
+
+#[cfg(feature = "db")]
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for AppliedBatches {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        <sqlx::types::Json<BTreeSet<SyncBatch>> as sqlx::Decode<sqlx::Postgres>>::decode(value)
+            .map(|json| Self(json.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::{QuestionData, QuestionTopicData};
+
+    #[test]
+    fn test_remaining() {
+        let mut sync_data = SyncData::default();
+
+        for mut question in fake::vec![QuestionData; 2] {
+            question.prepare_for_test().unwrap();
+            sync_data.add_for_sync(question);
+        }
+        for name in ["Cardiología", "Neumología"] {
+            sync_data.add_for_deletion(QuestionTopicData::new("MI".into(), name.into()).unwrap());
+        }
+
+        let question_batch = SyncBatch {
+            entity_type: ContentEntityType::Question,
+            operation: SyncOperation::Sync,
+        };
+
+        assert_eq!(
+            sync_data.batches(),
+            [
+                question_batch,
+                SyncBatch {
+                    entity_type: ContentEntityType::QuestionTopic,
+                    operation: SyncOperation::Delete,
+                },
+            ]
+        );
+
+        let mut state = SyncApplyState::new(&sync_data, Utc::now());
+        let changeset = state.mark_applied(question_batch, Utc::now());
+
+        assert_eq!(
+            changeset.applied_batches,
+            Some(state.applied_batches.clone())
+        );
+
+        let remaining = sync_data.remaining(&state);
+
+        assert!(remaining.questions.is_empty());
+        assert_eq!(remaining.question_topics.len(), 2);
+        assert!(!state.is_complete(&sync_data));
+
+        let mut other = sync_data.clone();
+        other.add_for_deletion(QuestionTopicData::new("MI".into(), "Nefrología".into()).unwrap());
+
+        assert_eq!(other.remaining(&state).len(), other.len());
+    }
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::course_data::CourseData;
+use super::question_data::QuestionData;
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
+pub struct BackfillPlan {
+    pub course_key: String,
+    pub max_tokens_per_batch: usize,
+    pub batches: Vec<BackfillBatch>,
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
+pub struct BackfillBatch {
+    pub question_ids: Vec<Uuid>,
+    pub estimated_tokens: usize,
+}
+
+impl BackfillPlan {
+    pub const CHARS_PER_TOKEN: usize = 4;
+
+    pub fn new(course: &CourseData, max_tokens_per_batch: usize) -> Self {
+        let mut batches: Vec<BackfillBatch> = vec![];
+
+        for question in course.questions_missing_explanations() {
+            let estimated_tokens = Self::estimate_tokens(question);
+
+            match batches.last_mut() {
+                Some(batch)
+                    if batch.estimated_tokens + estimated_tokens <= max_tokens_per_batch =>
+                {
+                    batch.question_ids.push(question.id);
+                    batch.estimated_tokens += estimated_tokens;
+                }
+                _ => batches.push(BackfillBatch {
+                    question_ids: vec![question.id],
+                    estimated_tokens,
+                }),
+            }
+        }
+
+        Self {
+            course_key: course.key.clone(),
+            max_tokens_per_batch,
+            batches,
+        }
+    }
+
+    /// Tokens of the prompt sent to explain `question`, which also has its
+    /// correct option and option explanations.
+    pub fn estimate_tokens(question: &QuestionData) -> usize {
+        question
+            .explanation_prompt()
+            .chars()
+            .count()
+            .div_ceil(Self::CHARS_PER_TOKEN)
+    }
+
+    pub fn question_count(&self) -> usize {
+        self.batches
+            .iter()
+            .map(|batch| batch.question_ids.len())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![QuestionData; 6];
+
+        for (index, question) in course.questions.iter_mut().enumerate() {
+            question.explanation = None;
+
+            if index == 0 {
+                question.explanation = Some(Faker.fake());
+            }
+
+            question.prepare_for_test().unwrap();
+        }
+
+        let plan = course.backfill_plan(1);
+
+        assert_eq!(plan.question_count(), 5);
+        assert_eq!(plan.batches.len(), 5);
+
+        let plan = course.backfill_plan(usize::MAX / 2);
+
+        assert_eq!(plan.batches.len(), 1);
+    }
+
+    #[test]
+    fn test_estimate_tokens_counts_option_explanations() {
+        let mut question: QuestionData = Faker.fake();
+
+        for question_option in &mut question.question_options {
+            question_option.explanation = None;
+        }
+
+        question.prepare_for_test().unwrap();
+        let estimated_tokens = BackfillPlan::estimate_tokens(&question);
+
+        question.question_options[0].explanation = Some("a".repeat(400));
+        question.prepare_for_test().unwrap();
+
+        assert!(BackfillPlan::estimate_tokens(&question) >= estimated_tokens + 100);
+    }
+}
@@ -1,41 +1,133 @@
 use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+#[cfg(any(test, feature = "testing"))]
+use fake::Dummy;
 use rust_decimal::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
-use super::{helpers::full_image_path, BUNDLE_IMAGES_DIR_NAME};
-use crate::traits::Hashable;
+use super::content_entity::ContentEntity;
+use super::{
+    course_data::CourseData,
+    date_range::DateRange,
+    helpers::{format_text, full_image_path, is_in_window, is_valid_window},
+    publish_state::PublishState,
+    validation_report::{ValidationError, ValidationReport},
+    BUNDLE_IMAGES_DIR_NAME,
+};
+use crate::slug::{is_slug, slugify};
+use crate::traits::{Hashable, Syncable};
 
 #[non_exhaustive]
-#[derive(medici_macros::Hashable, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Debug)]
+#[derive(
+    medici_macros::Hashable,
+    medici_macros::SyncEntity,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[medici(key = "key")]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
 pub struct BundleData {
     pub key: String,
 
     pub name: String,
+    #[medici(builder_default)]
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub slug: String,
     pub description: String,
+    #[medici(unordered_hash)]
     pub course_keys: Vec<String>,
+    #[cfg_attr(any(test, feature = "testing"), dummy(expr = "Decimal::new(2, 1)"))]
     pub discount: Decimal,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub image_file_name: PathBuf,
+    #[serde(default)]
+    pub alt_text: Option<String>,
+    #[medici(explicit_none)]
+    #[serde(default)]
+    pub available_from: Option<DateTime<Utc>>,
+    #[medici(explicit_none)]
+    #[serde(default)]
+    pub available_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub discount_schedule: Vec<(DateRange, Decimal)>,
+    #[medici(builder_default)]
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub publish_state: PublishState,
+    #[medici(explicit_none)]
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub publish_at: Option<DateTime<Utc>>,
+    #[medici(explicit_none)]
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub unpublish_at: Option<DateTime<Utc>>,
 
     pub hash: String,
 }
 
+/// Pricing rules bundles are checked against.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct BundlePricing {
+    /// Bundles cheaper than this aren't worth the payment fees.
+    pub min_price_in_uyu: Decimal,
+}
+
+impl Default for BundlePricing {
+    fn default() -> Self {
+        Self {
+            min_price_in_uyu: Decimal::ONE_HUNDRED,
+        }
+    }
+}
+
 impl BundleData {
+    pub const MIN_COURSE_COUNT: usize = 2;
+
+    // One argument per field; `Self::builder()` names them instead.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         key: String,
         name: String,
+        slug: Option<String>,
         description: String,
         course_keys: Vec<String>,
         discount: Decimal,
         image_file_name: PathBuf,
+        alt_text: Option<String>,
+        available_from: Option<DateTime<Utc>>,
+        available_until: Option<DateTime<Utc>>,
+        discount_schedule: Vec<(DateRange, Decimal)>,
+        publish_state: PublishState,
+        publish_at: Option<DateTime<Utc>>,
+        unpublish_at: Option<DateTime<Utc>>,
     ) -> Result<Self> {
         let mut data = Self {
             key,
             name,
+            slug: slug.unwrap_or_default(),
             description,
             course_keys,
             discount,
             image_file_name,
+            alt_text,
+            available_from,
+            available_until,
+            discount_schedule,
+            publish_state,
+            publish_at,
+            unpublish_at,
             hash: Default::default(),
         };
 
@@ -44,9 +136,9 @@ impl BundleData {
         Ok(data)
     }
 
-    fn process(&mut self) -> Result<()> {
+    pub fn process(&mut self) -> Result<()> {
         self.format();
-        self.check()?;
+        self.check().map_err(ValidationError::wrap)?;
 
         self.refresh_hash();
 
@@ -58,20 +150,303 @@ impl BundleData {
             bail!("invalid bundle name");
         }
 
-        if self.discount <= Decimal::ZERO {
+        if !is_slug(&self.slug) {
+            bail!("invalid slug {} in bundle with key {}", self.slug, self.key);
+        }
+
+        if !is_valid_discount(self.discount) {
             bail!("invalid bundle discount");
         }
 
+        self.check_availability()?;
+        self.check_discount_schedule()?;
+
+        Ok(())
+    }
+
+    fn check_availability(&self) -> Result<()> {
+        if !is_valid_window(self.available_from, self.available_until) {
+            bail!(
+                "invalid availability window in bundle with key {}",
+                self.key
+            );
+        }
+
+        if !is_valid_window(self.publish_at, self.unpublish_at) {
+            bail!("invalid publish window in bundle with key {}", self.key);
+        }
+
         Ok(())
     }
 
+    fn check_discount_schedule(&self) -> Result<()> {
+        for (index, (range, discount)) in self.discount_schedule.iter().enumerate() {
+            range.check()?;
+
+            if !is_valid_discount(*discount) {
+                bail!("invalid scheduled discount in bundle with key {}", self.key);
+            }
+
+            if self.discount_schedule[..index]
+                .iter()
+                .any(|(other_range, _)| other_range.overlaps(range))
+            {
+                bail!(
+                    "overlapping discount schedule in bundle with key {}",
+                    self.key
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the bundle can be bought at `at`, independently of whether it's listed.
+    pub fn is_available_at(&self, at: DateTime<Utc>) -> bool {
+        is_in_window(self.available_from, self.available_until, at)
+    }
+
+    /// Published and within its publish window.
+    pub fn is_visible_at(&self, at: DateTime<Utc>) -> bool {
+        self.publish_state == PublishState::Published
+            && is_in_window(self.publish_at, self.unpublish_at, at)
+    }
+
+    pub fn effective_discount(&self, at: DateTime<Utc>) -> Decimal {
+        self.discount_schedule
+            .iter()
+            .find(|(range, _)| range.contains(at))
+            .map(|(_, discount)| *discount)
+            .unwrap_or(self.discount)
+    }
+
+    pub fn check_strict(&self) -> Result<()> {
+        if self.alt_text.is_none() {
+            bail!("bundle with key {} has an image without alt text", self.key);
+        }
+
+        Ok(())
+    }
+
+    pub fn check_against(
+        &self,
+        courses: &[CourseData],
+        pricing: &BundlePricing,
+    ) -> ValidationReport {
+        const ENTITY: &str = "bundle";
+
+        let mut report = ValidationReport::default();
+
+        let distinct_course_keys = self.course_keys.iter().collect::<HashSet<&String>>();
+
+        if distinct_course_keys.len() < Self::MIN_COURSE_COUNT {
+            report.push(
+                ENTITY,
+                &self.key,
+                format!(
+                    "has {} distinct course(s), expected at least {}",
+                    distinct_course_keys.len(),
+                    Self::MIN_COURSE_COUNT
+                ),
+            );
+        }
+
+        for course_key in distinct_course_keys {
+            match courses.iter().find(|course| &course.key == course_key) {
+                None => report.push(ENTITY, &self.key, format!("unknown course {course_key}")),
+                Some(course)
+                    if course.publish_state.is_draft() && !self.publish_state.is_draft() =>
+                {
+                    report.push(ENTITY, &self.key, format!("course {course_key} is a draft"))
+                }
+                Some(_) => {}
+            }
+        }
+
+        if self.discount >= Decimal::ONE {
+            report.push(
+                ENTITY,
+                &self.key,
+                format!("discount {} is not below 100%", self.discount),
+            );
+        }
+
+        let price_in_uyu = self.price_in_uyu(courses);
+
+        if price_in_uyu < pricing.min_price_in_uyu {
+            report.push(
+                ENTITY,
+                &self.key,
+                format!(
+                    "price {price_in_uyu} is below the minimum of {}",
+                    pricing.min_price_in_uyu
+                ),
+            );
+        }
+
+        report
+    }
+
+    /// `discount` is a fraction of the summed course prices.
+    pub fn price_in_uyu(&self, courses: &[CourseData]) -> Decimal {
+        let full_price_in_uyu = courses
+            .iter()
+            .filter(|course| self.course_keys.contains(&course.key))
+            .filter_map(|course| course.price_in_uyu)
+            .sum::<Decimal>();
+
+        (full_price_in_uyu * (Decimal::ONE - self.discount)).round_dp(2)
+    }
+
     fn format(&mut self) {
         self.key = self.key.trim().to_string();
         self.name = self.name.trim().to_string();
+        self.slug = if self.slug.is_empty() {
+            slugify(&self.name)
+        } else {
+            slugify(&self.slug)
+        };
         self.description = self.description.trim().to_string();
+        self.alt_text = self
+            .alt_text
+            .as_deref()
+            .map(format_text)
+            .filter(|alt_text| !alt_text.is_empty());
+    }
+
+    pub fn slug(&self) -> &str {
+        &self.slug
+    }
+
+    pub fn check_unique_slugs(bundles: &[Self]) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        let mut keys_by_slug: HashMap<&str, &str> = HashMap::new();
+
+        for bundle in bundles {
+            if let Some(other_key) = keys_by_slug.insert(&bundle.slug, &bundle.key) {
+                report.push(
+                    "bundle",
+                    &bundle.key,
+                    format!("slug {} is also used by bundle {other_key}", bundle.slug),
+                );
+            }
+        }
+
+        report
     }
 
     pub fn full_image_path(&self) -> String {
         full_image_path(BUNDLE_IMAGES_DIR_NAME, &self.image_file_name)
     }
 }
+
+/// Discounts are fractions of the price, and a bundle can't be free.
+fn is_valid_discount(discount: Decimal) -> bool {
+    discount > Decimal::ZERO && discount < Decimal::ONE
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+
+    #[test]
+    fn test_check_against() {
+        let mut courses: Vec<CourseData> = fake::vec![CourseData; 2];
+
+        for course in courses.iter_mut() {
+            course.price_in_uyu = Some(Decimal::from(1000));
+        }
+
+        let mut data: BundleData = Faker.fake();
+        data.course_keys = courses.iter().map(|course| course.key.clone()).collect();
+
+        let pricing = BundlePricing::default();
+
+        assert!(data.check_against(&courses, &pricing).is_ok());
+        assert_eq!(data.price_in_uyu(&courses), Decimal::from(1600));
+
+        data.course_keys.push("unknown".into());
+        data.discount = Decimal::ONE;
+
+        assert_eq!(data.check_against(&courses, &pricing).issues.len(), 3);
+
+        data.discount = Decimal::new(2, 1);
+        data.course_keys.pop();
+
+        let pricing = BundlePricing {
+            min_price_in_uyu: Decimal::from(2000),
+        };
+
+        assert_eq!(data.check_against(&courses, &pricing).issues.len(), 1);
+    }
+
+    #[test]
+    fn test_slug() {
+        let mut bundles: Vec<BundleData> = fake::vec![BundleData; 2];
+
+        for bundle in bundles.iter_mut() {
+            bundle.name = "Pack Cirugía".into();
+            bundle.available_from = None;
+            bundle.available_until = None;
+            bundle.process().unwrap();
+        }
+
+        assert_eq!(bundles[0].slug(), "pack-cirugia");
+        assert_eq!(BundleData::check_unique_slugs(&bundles).issues.len(), 1);
+    }
+
+    #[test]
+    fn test_window_hash() {
+        let mut data: BundleData = Faker.fake();
+        let at = Utc::now();
+
+        data.available_from = None;
+        data.available_until = None;
+        data.publish_at = None;
+        data.unpublish_at = None;
+
+        let unbounded = data.compute_hash();
+
+        data.publish_at = Some(at);
+
+        let publish = data.compute_hash();
+
+        data.publish_at = None;
+        data.unpublish_at = Some(at);
+
+        let unpublish = data.compute_hash();
+
+        assert_ne!(unbounded, publish);
+        assert_ne!(unbounded, unpublish);
+        assert_ne!(publish, unpublish);
+    }
+
+    #[test]
+    fn test_effective_discount() {
+        let mut data: BundleData = Faker.fake();
+        let now = Utc::now();
+        let range = DateRange::new(now, now + chrono::Duration::days(7)).unwrap();
+        data.discount_schedule = vec![(range, Decimal::new(5, 1))];
+        data.available_from = None;
+        data.available_until = Some(range.end);
+
+        data.process().unwrap();
+
+        assert_eq!(data.effective_discount(now), Decimal::new(5, 1));
+        assert_eq!(data.effective_discount(range.end), data.discount);
+
+        assert!(data.is_available_at(now));
+        assert!(!data.is_available_at(range.end));
+
+        data.discount_schedule.push((range, Decimal::new(3, 1)));
+
+        assert!(data.process().is_err());
+
+        data.discount_schedule.pop();
+        data.discount_schedule[0].1 = Decimal::ONE;
+
+        assert!(data.process().is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Cache entries affected by a sync, so the engine can precompute them before
+//! invalidating the old ones instead of letting every client miss at once.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::SyncData;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CacheEntry {
+    Catalog,
+    Course { course_key: String },
+    QuestionPool { course_key: String },
+    Bundle { bundle_key: String },
+}
+
+impl CacheEntry {
+    pub fn key(&self) -> String {
+        match self {
+            Self::Catalog => "catalog".into(),
+            Self::Course { course_key } => format!("course:{course_key}"),
+            Self::QuestionPool { course_key } => format!("question_pool:{course_key}"),
+            Self::Bundle { bundle_key } => format!("bundle:{bundle_key}"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+pub struct CacheWarmPlan {
+    /// Entries to precompute once the sync is applied.
+    pub warm: BTreeSet<CacheEntry>,
+    /// Entries of deleted courses and bundles, to drop without warming.
+    pub evict: BTreeSet<CacheEntry>,
+}
+
+impl CacheWarmPlan {
+    pub fn is_empty(&self) -> bool {
+        self.warm.is_empty() && self.evict.is_empty()
+    }
+
+    /// Keys to pass to `cache::pipeline_delete`.
+    pub fn evict_keys(&self) -> Vec<String> {
+        self.evict.iter().map(CacheEntry::key).collect()
+    }
+}
+
+impl SyncData {
+    /// Entries to warm and evict after applying. Question deletions only carry
+    /// ids, so pools that just lost questions are left to regular invalidation.
+    pub fn cache_warm_plan(&self) -> CacheWarmPlan {
+        let mut plan = CacheWarmPlan::default();
+
+        let changed_pool_course_keys = self
+            .questions
+            .for_sync
+            .iter()
+            .map(|question| &question.course_key)
+            .chain(
+                self.question_topics
+                    .for_sync
+                    .iter()
+                    .map(|question_topic| &question_topic.course_key),
+            )
+            .chain(
+                self.question_sources
+                    .for_sync
+                    .iter()
+                    .map(|question_source| &question_source.course_key),
+            )
+            .filter(|course_key| !self.courses.for_deletion.contains(*course_key));
+
+        plan.warm.extend(
+            changed_pool_course_keys.map(|course_key| CacheEntry::QuestionPool {
+                course_key: course_key.clone(),
+            }),
+        );
+
+        for course in &self.courses.for_sync {
+            plan.warm.insert(CacheEntry::Course {
+                course_key: course.key.clone(),
+            });
+            plan.warm.insert(CacheEntry::QuestionPool {
+                course_key: course.key.clone(),
+            });
+        }
+
+        for bundle in &self.bundles.for_sync {
+            plan.warm.insert(CacheEntry::Bundle {
+                bundle_key: bundle.key.clone(),
+            });
+        }
+
+        for course_key in &self.courses.for_deletion {
+            plan.evict.insert(CacheEntry::Course {
+                course_key: course_key.clone(),
+            });
+            plan.evict.insert(CacheEntry::QuestionPool {
+                course_key: course_key.clone(),
+            });
+        }
+
+        for bundle_key in &self.bundles.for_deletion {
+            plan.evict.insert(CacheEntry::Bundle {
+                bundle_key: bundle_key.clone(),
+            });
+        }
+
+        if !self.courses.is_empty() || !self.bundles.is_empty() {
+            plan.warm.insert(CacheEntry::Catalog);
+        }
+
+        plan
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::sync::{BundleData, QuestionData};
+
+    #[test]
+    fn test_cache_warm_plan() {
+        let mut question: QuestionData = Faker.fake();
+        question.course_key = "MI".into();
+        let bundle: BundleData = Faker.fake();
+
+        let mut sync_data = SyncData::default();
+        sync_data.add_for_sync(question);
+        sync_data.add_for_sync(bundle.clone());
+        sync_data.courses.for_deletion.insert("OLD".into());
+
+        let plan = sync_data.cache_warm_plan();
+
+        assert_eq!(
+            plan.warm,
+            BTreeSet::from([
+                CacheEntry::Catalog,
+                CacheEntry::QuestionPool {
+                    course_key: "MI".into()
+                },
+                CacheEntry::Bundle {
+                    bundle_key: bundle.key
+                },
+            ])
+        );
+        assert_eq!(plan.evict_keys(), ["course:OLD", "question_pool:OLD"]);
+        assert!(SyncData::default().cache_warm_plan().is_empty());
+    }
+}
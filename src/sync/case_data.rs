@@ -0,0 +1,194 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+#[cfg(any(test, feature = "testing"))]
+use fake::{Dummy, Faker};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::content_entity::ContentEntity;
+use super::helpers::{format_text, full_image_path};
+use super::question_data::QuestionData;
+use super::validation_report::ValidationError;
+use crate::traits::{Hashable, Syncable};
+
+/// Clinical case: a vignette shared by several questions, asked in order.
+#[non_exhaustive]
+#[derive(
+    medici_macros::Hashable,
+    medici_macros::SyncEntity,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[medici(key = "id")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
+pub struct CaseData {
+    pub id: Uuid,
+
+    pub course_key: String,
+    /// Name of the topic of its questions.
+    pub topic: String,
+    pub text: String,
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = Vec<String>))]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub image_file_names: Vec<PathBuf>,
+    #[cfg_attr(any(test, feature = "testing"), dummy(faker = "(Faker, 1..=4)"))]
+    pub question_ids: Vec<Uuid>,
+
+    pub hash: String,
+}
+
+impl CaseData {
+    pub fn new(
+        id: Uuid,
+        course_key: String,
+        topic: String,
+        text: String,
+        image_file_names: Vec<PathBuf>,
+        question_ids: Vec<Uuid>,
+    ) -> Result<Self> {
+        let mut data = Self {
+            id,
+            course_key,
+            topic,
+            text,
+            image_file_names,
+            question_ids,
+            hash: Default::default(),
+        };
+
+        data.process()?;
+
+        Ok(data)
+    }
+
+    /// Case of `questions`, in that order, which must share their course and topic.
+    pub fn from_questions(
+        id: Uuid,
+        text: String,
+        image_file_names: Vec<PathBuf>,
+        questions: &[&QuestionData],
+    ) -> Result<Self> {
+        let Some(first) = questions.first() else {
+            bail!("case with ID {id} has no questions");
+        };
+
+        let data = Self::new(
+            id,
+            first.course_key.clone(),
+            first.topic.name.clone(),
+            text,
+            image_file_names,
+            questions.iter().map(|question| question.id).collect(),
+        )?;
+        data.check_questions(questions.iter().copied())?;
+
+        Ok(data)
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        self.format();
+        self.check().map_err(ValidationError::wrap)?;
+
+        self.refresh_hash();
+
+        Ok(())
+    }
+
+    fn format(&mut self) {
+        self.topic = self.topic.trim().to_string();
+        self.text = format_text(&self.text);
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.text.is_empty() || self.topic.is_empty() {
+            bail!("invalid case with ID {}", self.id);
+        }
+
+        if self.question_ids.is_empty() {
+            bail!("case with ID {} has no questions", self.id);
+        }
+
+        if self.question_ids.iter().collect::<HashSet<_>>().len() != self.question_ids.len() {
+            bail!("case with ID {} has duplicate questions", self.id);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every question of the case is among `questions` and shares
+    /// the case's course and topic.
+    pub fn check_questions<'a>(
+        &self,
+        questions: impl IntoIterator<Item = &'a QuestionData>,
+    ) -> Result<()> {
+        let questions = questions
+            .into_iter()
+            .map(|question| (question.id, question))
+            .collect::<HashMap<_, _>>();
+
+        for id in &self.question_ids {
+            let Some(question) = questions.get(id) else {
+                bail!("case with ID {} links missing question {id}", self.id);
+            };
+
+            if question.course_key != self.course_key || question.topic.name != self.topic {
+                bail!(
+                    "question with ID {id} isn't in the course and topic of case {}",
+                    self.id
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn full_image_paths(&self) -> Vec<String> {
+        self.image_file_names
+            .iter()
+            .map(|image_file_name| full_image_path(&self.course_key, image_file_name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::QuestionTopicData;
+
+    #[test]
+    fn test_from_questions() {
+        let mut questions = fake::vec![QuestionData; 3];
+
+        for question in &mut questions {
+            question.course_key = "mir".into();
+            question.topic = QuestionTopicData::new("mir".into(), "Cardiología".into()).unwrap();
+        }
+
+        let case = CaseData::from_questions(
+            Uuid::new_v4(),
+            " Varón de  65 años con dolor torácico. ".into(),
+            vec!["ecg.png".into()],
+            &questions.iter().rev().collect::<Vec<_>>(),
+        )
+        .unwrap();
+
+        assert_eq!(case.text, "Varón de 65 años con dolor torácico.");
+        assert_eq!(case.question_ids[0], questions[2].id);
+        assert_eq!(case.full_image_paths(), vec!["mir/ecg.png".to_string()]);
+
+        questions[1].topic = QuestionTopicData::new("mir".into(), "Neumología".into()).unwrap();
+
+        assert!(case.check_questions(&questions).is_err());
+        assert!(case.check_questions(&questions[..1]).is_err());
+    }
+}
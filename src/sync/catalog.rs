@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::bundle_data::BundleData;
+use super::course_data::CourseData;
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Catalog {
+    pub courses: Vec<CourseData>,
+    pub bundles: Vec<BundleData>,
+}
+
+impl Catalog {
+    pub fn new(courses: Vec<CourseData>, bundles: Vec<BundleData>) -> Self {
+        Self { courses, bundles }
+    }
+
+    pub fn course(&self, key: &str) -> Option<&CourseData> {
+        self.courses.iter().find(|course| course.key == key)
+    }
+
+    pub fn bundle(&self, key: &str) -> Option<&BundleData> {
+        self.bundles.iter().find(|bundle| bundle.key == key)
+    }
+
+    /// The catalog as listed at `at`: visible courses, and visible bundles whose
+    /// courses are all visible.
+    pub fn visible_at(&self, at: DateTime<Utc>) -> Self {
+        let courses = self
+            .courses
+            .iter()
+            .filter(|course| course.is_visible_at(at))
+            .cloned()
+            .collect::<Vec<_>>();
+        let bundles =
+            self.bundles
+                .iter()
+                .filter(|bundle| {
+                    bundle.is_visible_at(at)
+                        && bundle.course_keys.iter().all(|course_key| {
+                            courses.iter().any(|course| &course.key == course_key)
+                        })
+                })
+                .cloned()
+                .collect();
+
+        Self { courses, bundles }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::sync::PublishState;
+
+    #[test]
+    fn test_visible_at() {
+        let now = Utc::now();
+        let mut courses = fake::vec![CourseData; 3];
+        courses[1].publish_at = Some(now + Duration::days(1));
+        courses[2].publish_state = PublishState::Draft;
+
+        let mut bundles = fake::vec![BundleData; 2];
+        bundles[0].course_keys = vec![courses[0].key.clone()];
+        bundles[1].course_keys = vec![courses[0].key.clone(), courses[1].key.clone()];
+
+        let catalog = Catalog::new(courses, bundles);
+        let visible = catalog.visible_at(now);
+
+        assert_eq!(visible.courses.len(), 1);
+        assert_eq!(visible.bundles.len(), 1);
+        assert_eq!(catalog.visible_at(now + Duration::days(1)).bundles.len(), 2);
+    }
+}
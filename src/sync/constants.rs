@@ -1,2 +1,2 @@
-pub const ICON_IMAGES_DIR_NAME: &'static str = "icons";
-pub const BUNDLE_IMAGES_DIR_NAME: &'static str = "bundles";
+pub const ICON_IMAGES_DIR_NAME: &str = "icons";
+pub const BUNDLE_IMAGES_DIR_NAME: &str = "bundles";
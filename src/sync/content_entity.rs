@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+
+use super::SyncData;
+use crate::traits::Syncable;
+
+/// Every synced entity type, declared once and expanded by `$callback` into
+/// the code that handles all of them: each entry is the `ContentEntity`
+/// variant, its data, its field in `SyncData` and `SyncMetadata`, and the
+/// metadata index. Entries are in the order their rows can be inserted, which
+/// deletions reverse. Attributes apply to both fields, e.g. `#[serde(default)]`
+/// for entities added after payloads were first stored.
+macro_rules! with_content_entities {
+    ($callback:ident) => {
+        $callback! {
+            Course($crate::sync::CourseData) => courses: ::std::collections::HashMap<String, String>,
+            QuestionTopic($crate::sync::QuestionTopicData) => question_topics: ::std::collections::HashSet<String>,
+            QuestionSource($crate::sync::QuestionSourceData) => question_sources: ::std::collections::HashSet<String>,
+            Question($crate::sync::QuestionData) => questions: ::std::collections::HashMap<::uuid::Uuid, String>,
+            QuestionOption($crate::sync::QuestionOptionData) => question_options: ::std::collections::HashMap<::uuid::Uuid, String>,
+            #[serde(default)]
+            Flashcard($crate::sync::FlashcardData) => flashcards: ::std::collections::HashMap<::uuid::Uuid, String>,
+            #[serde(default)]
+            Case($crate::sync::CaseData) => cases: ::std::collections::HashMap<::uuid::Uuid, String>,
+            Bundle($crate::sync::BundleData) => bundles: ::std::collections::HashMap<String, String>,
+            Icon($crate::sync::IconData) => icons: ::std::collections::HashMap<String, String>,
+            #[serde(default)]
+            Achievement($crate::sync::AchievementData) => achievements: ::std::collections::HashMap<String, String>,
+            #[serde(default)]
+            LearningPath($crate::sync::LearningPathData) => learning_paths: ::std::collections::HashMap<String, String>,
+            #[serde(default)]
+            GlossaryTerm($crate::sync::GlossaryTermData) => glossary_terms: ::std::collections::HashMap<String, String>,
+        }
+    };
+}
+
+pub(crate) use with_content_entities;
+
+macro_rules! content_entities {
+    ($($(#[$attr:meta])* $variant:ident($data:ty) => $field:ident: $index:ty,)*) => {
+        #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+        #[serde(tag = "type", content = "data", rename_all = "snake_case")]
+        pub enum ContentEntity {
+            $($variant($data),)*
+        }
+
+        #[derive(
+            strum::Display,
+            Serialize,
+            Deserialize,
+            PartialEq,
+            Eq,
+            Hash,
+            PartialOrd,
+            Ord,
+            Clone,
+            Copy,
+            Debug,
+        )]
+        #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+        #[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+        #[serde(rename_all = "snake_case")]
+        #[strum(serialize_all = "snake_case")]
+        pub enum ContentEntityType {
+            $($variant,)*
+        }
+
+        impl ContentEntityType {
+            /// In the order their rows can be inserted.
+            pub const ALL: &'static [Self] = &[$(Self::$variant,)*];
+        }
+
+        impl ContentEntity {
+            pub fn entity_type(&self) -> ContentEntityType {
+                match self {
+                    $(Self::$variant(_) => ContentEntityType::$variant,)*
+                }
+            }
+
+            pub fn key_string(&self) -> String {
+                match self {
+                    $(Self::$variant(data) => data.sync_key().to_string(),)*
+                }
+            }
+
+            pub fn hash(&self) -> String {
+                match self {
+                    $(Self::$variant(data) => data.content_hash().into_owned(),)*
+                }
+            }
+        }
+
+        impl SyncData {
+            pub fn add_for_sync(&mut self, entity: impl Into<ContentEntity>) {
+                match entity.into() {
+                    $(ContentEntity::$variant(data) => {
+                        self.$field.for_sync.insert(data);
+                    })*
+                }
+            }
+
+            pub fn add_for_deletion(&mut self, entity: impl Into<ContentEntity>) {
+                match entity.into() {
+                    $(ContentEntity::$variant(data) => {
+                        self.$field.for_deletion.insert(data.sync_key());
+                    })*
+                }
+            }
+
+            pub fn entities_for_sync(&self) -> Vec<ContentEntity> {
+                let mut entities = vec![];
+
+                $(entities.extend(self.$field.for_sync.iter().cloned().map(ContentEntity::from));)*
+
+                entities
+            }
+        }
+    };
+}
+
+with_content_entities!(content_entities);
+
+impl std::fmt::Display for ContentEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.entity_type(), self.key_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::sync::{QuestionData, QuestionTopicData};
+
+    #[test]
+    fn test_sync_data_buckets() {
+        let mut question: QuestionData = Faker.fake();
+        question.prepare_for_test().unwrap();
+
+        let topic = question.topic.clone();
+        let entity = ContentEntity::from(question.clone());
+
+        assert_eq!(entity.entity_type(), ContentEntityType::Question);
+        assert_eq!(entity.key_string(), question.id.to_string());
+        assert_eq!(entity.hash(), question.hash);
+
+        let mut sync_data = SyncData::default();
+        sync_data.add_for_sync(entity);
+        sync_data.add_for_deletion(topic.clone());
+
+        assert!(sync_data.questions.for_sync.contains(&question));
+        assert!(sync_data
+            .question_topics
+            .for_deletion
+            .contains(&topic.key()));
+        assert_eq!(sync_data.entities_for_sync().len(), 1);
+    }
+
+    #[test]
+    fn test_serde() {
+        let entity = ContentEntity::from(QuestionTopicData::new("c".into(), "t".into()).unwrap());
+        let json = serde_json::to_value(&entity).unwrap();
+
+        assert_eq!(json["type"], "question_topic");
+        assert_eq!(
+            serde_json::from_value::<ContentEntity>(json).unwrap(),
+            entity
+        );
+    }
+}
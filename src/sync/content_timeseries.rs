@@ -0,0 +1,165 @@
+//! Daily content statistics per course, stored so content growth can be charted
+//! without reconstructing it from the history of the content repository.
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::course_data::CourseData;
+use super::course_stats::CourseStats;
+#[cfg(feature = "db")]
+use crate::traits::{Insertable, Table};
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(
+    feature = "db",
+    derive(medici_macros::Table, sqlx::FromRow),
+    medici(table_name = "content_timeseries")
+)]
+pub struct ContentTimeseriesPoint {
+    #[cfg_attr(feature = "db", medici(primary_key))]
+    pub id: Uuid,
+    pub date: NaiveDate,
+    pub course_key: String,
+    pub question_count: i64,
+    pub with_explanation_count: i64,
+    /// Percentage of questions with an explanation.
+    pub explanation_coverage: Decimal,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(
+    feature = "db",
+    derive(medici_macros::Insertable),
+    medici(table_struct = "ContentTimeseriesPoint")
+)]
+pub struct ContentTimeseriesPointInsert {
+    pub id: Uuid,
+    pub date: NaiveDate,
+    pub course_key: String,
+    pub question_count: i64,
+    pub with_explanation_count: i64,
+    pub explanation_coverage: Decimal,
+}
+
+impl ContentTimeseriesPoint {
+    pub fn new(date: NaiveDate, course: &CourseData) -> Self {
+        let stats = CourseStats::new(course);
+
+        Self {
+            id: Uuid::new_v4(),
+            date,
+            course_key: course.key.clone(),
+            question_count: stats.question_count as i64,
+            with_explanation_count: stats.with_explanation_count as i64,
+            explanation_coverage: stats.with_explanation_percentage,
+        }
+    }
+
+    pub fn to_insert(&self) -> ContentTimeseriesPointInsert {
+        ContentTimeseriesPointInsert {
+            id: self.id,
+            date: self.date,
+            course_key: self.course_key.clone(),
+            question_count: self.question_count,
+            with_explanation_count: self.with_explanation_count,
+            explanation_coverage: self.explanation_coverage,
+        }
+    }
+}
+
+/// Columns identifying a point, unique in the table.
+#[cfg(feature = "db")]
+const CONFLICT_COLUMNS: [&str; 2] = ["date", "course_key"];
+
+/// Inserts `points`, replacing those already recorded for the same date and
+/// course, so recording a day twice keeps the latest counts. `None` if there
+/// are no points, as an empty `VALUES` list isn't valid SQL.
+#[cfg(feature = "db")]
+pub fn append_points_query(
+    points: Vec<ContentTimeseriesPointInsert>,
+) -> Option<sqlx::QueryBuilder<'static, sqlx::Postgres>> {
+    if points.is_empty() {
+        return None;
+    }
+
+    let mut query = sqlx::QueryBuilder::new(format!(
+        "INSERT INTO {} ({}) ",
+        ContentTimeseriesPoint::TABLE_NAME,
+        ContentTimeseriesPointInsert::COLUMNS.join(", ")
+    ));
+
+    query.push_values(points, |mut separated, point| point.bind(&mut separated));
+
+    let updates = ContentTimeseriesPointInsert::COLUMNS
+        .iter()
+        .filter(|column| {
+            **column != ContentTimeseriesPoint::PRIMARY_KEY_COLUMN
+                && !CONFLICT_COLUMNS.contains(column)
+        })
+        .map(|column| format!("{column} = EXCLUDED.{column}"))
+        .collect::<Vec<_>>();
+
+    query.push(format!(
+        " ON CONFLICT ({}) DO UPDATE SET {}",
+        CONFLICT_COLUMNS.join(", "),
+        updates.join(", ")
+    ));
+
+    Some(query)
+}
+
+/// Points of the course with `course_key` from `since` on, oldest first.
+#[cfg(feature = "db")]
+pub fn read_points_query(
+    course_key: String,
+    since: NaiveDate,
+) -> sqlx::QueryBuilder<'static, sqlx::Postgres> {
+    let mut query = sqlx::QueryBuilder::new(format!(
+        "SELECT * FROM {} WHERE course_key = ",
+        ContentTimeseriesPoint::TABLE_NAME
+    ));
+
+    query.push_bind(course_key);
+    query.push(" AND date >= ");
+    query.push_bind(since);
+    query.push(" ORDER BY date");
+
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::sync::QuestionData;
+
+    #[test]
+    fn test_new() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![QuestionData; 4];
+
+        for (index, question) in course.questions.iter_mut().enumerate() {
+            question.explanation = (index == 0).then(|| Faker.fake());
+        }
+
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let point = ContentTimeseriesPoint::new(date, &course);
+
+        assert_eq!(point.question_count, 4);
+        assert_eq!(point.with_explanation_count, 1);
+        assert_eq!(point.explanation_coverage, Decimal::new(25, 0));
+
+        #[cfg(feature = "db")]
+        assert!(append_points_query(vec![]).is_none());
+        #[cfg(feature = "db")]
+        assert!(append_points_query(vec![point.to_insert()])
+            .unwrap()
+            .sql()
+            .ends_with(
+                "ON CONFLICT (date, course_key) DO UPDATE SET question_count = EXCLUDED.question_count, with_explanation_count = EXCLUDED.with_explanation_count, explanation_coverage = EXCLUDED.explanation_coverage"
+            ));
+    }
+}
@@ -1,36 +1,118 @@
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
 use anyhow::{bail, Result};
-#[cfg(test)]
-use fake::{Dummy, Fake, Faker};
+use chrono::{DateTime, Utc};
+#[cfg(any(test, feature = "testing"))]
+use fake::Dummy;
 use rust_decimal::prelude::*;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use super::helpers::full_image_path;
-use super::question_data::QuestionData;
+use super::backfill_plan::BackfillPlan;
+use super::case_data::CaseData;
+use super::content_entity::ContentEntity;
+use super::course_relation::CourseRelation;
+use super::course_stats::CourseStats;
+use super::coverage_report::CoverageReport;
+use super::helpers::{format_text, full_image_path, is_in_window, is_valid_window};
+use super::language_tag::LanguageTag;
+use super::license_data::LicenseData;
+use super::preview_subset::PreviewConfig;
+use super::publish_state::PublishState;
+use super::question_data::{OptionCountRange, QuestionData};
 use super::question_source_data::QuestionSourceData;
 use super::question_topic_data::QuestionTopicData;
-use crate::traits::Hashable;
+use super::unit_formatting::UnitRules;
+use super::validation_report::{ValidationError, ValidationReport};
+use crate::slug::{is_slug, slugify};
+use crate::traits::{Hashable, Syncable};
 
 #[non_exhaustive]
-#[derive(medici_macros::Hashable, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Debug)]
-#[cfg_attr(test, derive(Dummy))]
+#[derive(
+    medici_macros::Hashable,
+    medici_macros::SyncEntity,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[medici(key = "key")]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
 pub struct CourseData {
     pub key: String,
 
     pub name: String,
+    #[medici(builder_default)]
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub slug: String,
     pub short_name: String,
     pub description: Option<String>,
-    #[cfg_attr(test, dummy(default))]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
     pub price_in_uyu: Option<Decimal>,
+    #[medici(unordered_hash)]
     pub tags: Vec<String>,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub image_file_name: PathBuf,
+    #[serde(default)]
+    pub alt_text: Option<String>,
     pub year: Option<u16>,
     pub order: Option<u16>,
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub option_count_range: Option<OptionCountRange>,
+    /// Applied to its questions when they're processed, which hashes their effect.
+    #[serde(default)]
+    #[medici(skip_hash)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub unit_rules: Option<UnitRules>,
+    #[medici(builder_default)]
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub locale: LanguageTag,
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub license: Option<LicenseData>,
+    #[medici(builder_default)]
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub publish_state: PublishState,
+    #[medici(explicit_none)]
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub publish_at: Option<DateTime<Utc>>,
+    #[medici(explicit_none)]
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub unpublish_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub relations: Vec<CourseRelation>,
+    /// Selects `preview_question_ids` when the course is processed. Its
+    /// choice is hashed through them.
+    #[medici(skip_hash)]
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub preview: Option<PreviewConfig>,
+    /// Questions offered as a free preview, by ID.
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub preview_question_ids: Vec<Uuid>,
     #[serde(skip)]
+    #[medici(unordered_hash)]
     pub questions: Vec<QuestionData>,
+    /// Clinical cases of its questions, which are synced on their own.
+    #[serde(skip)]
+    #[medici(skip_hash)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub cases: Vec<CaseData>,
     #[serde(skip)]
     pub valid_topics: Vec<String>,
 
@@ -38,30 +120,57 @@ pub struct CourseData {
 }
 
 impl CourseData {
+    // One argument per field; `Self::builder()` names them instead.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         key: String,
         name: String,
+        slug: Option<String>,
         short_name: String,
         description: Option<String>,
         price_in_uyu: Option<Decimal>,
         tags: Vec<String>,
         image_file_name: PathBuf,
+        alt_text: Option<String>,
         year: Option<u16>,
         order: Option<u16>,
+        option_count_range: Option<OptionCountRange>,
+        unit_rules: Option<UnitRules>,
+        locale: LanguageTag,
+        license: Option<LicenseData>,
+        publish_state: PublishState,
+        publish_at: Option<DateTime<Utc>>,
+        unpublish_at: Option<DateTime<Utc>>,
+        relations: Vec<CourseRelation>,
+        preview: Option<PreviewConfig>,
         questions: Vec<QuestionData>,
+        cases: Vec<CaseData>,
         topics: Vec<String>,
     ) -> Result<Self> {
         let mut data = Self {
             key,
             name,
+            slug: slug.unwrap_or_default(),
             short_name,
             description,
             price_in_uyu,
             tags,
             image_file_name,
+            alt_text,
             year,
             order,
+            option_count_range,
+            unit_rules,
+            locale,
+            license,
+            publish_state,
+            publish_at,
+            unpublish_at,
+            relations,
+            preview,
+            preview_question_ids: vec![],
             questions,
+            cases,
             valid_topics: topics,
             hash: Default::default(),
         };
@@ -73,10 +182,14 @@ impl CourseData {
 
     pub fn process(&mut self) -> Result<()> {
         self.remove_blank_questions();
+        self.apply_option_count_range()?;
+        self.apply_unit_rules()?;
         self.format();
+        self.process_license()?;
         self.sort();
         self.deduplicate();
-        self.check()?;
+        self.select_preview();
+        self.check().map_err(ValidationError::wrap)?;
 
         self.refresh_hash();
 
@@ -107,14 +220,65 @@ impl CourseData {
                 },
                 ordering => ordering,
             });
+        self.relations.sort();
     }
 
     fn remove_blank_questions(&mut self) {
         self.questions.retain(|question| !question.is_blank());
     }
 
+    fn apply_option_count_range(&mut self) -> Result<()> {
+        let option_count_range = self.option_count_range.unwrap_or_default();
+
+        for question in &mut self.questions {
+            if question.option_count_range != option_count_range {
+                question.option_count_range = option_count_range;
+                question.process()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_unit_rules(&mut self) -> Result<()> {
+        for question in &mut self.questions {
+            if question.unit_rules != self.unit_rules {
+                question.unit_rules.clone_from(&self.unit_rules);
+                question.process()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn process_license(&mut self) -> Result<()> {
+        if let Some(license) = &mut self.license {
+            license.process()?;
+        }
+
+        Ok(())
+    }
+
+    /// Questions aren't serialized, so a course deserialized without them
+    /// keeps the preview it was synced with.
+    fn select_preview(&mut self) {
+        if self.questions.is_empty() {
+            return;
+        }
+
+        self.preview_question_ids = match &self.preview {
+            Some(config) => self
+                .preview_subset(config)
+                .into_iter()
+                .map(|question| question.id)
+                .collect(),
+            None => vec![],
+        };
+    }
+
     fn deduplicate(&mut self) {
         self.questions.dedup_by(|a, b| a.eq_data(b));
+        self.relations.dedup();
     }
 
     fn check(&self) -> Result<()> {
@@ -122,23 +286,132 @@ impl CourseData {
             bail!("invalid course with key {}", self.key);
         }
 
+        if !is_slug(&self.slug) {
+            bail!("invalid slug {} in course with key {}", self.slug, self.key);
+        }
+
         if let Some(price_in_uyu) = self.price_in_uyu {
             if price_in_uyu <= Decimal::ZERO {
                 bail!("invalid course price");
             }
         }
 
+        if !is_valid_window(self.publish_at, self.unpublish_at) {
+            bail!("invalid publish window in course with key {}", self.key);
+        }
+
+        if self
+            .relations
+            .iter()
+            .any(|relation| relation.course_key == self.key)
+        {
+            bail!("course with key {} is related to itself", self.key);
+        }
+
+        if let Some(question) = self
+            .questions
+            .iter()
+            .find(|question| question.translations.contains_key(&self.locale))
+        {
+            bail!(
+                "question with ID {} is translated to the course locale {}",
+                question.id,
+                self.locale
+            );
+        }
+
+        if !self.questions.is_empty() {
+            if let Some(id) = self
+                .preview_question_ids
+                .iter()
+                .find(|id| !self.questions.iter().any(|question| question.id == **id))
+            {
+                bail!(
+                    "preview question with ID {id} isn't in course with key {}",
+                    self.key
+                );
+            }
+
+            for case in &self.cases {
+                case.check_questions(&self.questions)?;
+            }
+        }
+
+        if let Some(case) = self.cases.iter().find(|case| case.course_key != self.key) {
+            bail!(
+                "case with ID {} isn't in course with key {}",
+                case.id,
+                self.key
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Published and within its publish window.
+    pub fn is_visible_at(&self, at: DateTime<Utc>) -> bool {
+        self.publish_state == PublishState::Published
+            && is_in_window(self.publish_at, self.unpublish_at, at)
+    }
+
+    /// Case that `question_id` belongs to, if any.
+    pub fn case_of(&self, question_id: Uuid) -> Option<&CaseData> {
+        self.cases
+            .iter()
+            .find(|case| case.question_ids.contains(&question_id))
+    }
+
+    pub fn check_strict(&self) -> Result<()> {
+        if self.alt_text.is_none() {
+            bail!("course with key {} has an image without alt text", self.key);
+        }
+
+        for question in &self.questions {
+            question.check_strict()?;
+        }
+
         Ok(())
     }
 
     fn format(&mut self) {
         self.name = self.name.trim().into();
+        self.slug = if self.slug.is_empty() {
+            slugify(&self.name)
+        } else {
+            slugify(&self.slug)
+        };
         self.short_name = self.short_name.trim().into();
         self.description = self
             .description
             .as_ref()
             .map(|description| description.trim().into());
         self.tags = self.tags.iter().map(|tag| tag.trim().into()).collect();
+        self.alt_text = self
+            .alt_text
+            .as_deref()
+            .map(format_text)
+            .filter(|alt_text| !alt_text.is_empty());
+    }
+
+    pub fn slug(&self) -> &str {
+        &self.slug
+    }
+
+    pub fn check_unique_slugs(courses: &[Self]) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        let mut keys_by_slug: HashMap<&str, &str> = HashMap::new();
+
+        for course in courses {
+            if let Some(other_key) = keys_by_slug.insert(&course.slug, &course.key) {
+                report.push(
+                    "course",
+                    &course.key,
+                    format!("slug {} is also used by course {other_key}", course.slug),
+                );
+            }
+        }
+
+        report
     }
 
     pub fn full_image_path(&self) -> String {
@@ -157,6 +430,14 @@ impl CourseData {
         )
     }
 
+    pub fn stats(&self) -> CourseStats {
+        CourseStats::new(self)
+    }
+
+    pub fn coverage_report(&self, min_per_topic: usize) -> CoverageReport {
+        CoverageReport::new(self, min_per_topic)
+    }
+
     pub fn questions_with_invalid_topics(&self) -> Vec<&QuestionData> {
         self.questions
             .iter()
@@ -165,10 +446,38 @@ impl CourseData {
             })
             .collect()
     }
+
+    pub fn questions_missing_explanations(&self) -> Vec<&QuestionData> {
+        self.questions
+            .iter()
+            .filter(|question| !question.is_blank() && question.explanation.is_none())
+            .collect()
+    }
+
+    pub fn backfill_plan(&self, max_tokens_per_batch: usize) -> BackfillPlan {
+        BackfillPlan::new(self, max_tokens_per_batch)
+    }
+
+    pub fn preview_subset(&self, config: &PreviewConfig) -> Vec<&QuestionData> {
+        config.select(self)
+    }
+
+    /// Marks the questions of `preview_subset` as free preview.
+    pub fn mark_preview(&mut self, config: &PreviewConfig) {
+        self.preview = Some(*config);
+        self.select_preview();
+        self.refresh_hash();
+    }
+
+    pub fn is_preview_question(&self, question_id: Uuid) -> bool {
+        self.preview_question_ids.contains(&question_id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use fake::{Fake, Faker};
+
     use super::*;
 
     #[test]
@@ -177,4 +486,100 @@ mod tests {
 
         data.process().unwrap();
     }
+
+    #[test]
+    fn test_preview() {
+        let mut data: CourseData = Faker.fake();
+        data.questions = fake::vec![QuestionData; 3];
+
+        for question in &mut data.questions {
+            question.prepare_for_test().unwrap();
+        }
+
+        data.preview = Some(PreviewConfig { per_topic: 1 });
+        data.process().unwrap();
+
+        assert!(!data.preview_question_ids.is_empty());
+
+        data.preview_question_ids.push(Uuid::new_v4());
+
+        assert!(data.check().is_err());
+
+        data.preview = None;
+        data.process().unwrap();
+
+        assert!(data.preview_question_ids.is_empty());
+    }
+
+    #[test]
+    fn test_cases() {
+        let mut data: CourseData = Faker.fake();
+        data.questions = fake::vec![QuestionData; 3];
+
+        for question in &mut data.questions {
+            question.course_key = data.key.clone();
+            question.topic =
+                QuestionTopicData::new(data.key.clone(), "Cardiología".into()).unwrap();
+            question.prepare_for_test().unwrap();
+        }
+
+        let case = CaseData::from_questions(
+            Uuid::new_v4(),
+            "Varón de 65 años con dolor torácico.".into(),
+            vec![],
+            &data.questions[1..].iter().collect::<Vec<_>>(),
+        )
+        .unwrap();
+        data.cases = vec![case.clone()];
+        data.process().unwrap();
+
+        assert_eq!(data.case_of(case.question_ids[1]), Some(&case));
+        assert!(data.case_of(Uuid::new_v4()).is_none());
+
+        let id = case.question_ids[0];
+        data.questions.retain(|question| question.id != id);
+
+        assert!(data.process().is_err());
+    }
+
+    #[test]
+    fn test_unordered_hash() {
+        let mut data: CourseData = Faker.fake();
+        data.tags = vec!["a".into(), "b".into()];
+        data.process().unwrap();
+
+        let hash = data.hash.clone();
+
+        data.tags.reverse();
+        data.refresh_hash();
+
+        assert_eq!(data.hash, hash);
+    }
+
+    #[test]
+    fn test_slug() {
+        let mut courses: Vec<CourseData> = fake::vec![CourseData; 2];
+
+        for course in courses.iter_mut() {
+            course.name = "Medicina Interna".into();
+            course.process().unwrap();
+        }
+
+        assert_eq!(courses[0].slug(), "medicina-interna");
+        assert_eq!(CourseData::check_unique_slugs(&courses).issues.len(), 1);
+    }
+
+    #[test]
+    fn test_check_strict() {
+        let mut data: CourseData = Faker.fake();
+        data.questions.clear();
+        data.alt_text = Some("  ".into());
+        data.process().unwrap();
+
+        assert!(data.check_strict().is_err());
+
+        data.alt_text = Some("Course cover".into());
+
+        data.check_strict().unwrap();
+    }
 }
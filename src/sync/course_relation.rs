@@ -0,0 +1,195 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::{CourseData, ValidationReport};
+use crate::traits::Hashable;
+
+#[derive(
+    strum::Display,
+    strum::EnumString,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    Debug,
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CourseRelationKind {
+    /// The related course should be taken first.
+    Prerequisite,
+    /// The related course is a good one to take after this one.
+    RecommendedNext,
+    Related,
+}
+
+/// Edge from the course holding it to the course with `course_key`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct CourseRelation {
+    pub kind: CourseRelationKind,
+    pub course_key: String,
+}
+
+impl Hashable for CourseRelation {
+    fn to_bytes(&self) -> Vec<u8> {
+        [self.kind.to_string().to_bytes(), self.course_key.to_bytes()].concat()
+    }
+}
+
+impl CourseData {
+    /// Checks that relations point to known courses and that prerequisite and
+    /// recommended-next edges have no cycles.
+    pub fn check_relations(courses: &[Self]) -> ValidationReport {
+        const ENTITY: &str = "course";
+
+        let mut report = ValidationReport::default();
+        let keys = courses
+            .iter()
+            .map(|course| course.key.as_str())
+            .collect::<HashSet<_>>();
+
+        for course in courses {
+            for relation in &course.relations {
+                if !keys.contains(relation.course_key.as_str()) {
+                    report.push(
+                        ENTITY,
+                        &course.key,
+                        format!(
+                            "{} relation to unknown course {}",
+                            relation.kind, relation.course_key
+                        ),
+                    );
+                }
+            }
+        }
+
+        for kind in [
+            CourseRelationKind::Prerequisite,
+            CourseRelationKind::RecommendedNext,
+        ] {
+            for cycle in find_cycles(courses, kind) {
+                report.push(
+                    ENTITY,
+                    &cycle[0],
+                    format!("{kind} relations form a cycle: {}", cycle.join(" -> ")),
+                );
+            }
+        }
+
+        report
+    }
+}
+
+/// Cycles found by a depth-first search, each as the keys along it ending with
+/// the first one again.
+fn find_cycles(courses: &[CourseData], kind: CourseRelationKind) -> Vec<Vec<String>> {
+    let edges = courses
+        .iter()
+        .map(|course| {
+            let targets = course
+                .relations
+                .iter()
+                .filter(|relation| relation.kind == kind)
+                .map(|relation| relation.course_key.as_str())
+                .collect::<Vec<_>>();
+
+            (course.key.as_str(), targets)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut cycles = vec![];
+    let mut finished = HashSet::new();
+
+    for course in courses {
+        let mut path = vec![];
+        visit(&course.key, &edges, &mut path, &mut finished, &mut cycles);
+    }
+
+    cycles
+}
+
+fn visit<'a>(
+    key: &'a str,
+    edges: &HashMap<&'a str, Vec<&'a str>>,
+    path: &mut Vec<&'a str>,
+    finished: &mut HashSet<&'a str>,
+    cycles: &mut Vec<Vec<String>>,
+) {
+    if finished.contains(key) {
+        return;
+    }
+
+    if let Some(start) = path.iter().position(|visited| *visited == key) {
+        let mut cycle = path[start..]
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        cycle.push(key.into());
+        cycles.push(cycle);
+
+        return;
+    }
+
+    path.push(key);
+
+    for target in edges.get(key).into_iter().flatten() {
+        visit(target, edges, path, finished, cycles);
+    }
+
+    path.pop();
+    finished.insert(key);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_relations() {
+        let mut courses = fake::vec![CourseData; 3];
+        let keys = ["ANATOMIA", "FISIOLOGIA", "FISIOPATOLOGIA"];
+
+        for (course, key) in courses.iter_mut().zip(keys) {
+            course.key = key.into();
+        }
+
+        let relation = |kind, course_key: &str| CourseRelation {
+            kind,
+            course_key: course_key.into(),
+        };
+
+        courses[1].relations = vec![relation(CourseRelationKind::Prerequisite, "ANATOMIA")];
+        courses[2].relations = vec![
+            relation(CourseRelationKind::Prerequisite, "FISIOLOGIA"),
+            relation(CourseRelationKind::Related, "ANATOMIA"),
+        ];
+        courses[0].relations = vec![relation(CourseRelationKind::Related, "FISIOPATOLOGIA")];
+
+        assert!(CourseData::check_relations(&courses).is_ok());
+
+        courses[0]
+            .relations
+            .push(relation(CourseRelationKind::Prerequisite, "FISIOPATOLOGIA"));
+        courses[1]
+            .relations
+            .push(relation(CourseRelationKind::RecommendedNext, "BIOQUIMICA"));
+
+        let report = CourseData::check_relations(&courses);
+
+        assert_eq!(report.issues.len(), 2);
+        assert_eq!(
+            report.issues[1].message,
+            "prerequisite relations form a cycle: ANATOMIA -> FISIOPATOLOGIA -> FISIOLOGIA -> ANATOMIA"
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+use chrono::Datelike;
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::course_data::CourseData;
+use super::question_source_data::QuestionSourceType;
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
+pub struct CourseStats {
+    pub question_count: usize,
+    pub by_topic: BTreeMap<String, usize>,
+    pub by_source_type: BTreeMap<QuestionSourceType, usize>,
+    pub by_year: BTreeMap<i32, usize>,
+    pub with_explanation_count: usize,
+    pub with_explanation_percentage: Decimal,
+    pub with_image_count: usize,
+}
+
+impl CourseStats {
+    pub fn new(course: &CourseData) -> Self {
+        let mut stats = Self {
+            question_count: course.questions.len(),
+            ..Default::default()
+        };
+
+        for question in &course.questions {
+            *stats
+                .by_topic
+                .entry(question.topic.name.clone())
+                .or_default() += 1;
+            *stats
+                .by_source_type
+                .entry(question.source.r#type.clone())
+                .or_default() += 1;
+
+            if let Some(date) = question.source.date {
+                *stats.by_year.entry(date.year()).or_default() += 1;
+            }
+
+            if question.explanation.is_some() {
+                stats.with_explanation_count += 1;
+            }
+
+            if question.image_file_name.is_some() {
+                stats.with_image_count += 1;
+            }
+        }
+
+        stats.with_explanation_percentage =
+            percentage(stats.with_explanation_count, stats.question_count);
+
+        stats
+    }
+}
+
+fn percentage(part: usize, total: usize) -> Decimal {
+    if total == 0 {
+        return Decimal::ZERO;
+    }
+
+    (Decimal::from(part) * Decimal::ONE_HUNDRED / Decimal::from(total)).round_dp(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::sync::QuestionData;
+
+    #[test]
+    fn test_new() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![QuestionData; 4];
+
+        for (index, question) in course.questions.iter_mut().enumerate() {
+            question.explanation = None;
+
+            if index == 0 {
+                question.explanation = Some(Faker.fake());
+            }
+
+            question.prepare_for_test().unwrap();
+        }
+
+        let stats = course.stats();
+
+        assert_eq!(stats.question_count, 4);
+        assert_eq!(stats.by_topic.values().sum::<usize>(), 4);
+        assert_eq!(stats.by_source_type.values().sum::<usize>(), 4);
+        assert_eq!(stats.with_explanation_count, 1);
+        assert_eq!(stats.with_explanation_percentage, Decimal::from(25));
+    }
+
+    #[test]
+    fn test_percentage_empty() {
+        assert_eq!(percentage(0, 0), Decimal::ZERO);
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::course_data::CourseData;
+use super::question_topic_data::QuestionTopicData;
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
+pub struct CoverageReport {
+    pub min_per_topic: usize,
+    pub topics_below_threshold: BTreeMap<String, usize>,
+    pub years_below_threshold: BTreeMap<i32, usize>,
+    pub valid_topics_without_questions: Vec<String>,
+}
+
+impl CoverageReport {
+    pub fn new(course: &CourseData, min_per_topic: usize) -> Self {
+        let stats = course.stats();
+
+        let topics_below_threshold = stats
+            .by_topic
+            .into_iter()
+            .filter(|(topic, count)| {
+                !QuestionTopicData::is_default_topic_name(topic) && *count < min_per_topic
+            })
+            .collect();
+
+        let years_below_threshold = stats
+            .by_year
+            .into_iter()
+            .filter(|(_, count)| *count < min_per_topic)
+            .collect();
+
+        let valid_topics_without_questions = course
+            .valid_topics
+            .iter()
+            .filter(|topic| {
+                !course
+                    .questions
+                    .iter()
+                    .any(|question| &question.topic.name == *topic)
+            })
+            .cloned()
+            .collect();
+
+        Self {
+            min_per_topic,
+            topics_below_threshold,
+            years_below_threshold,
+            valid_topics_without_questions,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.topics_below_threshold.is_empty()
+            && self.years_below_threshold.is_empty()
+            && self.valid_topics_without_questions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::sync::QuestionData;
+
+    #[test]
+    fn test_new() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![QuestionData; 3];
+
+        for question in course.questions.iter_mut() {
+            question.topic.name = "Topic 1".into();
+            question.prepare_for_test().unwrap();
+        }
+
+        course.valid_topics = vec!["Topic 1".into(), "Topic 2".into()];
+
+        let report = course.coverage_report(5);
+
+        assert_eq!(report.topics_below_threshold.get("Topic 1"), Some(&3));
+        assert_eq!(report.valid_topics_without_questions, vec!["Topic 2"]);
+        assert!(course.coverage_report(0).topics_below_threshold.is_empty());
+    }
+}
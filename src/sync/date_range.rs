@@ -0,0 +1,51 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::traits::Hashable;
+
+/// Half-open range: `start` is included and `end` is not.
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DateRange {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl DateRange {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Self> {
+        let data = Self { start, end };
+
+        data.check()?;
+
+        Ok(data)
+    }
+
+    pub fn check(&self) -> Result<()> {
+        if self.start >= self.end {
+            bail!("invalid date range {self}");
+        }
+
+        Ok(())
+    }
+
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.start <= at && at < self.end
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+impl std::fmt::Display for DateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start.to_rfc3339(), self.end.to_rfc3339())
+    }
+}
+
+impl Hashable for DateRange {
+    fn to_bytes(&self) -> Vec<u8> {
+        [self.start.to_bytes(), self.end.to_bytes()].concat()
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// Version of the sync payload format. Bump it when appliers can't handle
+/// payloads from older or newer versions.
+pub const SYNC_SCHEMA_VERSION: u32 = 1;
+
+/// Deployment a sync payload is meant for. Matches the `environment` config value.
+#[derive(
+    strum::Display,
+    strum::EnumString,
+    Serialize,
+    Deserialize,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    Debug,
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "db",
+    derive(sqlx::Type),
+    sqlx(type_name = "text", rename_all = "snake_case")
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Environment {
+    #[default]
+    Development,
+    Staging,
+    Production,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SyncCompatibilityError {
+    EnvironmentMismatch {
+        payload: Environment,
+        target: Environment,
+    },
+    SchemaVersionMismatch {
+        payload: u32,
+        supported: u32,
+    },
+}
+
+impl std::fmt::Display for SyncCompatibilityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EnvironmentMismatch { payload, target } => {
+                write!(f, "sync data for {payload} can't be applied to {target}")
+            }
+            Self::SchemaVersionMismatch { payload, supported } => {
+                write!(
+                    f,
+                    "sync data has schema version {payload}, expected {supported}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SyncCompatibilityError {}
@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use super::course_data::CourseData;
+use super::question_data::QuestionData;
+use super::validation_report::ValidationError;
+use crate::stats::ScoringScheme;
+
+/// How many questions of each topic a mock exam has. The same seed always
+/// selects the same questions, so an exam can be printed again.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ExamBlueprint {
+    pub course_key: String,
+    pub title: String,
+    pub topic_counts: BTreeMap<String, usize>,
+    #[serde(default)]
+    pub seed: u64,
+    /// Seconds the whole exam can take.
+    #[serde(default)]
+    pub total_seconds: Option<u32>,
+    /// Seconds for the questions of each topic, for exams timed by section.
+    #[serde(default)]
+    pub section_seconds: BTreeMap<String, u32>,
+    #[serde(default)]
+    pub scoring: ScoringScheme,
+}
+
+impl ExamBlueprint {
+    pub const MAX_TOTAL_SECONDS: u32 = 8 * 60 * 60;
+
+    pub fn question_count(&self) -> usize {
+        self.topic_counts.values().sum()
+    }
+
+    /// Checks the scoring scheme and that the timing fits the exam: every
+    /// section is a topic of the blueprint, leaves each of its questions the
+    /// minimum time limit and fits in the total time along with the other
+    /// sections.
+    pub fn check(&self) -> Result<()> {
+        self.scoring.check()?;
+
+        if self
+            .total_seconds
+            .is_some_and(|seconds| seconds == 0 || seconds > Self::MAX_TOTAL_SECONDS)
+        {
+            bail!(
+                "blueprint {} has a total time outside 1-{} seconds",
+                self.title,
+                Self::MAX_TOTAL_SECONDS
+            );
+        }
+
+        for (topic, &seconds) in &self.section_seconds {
+            let count = self.topic_counts.get(topic).copied().unwrap_or_default();
+
+            if count == 0 {
+                bail!(
+                    "blueprint {} times topic {topic} without questions",
+                    self.title
+                );
+            }
+
+            if (seconds as usize) < count * QuestionData::MIN_TIME_LIMIT_SECONDS as usize {
+                bail!(
+                    "blueprint {} leaves less than {} seconds per question of topic {topic}",
+                    self.title,
+                    QuestionData::MIN_TIME_LIMIT_SECONDS
+                );
+            }
+        }
+
+        let sections_seconds = self
+            .section_seconds
+            .values()
+            .map(|&seconds| seconds as u64)
+            .sum::<u64>();
+
+        if self
+            .total_seconds
+            .is_some_and(|seconds| sections_seconds > seconds as u64)
+        {
+            bail!(
+                "blueprint {} has sections longer than its total time",
+                self.title
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn select<'a>(&self, course: &'a CourseData) -> Result<Vec<&'a QuestionData>> {
+        self.check().map_err(ValidationError::wrap)?;
+
+        if course.key != self.course_key {
+            bail!(
+                "blueprint for course {} used with course {}",
+                self.course_key,
+                course.key
+            );
+        }
+
+        let mut selected = Vec::with_capacity(self.question_count());
+
+        for (topic, &count) in &self.topic_counts {
+            let mut questions = course
+                .questions
+                .iter()
+                .filter(|question| question.topic.name == *topic)
+                .collect::<Vec<_>>();
+
+            if questions.len() < count {
+                bail!(
+                    "topic {topic} has {} question(s), blueprint needs {count}",
+                    questions.len()
+                );
+            }
+
+            questions.sort_by_cached_key(|question| {
+                blake3::hash(&[self.seed.to_le_bytes().as_slice(), question.id.as_bytes()].concat())
+                    .to_hex()
+            });
+
+            selected.extend(questions.into_iter().take(count));
+        }
+
+        self.check_time_limits(&selected)?;
+
+        Ok(selected)
+    }
+
+    /// Checks that the time limits of `selected` fit in their sections and the
+    /// total time, so the exam can't run out before its questions do.
+    fn check_time_limits(&self, selected: &[&QuestionData]) -> Result<()> {
+        let mut topic_seconds = BTreeMap::<&str, u64>::new();
+
+        for question in selected {
+            *topic_seconds
+                .entry(question.topic.name.as_str())
+                .or_default() += question.time_limit_seconds.unwrap_or_default() as u64;
+        }
+
+        for (topic, &seconds) in &topic_seconds {
+            if self
+                .section_seconds
+                .get(*topic)
+                .is_some_and(|&section_seconds| seconds > section_seconds as u64)
+            {
+                bail!(
+                    "time limits of topic {topic} exceed its section in blueprint {}",
+                    self.title
+                );
+            }
+        }
+
+        if self
+            .total_seconds
+            .is_some_and(|seconds| topic_seconds.values().sum::<u64>() > seconds as u64)
+        {
+            bail!(
+                "time limits exceed the total time of blueprint {}",
+                self.title
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    #[test]
+    fn test_select() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![QuestionData; 6];
+
+        for (index, question) in course.questions.iter_mut().enumerate() {
+            question.topic.name = if index % 2 == 0 { "a" } else { "b" }.into();
+        }
+
+        let mut blueprint = ExamBlueprint {
+            course_key: course.key.clone(),
+            title: "Simulacro".into(),
+            topic_counts: BTreeMap::from([("a".into(), 2), ("b".into(), 1)]),
+            seed: 7,
+            total_seconds: None,
+            section_seconds: Default::default(),
+            scoring: Default::default(),
+        };
+
+        let selected = blueprint.select(&course).unwrap();
+
+        assert_eq!(selected.len(), 3);
+        assert_eq!(selected, blueprint.select(&course).unwrap());
+
+        blueprint.topic_counts.insert("b".into(), 4);
+
+        assert!(blueprint.select(&course).is_err());
+    }
+
+    #[test]
+    fn test_timing() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![QuestionData; 4];
+
+        for question in &mut course.questions {
+            question.topic.name = "a".into();
+            question.time_limit_seconds = Some(60);
+        }
+
+        let mut blueprint = ExamBlueprint {
+            course_key: course.key.clone(),
+            title: "Simulacro".into(),
+            topic_counts: BTreeMap::from([("a".into(), 3)]),
+            seed: 7,
+            total_seconds: Some(300),
+            section_seconds: BTreeMap::from([("a".into(), 180)]),
+            scoring: ScoringScheme::Formula,
+        };
+
+        assert_eq!(blueprint.select(&course).unwrap().len(), 3);
+
+        blueprint.section_seconds.insert("a".into(), 120);
+
+        assert!(blueprint.select(&course).is_err());
+
+        blueprint.section_seconds.insert("a".into(), 400);
+
+        assert!(blueprint.check().is_err());
+
+        blueprint.section_seconds = BTreeMap::from([("b".into(), 60)]);
+
+        assert!(blueprint.check().is_err());
+
+        blueprint.section_seconds.clear();
+        blueprint.total_seconds = Some(100);
+
+        assert!(blueprint.select(&course).is_err());
+
+        blueprint.total_seconds = None;
+        blueprint.scoring = ScoringScheme::MinusFraction(Decimal::TWO);
+
+        assert!(blueprint.check().is_err());
+    }
+}
@@ -1,14 +1,27 @@
 use anyhow::{bail, Result};
 use chrono::{DateTime, Utc};
-#[cfg(test)]
-use fake::{Dummy, Fake, Faker};
+#[cfg(any(test, feature = "testing"))]
+use fake::Dummy;
 use serde::{Deserialize, Serialize};
 
+use super::unit_formatting::{UnitFormatter, DEFAULT_UNIT_FORMATTER};
+use super::validation_report::ValidationError;
 use crate::traits::Hashable;
 
 #[non_exhaustive]
-#[derive(medici_macros::Hashable, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Debug)]
-#[cfg_attr(test, derive(Dummy))]
+#[derive(
+    medici_macros::Hashable,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
 pub struct ExplanationData {
     pub text: String,
     pub by: String,
@@ -31,9 +44,14 @@ impl ExplanationData {
         Ok(data)
     }
 
-    fn process(&mut self) -> Result<()> {
-        self.format();
-        self.check()?;
+    pub fn process(&mut self) -> Result<()> {
+        self.process_with(&DEFAULT_UNIT_FORMATTER)
+    }
+
+    /// Like `process`, with the unit rules of the explanation's course.
+    pub fn process_with(&mut self, formatter: &UnitFormatter) -> Result<()> {
+        self.format(formatter);
+        self.check().map_err(ValidationError::wrap)?;
 
         self.refresh_hash();
 
@@ -48,14 +66,16 @@ impl ExplanationData {
         Ok(())
     }
 
-    fn format(&mut self) {
-        self.text = self.text.trim().to_string();
+    fn format(&mut self, formatter: &UnitFormatter) {
+        self.text = formatter.format(self.text.trim());
         self.by = self.by.trim().to_string();
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use fake::{Fake, Faker};
+
     use super::*;
 
     #[test]
@@ -0,0 +1,218 @@
+use anyhow::{bail, Result};
+#[cfg(any(test, feature = "testing"))]
+use fake::Dummy;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::content_entity::ContentEntity;
+use super::question_data::QuestionData;
+use super::question_kind::QuestionKind;
+use super::validation_report::ValidationError;
+use crate::traits::{Hashable, Syncable};
+
+/// Card for the flashcard mode, generated from the question with `question_id`
+/// so every client shows the same front and back.
+#[non_exhaustive]
+#[derive(
+    medici_macros::Hashable,
+    medici_macros::SyncEntity,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[medici(key = "question_id")]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
+pub struct FlashcardData {
+    pub question_id: Uuid,
+
+    pub course_key: String,
+    /// Name of the question topic.
+    pub topic: String,
+    pub front: String,
+    pub back: String,
+
+    pub hash: String,
+}
+
+impl FlashcardData {
+    pub const BACK_SEPARATOR: &'static str = "\n\n";
+
+    pub fn new(
+        question_id: Uuid,
+        course_key: String,
+        topic: String,
+        front: String,
+        back: String,
+    ) -> Result<Self> {
+        let mut data = Self {
+            question_id,
+            course_key,
+            topic,
+            front,
+            back,
+            hash: Default::default(),
+        };
+
+        data.process()?;
+
+        Ok(data)
+    }
+
+    /// The front is the question text. The back is the answer, i.e. the correct
+    /// option or the numeric answer with its unit, followed by the explanation
+    /// if there's one. Matching and ordering questions fail with an
+    /// `UnsupportedKindError`, so callers can skip them.
+    pub fn from_question(question: &QuestionData) -> Result<Self> {
+        let answer = match &question.kind {
+            QuestionKind::MultipleChoice => {
+                let Some(correct_option) = question
+                    .question_options
+                    .iter()
+                    .find(|question_option| question_option.is_correct)
+                else {
+                    bail!("question with ID {} has no correct option", question.id);
+                };
+
+                correct_option.text.clone()
+            }
+            QuestionKind::Numeric(answer) => answer.to_string(),
+            QuestionKind::Matching(_) | QuestionKind::Ordering(_) => {
+                bail!(UnsupportedKindError {
+                    question_id: question.id,
+                });
+            }
+        };
+
+        let back = match &question.explanation {
+            Some(explanation) => format!("{answer}{}{}", Self::BACK_SEPARATOR, explanation.text),
+            None => answer,
+        };
+
+        Self::new(
+            question.id,
+            question.course_key.clone(),
+            question.topic.name.clone(),
+            question.text.clone(),
+            back,
+        )
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        self.format();
+        self.check().map_err(ValidationError::wrap)?;
+
+        self.refresh_hash();
+
+        Ok(())
+    }
+
+    fn format(&mut self) {
+        self.topic = self.topic.trim().to_string();
+        self.front = self.front.trim().to_string();
+        self.back = self.back.trim().to_string();
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.front.is_empty() || self.back.is_empty() {
+            bail!(
+                "invalid flashcard for question with ID {}",
+                self.question_id
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Question that flashcards can't be generated from.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UnsupportedKindError {
+    pub question_id: Uuid,
+}
+
+impl std::fmt::Display for UnsupportedKindError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "question with ID {} is of a kind without flashcards",
+            self.question_id
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedKindError {}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::sync::{NumericAnswer, OrderingAnswer, Tolerance};
+
+    #[test]
+    fn test_from_question() {
+        let mut question: QuestionData = Faker.fake();
+        question.explanation = None;
+        question.prepare_for_test().unwrap();
+
+        let flashcard = FlashcardData::from_question(&question).unwrap();
+
+        assert_eq!(flashcard.question_id, question.id);
+        assert_eq!(flashcard.front, question.text);
+        assert_eq!(flashcard.back, question.question_options[0].text);
+
+        question
+            .set_explanation("Es la causa más frecuente.".into(), "Medici".into())
+            .unwrap();
+        let explained_flashcard = FlashcardData::from_question(&question).unwrap();
+
+        assert_eq!(
+            explained_flashcard.back,
+            format!(
+                "{}\n\nEs la causa más frecuente.",
+                question.question_options[0].text
+            )
+        );
+        assert_ne!(explained_flashcard.hash, flashcard.hash);
+    }
+
+    #[test]
+    fn test_numeric_kind() {
+        let mut question: QuestionData = Faker.fake();
+        question.explanation = None;
+        question.question_options.clear();
+        question.kind = QuestionKind::Numeric(
+            NumericAnswer::new(Decimal::new(25, 1), Some("mL".into()), Tolerance::default())
+                .unwrap(),
+        );
+        question.process().unwrap();
+
+        let flashcard = FlashcardData::from_question(&question).unwrap();
+
+        assert_eq!(flashcard.back, "2.5 mL");
+    }
+
+    #[test]
+    fn test_unsupported_kind() {
+        let mut question: QuestionData = Faker.fake();
+        question.prepare_for_test().unwrap();
+        question.kind = QuestionKind::Ordering(
+            OrderingAnswer::new(vec!["Uno".into(), "Dos".into(), "Tres".into()]).unwrap(),
+        );
+
+        let error = FlashcardData::from_question(&question).unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<UnsupportedKindError>(),
+            Some(&UnsupportedKindError {
+                question_id: question.id
+            })
+        );
+    }
+}
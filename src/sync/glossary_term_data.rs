@@ -0,0 +1,247 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Result};
+#[cfg(any(test, feature = "testing"))]
+use fake::Dummy;
+use serde::{Deserialize, Serialize};
+
+use super::content_entity::ContentEntity;
+use super::helpers::format_text;
+use super::question_topic_data::QuestionTopicData;
+use super::validation_report::{ValidationError, ValidationReport};
+use crate::traits::{Hashable, Syncable};
+
+/// Medical term with its definition, shown in tooltips wherever the term or one
+/// of its aliases appears.
+#[non_exhaustive]
+#[derive(
+    medici_macros::Hashable,
+    medici_macros::SyncEntity,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[medici(key = "key")]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
+pub struct GlossaryTermData {
+    pub key: String,
+
+    pub term: String,
+    pub definition: String,
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub aliases: Vec<String>,
+    /// Keys of the question topics the term is relevant to.
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub topic_keys: Vec<String>,
+
+    pub hash: String,
+}
+
+/// Glossary term as written in content files.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct RawGlossaryTermData {
+    pub key: String,
+    pub term: String,
+    pub definition: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default, alias = "topics")]
+    pub topic_keys: Vec<String>,
+}
+
+impl GlossaryTermData {
+    pub fn new(
+        key: String,
+        term: String,
+        definition: String,
+        aliases: Vec<String>,
+        topic_keys: Vec<String>,
+    ) -> Result<Self> {
+        let mut data = Self {
+            key,
+            term,
+            definition,
+            aliases,
+            topic_keys,
+            hash: Default::default(),
+        };
+
+        data.process()?;
+
+        Ok(data)
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        self.format();
+        self.sort();
+        self.deduplicate();
+        self.check().map_err(ValidationError::wrap)?;
+
+        self.refresh_hash();
+
+        Ok(())
+    }
+
+    fn format(&mut self) {
+        self.key = self.key.trim().to_string();
+        self.term = self.term.trim().to_string();
+        self.definition = format_text(&self.definition);
+        self.aliases = self
+            .aliases
+            .iter()
+            .map(|alias| alias.trim().to_string())
+            .filter(|alias| !alias.is_empty())
+            .collect();
+        self.topic_keys = self
+            .topic_keys
+            .iter()
+            .map(|topic_key| topic_key.trim().to_string())
+            .filter(|topic_key| !topic_key.is_empty())
+            .collect();
+    }
+
+    fn sort(&mut self) {
+        self.aliases.sort();
+        self.topic_keys.sort();
+    }
+
+    fn deduplicate(&mut self) {
+        self.aliases.dedup();
+        self.topic_keys.dedup();
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.key.is_empty() || self.term.is_empty() || self.definition.is_empty() {
+            bail!("invalid glossary term with key {}", self.key);
+        }
+
+        if self
+            .aliases
+            .iter()
+            .any(|alias| alias.to_lowercase() == self.term.to_lowercase())
+        {
+            bail!(
+                "glossary term with key {} has its term as an alias",
+                self.key
+            );
+        }
+
+        Ok(())
+    }
+
+    /// The term followed by its aliases, as matched in text.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.term.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+
+    pub fn check_against(&self, topics: &[QuestionTopicData]) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        let known_topic_keys = topics
+            .iter()
+            .map(|topic| topic.key())
+            .collect::<HashSet<_>>();
+
+        for topic_key in &self.topic_keys {
+            if !known_topic_keys.contains(topic_key) {
+                report.push(
+                    "glossary term",
+                    &self.key,
+                    format!("unknown topic {topic_key}"),
+                );
+            }
+        }
+
+        report
+    }
+
+    /// Tooltips need each name, ignoring case, to belong to a single term.
+    pub fn check_unique_names(terms: &[Self]) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        let mut keys_by_name: HashMap<String, &str> = HashMap::new();
+
+        for term in terms {
+            for name in term.names() {
+                if let Some(other_key) = keys_by_name.insert(name.to_lowercase(), &term.key) {
+                    report.push(
+                        "glossary term",
+                        &term.key,
+                        format!("{name} is also a name of glossary term {other_key}"),
+                    );
+                }
+            }
+        }
+
+        report
+    }
+}
+
+impl RawGlossaryTermData {
+    pub fn into_glossary_term_data(self) -> Result<GlossaryTermData> {
+        GlossaryTermData::new(
+            self.key,
+            self.term,
+            self.definition,
+            self.aliases,
+            self.topic_keys,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process() {
+        let raw: RawGlossaryTermData = serde_json::from_value(serde_json::json!({
+            "key": "ic",
+            "term": "Insuficiencia cardíaca ",
+            "definition": "incapacidad del corazón de mantener un gasto cardíaco adecuado.",
+            "aliases": ["IC", "ICC", " IC"],
+            "topics": ["CARDIOLOGIA::Insuficiencia cardíaca"]
+        }))
+        .unwrap();
+
+        let data = raw.into_glossary_term_data().unwrap();
+
+        assert_eq!(data.term, "Insuficiencia cardíaca");
+        assert_eq!(data.aliases, vec!["IC", "ICC"]);
+
+        let topics = [QuestionTopicData::new("CARDIOLOGIA".into(), "Arritmias".into()).unwrap()];
+
+        assert_eq!(data.check_against(&topics).issues.len(), 1);
+    }
+
+    #[test]
+    fn test_check_unique_names() {
+        let term = |key: &str, term: &str, aliases: &[&str]| {
+            GlossaryTermData::new(
+                key.into(),
+                term.into(),
+                "Definición.".into(),
+                aliases.iter().map(ToString::to_string).collect(),
+                vec![],
+            )
+            .unwrap()
+        };
+
+        let terms = [
+            term("ic", "Insuficiencia cardíaca", &["IC"]),
+            term("ir", "Insuficiencia renal", &["ic"]),
+        ];
+
+        let report = GlossaryTermData::check_unique_names(&terms);
+
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].key, "ir");
+    }
+}
@@ -1,9 +1,15 @@
+use std::ops::Range;
 use std::path::Path;
 use std::sync::LazyLock;
 
+use chrono::{DateTime, Utc};
 use regex::Regex;
 
+use super::unit_formatting::{map_unprotected, UnitFormatter, DEFAULT_UNIT_FORMATTER};
+use crate::traits::Hashable;
+
 const UNITS_TO_SEPARATE: [&str; 1] = ["%"];
+const KEY_FIELD_ESCAPES: [(char, &str); 3] = [('%', "%25"), (':', "%3A"), ('!', "%21")];
 
 static WHITESPACE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s\s+").unwrap());
 static WHITESPACE_BEFORE_END_REGEX: LazyLock<Regex> =
@@ -11,9 +17,31 @@ static WHITESPACE_BEFORE_END_REGEX: LazyLock<Regex> =
 static DOUBLE_QUOTE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[“”]").unwrap());
 static SPACE_BEFORE_PERCENT_SIGN_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(&format!(r"(\d)({})", UNITS_TO_SEPARATE.join("|"))).unwrap());
+/// `$$...$$`, `\(...\)`, `\[...\]` and `$...$`, where an inline span can't
+/// start or end with whitespace, so amounts like "$100 and $200" aren't math.
+static MATH_SPAN_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)\$\$.+?\$\$|\\\(.+?\\\)|\\\[.+?\\\]|\$[^\s$](?:[^$]*?[^\s$])?\$").unwrap()
+});
 static END_PERIOD_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\.$").unwrap());
 
+/// Math spans are kept as written.
 pub fn format_text(text: &str) -> String {
+    format_text_with(text, &DEFAULT_UNIT_FORMATTER)
+}
+
+/// Like `format_text`, with other unit rules than the default ones.
+pub fn format_text_with(text: &str, formatter: &UnitFormatter) -> String {
+    map_unprotected(text, &formatter.protected_spans(text), |text| {
+        formatter.apply(&format_plain_text(text))
+    })
+}
+
+/// Applies the default unit rules, leaving whitespace as is.
+pub fn format_units(text: &str) -> String {
+    DEFAULT_UNIT_FORMATTER.format(text)
+}
+
+fn format_plain_text(text: &str) -> String {
     let mut formatted = text.trim().to_owned();
 
     formatted = WHITESPACE_REGEX.replace_all(&formatted, " ").into();
@@ -26,6 +54,25 @@ pub fn format_text(text: &str) -> String {
     formatted
 }
 
+/// Byte ranges of the LaTeX math spans of `text`, delimiters included. An
+/// inline `$...$` span followed by a digit is taken as an amount instead.
+pub fn math_spans(text: &str) -> Vec<Range<usize>> {
+    MATH_SPAN_REGEX
+        .find_iter(text)
+        .filter(|span| {
+            !span.as_str().starts_with('$')
+                || span.as_str().starts_with("$$")
+                || !text[span.end()..].starts_with(|char: char| char.is_ascii_digit())
+        })
+        .map(|span| span.range())
+        .collect()
+}
+
+/// Whether `text` has math that clients have to render, e.g. with KaTeX.
+pub fn contains_math(text: &str) -> bool {
+    !math_spans(text).is_empty()
+}
+
 pub fn remove_end_period(text: &str) -> String {
     END_PERIOD_REGEX.replace(text, "").into()
 }
@@ -36,6 +83,48 @@ pub fn capitalize_first_char(text: &mut str) {
     }
 }
 
+pub fn encode_key_field(value: &str) -> String {
+    value
+        .chars()
+        .fold(String::with_capacity(value.len()), |mut acc, char| {
+            match KEY_FIELD_ESCAPES
+                .iter()
+                .find(|(escaped, _)| *escaped == char)
+            {
+                Some((_, escape)) => acc.push_str(escape),
+                None => acc.push(char),
+            }
+
+            acc
+        })
+}
+
+pub fn decode_key_field(value: &str) -> Option<String> {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(index) = rest.find('%') {
+        decoded.push_str(&rest[..index]);
+
+        let (char, escape) = KEY_FIELD_ESCAPES
+            .iter()
+            .find(|(_, escape)| rest[index..].starts_with(escape))?;
+
+        decoded.push(*char);
+        rest = &rest[index + escape.len()..];
+    }
+
+    decoded.push_str(rest);
+
+    Some(decoded)
+}
+
+pub fn has_reserved_key_chars(value: &str) -> bool {
+    KEY_FIELD_ESCAPES
+        .iter()
+        .any(|(reserved, _)| value.contains(*reserved))
+}
+
 pub fn full_image_path<P>(key: &str, image_file_name: P) -> String
 where
     P: AsRef<Path>,
@@ -50,6 +139,26 @@ where
     )
 }
 
+/// Whether `at` falls in `[from, until)`, where a missing bound is unbounded.
+pub fn is_in_window(
+    from: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    at: DateTime<Utc>,
+) -> bool {
+    from.is_none_or(|from| from <= at) && until.is_none_or(|until| at < until)
+}
+
+pub fn is_valid_window(from: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> bool {
+    !matches!((from, until), (Some(from), Some(until)) if from >= until)
+}
+
+/// Bytes of `text` preceded by its length, so that strings hashed in a row
+/// can't run into each other, e.g. `["ab", "c"]` and `["a", "bc"]` hash
+/// differently.
+pub fn prefixed_bytes(text: &str) -> Vec<u8> {
+    [(text.len() as u32).to_bytes(), text.as_bytes().into()].concat()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,5 +169,21 @@ mod tests {
             format_text(" test  “text”   12.34%  . "),
             "test \"text\" 12.34 %."
         );
+        assert_eq!(
+            format_text(" Si  $p  <  0.05$ y \\( \\mu \\neq 5\\%  \\)  . "),
+            "Si $p  <  0.05$ y \\( \\mu \\neq 5\\%  \\)."
+        );
+        assert!(!contains_math("Cuesta $100 o $200."));
+    }
+
+    #[test]
+    fn test_key_field_encoding() {
+        let value = "Parcial: 100% ¡ok!";
+        let encoded = encode_key_field(value);
+
+        assert_eq!(encoded, "Parcial%3A 100%25 ¡ok%21");
+        assert!(!has_reserved_key_chars(&encoded.replace('%', "")));
+        assert_eq!(decode_key_field(&encoded).as_deref(), Some(value));
+        assert_eq!(decode_key_field("100%"), None);
     }
 }
@@ -1,41 +1,82 @@
 use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+#[cfg(any(test, feature = "testing"))]
+use fake::Dummy;
 use rust_decimal::prelude::*;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::path::PathBuf;
 
+use super::content_entity::ContentEntity;
 use super::{
-    helpers::{format_text, full_image_path},
-    ICON_IMAGES_DIR_NAME,
+    helpers::{format_text, full_image_path, is_in_window, is_valid_window},
+    ValidationError, ICON_IMAGES_DIR_NAME,
 };
-use crate::traits::Hashable;
+use crate::traits::{Hashable, Syncable};
 
 #[non_exhaustive]
-#[derive(medici_macros::Hashable, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Debug)]
+#[derive(
+    medici_macros::Hashable,
+    medici_macros::SyncEntity,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[medici(key = "key")]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
 pub struct IconData {
     pub key: String,
 
-    pub is_initial: bool,
+    #[serde(
+        alias = "is_initial",
+        deserialize_with = "IconUnlock::deserialize_compat"
+    )]
+    #[cfg_attr(any(test, feature = "testing"), dummy(expr = "IconUnlock::Initial"))]
+    pub unlock: IconUnlock,
     pub description: Option<String>,
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
     pub price_in_uyu: Option<Decimal>,
     pub image_file_name: PathBuf,
+    #[serde(default)]
+    pub alt_text: Option<String>,
+    #[medici(explicit_none)]
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub publish_at: Option<DateTime<Utc>>,
+    #[medici(explicit_none)]
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub unpublish_at: Option<DateTime<Utc>>,
 
     pub hash: String,
 }
 
 impl IconData {
+    // One argument per field; `Self::builder()` names them instead.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         key: String,
-        is_initial: bool,
+        unlock: IconUnlock,
         description: Option<String>,
         price_in_uyu: Option<Decimal>,
         image_file_name: PathBuf,
+        alt_text: Option<String>,
+        publish_at: Option<DateTime<Utc>>,
+        unpublish_at: Option<DateTime<Utc>>,
     ) -> Result<Self> {
         let mut data = Self {
             key,
-            is_initial,
+            unlock,
             description,
             price_in_uyu,
             image_file_name,
+            alt_text,
+            publish_at,
+            unpublish_at,
             hash: Default::default(),
         };
 
@@ -44,9 +85,9 @@ impl IconData {
         Ok(data)
     }
 
-    fn process(&mut self) -> Result<()> {
+    pub fn process(&mut self) -> Result<()> {
         self.format();
-        self.check()?;
+        self.check().map_err(ValidationError::wrap)?;
 
         self.refresh_hash();
 
@@ -60,16 +101,151 @@ impl IconData {
             }
         }
 
+        match &self.unlock {
+            IconUnlock::Purchase if self.price_in_uyu.is_none() => {
+                bail!("purchasable icon with key {} has no price", self.key)
+            }
+            IconUnlock::StreakDays(0) => {
+                bail!("icon with key {} unlocks with an empty streak", self.key)
+            }
+            IconUnlock::CourseCompletion(course_key) if course_key.is_empty() => {
+                bail!("icon with key {} unlocks with an invalid course", self.key)
+            }
+            _ => {}
+        }
+
+        if !is_valid_window(self.publish_at, self.unpublish_at) {
+            bail!("invalid publish window in icon with key {}", self.key);
+        }
+
+        Ok(())
+    }
+
+    pub fn is_initial(&self) -> bool {
+        self.unlock == IconUnlock::Initial
+    }
+
+    pub fn is_visible_at(&self, at: DateTime<Utc>) -> bool {
+        is_in_window(self.publish_at, self.unpublish_at, at)
+    }
+
+    pub fn check_strict(&self) -> Result<()> {
+        if self.alt_text.is_none() {
+            bail!("icon with key {} has an image without alt text", self.key);
+        }
+
         Ok(())
     }
 
     fn format(&mut self) {
         self.key = self.key.trim().to_string();
 
+        if let IconUnlock::CourseCompletion(course_key) = &mut self.unlock {
+            *course_key = course_key.trim().to_string();
+        }
+
         self.description = self.description.as_deref().map(format_text);
+        self.alt_text = self
+            .alt_text
+            .as_deref()
+            .map(format_text)
+            .filter(|alt_text| !alt_text.is_empty());
     }
 
     pub fn full_image_path(&self) -> String {
-        full_image_path(&ICON_IMAGES_DIR_NAME, &self.image_file_name)
+        full_image_path(ICON_IMAGES_DIR_NAME, &self.image_file_name)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum IconUnlock {
+    Initial,
+    Purchase,
+    StreakDays(u16),
+    CourseCompletion(String),
+}
+
+impl IconUnlock {
+    /// Also accepts the legacy `is_initial` flag, where `false` meant purchasable.
+    fn deserialize_compat<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Compat {
+            Legacy(bool),
+            Unlock(IconUnlock),
+        }
+
+        Ok(match Compat::deserialize(deserializer)? {
+            Compat::Legacy(true) => Self::Initial,
+            Compat::Legacy(false) => Self::Purchase,
+            Compat::Unlock(unlock) => unlock,
+        })
+    }
+}
+
+impl Hashable for IconUnlock {
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Initial => b"initial".to_vec(),
+            Self::Purchase => b"purchase".to_vec(),
+            Self::StreakDays(days) => [b"streak_days".to_vec(), days.to_bytes()].concat(),
+            Self::CourseCompletion(course_key) => {
+                [b"course_completion".to_vec(), course_key.to_bytes()].concat()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_legacy() {
+        let data: IconData = serde_json::from_str(
+            r#"{"key":"icon","is_initial":true,"description":null,"price_in_uyu":null,"image_file_name":"icon.png","hash":""}"#,
+        )
+        .unwrap();
+
+        assert!(data.is_initial());
+
+        let mut data: IconData = serde_json::from_str(
+            r#"{"key":"icon","is_initial":false,"description":null,"price_in_uyu":null,"image_file_name":"icon.png","hash":""}"#,
+        )
+        .unwrap();
+
+        assert_eq!(data.unlock, IconUnlock::Purchase);
+        // Legacy purchasable icons didn't require a price, which must be set now.
+        assert!(data.process().is_err());
+
+        data.price_in_uyu = Some(Decimal::ONE_HUNDRED);
+        data.process().unwrap();
+
+        let data: IconData = serde_json::from_str(
+            r#"{"key":"icon","unlock":{"streak_days":7},"description":null,"price_in_uyu":null,"image_file_name":"icon.png","hash":""}"#,
+        )
+        .unwrap();
+
+        assert_eq!(data.unlock, IconUnlock::StreakDays(7));
+    }
+
+    #[test]
+    fn test_check() {
+        let result = IconData::new(
+            "icon".into(),
+            IconUnlock::StreakDays(0),
+            None,
+            None,
+            "icon.png".into(),
+            None,
+            None,
+            None,
+        );
+
+        assert!(result.is_err());
     }
 }
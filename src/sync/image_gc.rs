@@ -0,0 +1,237 @@
+//! Planning of image cleanups. Images are only deleted once nothing
+//! references them after a sync, and a dry run lists them with their sizes.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::content_entity::with_content_entities;
+use super::{ContentEntity, ContentEntityType, SyncData, SyncIndex, SyncMetadata};
+use crate::traits::Syncable;
+
+/// Full image paths of each entity with images, keyed by entity type and key
+/// string, as stored before the sync. Entities of different types may share an
+/// image, e.g. a case and one of its questions.
+pub type ImageReferences = HashMap<(ContentEntityType, String), Vec<String>>;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct OrphanImage {
+    pub path: String,
+    /// `None` when the object is missing from the storage listing.
+    pub size: Option<u64>,
+}
+
+/// What a cleanup job would delete.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+pub struct ImageGcPlan {
+    pub images: Vec<OrphanImage>,
+}
+
+impl SyncData {
+    /// Image paths referenced before the sync but by no entity after it. References
+    /// of entities missing from `current_metadata` are ignored, as those were
+    /// already deleted.
+    pub fn orphan_image_keys(
+        &self,
+        current_metadata: &SyncMetadata,
+        referenced_paths: &ImageReferences,
+    ) -> BTreeSet<String> {
+        let mut references_after = referenced_paths
+            .iter()
+            .filter(|((entity_type, key), _)| current_metadata.contains(*entity_type, key))
+            .map(|(owner, paths)| (owner.clone(), paths.clone()))
+            .collect::<HashMap<_, _>>();
+
+        for key in &self.courses.for_deletion {
+            references_after.remove(&(ContentEntityType::Course, key.clone()));
+        }
+        for id in &self.questions.for_deletion {
+            references_after.remove(&(ContentEntityType::Question, id.to_string()));
+        }
+        for key in &self.bundles.for_deletion {
+            references_after.remove(&(ContentEntityType::Bundle, key.clone()));
+        }
+        for key in &self.icons.for_deletion {
+            references_after.remove(&(ContentEntityType::Icon, key.clone()));
+        }
+        for id in &self.cases.for_deletion {
+            references_after.remove(&(ContentEntityType::Case, id.to_string()));
+        }
+
+        for entity in self.entities_for_sync() {
+            let paths = match &entity {
+                ContentEntity::Course(course) => vec![course.full_image_path()],
+                ContentEntity::Question(question) => {
+                    question.full_image_path().into_iter().collect()
+                }
+                ContentEntity::Bundle(bundle) => vec![bundle.full_image_path()],
+                ContentEntity::Icon(icon) => vec![icon.full_image_path()],
+                ContentEntity::Case(case) => case.full_image_paths(),
+                _ => continue,
+            };
+
+            references_after.insert((entity.entity_type(), entity.key_string()), paths);
+        }
+
+        let paths_after = references_after.values().flatten().collect::<BTreeSet<_>>();
+
+        referenced_paths
+            .values()
+            .flatten()
+            .filter(|path| !paths_after.contains(path))
+            .cloned()
+            .collect()
+    }
+}
+
+macro_rules! contains {
+    ($($(#[$attr:meta])* $variant:ident($data:ty) => $field:ident: $index:ty,)*) => {
+        impl SyncMetadata {
+            /// Whether the entity with `key` (as in `ContentEntity::key_string`) is synced.
+            pub fn contains(&self, entity_type: ContentEntityType, key: &str) -> bool {
+                match entity_type {
+                    $(ContentEntityType::$variant => key
+                        .parse::<<$data as Syncable>::Key>()
+                        .is_ok_and(|key| self.$field.is_recorded(&key)),)*
+                }
+            }
+        }
+    };
+}
+
+with_content_entities!(contains);
+
+impl ImageGcPlan {
+    /// `object_sizes` is a listing of the image storage, from path to size in bytes.
+    pub fn new(orphan_paths: BTreeSet<String>, object_sizes: &HashMap<String, u64>) -> Self {
+        Self {
+            images: orphan_paths
+                .into_iter()
+                .map(|path| OrphanImage {
+                    size: object_sizes.get(&path).copied(),
+                    path,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.images.iter().filter_map(|image| image.size).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+}
+
+impl Display for ImageGcPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for image in &self.images {
+            match image.size {
+                Some(size) => writeln!(f, "{} ({size} bytes)", image.path)?,
+                None => writeln!(f, "{} (missing)", image.path)?,
+            }
+        }
+
+        write!(
+            f,
+            "{} images, {} bytes",
+            self.images.len(),
+            self.total_size()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::sync::{CaseData, CourseData, QuestionData};
+
+    #[test]
+    fn test_orphan_image_keys() {
+        let mut course: CourseData = Faker.fake();
+        let mut question: QuestionData = Faker.fake();
+        question.image_file_name = Some("ecg.png".into());
+        question.course_key = course.key.clone();
+        let deleted_question_id = Uuid::new_v4();
+        let mut case: CaseData = Faker.fake();
+        case.course_key = course.key.clone();
+        case.image_file_names = vec!["rx.png".into(), "tc.png".into()];
+        let deleted_case_id = Uuid::new_v4();
+
+        let mut metadata = SyncMetadata::default();
+        metadata.courses.insert(course.key.clone(), "hash".into());
+        metadata.questions.insert(question.id, "hash".into());
+        metadata
+            .questions
+            .insert(deleted_question_id, "hash".into());
+        metadata.cases.insert(case.id, "hash".into());
+        metadata.cases.insert(deleted_case_id, "hash".into());
+
+        let referenced_paths = ImageReferences::from([
+            (
+                (ContentEntityType::Course, course.key.clone()),
+                vec![format!("{}/old.png", course.key)],
+            ),
+            (
+                (ContentEntityType::Question, question.id.to_string()),
+                vec![question.full_image_path().unwrap()],
+            ),
+            (
+                (ContentEntityType::Question, deleted_question_id.to_string()),
+                vec![format!("{}/rx.png", course.key)],
+            ),
+            (
+                (ContentEntityType::Question, Uuid::new_v4().to_string()),
+                vec![format!("{}/stale.png", course.key)],
+            ),
+            (
+                (ContentEntityType::Case, case.id.to_string()),
+                vec![
+                    format!("{}/rx.png", course.key),
+                    format!("{}/eco.png", course.key),
+                ],
+            ),
+            (
+                (ContentEntityType::Case, deleted_case_id.to_string()),
+                vec![
+                    format!("{}/ecg.png", course.key),
+                    format!("{}/ap.png", course.key),
+                ],
+            ),
+        ]);
+
+        course.image_file_name = "new.png".into();
+        let mut sync_data = SyncData::default();
+        sync_data.add_for_sync(course.clone());
+        sync_data.add_for_sync(case);
+        sync_data.questions.for_deletion.insert(deleted_question_id);
+        sync_data.cases.for_deletion.insert(deleted_case_id);
+
+        let orphans = sync_data.orphan_image_keys(&metadata, &referenced_paths);
+
+        // rx.png is still used by the case and ecg.png by the question.
+        assert_eq!(
+            orphans,
+            BTreeSet::from([
+                format!("{}/old.png", course.key),
+                format!("{}/stale.png", course.key),
+                format!("{}/eco.png", course.key),
+                format!("{}/ap.png", course.key),
+            ])
+        );
+
+        let plan = ImageGcPlan::new(
+            orphans,
+            &HashMap::from([(format!("{}/old.png", course.key), 1024)]),
+        );
+
+        assert_eq!(plan.total_size(), 1024);
+        assert!(plan.to_string().ends_with("4 images, 1024 bytes"));
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::LazyLock;
+
+use anyhow::{bail, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::traits::Hashable;
+
+static LANGUAGE_TAG_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-z]{2,3}(-[A-Z]{2})?$").unwrap());
+
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, PartialOrd, Ord, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS), ts(type = "string"))]
+#[serde(try_from = "String", into = "String")]
+pub struct LanguageTag(String);
+
+impl LanguageTag {
+    pub const DEFAULT: &'static str = "es";
+
+    pub fn new(tag: &str) -> Result<Self> {
+        let tag = match tag.trim().split_once(['-', '_']) {
+            Some((language, region)) => {
+                format!("{}-{}", language.to_lowercase(), region.to_uppercase())
+            }
+            None => tag.trim().to_lowercase(),
+        };
+
+        if !LANGUAGE_TAG_REGEX.is_match(&tag) {
+            bail!("invalid language tag {tag}");
+        }
+
+        Ok(Self(tag))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn language(&self) -> &str {
+        self.0.split('-').next().unwrap_or(&self.0)
+    }
+}
+
+impl Default for LanguageTag {
+    fn default() -> Self {
+        Self(Self::DEFAULT.into())
+    }
+}
+
+impl TryFrom<String> for LanguageTag {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::new(&value)
+    }
+}
+
+impl From<LanguageTag> for String {
+    fn from(value: LanguageTag) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for LanguageTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Hashable for LanguageTag {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(LanguageTag::new(" pt_br ").unwrap().as_str(), "pt-BR");
+        assert_eq!(LanguageTag::new("ES").unwrap().language(), "es");
+        assert!(LanguageTag::new("portuguese").is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Result};
+#[cfg(any(test, feature = "testing"))]
+use fake::Dummy;
+use serde::{Deserialize, Serialize};
+
+use super::content_entity::ContentEntity;
+use super::course_data::CourseData;
+use super::helpers::format_text;
+use super::validation_report::{ValidationError, ValidationReport};
+use crate::traits::{Hashable, Syncable};
+
+/// Curated study plan, e.g. for a residency exam: courses to take in order,
+/// with milestones along the way.
+#[non_exhaustive]
+#[derive(
+    medici_macros::Hashable,
+    medici_macros::SyncEntity,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[medici(key = "key")]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
+pub struct LearningPathData {
+    pub key: String,
+
+    pub name: String,
+    pub description: Option<String>,
+    /// In the order they should be taken.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        dummy(expr = "vec![\"ANATOMIA\".into(), \"FISIOLOGIA\".into()]")
+    )]
+    pub course_keys: Vec<String>,
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub milestones: Vec<MilestoneData>,
+
+    pub hash: String,
+}
+
+/// Checkpoint reached once the course with `course_key` is completed.
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct MilestoneData {
+    pub name: String,
+    pub course_key: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Learning path as written in authoring files.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct RawLearningPathData {
+    pub key: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(alias = "courses")]
+    pub course_keys: Vec<String>,
+    #[serde(default)]
+    pub milestones: Vec<MilestoneData>,
+}
+
+impl LearningPathData {
+    pub const MIN_COURSE_COUNT: usize = 2;
+
+    pub fn new(
+        key: String,
+        name: String,
+        description: Option<String>,
+        course_keys: Vec<String>,
+        milestones: Vec<MilestoneData>,
+    ) -> Result<Self> {
+        let mut data = Self {
+            key,
+            name,
+            description,
+            course_keys,
+            milestones,
+            hash: Default::default(),
+        };
+
+        data.process()?;
+
+        Ok(data)
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        self.format();
+        self.sort();
+        self.check().map_err(ValidationError::wrap)?;
+
+        self.refresh_hash();
+
+        Ok(())
+    }
+
+    fn format(&mut self) {
+        self.key = self.key.trim().to_string();
+        self.name = self.name.trim().to_string();
+        self.description = self
+            .description
+            .as_deref()
+            .map(format_text)
+            .filter(|description| !description.is_empty());
+        self.course_keys = self
+            .course_keys
+            .iter()
+            .map(|course_key| course_key.trim().to_string())
+            .collect();
+
+        for milestone in &mut self.milestones {
+            milestone.name = milestone.name.trim().to_string();
+            milestone.course_key = milestone.course_key.trim().to_string();
+            milestone.description = milestone
+                .description
+                .as_deref()
+                .map(format_text)
+                .filter(|description| !description.is_empty());
+        }
+    }
+
+    /// Milestones follow the order of their courses in the path.
+    fn sort(&mut self) {
+        let course_keys = &self.course_keys;
+
+        self.milestones.sort_by_key(|milestone| {
+            course_keys
+                .iter()
+                .position(|course_key| course_key == &milestone.course_key)
+        });
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.key.is_empty() || self.name.is_empty() {
+            bail!("invalid learning path with key {}", self.key);
+        }
+
+        let distinct_course_keys = self.course_keys.iter().collect::<HashSet<_>>();
+
+        if distinct_course_keys.len() != self.course_keys.len() {
+            bail!("learning path with key {} repeats a course", self.key);
+        }
+
+        if distinct_course_keys.len() < Self::MIN_COURSE_COUNT {
+            bail!(
+                "learning path with key {} has {} course(s), expected at least {}",
+                self.key,
+                distinct_course_keys.len(),
+                Self::MIN_COURSE_COUNT
+            );
+        }
+
+        for milestone in &self.milestones {
+            if milestone.name.is_empty() {
+                bail!(
+                    "learning path with key {} has an unnamed milestone",
+                    self.key
+                );
+            }
+
+            if !distinct_course_keys.contains(&milestone.course_key) {
+                bail!(
+                    "milestone {} of learning path with key {} is after course {}, which isn't in the path",
+                    milestone.name,
+                    self.key,
+                    milestone.course_key
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn check_against(&self, courses: &[CourseData]) -> ValidationReport {
+        const ENTITY: &str = "learning path";
+
+        let mut report = ValidationReport::default();
+
+        for course_key in &self.course_keys {
+            match courses.iter().find(|course| &course.key == course_key) {
+                None => report.push(ENTITY, &self.key, format!("unknown course {course_key}")),
+                Some(course) if course.publish_state.is_draft() => {
+                    report.push(ENTITY, &self.key, format!("course {course_key} is a draft"))
+                }
+                Some(_) => {}
+            }
+        }
+
+        report
+    }
+}
+
+impl RawLearningPathData {
+    pub fn into_learning_path_data(self) -> Result<LearningPathData> {
+        LearningPathData::new(
+            self.key,
+            self.name,
+            self.description,
+            self.course_keys,
+            self.milestones,
+        )
+    }
+}
+
+impl Hashable for MilestoneData {
+    fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.name.to_bytes(),
+            self.course_key.to_bytes(),
+            self.description.to_bytes(),
+        ]
+        .concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+
+    #[test]
+    fn test_process() {
+        let raw: RawLearningPathData = serde_json::from_value(serde_json::json!({
+            "key": "residencia-medicina-interna",
+            "name": " Residencia de Medicina Interna ",
+            "courses": ["ANATOMIA", "FISIOLOGIA", "MI"],
+            "milestones": [
+                { "name": "Clínica", "course_key": "MI" },
+                { "name": "Ciencias básicas", "course_key": "FISIOLOGIA" }
+            ]
+        }))
+        .unwrap();
+
+        let data = raw.into_learning_path_data().unwrap();
+
+        assert_eq!(data.name, "Residencia de Medicina Interna");
+        assert_eq!(data.milestones[0].name, "Ciencias básicas");
+
+        let mut data: LearningPathData = Faker.fake();
+        data.milestones = vec![MilestoneData {
+            name: "Final".into(),
+            course_key: "PEDIATRIA".into(),
+            description: None,
+        }];
+
+        assert!(data.process().is_err());
+    }
+
+    #[test]
+    fn test_check_against() {
+        let data: LearningPathData = Faker.fake();
+        let mut course: CourseData = Faker.fake();
+        course.key = data.course_keys[0].clone();
+
+        assert_eq!(data.check_against(&[course]).issues.len(), 1);
+    }
+}
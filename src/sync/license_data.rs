@@ -0,0 +1,159 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use super::helpers::format_text;
+use super::validation_report::ValidationError;
+use crate::traits::Hashable;
+
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct LicenseData {
+    pub source_institution: String,
+    pub kind: LicenseKind,
+    pub attribution_text: String,
+    pub url: Option<String>,
+}
+
+impl LicenseData {
+    pub fn new(
+        source_institution: String,
+        kind: LicenseKind,
+        attribution_text: String,
+        url: Option<String>,
+    ) -> Result<Self> {
+        let mut data = Self {
+            source_institution,
+            kind,
+            attribution_text,
+            url,
+        };
+
+        data.process()?;
+
+        Ok(data)
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        self.format();
+        self.check().map_err(ValidationError::wrap)?;
+
+        Ok(())
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.source_institution.is_empty() || self.attribution_text.is_empty() {
+            bail!("invalid license");
+        }
+
+        if let Some(url) = &self.url {
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                bail!("invalid license URL {url}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Line crediting the source, e.g. "Material cedido — Facultad de
+    /// Medicina, CC BY (https://…)".
+    pub fn attribution(&self) -> String {
+        let mut line = format!("{} — {}", self.attribution_text, self.source_institution);
+
+        if let Some(label) = self.kind.label() {
+            line.push_str(&format!(", {label}"));
+        }
+
+        if let Some(url) = &self.url {
+            line.push_str(&format!(" ({url})"));
+        }
+
+        line
+    }
+
+    fn format(&mut self) {
+        self.source_institution = self.source_institution.trim().to_string();
+        self.attribution_text = format_text(&self.attribution_text);
+        self.url = self
+            .url
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(Into::into);
+    }
+}
+
+impl Hashable for LicenseData {
+    fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.source_institution.to_bytes(),
+            self.kind.to_string().to_bytes(),
+            self.attribution_text.to_bytes(),
+            self.url.to_bytes(),
+        ]
+        .concat()
+    }
+}
+
+#[derive(
+    strum::Display, Serialize, Deserialize, PartialEq, Hash, Eq, PartialOrd, Ord, Clone, Debug,
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[cfg_attr(
+    feature = "db",
+    derive(sqlx::Type),
+    sqlx(type_name = "text", rename_all = "snake_case")
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum LicenseKind {
+    CcBy,
+    CcBySa,
+    CcByNc,
+    CcByNcSa,
+    PublicDomain,
+    Proprietary,
+    Other,
+}
+
+impl LicenseKind {
+    /// Short name of Creative Commons licenses, which attributions must state.
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            Self::CcBy => Some("CC BY"),
+            Self::CcBySa => Some("CC BY-SA"),
+            Self::CcByNc => Some("CC BY-NC"),
+            Self::CcByNcSa => Some("CC BY-NC-SA"),
+            Self::PublicDomain | Self::Proprietary | Self::Other => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let data = LicenseData::new(
+            " Facultad de Medicina ".into(),
+            LicenseKind::CcBy,
+            "Material  cedido ".into(),
+            Some(" https://example.com ".into()),
+        )
+        .unwrap();
+
+        assert_eq!(data.source_institution, "Facultad de Medicina");
+        assert_eq!(data.url.as_deref(), Some("https://example.com"));
+        assert_eq!(
+            data.attribution(),
+            "Material cedido — Facultad de Medicina, CC BY (https://example.com)"
+        );
+
+        assert!(
+            LicenseData::new("a".into(), LicenseKind::Other, "b".into(), Some("c".into())).is_err()
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use super::helpers::{format_text, full_image_path};
+use super::validation_report::ValidationError;
+use crate::traits::Hashable;
+
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct MediaAttachment {
+    pub kind: MediaKind,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub file_name: PathBuf,
+    pub duration_seconds: u32,
+    #[serde(default)]
+    pub transcript: Option<String>,
+}
+
+impl MediaAttachment {
+    pub fn new(
+        kind: MediaKind,
+        file_name: PathBuf,
+        duration_seconds: u32,
+        transcript: Option<String>,
+    ) -> Result<Self> {
+        let mut data = Self {
+            kind,
+            file_name,
+            duration_seconds,
+            transcript,
+        };
+
+        data.process()?;
+
+        Ok(data)
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        self.format();
+        self.check().map_err(ValidationError::wrap)?;
+
+        Ok(())
+    }
+
+    fn check(&self) -> Result<()> {
+        let extension = self
+            .file_name
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+
+        if !self.kind.extensions().contains(&extension.as_str()) {
+            bail!(
+                "invalid {} file {}",
+                self.kind,
+                self.file_name.to_string_lossy()
+            );
+        }
+
+        if self.duration_seconds == 0 {
+            bail!(
+                "{} file {} has no duration",
+                self.kind,
+                self.file_name.to_string_lossy()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn format(&mut self) {
+        self.transcript = self
+            .transcript
+            .as_deref()
+            .map(format_text)
+            .filter(|transcript| !transcript.is_empty());
+    }
+
+    /// Path of the file in the course's bucket, next to its images.
+    pub fn full_path(&self, course_key: &str) -> String {
+        full_image_path(course_key, &self.file_name)
+    }
+}
+
+impl Hashable for MediaAttachment {
+    fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.kind.to_string().to_bytes(),
+            self.file_name.to_bytes(),
+            self.duration_seconds.to_bytes(),
+            self.transcript.to_bytes(),
+        ]
+        .concat()
+    }
+}
+
+#[derive(
+    strum::Display, Serialize, Deserialize, PartialEq, Hash, Eq, PartialOrd, Ord, Clone, Copy, Debug,
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum MediaKind {
+    Audio,
+    Video,
+}
+
+impl MediaKind {
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Self::Audio => &["mp3", "m4a", "aac", "wav", "ogg", "opus"],
+            Self::Video => &["mp4", "m4v", "webm", "mov"],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let data = MediaAttachment::new(
+            MediaKind::Audio,
+            "soplo.MP3".into(),
+            12,
+            Some(" Soplo  sistólico ".into()),
+        )
+        .unwrap();
+
+        assert_eq!(data.transcript.as_deref(), Some("Soplo sistólico"));
+        assert_eq!(data.full_path("mir"), "mir/soplo.MP3");
+
+        assert!(MediaAttachment::new(MediaKind::Video, "soplo.mp3".into(), 12, None).is_err());
+        assert!(MediaAttachment::new(MediaKind::Audio, "soplo.mp3".into(), 0, None).is_err());
+    }
+}
@@ -1,23 +1,85 @@
+mod achievement_data;
+mod apply_state;
+mod backfill_plan;
 mod bundle_data;
+mod cache_warm_plan;
+mod case_data;
+mod catalog;
 mod constants;
+mod content_entity;
+mod content_timeseries;
 mod course_data;
+mod course_relation;
+mod course_stats;
+mod coverage_report;
+mod date_range;
+mod environment;
+mod exam_blueprint;
 mod explanation_data;
+mod flashcard_data;
+mod glossary_term_data;
 mod helpers;
 mod icon_data;
+mod image_gc;
+mod language_tag;
+mod learning_path_data;
+mod license_data;
+mod media_attachment;
+mod preview_subset;
+mod publish_state;
 mod question_data;
+mod question_kind;
 mod question_option_data;
+mod question_patch;
 mod question_source_data;
 mod question_topic_data;
+mod raw_course_data;
+mod sync_plan;
+mod sync_report;
+mod translated_question;
 mod types;
+mod unit_formatting;
+mod validation_report;
 
+pub use achievement_data::*;
+pub use apply_state::*;
+pub use backfill_plan::*;
 pub use bundle_data::*;
+pub use cache_warm_plan::*;
+pub use case_data::*;
+pub use catalog::*;
 pub use constants::*;
+pub use content_entity::*;
+pub use content_timeseries::*;
 pub use course_data::*;
+pub use course_relation::*;
+pub use course_stats::*;
+pub use coverage_report::*;
+pub use date_range::*;
+pub use environment::*;
+pub use exam_blueprint::*;
 pub use explanation_data::*;
+pub use flashcard_data::*;
+pub use glossary_term_data::*;
 pub use helpers::*;
 pub use icon_data::*;
+pub use image_gc::*;
+pub use language_tag::*;
+pub use learning_path_data::*;
+pub use license_data::*;
+pub use media_attachment::*;
+pub use preview_subset::*;
+pub use publish_state::*;
 pub use question_data::*;
+pub use question_kind::*;
 pub use question_option_data::*;
+pub use question_patch::*;
 pub use question_source_data::*;
 pub use question_topic_data::*;
+pub use raw_course_data::*;
+pub use sync_plan::*;
+pub use sync_report::*;
+pub use translated_question::*;
 pub use types::*;
+pub use unit_formatting::*;
+pub use validation_report::*;
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::course_data::CourseData;
+use super::question_data::QuestionData;
+
+/// Free preview of a course, offered to users without access to it.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct PreviewConfig {
+    pub per_topic: usize,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self { per_topic: 2 }
+    }
+}
+
+impl PreviewConfig {
+    /// Up to `per_topic` questions of each topic, preferring questions with an
+    /// explanation. The rest of the order comes from hashing the course key and
+    /// question ID, so the subset only changes when its questions do.
+    pub fn select<'a>(&self, course: &'a CourseData) -> Vec<&'a QuestionData> {
+        let mut by_topic = BTreeMap::<&str, Vec<&QuestionData>>::new();
+
+        for question in &course.questions {
+            by_topic
+                .entry(question.topic.name.as_str())
+                .or_default()
+                .push(question);
+        }
+
+        let mut selected = by_topic
+            .into_values()
+            .flat_map(|mut questions| {
+                questions.sort_by_cached_key(|question| {
+                    (
+                        question.explanation.is_none(),
+                        blake3::hash(&[course.key.as_bytes(), question.id.as_bytes()].concat())
+                            .to_hex(),
+                    )
+                });
+                questions.truncate(self.per_topic);
+
+                questions
+            })
+            .collect::<Vec<_>>();
+        selected.sort_by_key(|question| question.id);
+
+        selected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::sync::QuestionTopicData;
+
+    #[test]
+    fn test_select() {
+        let mut course: CourseData = Faker.fake();
+        course.questions = fake::vec![QuestionData; 7];
+
+        for (index, question) in course.questions.iter_mut().enumerate() {
+            let topic = if index < 4 {
+                "Cardiología"
+            } else {
+                "Neumología"
+            };
+
+            question.topic = QuestionTopicData::new(course.key.clone(), topic.into()).unwrap();
+            question.explanation = (index != 0).then(|| Faker.fake());
+        }
+
+        let config = PreviewConfig { per_topic: 3 };
+        let selected = course.preview_subset(&config);
+
+        assert_eq!(selected.len(), 6);
+        assert!(selected
+            .iter()
+            .all(|question| question.explanation.is_some()));
+
+        let ids = selected
+            .iter()
+            .map(|question| question.id)
+            .collect::<Vec<_>>();
+        course.questions.reverse();
+        course.mark_preview(&config);
+
+        assert_eq!(course.preview_question_ids, ids);
+        assert!(course.is_preview_question(ids[0]));
+    }
+}
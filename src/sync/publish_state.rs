@@ -0,0 +1,44 @@
+#[cfg(any(test, feature = "testing"))]
+use fake::Dummy;
+use serde::{Deserialize, Serialize};
+
+use crate::traits::Hashable;
+
+#[derive(
+    strum::Display,
+    strum::EnumString,
+    Serialize,
+    Deserialize,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    Debug,
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum PublishState {
+    /// Unfinished; only synced to staging.
+    Draft,
+    #[default]
+    Published,
+    /// Still synced so owners keep access, but no longer offered.
+    Archived,
+}
+
+impl PublishState {
+    pub fn is_draft(&self) -> bool {
+        *self == Self::Draft
+    }
+}
+
+impl Hashable for PublishState {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_string().to_bytes()
+    }
+}
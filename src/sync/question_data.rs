@@ -1,25 +1,46 @@
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::PathBuf;
 
 use anyhow::{bail, Result};
 use chrono::Utc;
-#[cfg(test)]
-use fake::{Dummy, Fake, Faker};
+#[cfg(any(test, feature = "testing"))]
+use fake::{Dummy, Faker};
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 use uuid::Uuid;
 
+use super::content_entity::ContentEntity;
 use super::explanation_data::ExplanationData;
-use super::helpers::{format_text, full_image_path};
+use super::helpers::{contains_math, format_text_with, full_image_path};
+use super::language_tag::LanguageTag;
+use super::license_data::LicenseData;
+use super::media_attachment::MediaAttachment;
+use super::question_kind::QuestionKind;
 use super::question_option_data::QuestionOptionData;
 use super::question_source_data::QuestionSourceData;
 use super::question_topic_data::QuestionTopicData;
-use crate::traits::Hashable;
+use super::translated_question::TranslatedQuestion;
+use super::unit_formatting::{unit_formatter, UnitFormatter, UnitRules};
+use super::validation_report::ValidationError;
+use crate::render::{self, OptionLabels, RenderOptions};
+use crate::traits::{Hashable, Syncable};
 
 #[non_exhaustive]
-#[derive(medici_macros::Hashable, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Debug)]
-#[cfg_attr(test, derive(Dummy))]
+#[derive(
+    medici_macros::Hashable,
+    medici_macros::SyncEntity,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[medici(key = "id")]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
 pub struct QuestionData {
     pub id: Uuid,
 
@@ -29,18 +50,52 @@ pub struct QuestionData {
     pub explanation: Option<ExplanationData>,
     pub topic: QuestionTopicData,
     pub topic_by: Option<String>,
+    #[medici(unordered_hash)]
     pub tags: Vec<String>,
     pub image_file_name: Option<PathBuf>,
+    #[serde(default)]
+    pub alt_text: Option<String>,
     #[serde(skip)]
-    #[cfg_attr(test, dummy(faker = "(Faker, 2..=5)"))]
+    #[cfg_attr(any(test, feature = "testing"), dummy(faker = "(Faker, 2..=5)"))]
     pub question_options: Vec<QuestionOptionData>,
+    #[medici(builder_default)]
+    #[serde(default)]
+    #[medici(skip_hash)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub option_count_range: OptionCountRange,
+    /// Those of the course; the default ones if it has none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[medici(skip_hash)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub unit_rules: Option<UnitRules>,
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub translations: BTreeMap<LanguageTag, TranslatedQuestion>,
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub license: Option<LicenseData>,
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub media: Vec<MediaAttachment>,
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub kind: QuestionKind,
+    /// Seconds the question can be answered in, in timed quizzes and exams.
+    #[serde(default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub time_limit_seconds: Option<u32>,
 
     pub hash: String,
 }
 
 impl QuestionData {
     pub const TOPIC_KEY_SEPARATOR: &'static str = "::";
+    pub const MAX_OPTION_REFERENCE: u16 = 25;
+    pub const MIN_TIME_LIMIT_SECONDS: u32 = 10;
+    pub const MAX_TIME_LIMIT_SECONDS: u32 = 30 * 60;
 
+    // One argument per field; `Self::builder()` names them instead.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: Uuid,
         course_key: String,
@@ -50,8 +105,16 @@ impl QuestionData {
         topic_by: Option<String>,
         tags: Vec<String>,
         image_file_name: Option<PathBuf>,
+        alt_text: Option<String>,
         question_options: Vec<QuestionOptionData>,
         source: QuestionSourceData,
+        option_count_range: OptionCountRange,
+        unit_rules: Option<UnitRules>,
+        translations: BTreeMap<LanguageTag, TranslatedQuestion>,
+        license: Option<LicenseData>,
+        media: Vec<MediaAttachment>,
+        kind: QuestionKind,
+        time_limit_seconds: Option<u32>,
     ) -> Result<Self> {
         let mut data = Self {
             id,
@@ -63,7 +126,15 @@ impl QuestionData {
             topic_by,
             tags,
             image_file_name,
+            alt_text,
             question_options,
+            option_count_range,
+            unit_rules,
+            translations,
+            license,
+            media,
+            kind,
+            time_limit_seconds,
             hash: Default::default(),
         };
 
@@ -77,17 +148,75 @@ impl QuestionData {
     }
 
     pub fn process(&mut self) -> Result<()> {
+        let formatter = unit_formatter(self.unit_rules.as_ref())?;
+
+        self.process_options(&formatter)?;
         self.remove_blank_options();
-        self.format();
+        self.format(&formatter);
+        self.process_translations()?;
+        self.process_explanation(&formatter)?;
+        self.process_license()?;
+        self.process_media()?;
+        self.kind.process()?;
         self.sort();
         self.deduplicate();
-        self.check()?;
+        self.renumber_references();
+        self.check().map_err(ValidationError::wrap)?;
 
         self.refresh_hash();
 
         Ok(())
     }
 
+    fn process_translations(&mut self) -> Result<()> {
+        for translation in self.translations.values_mut() {
+            translation.process()?;
+        }
+
+        Ok(())
+    }
+
+    fn process_options(&mut self, formatter: &UnitFormatter) -> Result<()> {
+        for question_option in &mut self.question_options {
+            question_option.process_with(formatter)?;
+        }
+
+        Ok(())
+    }
+
+    fn process_explanation(&mut self, formatter: &UnitFormatter) -> Result<()> {
+        if let Some(explanation) = &mut self.explanation {
+            explanation.process_with(formatter)?;
+        }
+
+        Ok(())
+    }
+
+    fn process_license(&mut self) -> Result<()> {
+        if let Some(license) = &mut self.license {
+            license.process()?;
+        }
+
+        Ok(())
+    }
+
+    fn process_media(&mut self) -> Result<()> {
+        for attachment in &mut self.media {
+            attachment.process()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn renumber_references(&mut self) {
+        let mut indices = (0..self.question_options.len()).collect::<Vec<usize>>();
+        indices.sort_by_key(|&index| (self.question_options[index].reference, index));
+
+        for (reference, index) in indices.into_iter().enumerate() {
+            self.question_options[index].reference = reference as u16;
+        }
+    }
+
     fn sort(&mut self) {
         self.question_options.sort_by(|a, b| {
             if a.is_correct {
@@ -120,20 +249,101 @@ impl QuestionData {
     }
 
     fn check(&self) -> Result<()> {
+        self.check_kind()?;
         self.check_question_option_count()?;
         self.check_duplicates_in_question_options()?;
         self.check_correct_count()?;
+        self.check_references()?;
+        self.check_translations()?;
+        self.check_media()?;
+        self.check_time_limit()?;
+
+        Ok(())
+    }
+
+    fn check_translations(&self) -> Result<()> {
+        if let Some((locale, _)) = self.translations.iter().find(|(_, translation)| {
+            translation.question_options.len() != self.question_options.len()
+        }) {
+            bail!(
+                "question with ID {} has a {locale} translation with a mismatched option count",
+                self.id
+            );
+        }
+
+        Ok(())
+    }
+
+    fn check_kind(&self) -> Result<()> {
+        if !self.kind.is_multiple_choice() && !self.question_options.is_empty() {
+            bail!(
+                "question with ID {} has options but isn't multiple choice",
+                self.id
+            );
+        }
+
+        Ok(())
+    }
+
+    fn check_media(&self) -> Result<()> {
+        let file_names = self
+            .media
+            .iter()
+            .map(|attachment| &attachment.file_name)
+            .collect::<HashSet<_>>();
+
+        if file_names.len() != self.media.len() {
+            bail!("question with ID {} has duplicate media files", self.id);
+        }
+
+        Ok(())
+    }
+
+    fn check_time_limit(&self) -> Result<()> {
+        if self.time_limit_seconds.is_some_and(|seconds| {
+            !(Self::MIN_TIME_LIMIT_SECONDS..=Self::MAX_TIME_LIMIT_SECONDS).contains(&seconds)
+        }) {
+            bail!(
+                "question with ID {} has a time limit outside {}-{} seconds",
+                self.id,
+                Self::MIN_TIME_LIMIT_SECONDS,
+                Self::MAX_TIME_LIMIT_SECONDS
+            );
+        }
+
+        Ok(())
+    }
+
+    /// References are contiguous after `renumber_references`, so only their
+    /// range is checked.
+    fn check_references(&self) -> Result<()> {
+        if self
+            .question_options
+            .iter()
+            .any(|question_option| question_option.reference > Self::MAX_OPTION_REFERENCE)
+        {
+            bail!(
+                "question with ID {} has option references beyond {}",
+                self.id,
+                OptionLabels::LowerAlpha.label(Self::MAX_OPTION_REFERENCE)
+            );
+        }
 
         Ok(())
     }
 
     fn check_question_option_count(&self) -> Result<()> {
-        if !self.is_blank() && (self.question_options.len() < 2 || self.question_options.len() > 5)
+        if !self.is_blank()
+            && self.kind.is_multiple_choice()
+            && !self
+                .option_count_range
+                .contains(self.question_options.len())
         {
             bail!(
-                "question with ID {} has {} option(s)",
+                "question with ID {} has {} option(s), expected {}",
                 self.id,
-                self.question_options.len()
+                self.question_options.len(),
+                self.option_count_range
             );
         }
 
@@ -162,7 +372,7 @@ impl QuestionData {
             .filter(|option| option.is_correct)
             .count();
 
-        if !self.is_blank() && correct_count != 1 {
+        if !self.is_blank() && self.kind.is_multiple_choice() && correct_count != 1 {
             bail!(
                 "question with ID {} has {correct_count} correct options",
                 self.id
@@ -172,8 +382,16 @@ impl QuestionData {
         Ok(())
     }
 
-    fn format(&mut self) {
-        self.text = format_text(&self.text);
+    pub fn check_strict(&self) -> Result<()> {
+        if self.image_file_name.is_some() && self.alt_text.is_none() {
+            bail!("question with ID {} has an image without alt text", self.id);
+        }
+
+        Ok(())
+    }
+
+    fn format(&mut self, formatter: &UnitFormatter) {
+        self.text = format_text_with(&self.text, formatter);
 
         self.tags = self
             .tags
@@ -181,6 +399,12 @@ impl QuestionData {
             .map(|tag| tag.trim().to_string())
             .filter(|tag| !tag.is_empty())
             .collect();
+
+        self.alt_text = self
+            .alt_text
+            .as_deref()
+            .map(|alt_text| format_text_with(alt_text, formatter))
+            .filter(|alt_text| !alt_text.is_empty());
     }
 
     pub fn topic_key(&self) -> String {
@@ -199,13 +423,19 @@ impl QuestionData {
     }
 
     pub fn set_explanation(&mut self, text: String, by: String) -> Result<()> {
-        self.explanation
-            .replace(ExplanationData::new(text, by, Utc::now())?);
+        let mut explanation = ExplanationData {
+            text,
+            by,
+            date: Utc::now(),
+            hash: Default::default(),
+        };
+        explanation.process_with(&*unit_formatter(self.unit_rules.as_ref())?)?;
+        self.explanation.replace(explanation);
 
         self.process()
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "testing"))]
     pub fn prepare_for_test(&mut self) -> Result<()> {
         for (index, question_option) in self.question_options.iter_mut().enumerate() {
             question_option.question_id = self.id;
@@ -213,13 +443,70 @@ impl QuestionData {
             if index == 0 {
                 question_option.is_correct = true;
             }
-
-            question_option.process()?;
         }
 
         self.process()
     }
 
+    pub fn explanation_prompt(&self) -> String {
+        let labels = OptionLabels::LowerAlpha;
+        let mut prompt = render::to_text(
+            self,
+            &RenderOptions {
+                labels,
+                ..Default::default()
+            },
+        );
+
+        if let Some(correct_option) = self
+            .question_options
+            .iter()
+            .find(|question_option| question_option.is_correct)
+        {
+            prompt.push_str(&format!(
+                "\n\nCorrect option: {}",
+                labels.label(correct_option.reference)
+            ));
+        }
+
+        let option_explanations =
+            self.question_options
+                .iter()
+                .fold(
+                    String::new(),
+                    |acc, question_option| match &question_option.explanation {
+                        Some(explanation) => format!(
+                            "{acc}\n{}. {explanation}",
+                            labels.label(question_option.reference)
+                        ),
+                        None => acc,
+                    },
+                );
+
+        if !option_explanations.is_empty() {
+            prompt.push_str(&format!("\n\nOption notes:{option_explanations}"));
+        }
+
+        prompt
+    }
+
+    /// Whether the text, options or explanations have LaTeX math, so clients
+    /// know to load a math renderer.
+    pub fn contains_math(&self) -> bool {
+        contains_math(&self.text)
+            || self.question_options.iter().any(|question_option| {
+                contains_math(&question_option.text)
+                    || question_option
+                        .explanation
+                        .as_deref()
+                        .is_some_and(contains_math)
+            })
+            || self
+                .explanation
+                .as_ref()
+                .is_some_and(|explanation| contains_math(&explanation.text))
+    }
+
     pub fn full_image_path(&self) -> Option<String> {
         Some(full_image_path(
             &self.course_key,
@@ -228,27 +515,64 @@ impl QuestionData {
     }
 }
 
+/// Options are listed by reference, without revealing the answer.
 impl std::fmt::Display for QuestionData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let question_options_string =
-            self.question_options
-                .iter()
-                .fold(String::new(), |acc, question_option| {
-                    format!(
-                        "{acc}\n{}. {question_option}",
-                        (97 + question_option.reference as u8) as char
-                    )
-                });
+        write!(f, "{}", render::to_text(self, &RenderOptions::default()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct OptionCountRange {
+    pub min: u16,
+    pub max: u16,
+}
+
+impl OptionCountRange {
+    pub const DEFAULT: Self = Self { min: 2, max: 5 };
+
+    pub fn new(min: u16, max: u16) -> Result<Self> {
+        if min < Self::DEFAULT.min || min > max {
+            bail!("invalid option count range {min}-{max}");
+        }
+
+        Ok(Self { min, max })
+    }
+
+    pub fn contains(&self, count: usize) -> bool {
+        (self.min as usize..=self.max as usize).contains(&count)
+    }
+}
+
+impl Default for OptionCountRange {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl std::fmt::Display for OptionCountRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.min, self.max)
+    }
+}
 
-        write!(f, "{}\n{}", self.text, question_options_string)
+impl Hashable for OptionCountRange {
+    fn to_bytes(&self) -> Vec<u8> {
+        [self.min.to_bytes(), self.max.to_bytes()].concat()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use fake::{Fake, Faker};
     use proptest::prelude::*;
 
+    use rust_decimal::Decimal;
+
     use super::*;
+    use crate::sync::NumericAnswer;
 
     #[test]
     fn test_process() {
@@ -277,6 +601,134 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_option_count_range() {
+        let mut data: QuestionData = Faker.fake();
+        data.question_options = fake::vec![_; 7];
+
+        assert!(data.prepare_for_test().is_err());
+
+        data.option_count_range = OptionCountRange::new(2, 8).unwrap();
+
+        data.prepare_for_test().unwrap();
+    }
+
+    #[test]
+    fn test_explanation_prompt() {
+        let mut data: QuestionData = Faker.fake();
+        data.question_options = fake::vec![_; 2];
+
+        for (index, question_option) in data.question_options.iter_mut().enumerate() {
+            question_option.reference = index as u16;
+            question_option.explanation = None;
+        }
+
+        data.question_options[1].explanation = Some("Not this one".into());
+        data.prepare_for_test().unwrap();
+
+        let prompt = data.explanation_prompt();
+
+        assert!(prompt.contains("Correct option: a"));
+        assert!(prompt.contains("b. Not this one"));
+    }
+
+    #[test]
+    fn test_renumber_references() {
+        let mut data: QuestionData = Faker.fake();
+        data.question_options = fake::vec![_; 3];
+
+        for (question_option, reference) in data.question_options.iter_mut().zip([7, 3, 3]) {
+            question_option.reference = reference;
+        }
+
+        data.renumber_references();
+
+        assert_eq!(
+            data.question_options
+                .iter()
+                .map(|question_option| question_option.reference)
+                .collect::<Vec<u16>>(),
+            vec![2, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_references_out_of_range() {
+        let mut data: QuestionData = Faker.fake();
+        data.question_options = fake::vec![_; 27];
+        data.option_count_range = OptionCountRange::new(2, 30).unwrap();
+
+        assert!(data.prepare_for_test().is_err());
+    }
+
+    #[test]
+    fn test_translations() {
+        let mut data: QuestionData = Faker.fake();
+        data.question_options = fake::vec![_; 3];
+        data.translations.insert(
+            LanguageTag::new("pt").unwrap(),
+            TranslatedQuestion::new("Texto".into(), vec!["A".into(), "B".into()], None).unwrap(),
+        );
+
+        assert!(data.prepare_for_test().is_err());
+
+        data.translations
+            .values_mut()
+            .for_each(|translation| translation.question_options.push("C".into()));
+
+        data.prepare_for_test().unwrap();
+    }
+
+    #[test]
+    fn test_unit_rules() {
+        let mut data: QuestionData = Faker.fake();
+        data.text = "¿Dosis de 5mg/kg de amoxicilina?".into();
+        data.question_options[0].text = "5mg/kg cada 8 horas".into();
+        data.explanation = Some(ExplanationData {
+            text: "Se indican 5mg/kg.".into(),
+            by: "Medici".into(),
+            date: Utc::now(),
+            hash: Default::default(),
+        });
+        data.unit_rules = Some(UnitRules {
+            do_not_touch: vec!["5mg/kg".into()],
+            ..Default::default()
+        });
+
+        let mut default_data = data.clone();
+        default_data.unit_rules = None;
+
+        data.prepare_for_test().unwrap();
+        default_data.prepare_for_test().unwrap();
+
+        assert_eq!(data.text, "¿Dosis de 5mg/kg de amoxicilina?");
+        assert!(data
+            .question_options
+            .iter()
+            .any(|question_option| question_option.text == "5mg/kg cada 8 horas."));
+        assert_eq!(data.explanation.unwrap().text, "Se indican 5mg/kg.");
+        assert_eq!(
+            default_data.text,
+            "¿Dosis de 5\u{2009}mg/kg de amoxicilina?"
+        );
+        assert_ne!(data.hash, default_data.hash);
+    }
+
+    #[test]
+    fn test_numeric_kind() {
+        let mut data: QuestionData = Faker.fake();
+        data.question_options = fake::vec![_; 3];
+        data.kind = QuestionKind::Numeric(
+            NumericAnswer::new(Decimal::from(5), Some("mg".into()), Default::default()).unwrap(),
+        );
+
+        assert!(data.prepare_for_test().is_err());
+
+        data.question_options.clear();
+
+        data.prepare_for_test().unwrap();
+    }
+
     #[test]
     fn test_blank_option() {
         let mut data: QuestionData = Faker.fake();
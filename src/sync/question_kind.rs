@@ -0,0 +1,501 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::helpers::{format_text, prefixed_bytes};
+use super::validation_report::ValidationError;
+use crate::traits::Hashable;
+
+pub const MIN_MATCHING_PAIRS: usize = 3;
+pub const MIN_ORDERING_ITEMS: usize = 3;
+
+/// How a question is answered. Multiple choice questions are answered with
+/// their options; the other kinds carry their answer and have no options.
+#[derive(Serialize, Deserialize, Default, PartialEq, Hash, Eq, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuestionKind {
+    #[default]
+    MultipleChoice,
+    Numeric(NumericAnswer),
+    Matching(MatchingAnswer),
+    Ordering(OrderingAnswer),
+}
+
+impl QuestionKind {
+    pub fn is_multiple_choice(&self) -> bool {
+        matches!(self, Self::MultipleChoice)
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        match self {
+            Self::MultipleChoice => Ok(()),
+            Self::Numeric(answer) => answer.process(),
+            Self::Matching(answer) => answer.process(),
+            Self::Ordering(answer) => answer.process(),
+        }
+    }
+}
+
+impl Hashable for QuestionKind {
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::MultipleChoice => "multiple_choice".to_string().to_bytes(),
+            Self::Numeric(answer) => ["numeric".to_string().to_bytes(), answer.to_bytes()].concat(),
+            Self::Matching(answer) => {
+                ["matching".to_string().to_bytes(), answer.to_bytes()].concat()
+            }
+            Self::Ordering(answer) => {
+                ["ordering".to_string().to_bytes(), answer.to_bytes()].concat()
+            }
+        }
+    }
+}
+
+/// Answer of a calculation, e.g. a dosage, graded with Decimal math so
+/// tolerances are exact.
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct NumericAnswer {
+    #[cfg_attr(feature = "ts_types", ts(as = "String"))]
+    pub value: Decimal,
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub tolerance: Tolerance,
+}
+
+impl NumericAnswer {
+    pub fn new(value: Decimal, unit: Option<String>, tolerance: Tolerance) -> Result<Self> {
+        let mut data = Self {
+            value,
+            unit,
+            tolerance,
+        };
+
+        data.process()?;
+
+        Ok(data)
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        self.format();
+        self.check().map_err(ValidationError::wrap)?;
+
+        Ok(())
+    }
+
+    fn check(&self) -> Result<()> {
+        match self.tolerance {
+            Tolerance::Absolute(margin) if margin < Decimal::ZERO => {
+                bail!("invalid numeric answer tolerance {margin}")
+            }
+            Tolerance::Relative(share) if share < Decimal::ZERO || share > Decimal::ONE => {
+                bail!("invalid numeric answer relative tolerance {share}")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn format(&mut self) {
+        self.value = self.value.normalize();
+        self.tolerance = match self.tolerance {
+            Tolerance::Absolute(margin) => Tolerance::Absolute(margin.normalize()),
+            Tolerance::Relative(share) => Tolerance::Relative(share.normalize()),
+        };
+        self.unit = self
+            .unit
+            .as_deref()
+            .map(str::trim)
+            .filter(|unit| !unit.is_empty())
+            .map(Into::into);
+    }
+
+    /// Largest accepted distance from the expected value.
+    pub fn margin(&self) -> Decimal {
+        match self.tolerance {
+            Tolerance::Absolute(margin) => margin,
+            Tolerance::Relative(share) => (self.value * share).abs(),
+        }
+    }
+
+    pub fn is_correct(&self, answer: Decimal) -> bool {
+        (answer - self.value).abs() <= self.margin()
+    }
+
+    /// Parses an answer as typed by a user, with a decimal point or comma and
+    /// optionally followed by the unit, e.g. "2,5 mg".
+    pub fn parse_answer(&self, input: &str) -> Result<Decimal> {
+        let mut number = input.trim();
+
+        if let Some(unit) = &self.unit {
+            if number.len() > unit.len()
+                && number.is_char_boundary(number.len() - unit.len())
+                && number[number.len() - unit.len()..].eq_ignore_ascii_case(unit)
+            {
+                number = number[..number.len() - unit.len()].trim_end();
+            }
+        }
+
+        let number = if number.contains('.') {
+            number.to_owned()
+        } else {
+            number.replacen(',', ".", 1)
+        };
+
+        Decimal::from_str(&number).map_err(|_| anyhow!("invalid numeric answer {input}"))
+    }
+
+    /// Whether the typed answer is within the tolerance. Unparseable answers are wrong.
+    pub fn grade(&self, input: &str) -> bool {
+        self.parse_answer(input)
+            .is_ok_and(|answer| self.is_correct(answer))
+    }
+}
+
+impl std::fmt::Display for NumericAnswer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.unit {
+            Some(unit) => write!(f, "{} {unit}", self.value),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+impl Hashable for NumericAnswer {
+    fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.value.to_bytes(),
+            self.unit.to_bytes(),
+            self.tolerance.to_bytes(),
+        ]
+        .concat()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum Tolerance {
+    /// Largest accepted difference, in the answer's unit.
+    Absolute(#[cfg_attr(feature = "ts_types", ts(as = "String"))] Decimal),
+    /// Largest accepted difference as a share of the expected value, from 0 to 1.
+    Relative(#[cfg_attr(feature = "ts_types", ts(as = "String"))] Decimal),
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self::Absolute(Decimal::ZERO)
+    }
+}
+
+impl Hashable for Tolerance {
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Absolute(margin) => ["absolute".to_string().to_bytes(), margin.to_bytes()],
+            Self::Relative(share) => ["relative".to_string().to_bytes(), share.to_bytes()],
+        }
+        .concat()
+    }
+}
+
+/// Left items to be matched with right items, e.g. drugs and their mechanisms.
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct MatchingAnswer {
+    pub pairs: Vec<MatchingPair>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct MatchingPair {
+    pub left: String,
+    pub right: String,
+}
+
+impl MatchingAnswer {
+    pub fn new(pairs: Vec<MatchingPair>) -> Result<Self> {
+        let mut data = Self { pairs };
+
+        data.process()?;
+
+        Ok(data)
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        for pair in &mut self.pairs {
+            pair.left = format_text(&pair.left);
+            pair.right = format_text(&pair.right);
+        }
+
+        self.check().map_err(ValidationError::wrap)
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.pairs.len() < MIN_MATCHING_PAIRS {
+            bail!(
+                "matching questions need at least {MIN_MATCHING_PAIRS} pairs, got {}",
+                self.pairs.len()
+            );
+        }
+
+        if self
+            .pairs
+            .iter()
+            .any(|pair| pair.left.is_empty() || pair.right.is_empty())
+        {
+            bail!("matching question with an empty item");
+        }
+
+        if has_duplicates(self.pairs.iter().map(|pair| &pair.left))
+            || has_duplicates(self.pairs.iter().map(|pair| &pair.right))
+        {
+            bail!("matching question with duplicate items");
+        }
+
+        Ok(())
+    }
+
+    /// Order in which the right items are shown, as indices of `pairs`. It
+    /// depends only on `seed`, e.g. the question ID, so every client shows the
+    /// same order.
+    pub fn right_order(&self, seed: &[u8]) -> Vec<usize> {
+        presentation_order(self.pairs.iter().map(|pair| &pair.right), seed)
+    }
+
+    /// Share of left items matched with their right item, from 0 to 1, given
+    /// the index in `pairs` of the right item chosen for each left item.
+    pub fn grade(&self, response: &[Option<usize>]) -> Decimal {
+        let correct = self
+            .pairs
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| response.get(*index).copied().flatten() == Some(*index))
+            .count();
+
+        share(correct, self.pairs.len())
+    }
+}
+
+impl Hashable for MatchingAnswer {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.pairs
+            .iter()
+            .flat_map(|pair| [prefixed_bytes(&pair.left), prefixed_bytes(&pair.right)].concat())
+            .collect()
+    }
+}
+
+/// Items in their correct sequence, e.g. the steps of a procedure.
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct OrderingAnswer {
+    pub items: Vec<String>,
+}
+
+impl OrderingAnswer {
+    pub fn new(items: Vec<String>) -> Result<Self> {
+        let mut data = Self { items };
+
+        data.process()?;
+
+        Ok(data)
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        for item in &mut self.items {
+            *item = format_text(item);
+        }
+
+        self.check().map_err(ValidationError::wrap)
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.items.len() < MIN_ORDERING_ITEMS {
+            bail!(
+                "ordering questions need at least {MIN_ORDERING_ITEMS} items, got {}",
+                self.items.len()
+            );
+        }
+
+        if self.items.iter().any(String::is_empty) {
+            bail!("ordering question with an empty item");
+        }
+
+        if has_duplicates(&self.items) {
+            bail!("ordering question with duplicate items");
+        }
+
+        Ok(())
+    }
+
+    /// Order in which the items are shown, as indices of `items`, which is
+    /// never the correct one. Like `MatchingAnswer::right_order`, it depends
+    /// only on `seed`.
+    pub fn presented_order(&self, seed: &[u8]) -> Vec<usize> {
+        let mut order = presentation_order(&self.items, seed);
+
+        if order.iter().enumerate().all(|(index, &item)| index == item) {
+            order.rotate_left(1);
+        }
+
+        order
+    }
+
+    /// Share of pairs of items that the response puts in the right relative
+    /// order, from 0 to 1, so one misplaced item only costs part of the credit.
+    /// The response lists indices of `items`; missing items count as wrong.
+    pub fn grade(&self, response: &[usize]) -> Decimal {
+        let positions = (0..self.items.len())
+            .map(|item| response.iter().position(|&index| index == item))
+            .collect::<Vec<_>>();
+        let pair_count = self.items.len() * self.items.len().saturating_sub(1) / 2;
+        let correct = (0..self.items.len())
+            .flat_map(|first| (first + 1..self.items.len()).map(move |second| (first, second)))
+            .filter(|&(first, second)| {
+                matches!((positions[first], positions[second]), (Some(a), Some(b)) if a < b)
+            })
+            .count();
+
+        share(correct, pair_count)
+    }
+}
+
+impl Hashable for OrderingAnswer {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.items
+            .iter()
+            .flat_map(|item| prefixed_bytes(item))
+            .collect()
+    }
+}
+
+fn has_duplicates<'a>(items: impl IntoIterator<Item = &'a String>) -> bool {
+    let mut seen = HashSet::new();
+
+    items
+        .into_iter()
+        .any(|item| !seen.insert(item.to_lowercase()))
+}
+
+fn presentation_order<'a>(items: impl IntoIterator<Item = &'a String>, seed: &[u8]) -> Vec<usize> {
+    let mut order = items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| (blake3::hash(&[seed, item.as_bytes()].concat()), index))
+        .collect::<Vec<_>>();
+    order.sort_by_key(|(hash, _)| *hash.as_bytes());
+
+    order.into_iter().map(|(_, index)| index).collect()
+}
+
+fn share(part: usize, total: usize) -> Decimal {
+    if total == 0 {
+        return Decimal::ZERO;
+    }
+
+    (Decimal::from(part) / Decimal::from(total)).round_dp(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grade() {
+        let answer = NumericAnswer::new(
+            Decimal::new(250, 2),
+            Some(" mL ".into()),
+            Tolerance::Relative(Decimal::new(4, 2)),
+        )
+        .unwrap();
+
+        assert_eq!(answer.to_string(), "2.5 mL");
+        assert_eq!(answer.margin(), Decimal::new(1, 1));
+        assert!(answer.grade("2,6 ml"));
+        assert!(answer.grade("2.4"));
+        assert!(!answer.grade("2.61"));
+        assert!(!answer.grade("dos"));
+
+        let kind: QuestionKind = serde_json::from_value(serde_json::json!({
+            "type": "numeric",
+            "value": "120",
+        }))
+        .unwrap();
+
+        assert!(
+            matches!(&kind, QuestionKind::Numeric(answer) if answer.is_correct(Decimal::from(120)))
+        );
+        assert!(NumericAnswer::new(
+            Decimal::ONE,
+            None,
+            Tolerance::Absolute(Decimal::NEGATIVE_ONE)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_matching_and_ordering() {
+        let matching = MatchingAnswer::new(
+            [
+                ("Furosemida", "Asa de Henle"),
+                ("Tiazida", "Túbulo distal"),
+                ("Amilorida", "Túbulo colector"),
+            ]
+            .into_iter()
+            .map(|(left, right)| MatchingPair {
+                left: left.into(),
+                right: right.into(),
+            })
+            .collect(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            matching.grade(&[Some(0), Some(2), Some(1)]),
+            Decimal::new(3333, 4)
+        );
+        assert_eq!(matching.grade(&[Some(0), Some(1), Some(2)]), Decimal::ONE);
+        assert_eq!(matching.right_order(b"seed"), matching.right_order(b"seed"));
+
+        let ordering =
+            OrderingAnswer::new(vec!["A".into(), "B".into(), "C".into(), "D".into()]).unwrap();
+
+        assert_eq!(ordering.grade(&[0, 1, 2, 3]), Decimal::ONE);
+        // Moving D first only breaks its 3 pairs out of 6.
+        assert_eq!(ordering.grade(&[3, 0, 1, 2]), Decimal::new(5, 1));
+        assert_eq!(ordering.grade(&[]), Decimal::ZERO);
+        assert_eq!(OrderingAnswer { items: vec![] }.grade(&[]), Decimal::ZERO);
+        assert_ne!(ordering.presented_order(b"seed"), vec![0, 1, 2, 3]);
+        assert!(OrderingAnswer::new(vec!["A".into(), "a".into(), "B".into()]).is_err());
+        assert!(MatchingAnswer::new(matching.pairs[..2].to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_items_hash_separately() {
+        let ordering = |items: [&str; 3]| OrderingAnswer {
+            items: items.map(String::from).to_vec(),
+        };
+        let matching = |left: &str, right: &str| MatchingAnswer {
+            pairs: vec![MatchingPair {
+                left: left.into(),
+                right: right.into(),
+            }],
+        };
+
+        assert_ne!(
+            ordering(["ab", "c", "d"]).to_bytes(),
+            ordering(["a", "bc", "d"]).to_bytes()
+        );
+        assert_ne!(
+            matching("Furosemida", "Asa").to_bytes(),
+            matching("Furosemid", "aAsa").to_bytes()
+        );
+    }
+}
@@ -1,27 +1,46 @@
 use anyhow::Result;
-#[cfg(test)]
-use fake::{Dummy, Fake, Faker};
+#[cfg(any(test, feature = "testing"))]
+use fake::Dummy;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::{capitalize_first_char, helpers::format_text};
-use crate::traits::Hashable;
+use super::content_entity::ContentEntity;
+use super::unit_formatting::{UnitFormatter, DEFAULT_UNIT_FORMATTER};
+use super::{capitalize_first_char, helpers::format_text_with, ValidationError};
+use crate::traits::{Hashable, Syncable};
 
 #[non_exhaustive]
-#[derive(medici_macros::Hashable, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Debug)]
-#[cfg_attr(test, derive(Dummy))]
+#[derive(
+    medici_macros::Hashable,
+    medici_macros::SyncEntity,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[medici(key = "id")]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
 pub struct QuestionOptionData {
     pub id: Uuid,
 
     pub question_id: Uuid,
     pub text: String,
-    #[cfg_attr(test, dummy(default))]
+    #[medici(builder_default)]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
     pub is_correct: bool,
+    #[medici(builder_default)]
     #[medici(skip_hash)]
     pub reference: u16,
+    #[medici(builder_default)]
     #[medici(skip_hash)]
-    #[cfg_attr(test, dummy(default))]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
     pub preserve_case: bool,
+    #[serde(default)]
+    pub explanation: Option<String>,
 
     pub hash: String,
 }
@@ -34,6 +53,7 @@ impl QuestionOptionData {
         correct: bool,
         reference: u16,
         preserve_case: bool,
+        explanation: Option<String>,
     ) -> Result<Self> {
         let mut data = Self {
             id,
@@ -43,6 +63,7 @@ impl QuestionOptionData {
             hash: Default::default(),
             reference,
             preserve_case,
+            explanation,
         };
 
         data.process()?;
@@ -51,8 +72,13 @@ impl QuestionOptionData {
     }
 
     pub fn process(&mut self) -> Result<()> {
-        self.format();
-        self.check()?;
+        self.process_with(&DEFAULT_UNIT_FORMATTER)
+    }
+
+    /// Like `process`, with the unit rules of the option's course.
+    pub fn process_with(&mut self, formatter: &UnitFormatter) -> Result<()> {
+        self.format(formatter);
+        self.check().map_err(ValidationError::wrap)?;
 
         self.refresh_hash();
 
@@ -73,8 +99,8 @@ impl QuestionOptionData {
         Ok(())
     }
 
-    fn format(&mut self) {
-        self.text = format_text(&self.text);
+    fn format(&mut self, formatter: &UnitFormatter) {
+        self.text = format_text_with(&self.text, formatter);
 
         if !self.text.is_empty() {
             self.ensure_text_ends_with_period();
@@ -83,6 +109,12 @@ impl QuestionOptionData {
         if !self.preserve_case {
             capitalize_first_char(&mut self.text);
         }
+
+        self.explanation = self
+            .explanation
+            .as_deref()
+            .map(|explanation| format_text_with(explanation, formatter))
+            .filter(|explanation| !explanation.is_empty());
     }
 
     fn ensure_text_ends_with_period(&mut self) {
@@ -102,6 +134,8 @@ impl std::fmt::Display for QuestionOptionData {
 
 #[cfg(test)]
 mod tests {
+    use fake::{Fake, Faker};
+
     use super::*;
 
     #[test]
@@ -123,6 +157,15 @@ mod tests {
         assert_eq!(data.text, "o.");
     }
 
+    #[test]
+    fn test_process_explanation() {
+        let mut data: QuestionOptionData = Faker.fake();
+        data.explanation = Some("  ".into());
+        data.process().unwrap();
+
+        assert_eq!(data.explanation, None);
+    }
+
     #[test]
     fn test_hash() {
         let mut data1: QuestionOptionData = Faker.fake();
@@ -0,0 +1,239 @@
+//! JSON Patch (RFC 6902) edits of questions from the admin API. Patches apply
+//! to an editable view of the question rather than the question itself, so
+//! only whitelisted fields can change:
+//!
+//! - `/text`
+//! - `/topic`, the topic name
+//! - `/tags` and `/tags/<index>`
+//! - `/question_options/<index>/text` and `/question_options/<index>/is_correct`
+
+use anyhow::{bail, Result};
+use json_patch::{Patch, PatchOperation};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::{QuestionData, QuestionTopicData, ValidationError};
+
+/// A field changed by a patch, after formatting, for audit logs.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct FieldChange {
+    /// Options are identified by id, e.g. `/question_options/<id>/text`.
+    pub path: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+struct EditableQuestion {
+    text: String,
+    topic: String,
+    tags: Vec<String>,
+    question_options: Vec<EditableQuestionOption>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+struct EditableQuestionOption {
+    id: Uuid,
+    text: String,
+    is_correct: bool,
+}
+
+impl QuestionData {
+    /// Applies `patch` to a copy of the question and processes it again,
+    /// returning it with the fields that changed. Rejected patches fail with
+    /// a `ValidationError`.
+    pub fn apply_patch(&self, patch: &Patch) -> Result<(Self, Vec<FieldChange>)> {
+        self.patched(patch).map_err(ValidationError::wrap)
+    }
+
+    fn patched(&self, patch: &Patch) -> Result<(Self, Vec<FieldChange>)> {
+        for operation in &patch.0 {
+            check_patch_path(operation.path().as_str())?;
+
+            if let PatchOperation::Move(move_operation) = operation {
+                check_patch_path(move_operation.from.as_str())?;
+            }
+            if let PatchOperation::Copy(copy_operation) = operation {
+                check_patch_path(copy_operation.from.as_str())?;
+            }
+        }
+
+        let before = EditableQuestion::from(self);
+        let mut document = serde_json::to_value(&before)?;
+
+        json_patch::patch(&mut document, patch)?;
+
+        let edited: EditableQuestion = serde_json::from_value(document)?;
+
+        if edited.question_options.len() != before.question_options.len()
+            || edited
+                .question_options
+                .iter()
+                .zip(&before.question_options)
+                .any(|(edited_option, option)| edited_option.id != option.id)
+        {
+            bail!("question options can't be added, removed or reordered");
+        }
+
+        let mut question = self.clone();
+        question.text = edited.text;
+        question.tags = edited.tags;
+
+        if edited.topic != before.topic {
+            question.topic = QuestionTopicData::new(question.course_key.clone(), edited.topic)?;
+            question.topic_by = None;
+        }
+
+        for edited_option in edited.question_options {
+            let Some(question_option) = question
+                .question_options
+                .iter_mut()
+                .find(|question_option| question_option.id == edited_option.id)
+            else {
+                continue;
+            };
+
+            question_option.text = edited_option.text;
+            question_option.is_correct = edited_option.is_correct;
+        }
+
+        question.process()?;
+
+        let changes = field_changes(&before, &EditableQuestion::from(&question));
+
+        Ok((question, changes))
+    }
+}
+
+fn check_patch_path(path: &str) -> Result<()> {
+    let segments = path.split('/').skip(1).collect::<Vec<_>>();
+    let is_index = |segment: &str| segment.parse::<usize>().is_ok();
+
+    let allowed = match segments[..] {
+        ["text"] | ["topic"] | ["tags"] => true,
+        ["tags", index] => index == "-" || is_index(index),
+        ["question_options", index, "text" | "is_correct"] => is_index(index),
+        _ => false,
+    };
+
+    if !allowed {
+        bail!("patching {path:?} isn't allowed");
+    }
+
+    Ok(())
+}
+
+fn field_changes(before: &EditableQuestion, after: &EditableQuestion) -> Vec<FieldChange> {
+    let mut changes = vec![];
+    let mut push_change = |path: String, old: Value, new: Value| {
+        if old != new {
+            changes.push(FieldChange { path, old, new });
+        }
+    };
+
+    push_change(
+        "/text".into(),
+        before.text.clone().into(),
+        after.text.clone().into(),
+    );
+    push_change(
+        "/topic".into(),
+        before.topic.clone().into(),
+        after.topic.clone().into(),
+    );
+    push_change(
+        "/tags".into(),
+        before.tags.clone().into(),
+        after.tags.clone().into(),
+    );
+
+    for option_before in &before.question_options {
+        let Some(option_after) = after
+            .question_options
+            .iter()
+            .find(|option_after| option_after.id == option_before.id)
+        else {
+            continue;
+        };
+
+        push_change(
+            format!("/question_options/{}/text", option_before.id),
+            option_before.text.clone().into(),
+            option_after.text.clone().into(),
+        );
+        push_change(
+            format!("/question_options/{}/is_correct", option_before.id),
+            option_before.is_correct.into(),
+            option_after.is_correct.into(),
+        );
+    }
+
+    changes
+}
+
+impl From<&QuestionData> for EditableQuestion {
+    fn from(question: &QuestionData) -> Self {
+        Self {
+            text: question.text.clone(),
+            topic: question.topic.name.clone(),
+            tags: question.tags.clone(),
+            question_options: question
+                .question_options
+                .iter()
+                .map(|question_option| EditableQuestionOption {
+                    id: question_option.id,
+                    text: question_option.text.clone(),
+                    is_correct: question_option.is_correct,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_apply_patch() {
+        let mut question: QuestionData = Faker.fake();
+        question.prepare_for_test().unwrap();
+
+        let patch: Patch = serde_json::from_value(json!([
+            { "op": "replace", "path": "/text", "value": " ¿Cuál es el diagnóstico  más probable? " },
+            { "op": "replace", "path": "/question_options/1/text", "value": "Neumonía" },
+        ]))
+        .unwrap();
+
+        let option_id = question.question_options[1].id;
+        let (patched, changes) = question.apply_patch(&patch).unwrap();
+
+        assert_eq!(patched.text, "¿Cuál es el diagnóstico más probable?");
+        assert_ne!(patched.hash, question.hash);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| change.path.as_str())
+                .collect::<Vec<_>>(),
+            ["/text", &format!("/question_options/{option_id}/text")]
+        );
+        assert_eq!(
+            changes[0].new,
+            json!("¿Cuál es el diagnóstico más probable?")
+        );
+
+        let forbidden: Patch = serde_json::from_value(json!([
+            { "op": "replace", "path": "/hash", "value": "" },
+        ]))
+        .unwrap();
+
+        assert!(question
+            .apply_patch(&forbidden)
+            .unwrap_err()
+            .is::<ValidationError>());
+    }
+}
@@ -1,20 +1,39 @@
+use std::str::FromStr;
+
 use anyhow::{bail, Result};
 use chrono::NaiveDate;
-#[cfg(test)]
-use fake::{Dummy, Fake, Faker};
+#[cfg(any(test, feature = "testing"))]
+use fake::Dummy;
 use serde::{Deserialize, Serialize};
 
-use crate::traits::Hashable;
+use super::content_entity::ContentEntity;
+use super::helpers::{decode_key_field, encode_key_field, has_reserved_key_chars};
+use super::validation_report::ValidationError;
+use crate::slug::fold_accent;
+use crate::traits::{Hashable, Syncable};
 
 #[non_exhaustive]
-#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Debug)]
-#[cfg_attr(test, derive(Dummy))]
+#[derive(
+    medici_macros::SyncEntity,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Hash,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[medici(key_fn = "key", key_type = "String")]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
 pub struct QuestionSourceData {
     pub course_key: String,
     pub r#type: QuestionSourceType,
     pub name: Option<String>,
     pub date: Option<NaiveDate>,
-    pub variant: Option<String>,
+    #[serde(default, alias = "variant")]
+    #[cfg_attr(any(test, feature = "testing"), dummy(default))]
+    pub period: Option<ExamPeriod>,
 }
 
 impl QuestionSourceData {
@@ -26,14 +45,14 @@ impl QuestionSourceData {
         r#type: QuestionSourceType,
         name: Option<String>,
         date: Option<NaiveDate>,
-        variant: Option<String>,
+        period: Option<ExamPeriod>,
     ) -> Result<Self> {
         let mut data = Self {
             course_key,
             r#type,
             name,
             date,
-            variant,
+            period,
         };
 
         data.process()?;
@@ -42,6 +61,30 @@ impl QuestionSourceData {
     }
 
     pub fn key(&self) -> String {
+        format!(
+            "{}{}{}{}{}{}{}{}{}",
+            self.course_key,
+            Self::KEY_SEPARATOR,
+            self.r#type,
+            Self::KEY_SEPARATOR,
+            self.name
+                .as_deref()
+                .map(encode_key_field)
+                .unwrap_or(Self::EMPTY_FIELD_KEY_VALUE.into()),
+            Self::KEY_SEPARATOR,
+            self.date
+                .map(|date| date.to_string())
+                .unwrap_or(Self::EMPTY_FIELD_KEY_VALUE.into()),
+            Self::KEY_SEPARATOR,
+            self.period
+                .as_ref()
+                .map(|period| encode_key_field(&period.to_string()))
+                .unwrap_or(Self::EMPTY_FIELD_KEY_VALUE.into())
+        )
+    }
+
+    /// Key format used before field values were escaped, to migrate stored keys.
+    pub fn legacy_key(&self) -> String {
         format!(
             "{}{}{}{}{}{}{}{}{}",
             self.course_key,
@@ -54,20 +97,63 @@ impl QuestionSourceData {
                 .map(|date| date.to_string())
                 .unwrap_or(Self::EMPTY_FIELD_KEY_VALUE.into()),
             Self::KEY_SEPARATOR,
-            self.variant
-                .as_deref()
-                .unwrap_or(Self::EMPTY_FIELD_KEY_VALUE)
+            self.period
+                .as_ref()
+                .map(|period| period.to_string())
+                .unwrap_or(Self::EMPTY_FIELD_KEY_VALUE.into())
         )
     }
 
-    fn process(&mut self) -> Result<()> {
+    pub fn from_key(key: &str) -> Result<Self, SourceKeyError> {
+        fn optional_field(value: &str) -> Result<Option<String>, SourceKeyError> {
+            if value == QuestionSourceData::EMPTY_FIELD_KEY_VALUE {
+                return Ok(None);
+            }
+
+            decode_key_field(value)
+                .map(Some)
+                .ok_or_else(|| SourceKeyError::InvalidEncoding(value.into()))
+        }
+
+        let fields = key.split(Self::KEY_SEPARATOR).collect::<Vec<&str>>();
+
+        let [course_key, r#type, name, date, period] = fields[..] else {
+            return Err(SourceKeyError::FieldCount(fields.len()));
+        };
+
+        if course_key.is_empty() {
+            return Err(SourceKeyError::EmptyCourseKey);
+        }
+
+        let r#type = QuestionSourceType::from_str(r#type)
+            .map_err(|_| SourceKeyError::InvalidType(r#type.into()))?;
+
+        let date = optional_field(date)?
+            .map(|date| NaiveDate::from_str(&date).map_err(|_| SourceKeyError::InvalidDate(date)))
+            .transpose()?;
+
+        Self::new(
+            course_key.into(),
+            r#type,
+            optional_field(name)?,
+            date,
+            optional_field(period)?.map(ExamPeriod::from),
+        )
+        .map_err(|error| SourceKeyError::InvalidSource(error.to_string()))
+    }
+
+    pub fn process(&mut self) -> Result<()> {
         self.format();
-        self.check()?;
+        self.check().map_err(ValidationError::wrap)?;
 
         Ok(())
     }
 
     fn check(&self) -> Result<()> {
+        if has_reserved_key_chars(&self.course_key) {
+            bail!("invalid course key {} in question source", self.course_key);
+        }
+
         if (self.date.is_none()
             && (self.r#type == QuestionSourceType::Exam
                 || self.r#type == QuestionSourceType::Partial))
@@ -92,9 +178,34 @@ impl Hashable for QuestionSourceData {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SourceKeyError {
+    FieldCount(usize),
+    EmptyCourseKey,
+    InvalidType(String),
+    InvalidDate(String),
+    InvalidEncoding(String),
+    InvalidSource(String),
+}
+
+impl std::fmt::Display for SourceKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FieldCount(count) => write!(f, "source key has {count} field(s), expected 5"),
+            Self::EmptyCourseKey => write!(f, "source key has an empty course key"),
+            Self::InvalidType(r#type) => write!(f, "invalid source type {type}"),
+            Self::InvalidDate(date) => write!(f, "invalid source date {date}"),
+            Self::InvalidEncoding(field) => write!(f, "invalid source key field {field}"),
+            Self::InvalidSource(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for SourceKeyError {}
+
 #[derive(
-    sqlx::Type,
     strum::Display,
+    strum::EnumString,
     Serialize,
     Deserialize,
     PartialEq,
@@ -105,8 +216,13 @@ impl Hashable for QuestionSourceData {
     Clone,
     Debug,
 )]
-#[cfg_attr(test, derive(Dummy))]
-#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
+#[cfg_attr(
+    feature = "db",
+    derive(sqlx::Type),
+    sqlx(type_name = "text", rename_all = "snake_case")
+)]
 #[serde(rename_all = "snake_case")]
 #[strum(serialize_all = "snake_case")]
 pub enum QuestionSourceType {
@@ -116,8 +232,67 @@ pub enum QuestionSourceType {
     Other,
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, PartialOrd, Ord, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS), ts(type = "string"))]
+#[serde(from = "String", into = "String")]
+pub enum ExamPeriod {
+    First,
+    Second,
+    December,
+    February,
+    Custom(String),
+}
+
+impl ExamPeriod {
+    pub fn parse(text: &str) -> Self {
+        let normalized = text
+            .chars()
+            .map(fold_accent)
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        match normalized.as_str() {
+            "first" | "1" | "1ra" | "1er" | "1era" | "1a" | "primera" | "primer" | "v1" => {
+                Self::First
+            }
+            "second" | "2" | "2da" | "2do" | "2a" | "segunda" | "segundo" | "v2" => Self::Second,
+            "december" | "diciembre" | "dic" => Self::December,
+            "february" | "febrero" | "feb" => Self::February,
+            _ => Self::Custom(text.trim().into()),
+        }
+    }
+}
+
+impl From<String> for ExamPeriod {
+    fn from(value: String) -> Self {
+        Self::parse(&value)
+    }
+}
+
+impl From<ExamPeriod> for String {
+    fn from(value: ExamPeriod) -> Self {
+        value.to_string()
+    }
+}
+
+impl std::fmt::Display for ExamPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::First => write!(f, "first"),
+            Self::Second => write!(f, "second"),
+            Self::December => write!(f, "december"),
+            Self::February => write!(f, "february"),
+            Self::Custom(period) => write!(f, "{period}"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use fake::{Fake, Faker};
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -128,4 +303,87 @@ mod tests {
 
         assert!(data.process().is_err());
     }
+
+    #[test]
+    fn test_exam_period() {
+        assert_eq!(ExamPeriod::parse(" 2da "), ExamPeriod::Second);
+        assert_eq!(ExamPeriod::parse("Segunda"), ExamPeriod::Second);
+        assert_eq!(ExamPeriod::parse("V1"), ExamPeriod::First);
+        assert_eq!(ExamPeriod::parse("Diciembre"), ExamPeriod::December);
+        assert_eq!(
+            ExamPeriod::parse("Extraordinario "),
+            ExamPeriod::Custom("Extraordinario".into())
+        );
+    }
+
+    #[test]
+    fn test_deserialize_variant() {
+        let data: QuestionSourceData = serde_json::from_str(
+            r#"{"course_key":"c","type":"exam","name":null,"date":"2024-02-01","variant":"segunda"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(data.period, Some(ExamPeriod::Second));
+        assert_eq!(data.key(), "c::exam::!::2024-02-01::second");
+    }
+
+    #[test]
+    fn test_from_key_errors() {
+        assert_eq!(
+            QuestionSourceData::from_key("c::exam::!"),
+            Err(SourceKeyError::FieldCount(3))
+        );
+        assert_eq!(
+            QuestionSourceData::from_key("c::quiz::!::!::!"),
+            Err(SourceKeyError::InvalidType("quiz".into()))
+        );
+        assert_eq!(
+            QuestionSourceData::from_key("c::exam::!::2024-13-01::!"),
+            Err(SourceKeyError::InvalidDate("2024-13-01".into()))
+        );
+        assert!(matches!(
+            QuestionSourceData::from_key("c::exam::!::!::!"),
+            Err(SourceKeyError::InvalidSource(_))
+        ));
+    }
+
+    fn source_type_strategy() -> impl Strategy<Value = QuestionSourceType> {
+        prop_oneof![
+            Just(QuestionSourceType::Exam),
+            Just(QuestionSourceType::Partial),
+            Just(QuestionSourceType::SelfAssessment),
+            Just(QuestionSourceType::Other),
+        ]
+    }
+
+    fn period_strategy() -> impl Strategy<Value = ExamPeriod> {
+        prop_oneof![
+            Just(ExamPeriod::First),
+            Just(ExamPeriod::Second),
+            Just(ExamPeriod::December),
+            Just(ExamPeriod::February),
+            "[A-Z][a-z:!]{5,10}x".prop_map(ExamPeriod::Custom),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_from_key_round_trip(
+            course_key in "[a-z0-9_-]{1,10}",
+            r#type in source_type_strategy(),
+            name in proptest::option::of("[a-zA-Z0-9:!%]([a-zA-Z0-9:!% ]{0,10}[a-zA-Z0-9:!%])?"),
+            days in proptest::option::of(0..20_000i64),
+            period in proptest::option::of(period_strategy()),
+        ) {
+            let date = days.map(|days| {
+                NaiveDate::from_ymd_opt(1980, 1, 1).unwrap() + chrono::Duration::days(days)
+            });
+
+            let Ok(data) = QuestionSourceData::new(course_key, r#type, name, date, period) else {
+                return Ok(());
+            };
+
+            prop_assert_eq!(QuestionSourceData::from_key(&data.key()).unwrap(), data);
+        }
+    }
 }
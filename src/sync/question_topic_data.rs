@@ -1,17 +1,30 @@
-use anyhow::Result;
-#[cfg(test)]
-use fake::{Dummy, Fake, Faker};
+use anyhow::{bail, Result};
+#[cfg(any(test, feature = "testing"))]
+use fake::Dummy;
 use serde::{Deserialize, Serialize};
 
+use super::content_entity::ContentEntity;
 use super::{
     capitalize_first_char,
-    helpers::{format_text, remove_end_period},
+    helpers::{encode_key_field, format_text, has_reserved_key_chars, remove_end_period},
+    ValidationError,
 };
-use crate::traits::Hashable;
+use crate::traits::{Hashable, Syncable};
 
 #[non_exhaustive]
-#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Debug)]
-#[cfg_attr(test, derive(Dummy))]
+#[derive(
+    medici_macros::SyncEntity,
+    medici_macros::Builder,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Hash,
+    Eq,
+    Clone,
+    Debug,
+)]
+#[medici(key_fn = "key", key_type = "String")]
+#[cfg_attr(any(test, feature = "testing"), derive(Dummy))]
 pub struct QuestionTopicData {
     pub course_key: String,
     pub name: String,
@@ -24,15 +37,38 @@ impl QuestionTopicData {
     pub fn new(course_key: String, name: String) -> Result<Self> {
         let mut data = Self { course_key, name };
 
-        data.format();
+        data.process()?;
 
         Ok(data)
     }
 
+    pub fn process(&mut self) -> Result<()> {
+        self.format();
+        self.check().map_err(ValidationError::wrap)
+    }
+
     pub fn key(&self) -> String {
+        format!(
+            "{}{}{}",
+            self.course_key,
+            Self::KEY_SEPARATOR,
+            encode_key_field(&self.name)
+        )
+    }
+
+    /// Key format used before field values were escaped, to migrate stored keys.
+    pub fn legacy_key(&self) -> String {
         format!("{}{}{}", self.course_key, Self::KEY_SEPARATOR, self.name)
     }
 
+    fn check(&self) -> Result<()> {
+        if has_reserved_key_chars(&self.course_key) {
+            bail!("invalid course key {} in topic", self.course_key);
+        }
+
+        Ok(())
+    }
+
     pub fn is_blank(&self) -> bool {
         self.name.is_empty()
     }
@@ -60,6 +96,8 @@ impl Hashable for QuestionTopicData {
 
 #[cfg(test)]
 mod tests {
+    use fake::{Fake, Faker};
+
     use super::*;
 
     #[test]
@@ -70,4 +108,28 @@ mod tests {
 
         assert_eq!(data.name, "Topic 1");
     }
+
+    #[test]
+    fn test_key() {
+        let data = QuestionTopicData::new("course".into(), "Tema: arritmias".into()).unwrap();
+
+        assert_eq!(data.key(), "course::Tema%3A arritmias");
+        assert_eq!(data.legacy_key(), "course::Tema: arritmias");
+        assert!(QuestionTopicData::new("course::x".into(), "Tema".into()).is_err());
+    }
+
+    #[test]
+    fn test_builder() {
+        let data = QuestionTopicData::builder()
+            .course_key("c")
+            .name(" topic. ")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            data,
+            QuestionTopicData::new("c".into(), "Topic".into()).unwrap()
+        );
+        assert!(QuestionTopicData::builder().name("topic").build().is_err());
+    }
 }
@@ -0,0 +1,552 @@
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::prelude::*;
+use serde::de::{DeserializeOwned, DeserializeSeed, Error, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use uuid::Uuid;
+
+use super::{
+    CaseData, CourseData, CourseRelation, ExamPeriod, ExplanationData, LanguageTag, LicenseData,
+    MediaAttachment, OptionCountRange, PreviewConfig, PublishState, QuestionData, QuestionKind,
+    QuestionOptionData, QuestionSourceData, QuestionSourceType, TranslatedQuestion, UnitRules,
+};
+
+/// Course as written in authoring files, with its questions inline.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct RawCourseData {
+    pub key: String,
+    pub name: String,
+    #[serde(default)]
+    pub slug: Option<String>,
+    pub short_name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    #[cfg_attr(feature = "ts_types", ts(as = "Option<String>"))]
+    pub price_in_uyu: Option<Decimal>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(alias = "image")]
+    pub image_file_name: PathBuf,
+    #[serde(default)]
+    pub alt_text: Option<String>,
+    #[serde(default)]
+    pub year: Option<u16>,
+    #[serde(default)]
+    pub order: Option<u16>,
+    #[serde(default)]
+    pub option_count_range: Option<OptionCountRange>,
+    #[serde(default)]
+    pub unit_rules: Option<UnitRules>,
+    #[serde(default)]
+    pub locale: LanguageTag,
+    #[serde(default)]
+    pub license: Option<LicenseData>,
+    #[serde(default)]
+    pub publish_state: PublishState,
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub unpublish_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub relations: Vec<CourseRelation>,
+    #[serde(default)]
+    pub preview: Option<PreviewConfig>,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    pub questions: Vec<RawQuestionData>,
+    #[serde(default)]
+    pub cases: Vec<RawCaseData>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct RawQuestionData {
+    pub id: Uuid,
+    pub text: String,
+    #[serde(default)]
+    pub explanation: Option<ExplanationData>,
+    pub topic: String,
+    #[serde(default)]
+    pub topic_by: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, alias = "image")]
+    pub image_file_name: Option<PathBuf>,
+    #[serde(default)]
+    pub alt_text: Option<String>,
+    #[serde(default)]
+    pub question_options: Vec<RawQuestionOptionData>,
+    pub source: RawQuestionSourceData,
+    #[serde(default)]
+    pub translations: BTreeMap<LanguageTag, TranslatedQuestion>,
+    #[serde(default)]
+    pub license: Option<LicenseData>,
+    #[serde(default)]
+    pub media: Vec<MediaAttachment>,
+    #[serde(default)]
+    pub kind: QuestionKind,
+    #[serde(default)]
+    pub time_limit_seconds: Option<u32>,
+}
+
+/// Clinical case of questions of the course, listed by ID in the order they're asked.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct RawCaseData {
+    pub id: Uuid,
+    pub text: String,
+    #[serde(default, alias = "images")]
+    pub image_file_names: Vec<PathBuf>,
+    pub question_ids: Vec<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct RawQuestionOptionData {
+    pub id: Uuid,
+    pub text: String,
+    #[serde(alias = "correct")]
+    pub is_correct: bool,
+    #[serde(default)]
+    pub preserve_case: bool,
+    #[serde(default)]
+    pub explanation: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct RawQuestionSourceData {
+    pub r#type: QuestionSourceType,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub date: Option<NaiveDate>,
+    #[serde(default, alias = "variant")]
+    pub period: Option<ExamPeriod>,
+}
+
+impl RawCourseData {
+    /// Parses an authoring file, failing on any field this crate doesn't know.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let (data, unknown_fields) = from_slice_tracking_unknown(bytes)?;
+
+        if !unknown_fields.is_empty() {
+            bail!("unknown field(s) in course: {}", unknown_fields.join(", "));
+        }
+
+        Ok(data)
+    }
+
+    /// Parses an authoring file, returning the paths of unknown fields instead of failing.
+    pub fn from_slice_lenient(bytes: &[u8]) -> Result<(Self, Vec<String>)> {
+        from_slice_tracking_unknown(bytes)
+    }
+
+    /// Parses an authoring file without holding all of its questions in memory,
+    /// passing each one to `on_question` as it's read. The returned course has no
+    /// questions. Like `from_slice`, fails on any field this crate doesn't know,
+    /// stopping at the first question that has one.
+    pub fn from_reader<R, F>(reader: R, on_question: F) -> Result<Self>
+    where
+        R: Read,
+        F: FnMut(RawQuestionData, &ImportProgress) -> Result<()>,
+    {
+        let bytes_read = Rc::new(Cell::new(0));
+        let reader = BufReader::new(CountingReader {
+            inner: reader,
+            bytes_read: bytes_read.clone(),
+        });
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+
+        let mut fields = deserializer.deserialize_map(StreamingCourseVisitor {
+            bytes_read,
+            on_question,
+        })?;
+
+        deserializer.end()?;
+
+        fields.insert("questions".into(), serde_json::Value::Array(vec![]));
+
+        let mut unknown_fields = vec![];
+        let data = serde_ignored::deserialize(serde_json::Value::Object(fields), |path| {
+            unknown_fields.push(path.to_string())
+        })?;
+
+        if !unknown_fields.is_empty() {
+            bail!("unknown field(s) in course: {}", unknown_fields.join(", "));
+        }
+
+        Ok(data)
+    }
+
+    pub fn into_course_data(self) -> Result<CourseData> {
+        let option_count_range = self.option_count_range.unwrap_or_default();
+        let questions = self
+            .questions
+            .into_iter()
+            .map(|question| {
+                question.into_question_data(&self.key, option_count_range, self.unit_rules.clone())
+            })
+            .collect::<Result<Vec<QuestionData>>>()?;
+        let cases = self
+            .cases
+            .into_iter()
+            .map(|case| case.into_case_data(&questions))
+            .collect::<Result<Vec<CaseData>>>()?;
+
+        CourseData::new(
+            self.key,
+            self.name,
+            self.slug,
+            self.short_name,
+            self.description,
+            self.price_in_uyu,
+            self.tags,
+            self.image_file_name,
+            self.alt_text,
+            self.year,
+            self.order,
+            self.option_count_range,
+            self.unit_rules,
+            self.locale,
+            self.license,
+            self.publish_state,
+            self.publish_at,
+            self.unpublish_at,
+            self.relations,
+            self.preview,
+            questions,
+            cases,
+            self.topics,
+        )
+    }
+}
+
+impl RawCaseData {
+    /// `questions` are those of the course.
+    pub fn into_case_data(self, questions: &[QuestionData]) -> Result<CaseData> {
+        let linked = self
+            .question_ids
+            .iter()
+            .map(|id| {
+                questions
+                    .iter()
+                    .find(|question| question.id == *id)
+                    .ok_or_else(|| anyhow!("case with ID {} links missing question {id}", self.id))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        CaseData::from_questions(self.id, self.text, self.image_file_names, &linked)
+    }
+}
+
+impl RawQuestionData {
+    /// `option_count_range` and `unit_rules` are those of the course, which the
+    /// question must satisfy and is formatted with.
+    pub fn into_question_data(
+        self,
+        course_key: &str,
+        option_count_range: OptionCountRange,
+        unit_rules: Option<UnitRules>,
+    ) -> Result<QuestionData> {
+        // Processed with the question, so they get its unit rules.
+        let question_options = self
+            .question_options
+            .into_iter()
+            .enumerate()
+            .map(|(index, question_option)| QuestionOptionData {
+                id: question_option.id,
+                question_id: self.id,
+                text: question_option.text,
+                is_correct: question_option.is_correct,
+                reference: index as u16,
+                preserve_case: question_option.preserve_case,
+                explanation: question_option.explanation,
+                hash: Default::default(),
+            })
+            .collect();
+
+        let source = QuestionSourceData::new(
+            course_key.into(),
+            self.source.r#type,
+            self.source.name,
+            self.source.date,
+            self.source.period,
+        )?;
+
+        QuestionData::new(
+            self.id,
+            course_key.into(),
+            self.text,
+            self.explanation,
+            self.topic,
+            self.topic_by,
+            self.tags,
+            self.image_file_name,
+            self.alt_text,
+            question_options,
+            source,
+            option_count_range,
+            unit_rules,
+            self.translations,
+            self.license,
+            self.media,
+            self.kind,
+            self.time_limit_seconds,
+        )
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct ImportProgress {
+    pub bytes_read: u64,
+    pub question_count: usize,
+}
+
+struct CountingReader<R> {
+    inner: R,
+    bytes_read: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.bytes_read.set(self.bytes_read.get() + count as u64);
+
+        Ok(count)
+    }
+}
+
+struct StreamingCourseVisitor<F> {
+    bytes_read: Rc<Cell<u64>>,
+    on_question: F,
+}
+
+impl<'de, F> Visitor<'de> for StreamingCourseVisitor<F>
+where
+    F: FnMut(RawQuestionData, &ImportProgress) -> Result<()>,
+{
+    type Value = serde_json::Map<String, serde_json::Value>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a course object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut fields = serde_json::Map::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "questions" {
+                map.next_value_seed(QuestionsSeed {
+                    bytes_read: &self.bytes_read,
+                    on_question: &mut self.on_question,
+                })?;
+            } else {
+                fields.insert(key, map.next_value()?);
+            }
+        }
+
+        Ok(fields)
+    }
+}
+
+struct QuestionsSeed<'a, F> {
+    bytes_read: &'a Cell<u64>,
+    on_question: &'a mut F,
+}
+
+impl<'de, F> DeserializeSeed<'de> for QuestionsSeed<'_, F>
+where
+    F: FnMut(RawQuestionData, &ImportProgress) -> Result<()>,
+{
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F> Visitor<'de> for QuestionsSeed<'_, F>
+where
+    F: FnMut(RawQuestionData, &ImportProgress) -> Result<()>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of questions")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut progress = ImportProgress::default();
+        let mut unknown_fields = vec![];
+
+        while let Some(question) = seq.next_element_seed(QuestionSeed {
+            index: progress.question_count,
+            unknown_fields: &mut unknown_fields,
+        })? {
+            if !unknown_fields.is_empty() {
+                return Err(A::Error::custom(format!(
+                    "unknown field(s) in course: {}",
+                    unknown_fields.join(", ")
+                )));
+            }
+
+            progress.bytes_read = self.bytes_read.get();
+            progress.question_count += 1;
+
+            (self.on_question)(question, &progress)
+                .map_err(|error| A::Error::custom(format!("{error:#}")))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A question of a streamed course, collecting the paths of its unknown fields
+/// as in `from_slice`.
+struct QuestionSeed<'a> {
+    index: usize,
+    unknown_fields: &'a mut Vec<String>,
+}
+
+impl<'de> DeserializeSeed<'de> for QuestionSeed<'_> {
+    type Value = RawQuestionData;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        serde_ignored::deserialize(deserializer, |path| {
+            self.unknown_fields
+                .push(format!("questions.{}.{path}", self.index))
+        })
+    }
+}
+
+fn from_slice_tracking_unknown<T: DeserializeOwned>(bytes: &[u8]) -> Result<(T, Vec<String>)> {
+    let mut unknown_fields = vec![];
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+
+    let data = serde_ignored::deserialize(&mut deserializer, |path| {
+        unknown_fields.push(path.to_string())
+    })?;
+
+    deserializer.end()?;
+
+    Ok((data, unknown_fields))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COURSE_JSON: &str = r#"{
+        "key": "anatomy",
+        "name": "Anatomía",
+        "short_name": "Anatomía",
+        "image": "anatomy.png",
+        "questions": [{
+            "id": "0c7ab4c5-5a1e-4e1c-9a8f-1b4a0c6a3f10",
+            "text": "¿Cuál es el hueso más largo?",
+            "topic": "Huesos",
+            "difficulty": "hard",
+            "source": {"type": "exam", "date": "2024-02-01", "variant": "1ra"},
+            "question_options": [
+                {"id": "6f1b0a8e-43c8-4a5b-8a57-5b8e8a9f0a11", "text": "Fémur", "correct": true},
+                {"id": "6f1b0a8e-43c8-4a5b-8a57-5b8e8a9f0a12", "text": "Tibia", "correct": false}
+            ]
+        }]
+    }"#;
+
+    #[test]
+    fn test_from_slice() {
+        assert!(RawCourseData::from_slice(COURSE_JSON.as_bytes()).is_err());
+
+        let (data, unknown_fields) =
+            RawCourseData::from_slice_lenient(COURSE_JSON.as_bytes()).unwrap();
+
+        assert_eq!(unknown_fields, vec!["questions.0.difficulty"]);
+
+        let mut wide = data.clone();
+        wide.option_count_range = Some(OptionCountRange::new(2, 6).unwrap());
+
+        for index in 0..4 {
+            wide.questions[0]
+                .question_options
+                .push(RawQuestionOptionData {
+                    id: Uuid::new_v4(),
+                    text: format!("Costilla {index}"),
+                    is_correct: false,
+                    preserve_case: false,
+                    explanation: None,
+                });
+        }
+
+        assert_eq!(
+            wide.into_course_data().unwrap().questions[0]
+                .question_options
+                .len(),
+            6
+        );
+
+        let mut with_case = data.clone();
+        with_case.cases = vec![RawCaseData {
+            id: Uuid::new_v4(),
+            text: "Paciente de 30 años con dolor en el muslo.".into(),
+            image_file_names: vec!["rx.png".into()],
+            question_ids: vec![data.questions[0].id],
+        }];
+
+        let case_course = with_case.clone().into_course_data().unwrap();
+
+        assert_eq!(case_course.cases[0].topic, "Huesos");
+        assert_eq!(case_course.cases[0].full_image_paths(), ["anatomy/rx.png"]);
+
+        with_case.cases[0].question_ids.push(Uuid::new_v4());
+
+        assert!(with_case.into_course_data().is_err());
+
+        let course = data.into_course_data().unwrap();
+        let question = &course.questions[0];
+
+        assert_eq!(course.image_file_name, PathBuf::from("anatomy.png"));
+        assert_eq!(question.source.period, Some(ExamPeriod::First));
+        assert!(question.question_options[0].is_correct);
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let error = RawCourseData::from_reader(COURSE_JSON.as_bytes(), |_, _| Ok(())).unwrap_err();
+
+        assert!(error.to_string().contains("questions.0.difficulty"));
+
+        let json = COURSE_JSON.replace(r#""difficulty": "hard","#, "");
+        let mut question_ids = vec![];
+        let mut last_progress = ImportProgress::default();
+
+        let data = RawCourseData::from_reader(json.as_bytes(), |question, progress| {
+            question_ids.push(question.id);
+            last_progress = progress.clone();
+
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(data.key, "anatomy");
+        assert!(data.questions.is_empty());
+        assert_eq!(question_ids.len(), 1);
+        assert_eq!(last_progress.question_count, 1);
+        assert_eq!(last_progress.bytes_read, json.len() as u64);
+
+        let typo = json.replacen(r#""short_name""#, r#""shortname": "Anat", "short_name""#, 1);
+        let error = RawCourseData::from_reader(typo.as_bytes(), |_, _| Ok(())).unwrap_err();
+
+        assert!(error.to_string().contains("shortname"));
+    }
+}
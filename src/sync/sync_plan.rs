@@ -0,0 +1,249 @@
+//! Rate-of-change guardrails, so a corrupted content export can't wipe
+//! production. A plan that deletes or changes too much of an entity type is
+//! rejected unless forced.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::content_entity::with_content_entities;
+use super::{ContentEntityType, SyncBatch, SyncData, SyncMetadata, SyncOperation};
+
+/// Sync data along with how many entities of each type are already synced.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SyncPlan {
+    pub sync_data: SyncData,
+    pub existing_counts: BTreeMap<ContentEntityType, usize>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct GuardrailConfig {
+    pub thresholds: BTreeMap<ContentEntityType, GuardrailThresholds>,
+    /// Apply the plan even if it exceeds the thresholds.
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Copy, Debug)]
+pub struct GuardrailThresholds {
+    /// Percentage of existing entities, from 0 to 100.
+    #[serde(default)]
+    pub max_deleted_percent: Option<f64>,
+    /// Entities synced or deleted.
+    #[serde(default)]
+    pub max_changed: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GuardrailViolation {
+    TooManyDeleted {
+        entity_type: ContentEntityType,
+        deleted: usize,
+        existing: usize,
+        max_percent: f64,
+    },
+    TooManyChanged {
+        entity_type: ContentEntityType,
+        changed: usize,
+        max: usize,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct GuardrailError {
+    pub violations: Vec<GuardrailViolation>,
+}
+
+impl Default for GuardrailConfig {
+    /// At most 10% of questions deleted and 10 courses changed.
+    fn default() -> Self {
+        Self {
+            thresholds: BTreeMap::from([
+                (
+                    ContentEntityType::Question,
+                    GuardrailThresholds {
+                        max_deleted_percent: Some(10.0),
+                        max_changed: None,
+                    },
+                ),
+                (
+                    ContentEntityType::Course,
+                    GuardrailThresholds {
+                        max_deleted_percent: None,
+                        max_changed: Some(10),
+                    },
+                ),
+            ]),
+            force: false,
+        }
+    }
+}
+
+impl SyncPlan {
+    pub fn new(sync_data: SyncData, metadata: &SyncMetadata) -> Self {
+        let existing_counts = ContentEntityType::ALL
+            .iter()
+            .map(|&entity_type| (entity_type, metadata.synced_count(entity_type)))
+            .collect();
+
+        Self {
+            sync_data,
+            existing_counts,
+        }
+    }
+
+    pub fn violations(&self, config: &GuardrailConfig) -> Vec<GuardrailViolation> {
+        let mut violations = vec![];
+
+        for (&entity_type, thresholds) in &config.thresholds {
+            let deleted = self.sync_data.batch_len(SyncBatch {
+                entity_type,
+                operation: SyncOperation::Delete,
+            });
+            let changed = deleted
+                + self.sync_data.batch_len(SyncBatch {
+                    entity_type,
+                    operation: SyncOperation::Sync,
+                });
+            let existing = self
+                .existing_counts
+                .get(&entity_type)
+                .copied()
+                .unwrap_or_default();
+
+            if let Some(max_percent) = thresholds.max_deleted_percent {
+                if deleted > 0 && deleted as f64 * 100.0 > existing as f64 * max_percent {
+                    violations.push(GuardrailViolation::TooManyDeleted {
+                        entity_type,
+                        deleted,
+                        existing,
+                        max_percent,
+                    });
+                }
+            }
+
+            if let Some(max) = thresholds.max_changed {
+                if changed > max {
+                    violations.push(GuardrailViolation::TooManyChanged {
+                        entity_type,
+                        changed,
+                        max,
+                    });
+                }
+            }
+        }
+
+        violations
+    }
+
+    /// Fails if the plan exceeds any threshold and `config.force` isn't set.
+    pub fn check_guardrails(&self, config: &GuardrailConfig) -> Result<(), GuardrailError> {
+        let violations = self.violations(config);
+
+        if violations.is_empty() || config.force {
+            Ok(())
+        } else {
+            Err(GuardrailError { violations })
+        }
+    }
+}
+
+macro_rules! synced_count {
+    ($($(#[$attr:meta])* $variant:ident($data:ty) => $field:ident: $index:ty,)*) => {
+        impl SyncMetadata {
+            pub fn synced_count(&self, entity_type: ContentEntityType) -> usize {
+                match entity_type {
+                    $(ContentEntityType::$variant => self.$field.len(),)*
+                }
+            }
+        }
+    };
+}
+
+with_content_entities!(synced_count);
+
+impl std::fmt::Display for GuardrailViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyDeleted {
+                entity_type,
+                deleted,
+                existing,
+                max_percent,
+            } => write!(
+                f,
+                "deletes {deleted} of {existing} {entity_type} entities, more than {max_percent}%"
+            ),
+            Self::TooManyChanged {
+                entity_type,
+                changed,
+                max,
+            } => write!(
+                f,
+                "changes {changed} {entity_type} entities, more than {max}"
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for GuardrailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let violations = self
+            .violations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        write!(f, "sync plan exceeds guardrails: {}", violations.join("; "))
+    }
+}
+
+impl std::error::Error for GuardrailError {}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_check_guardrails() {
+        let mut metadata = SyncMetadata::default();
+        let mut sync_data = SyncData::default();
+
+        for index in 0..20 {
+            let id = Uuid::new_v4();
+            metadata.questions.insert(id, "hash".into());
+
+            if index < 3 {
+                sync_data.questions.for_deletion.insert(id);
+            }
+        }
+
+        let plan = SyncPlan::new(sync_data.clone(), &metadata);
+        let mut config = GuardrailConfig::default();
+
+        assert_eq!(
+            plan.check_guardrails(&config),
+            Err(GuardrailError {
+                violations: vec![GuardrailViolation::TooManyDeleted {
+                    entity_type: ContentEntityType::Question,
+                    deleted: 3,
+                    existing: 20,
+                    max_percent: 10.0,
+                }]
+            })
+        );
+
+        config.force = true;
+
+        assert!(plan.check_guardrails(&config).is_ok());
+
+        sync_data.questions.for_deletion.clear();
+
+        assert!(SyncPlan::new(sync_data, &metadata)
+            .violations(&GuardrailConfig::default())
+            .is_empty());
+    }
+}
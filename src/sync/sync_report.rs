@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::{ContentEntityType, SyncBatch, SyncData, SyncOperation};
+
+/// Outcome of applying a `SyncData`, for logs and job summaries.
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct SyncReport {
+    pub counts: BTreeMap<ContentEntityType, SyncCounts>,
+    #[cfg_attr(feature = "ts_types", ts(type = "number"))]
+    pub duration_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct SyncCounts {
+    pub synced: usize,
+    pub deleted: usize,
+}
+
+impl SyncReport {
+    pub fn new(sync_data: &SyncData, duration: Duration) -> Self {
+        let counts = ContentEntityType::ALL
+            .iter()
+            .map(|&entity_type| {
+                let counts = SyncCounts {
+                    synced: sync_data.batch_len(SyncBatch {
+                        entity_type,
+                        operation: SyncOperation::Sync,
+                    }),
+                    deleted: sync_data.batch_len(SyncBatch {
+                        entity_type,
+                        operation: SyncOperation::Delete,
+                    }),
+                };
+
+                (entity_type, counts)
+            })
+            .filter(|(_, counts)| counts.synced + counts.deleted > 0)
+            .collect();
+
+        Self {
+            counts,
+            duration_ms: duration.as_millis() as u64,
+        }
+    }
+
+    pub fn synced_count(&self) -> usize {
+        self.counts.values().map(|counts| counts.synced).sum()
+    }
+
+    pub fn deleted_count(&self) -> usize {
+        self.counts.values().map(|counts| counts.deleted).sum()
+    }
+}
+
+impl std::fmt::Display for SyncReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = self
+            .counts
+            .iter()
+            .map(|(entity_type, counts)| {
+                format!("{entity_type}: +{} -{}", counts.synced, counts.deleted)
+            })
+            .collect::<Vec<String>>();
+
+        write!(f, "{} in {} ms", counts.join(", "), self.duration_ms)
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use super::helpers::{format_text, prefixed_bytes};
+use super::validation_report::ValidationError;
+use crate::traits::Hashable;
+
+/// Option texts are ordered by option reference.
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, PartialEq, Hash, Eq, Clone, Debug)]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+pub struct TranslatedQuestion {
+    pub text: String,
+    pub question_options: Vec<String>,
+    pub explanation: Option<String>,
+}
+
+impl TranslatedQuestion {
+    pub fn new(
+        text: String,
+        question_options: Vec<String>,
+        explanation: Option<String>,
+    ) -> Result<Self> {
+        let mut data = Self {
+            text,
+            question_options,
+            explanation,
+        };
+
+        data.process()?;
+
+        Ok(data)
+    }
+
+    pub fn process(&mut self) -> Result<()> {
+        self.format();
+        self.check().map_err(ValidationError::wrap)?;
+
+        Ok(())
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.text.is_empty() || self.question_options.iter().any(String::is_empty) {
+            bail!("invalid translated question");
+        }
+
+        Ok(())
+    }
+
+    fn format(&mut self) {
+        self.text = format_text(&self.text);
+        self.question_options = self
+            .question_options
+            .iter()
+            .map(|question_option| format_text(question_option))
+            .collect();
+        self.explanation = self
+            .explanation
+            .as_deref()
+            .map(format_text)
+            .filter(|explanation| !explanation.is_empty());
+    }
+}
+
+impl Hashable for TranslatedQuestion {
+    fn to_bytes(&self) -> Vec<u8> {
+        [
+            prefixed_bytes(&self.text),
+            self.question_options
+                .iter()
+                .flat_map(|question_option| prefixed_bytes(question_option))
+                .collect(),
+            self.explanation
+                .as_deref()
+                .map(prefixed_bytes)
+                .unwrap_or_default(),
+        ]
+        .concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_hash_separately() {
+        let translation = |question_options: [&str; 2]| {
+            TranslatedQuestion::new(
+                "Which bone is the longest?".into(),
+                question_options.map(String::from).to_vec(),
+                None,
+            )
+            .unwrap()
+        };
+
+        assert_ne!(
+            translation(["Femur", "Tibia"]).to_bytes(),
+            translation(["Femu", "rTibia"]).to_bytes()
+        );
+    }
+}
@@ -2,37 +2,259 @@ use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::hash::Hash;
 
+#[cfg(feature = "compression")]
+use anyhow::bail;
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::content_entity::with_content_entities;
 use super::{
-    BundleData, CourseData, IconData, QuestionData, QuestionOptionData, QuestionSourceData,
-    QuestionTopicData,
+    AchievementData, BundleData, CaseData, ContentEntity, CourseData, Environment, FlashcardData,
+    GlossaryTermData, IconData, LearningPathData, QuestionData, QuestionOptionData,
+    QuestionSourceData, QuestionTopicData, SyncCompatibilityError, ValidationReport,
+    SYNC_SCHEMA_VERSION,
 };
+use crate::traits::Syncable;
 
-#[derive(Serialize, Deserialize, Default, Clone, Debug)]
-pub struct SyncData {
-    pub courses: CoursesSyncData,
-    pub questions: QuestionsSyncData,
-    pub question_options: QuestionOptionsSyncData,
-    pub question_topics: QuestionTopicsSyncData,
-    pub question_sources: QuestionSourcesSyncData,
-    pub bundles: BundlesSyncData,
-    pub icons: IconsSyncData,
+macro_rules! sync_types {
+    ($($(#[$attr:meta])* $variant:ident($data:ty) => $field:ident: $index:ty,)*) => {
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        pub struct SyncData {
+            pub environment: Environment,
+            #[serde(default)]
+            pub schema_version: u32,
+            $($(#[$attr])* pub $field: ElementSyncData<$data, <$data as Syncable>::Key>,)*
+        }
+
+        impl Default for SyncData {
+            fn default() -> Self {
+                Self {
+                    environment: Default::default(),
+                    schema_version: SYNC_SCHEMA_VERSION,
+                    $($field: Default::default(),)*
+                }
+            }
+        }
+
+        impl SyncData {
+            pub fn merge(&mut self, other: Self) {
+                $(self.$field.merge(other.$field);)*
+            }
+
+            pub fn len(&self) -> usize {
+                0 $(+ self.$field.len())*
+            }
+        }
+
+        impl Display for SyncData {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let lines = [$(format!("{}: {}", label(stringify!($field)), self.$field),)*];
+
+                write!(f, "{}", lines.join("\n"))
+            }
+        }
+
+        #[derive(Default)]
+        struct SyncBuckets {
+            $($field: Vec<$data>,)*
+        }
+
+        impl SyncBuckets {
+            fn push(&mut self, entity: ContentEntity) {
+                match entity {
+                    $(ContentEntity::$variant(data) => self.$field.push(data),)*
+                }
+            }
+
+            fn diff(self, metadata: &SyncMetadata) -> SyncData {
+                SyncData {
+                    environment: metadata.environment,
+                    schema_version: SYNC_SCHEMA_VERSION,
+                    $($field: ElementSyncData::diff(self.$field, &metadata.$field),)*
+                }
+            }
+        }
+
+        #[derive(Serialize, Deserialize, Default, Clone, Debug)]
+        #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+        pub struct SyncMetadata {
+            pub environment: Environment,
+            /// Schema version of the last applied sync data.
+            #[serde(default)]
+            pub schema_version: u32,
+            $($(#[$attr])* pub $field: $index,)*
+        }
+
+        impl SyncMetadata {
+            pub fn record(&mut self, sync_data: &SyncData) {
+                self.schema_version = sync_data.schema_version;
+                $(sync_data.$field.record(&mut self.$field);)*
+            }
+        }
+    };
 }
 
-impl Display for SyncData {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f,
-            "Courses: {}\nQuestions: {}\nQuestion options: {}\nQuestion topics: {}\nQuestion sources: {}\nBundles: {}\nIcons: {}",
-            self.courses,
-            self.questions,
-            self.question_options,
-            self.question_topics,
-            self.question_sources,
-            self.bundles,
-            self.icons
-        )
+with_content_entities!(sync_types);
+
+/// "question_options" as "Question options".
+fn label(field: &str) -> String {
+    let label = field.replace('_', " ");
+    let mut chars = label.chars();
+
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+impl SyncData {
+    pub const COMPRESSED_MAGIC: [u8; 4] = *b"MSYN";
+    pub const COMPRESSED_FORMAT_VERSION: u8 = 1;
+    pub const COMPRESSION_LEVEL: i32 = 3;
+
+    #[cfg(feature = "compression")]
+    const COMPRESSED_HEADER_LEN: usize = 4 + 1 + blake3::OUT_LEN;
+
+    /// Header (magic, format version, BLAKE3 checksum of the encoded data) followed
+    /// by the MessagePack encoding compressed with zstd.
+    #[cfg(feature = "compression")]
+    pub fn to_compressed_bytes(&self) -> Result<Vec<u8>> {
+        let encoded = rmp_serde::to_vec_named(self)?;
+        let compressed = zstd::encode_all(encoded.as_slice(), Self::COMPRESSION_LEVEL)?;
+
+        let mut bytes = Vec::with_capacity(Self::COMPRESSED_HEADER_LEN + compressed.len());
+        bytes.extend(Self::COMPRESSED_MAGIC);
+        bytes.push(Self::COMPRESSED_FORMAT_VERSION);
+        bytes.extend(blake3::hash(&encoded).as_bytes());
+        bytes.extend(compressed);
+
+        Ok(bytes)
+    }
+
+    #[cfg(feature = "compression")]
+    pub fn from_compressed_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < Self::COMPRESSED_HEADER_LEN || bytes[..4] != Self::COMPRESSED_MAGIC {
+            bail!("invalid compressed sync data header");
+        }
+
+        let version = bytes[4];
+
+        if version != Self::COMPRESSED_FORMAT_VERSION {
+            bail!("unsupported compressed sync data version {version}");
+        }
+
+        let checksum = &bytes[5..Self::COMPRESSED_HEADER_LEN];
+        let encoded = zstd::decode_all(&bytes[Self::COMPRESSED_HEADER_LEN..])?;
+
+        if blake3::hash(&encoded).as_bytes() != checksum {
+            bail!("compressed sync data checksum mismatch");
+        }
+
+        Ok(rmp_serde::from_slice(&encoded)?)
+    }
+
+    /// Targets the environment of `metadata`. Unless `include_drafts` is set, draft
+    /// courses and bundles are left out along with the questions of draft courses,
+    /// so they're deleted if already synced. Fails if a case links a question
+    /// that isn't synced along with it or is in another course or topic.
+    pub fn diff(
+        entities: impl IntoIterator<Item = ContentEntity>,
+        metadata: &SyncMetadata,
+        include_drafts: bool,
+    ) -> Result<Self> {
+        let mut buckets = SyncBuckets::default();
+
+        for entity in entities {
+            buckets.push(entity);
+        }
+
+        if !include_drafts {
+            buckets.remove_drafts();
+        }
+
+        buckets.check_cases().into_result()?;
+
+        Ok(buckets.diff(metadata))
+    }
+
+    /// Must pass before applying to the environment `metadata` belongs to.
+    pub fn compatible_with(&self, metadata: &SyncMetadata) -> Result<(), SyncCompatibilityError> {
+        if self.environment != metadata.environment {
+            return Err(SyncCompatibilityError::EnvironmentMismatch {
+                payload: self.environment,
+                target: metadata.environment,
+            });
+        }
+
+        if self.schema_version != SYNC_SCHEMA_VERSION {
+            return Err(SyncCompatibilityError::SchemaVersionMismatch {
+                payload: self.schema_version,
+                supported: SYNC_SCHEMA_VERSION,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl SyncBuckets {
+    fn check_cases(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        let questions = self
+            .questions
+            .iter()
+            .map(|question| (question.id, question))
+            .collect::<HashMap<_, _>>();
+
+        for case in &self.cases {
+            let linked = case
+                .question_ids
+                .iter()
+                .filter_map(|id| questions.get(id).copied());
+
+            if let Err(error) = case.check_questions(linked) {
+                report.push("case", &case.id.to_string(), error.to_string());
+            }
+        }
+
+        report
+    }
+
+    fn remove_drafts(&mut self) {
+        let draft_course_keys = self
+            .courses
+            .iter()
+            .filter(|course| course.publish_state.is_draft())
+            .map(|course| course.key.clone())
+            .collect::<HashSet<_>>();
+        let draft_question_ids = self
+            .questions
+            .iter()
+            .filter(|question| draft_course_keys.contains(&question.course_key))
+            .map(|question| question.id)
+            .collect::<HashSet<_>>();
+
+        self.courses
+            .retain(|course| !draft_course_keys.contains(&course.key));
+        self.questions
+            .retain(|question| !draft_question_ids.contains(&question.id));
+        self.question_options
+            .retain(|question_option| !draft_question_ids.contains(&question_option.question_id));
+        self.flashcards
+            .retain(|flashcard| !draft_question_ids.contains(&flashcard.question_id));
+        self.question_topics
+            .retain(|question_topic| !draft_course_keys.contains(&question_topic.course_key));
+        self.question_sources
+            .retain(|question_source| !draft_course_keys.contains(&question_source.course_key));
+        self.cases
+            .retain(|case| !draft_course_keys.contains(&case.course_key));
+        self.bundles
+            .retain(|bundle| !bundle.publish_state.is_draft());
     }
 }
 
@@ -43,6 +265,11 @@ pub type QuestionTopicsSyncData = ElementSyncData<QuestionTopicData, String>;
 pub type QuestionSourcesSyncData = ElementSyncData<QuestionSourceData, String>;
 pub type BundlesSyncData = ElementSyncData<BundleData, String>;
 pub type IconsSyncData = ElementSyncData<IconData, String>;
+pub type AchievementsSyncData = ElementSyncData<AchievementData, String>;
+pub type LearningPathsSyncData = ElementSyncData<LearningPathData, String>;
+pub type FlashcardsSyncData = ElementSyncData<FlashcardData, Uuid>;
+pub type GlossaryTermsSyncData = ElementSyncData<GlossaryTermData, String>;
+pub type CasesSyncData = ElementSyncData<CaseData, Uuid>;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ElementSyncData<T: Eq + Hash, K: Eq + Hash> {
@@ -59,6 +286,60 @@ impl<T: Eq + Hash, K: Eq + Hash> Default for ElementSyncData<T, K> {
     }
 }
 
+impl<T: Eq + Hash, K: Eq + Hash> ElementSyncData<T, K> {
+    pub fn merge(&mut self, other: Self) {
+        self.for_sync.extend(other.for_sync);
+        self.for_deletion.extend(other.for_deletion);
+    }
+
+    pub fn len(&self) -> usize {
+        self.for_sync.len() + self.for_deletion.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Syncable> ElementSyncData<T, T::Key> {
+    /// Elements whose hash differs from the synced one, plus synced keys no longer present.
+    pub fn diff(elements: impl IntoIterator<Item = T>, metadata: &impl SyncIndex<T::Key>) -> Self {
+        let mut keys = HashSet::new();
+        let mut for_sync = HashSet::new();
+
+        for element in elements {
+            let key = element.sync_key();
+
+            if !metadata.is_synced(&key, &element.content_hash()) {
+                for_sync.insert(element);
+            }
+
+            keys.insert(key);
+        }
+
+        let for_deletion = metadata
+            .synced_keys()
+            .filter(|key| !keys.contains(key))
+            .cloned()
+            .collect();
+
+        Self {
+            for_sync,
+            for_deletion,
+        }
+    }
+
+    pub fn record(&self, metadata: &mut impl SyncIndex<T::Key>) {
+        for key in &self.for_deletion {
+            metadata.forget(key);
+        }
+
+        for element in &self.for_sync {
+            metadata.record(element.sync_key(), element.content_hash().into_owned());
+        }
+    }
+}
+
 impl<T: Eq + Hash, K: Eq + Hash> Display for ElementSyncData<T, K> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -70,13 +351,268 @@ impl<T: Eq + Hash, K: Eq + Hash> Display for ElementSyncData<T, K> {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct SyncMetadata {
-    pub courses: HashMap<String, String>,
-    pub questions: HashMap<Uuid, String>,
-    pub question_options: HashMap<Uuid, String>,
-    pub question_topics: HashSet<String>,
-    pub question_sources: HashSet<String>,
-    pub bundles: HashMap<String, String>,
-    pub icons: HashMap<String, String>,
+impl SyncMetadata {
+    pub fn new(environment: Environment) -> Self {
+        Self {
+            environment,
+            ..Default::default()
+        }
+    }
+}
+
+/// Synced state of one entity type, either keys with their hashes or keys alone.
+pub trait SyncIndex<K> {
+    fn is_synced(&self, key: &K, hash: &str) -> bool;
+
+    /// Whether `key` is synced, whatever its hash.
+    fn is_recorded(&self, key: &K) -> bool;
+
+    fn synced_keys<'a>(&'a self) -> impl Iterator<Item = &'a K>
+    where
+        K: 'a;
+
+    fn record(&mut self, key: K, hash: String);
+
+    fn forget(&mut self, key: &K);
+}
+
+impl<K: Eq + Hash> SyncIndex<K> for HashMap<K, String> {
+    fn is_synced(&self, key: &K, hash: &str) -> bool {
+        self.get(key).is_some_and(|synced_hash| synced_hash == hash)
+    }
+
+    fn is_recorded(&self, key: &K) -> bool {
+        self.contains_key(key)
+    }
+
+    fn synced_keys<'a>(&'a self) -> impl Iterator<Item = &'a K>
+    where
+        K: 'a,
+    {
+        self.keys()
+    }
+
+    fn record(&mut self, key: K, hash: String) {
+        self.insert(key, hash);
+    }
+
+    fn forget(&mut self, key: &K) {
+        self.remove(key);
+    }
+}
+
+impl<K: Eq + Hash> SyncIndex<K> for HashSet<K> {
+    fn is_synced(&self, key: &K, _hash: &str) -> bool {
+        self.contains(key)
+    }
+
+    fn is_recorded(&self, key: &K) -> bool {
+        self.contains(key)
+    }
+
+    fn synced_keys<'a>(&'a self) -> impl Iterator<Item = &'a K>
+    where
+        K: 'a,
+    {
+        self.iter()
+    }
+
+    fn record(&mut self, key: K, _hash: String) {
+        self.insert(key);
+    }
+
+    fn forget(&mut self, key: &K) {
+        self.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_and_record() {
+        let mut questions = fake::vec![QuestionData; 3];
+
+        for question in &mut questions {
+            question.prepare_for_test().unwrap();
+        }
+
+        let topic = questions[0].topic.clone();
+        let mut metadata = SyncMetadata::default();
+        metadata.question_topics.insert("removed".into());
+
+        let entities = questions
+            .iter()
+            .cloned()
+            .map(ContentEntity::from)
+            .chain([topic.clone().into()]);
+        let sync_data = SyncData::diff(entities.clone(), &metadata, false).unwrap();
+
+        assert_eq!(sync_data.questions.for_sync.len(), 3);
+        assert_eq!(sync_data.question_topics.for_sync.len(), 1);
+        assert!(sync_data.question_topics.for_deletion.contains("removed"));
+        assert_eq!(sync_data.len(), 5);
+
+        metadata.record(&sync_data);
+
+        assert_eq!(metadata.questions.len(), 3);
+        assert_eq!(metadata.question_topics, HashSet::from([topic.key()]));
+        assert!(SyncData::diff(entities, &metadata, false)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_compatible_with() {
+        let production = SyncMetadata::new(Environment::Production);
+        let staging = SyncMetadata::new(Environment::Staging);
+        let sync_data = SyncData::diff([], &staging, true).unwrap();
+
+        assert_eq!(sync_data.compatible_with(&staging), Ok(()));
+        assert_eq!(
+            sync_data.compatible_with(&production),
+            Err(SyncCompatibilityError::EnvironmentMismatch {
+                payload: Environment::Staging,
+                target: Environment::Production,
+            })
+        );
+
+        let legacy = serde_json::json!({
+            "courses": {"for_sync": [], "for_deletion": []},
+            "questions": {"for_sync": [], "for_deletion": []},
+            "question_options": {"for_sync": [], "for_deletion": []},
+            "question_topics": {"for_sync": [], "for_deletion": []},
+            "question_sources": {"for_sync": [], "for_deletion": []},
+            "bundles": {"for_sync": [], "for_deletion": []},
+            "icons": {"for_sync": [], "for_deletion": []}
+        });
+
+        assert!(serde_json::from_value::<SyncData>(legacy.clone()).is_err());
+
+        let mut unversioned = legacy;
+        unversioned["environment"] = serde_json::json!(Environment::Staging);
+        let unversioned: SyncData = serde_json::from_value(unversioned).unwrap();
+
+        assert_eq!(
+            unversioned.compatible_with(&staging),
+            Err(SyncCompatibilityError::SchemaVersionMismatch {
+                payload: 0,
+                supported: SYNC_SCHEMA_VERSION,
+            })
+        );
+    }
+
+    #[test]
+    fn test_diff_drafts() {
+        use fake::{Fake, Faker};
+
+        use crate::sync::PublishState;
+
+        let mut course = Faker.fake::<CourseData>();
+        course.publish_state = PublishState::Draft;
+
+        let mut question = Faker.fake::<QuestionData>();
+        question.course_key = course.key.clone();
+        question.prepare_for_test().unwrap();
+
+        let mut metadata = SyncMetadata::default();
+        metadata.courses.insert(course.key.clone(), "old".into());
+
+        let entities = [course.clone().into(), question.clone().into()];
+        let sync_data = SyncData::diff(entities.clone(), &metadata, false).unwrap();
+
+        assert!(sync_data.courses.for_sync.is_empty());
+        assert!(sync_data.questions.for_sync.is_empty());
+        assert!(sync_data.courses.for_deletion.contains(&course.key));
+
+        let sync_data = SyncData::diff(entities, &metadata, true).unwrap();
+
+        assert_eq!(sync_data.courses.for_sync.len(), 1);
+        assert_eq!(sync_data.questions.for_sync.len(), 1);
+    }
+
+    #[test]
+    fn test_diff_cases() {
+        use crate::sync::{QuestionTopicData, ValidationError};
+
+        let mut questions = fake::vec![QuestionData; 2];
+
+        for question in &mut questions {
+            question.course_key = "mir".into();
+            question.topic = QuestionTopicData::new("mir".into(), "Cardiología".into()).unwrap();
+            question.prepare_for_test().unwrap();
+        }
+
+        let case = CaseData::from_questions(
+            Uuid::new_v4(),
+            "Varón de 65 años con dolor torácico.".into(),
+            vec![],
+            &questions.iter().collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let metadata = SyncMetadata::default();
+        let entities = questions
+            .iter()
+            .cloned()
+            .map(ContentEntity::from)
+            .chain([case.clone().into()]);
+
+        assert_eq!(
+            SyncData::diff(entities, &metadata, false)
+                .unwrap()
+                .cases
+                .for_sync
+                .len(),
+            1
+        );
+
+        let deleted = [questions[0].clone().into(), case.clone().into()];
+
+        assert!(SyncData::diff(deleted, &metadata, false).is_err());
+
+        questions[1].topic = QuestionTopicData::new("mir".into(), "Neumología".into()).unwrap();
+        let moved = questions
+            .iter()
+            .cloned()
+            .map(ContentEntity::from)
+            .chain([case.into()]);
+        let error = SyncData::diff(moved, &metadata, false).unwrap_err();
+
+        assert!(error.is::<ValidationError>());
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_compressed_bytes() {
+        use fake::{Fake, Faker};
+
+        let mut sync_data = SyncData::default();
+
+        for mut question in fake::vec![QuestionData; 3] {
+            question.prepare_for_test().unwrap();
+            sync_data.add_for_sync(question);
+        }
+
+        sync_data.add_for_deletion(Faker.fake::<IconData>());
+
+        let mut bytes = sync_data.to_compressed_bytes().unwrap();
+        let decoded = SyncData::from_compressed_bytes(&bytes).unwrap();
+
+        let hashes = |sync_data: &SyncData| {
+            sync_data
+                .questions
+                .for_sync
+                .iter()
+                .map(|question| question.hash.clone())
+                .collect::<HashSet<String>>()
+        };
+
+        assert_eq!(hashes(&decoded), hashes(&sync_data));
+        assert_eq!(decoded.icons.for_deletion, sync_data.icons.for_deletion);
+
+        *bytes.last_mut().unwrap() ^= 1;
+
+        assert!(SyncData::from_compressed_bytes(&bytes).is_err());
+    }
 }
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use anyhow::Result;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use super::helpers::math_spans;
+
+/// Units in the order they're matched, so compound units win over their prefixes.
+const DEFAULT_UNITS: [&str; 30] = [
+    "mOsm/kg", "µmol/L", "mmol/L", "mEq/L", "mg/dL", "mg/kg", "ng/mL", "pg/mL", "g/dL", "UI/L",
+    "U/L", "cmH2O", "mmHg", "kcal", "mcg", "µg", "mg", "kg", "mL", "ml", "dL", "lpm", "cpm", "°C",
+    "g", "L", "mm", "cm", "UI", "mU",
+];
+/// Thin space, as typeset between a number and its unit.
+const UNIT_SEPARATOR: char = '\u{2009}';
+const SUPERSCRIPT_DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+static CARET_EXPONENT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"([\p{L}\d)])\^\(?(-?\d+)\)?").unwrap());
+static LENGTH_EXPONENT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d[ \u{2009}\u{202F}]?|/)(km|cm|mm|dm|m)([23])\b").unwrap());
+
+pub(super) static DEFAULT_UNIT_FORMATTER: LazyLock<Arc<UnitFormatter>> =
+    LazyLock::new(|| Arc::new(UnitFormatter::new(&UnitRules::default()).unwrap()));
+/// Formatters of the custom rules of courses, compiled once each.
+static UNIT_FORMATTERS: LazyLock<Mutex<HashMap<UnitRules, Arc<UnitFormatter>>>> =
+    LazyLock::new(Default::default);
+
+/// How quantities are written in content text. Courses can set their own,
+/// which apply to their questions, options and explanations.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "ts_types", derive(ts_rs::TS))]
+#[serde(default)]
+pub struct UnitRules {
+    /// Units set apart from the number before them with a thin space, e.g. "5 mg/dL".
+    pub units: Vec<String>,
+    /// Whether exponents like `10^9` and `m2` become superscripts.
+    pub normalize_superscripts: bool,
+    /// Terms left as written, e.g. drug names that look like a quantity.
+    pub do_not_touch: Vec<String>,
+}
+
+impl Default for UnitRules {
+    fn default() -> Self {
+        Self {
+            units: DEFAULT_UNITS.into_iter().map(Into::into).collect(),
+            normalize_superscripts: true,
+            do_not_touch: vec![],
+        }
+    }
+}
+
+/// Compiled formatter of `rules`, or of the default rules if there are none.
+pub fn unit_formatter(rules: Option<&UnitRules>) -> Result<Arc<UnitFormatter>> {
+    let Some(rules) = rules else {
+        return Ok(DEFAULT_UNIT_FORMATTER.clone());
+    };
+
+    let mut formatters = UNIT_FORMATTERS.lock().unwrap();
+
+    if let Some(formatter) = formatters.get(rules) {
+        return Ok(formatter.clone());
+    }
+
+    let formatter = Arc::new(UnitFormatter::new(rules)?);
+    formatters.insert(rules.clone(), formatter.clone());
+
+    Ok(formatter)
+}
+
+/// Compiled `UnitRules`.
+#[derive(Clone, Debug)]
+pub struct UnitFormatter {
+    unit_regex: Option<Regex>,
+    do_not_touch_regex: Option<Regex>,
+    normalize_superscripts: bool,
+}
+
+impl UnitFormatter {
+    pub fn new(rules: &UnitRules) -> Result<Self> {
+        let mut units = rules
+            .units
+            .iter()
+            .map(|unit| unit.trim())
+            .filter(|unit| !unit.is_empty())
+            .collect::<Vec<_>>();
+        units.sort_by_key(|unit| std::cmp::Reverse(unit.chars().count()));
+        units.dedup();
+
+        Ok(Self {
+            unit_regex: alternation(&units)
+                .map(|units| Regex::new(&format!(r"(\d)[ \u{{2009}}\u{{202F}}]?({units})\b")))
+                .transpose()?,
+            do_not_touch_regex: alternation(&rules.do_not_touch)
+                .map(|terms| Regex::new(&terms))
+                .transpose()?,
+            normalize_superscripts: rules.normalize_superscripts,
+        })
+    }
+
+    /// Applies the rules outside math spans and do-not-touch terms, leaving
+    /// whitespace as is. Explanations, which may span several paragraphs, are
+    /// formatted with this instead of `format_text`.
+    pub fn format(&self, text: &str) -> String {
+        map_unprotected(text, &self.protected_spans(text), |text| self.apply(text))
+    }
+
+    pub(super) fn protected_spans(&self, text: &str) -> Vec<Range<usize>> {
+        let mut spans = math_spans(text);
+
+        if let Some(regex) = &self.do_not_touch_regex {
+            spans.extend(regex.find_iter(text).map(|term| term.range()));
+        }
+
+        spans.sort_by_key(|span| span.start);
+
+        spans.into_iter().fold(vec![], |mut merged, span| {
+            match merged.last_mut() {
+                Some(last) if span.start < last.end => last.end = last.end.max(span.end),
+                _ => merged.push(span),
+            }
+
+            merged
+        })
+    }
+
+    /// Applies the rules to text without protected spans.
+    pub(super) fn apply(&self, text: &str) -> String {
+        let mut formatted = text.to_owned();
+
+        if self.normalize_superscripts {
+            formatted = CARET_EXPONENT_REGEX
+                .replace_all(&formatted, |captures: &Captures| {
+                    format!("{}{}", &captures[1], superscript(&captures[2]))
+                })
+                .into();
+            formatted = LENGTH_EXPONENT_REGEX
+                .replace_all(&formatted, |captures: &Captures| {
+                    format!(
+                        "{}{}{}",
+                        &captures[1],
+                        &captures[2],
+                        superscript(&captures[3])
+                    )
+                })
+                .into();
+        }
+
+        // After superscripts, so "5 cm2" and "5cm²" end up written the same.
+        if let Some(regex) = &self.unit_regex {
+            formatted = regex
+                .replace_all(&formatted, format!("$1{UNIT_SEPARATOR}$2"))
+                .into();
+        }
+
+        formatted
+    }
+}
+
+/// Applies `format` to `text` with each of `spans` swapped for a placeholder,
+/// then puts the spans back as they were.
+pub(super) fn map_unprotected(
+    text: &str,
+    spans: &[Range<usize>],
+    format: impl FnOnce(&str) -> String,
+) -> String {
+    /// Private use character, so it can't match any formatting rule.
+    const PLACEHOLDER: char = '\u{E000}';
+
+    if spans.is_empty() {
+        return format(text);
+    }
+
+    let mut masked = String::with_capacity(text.len());
+    let mut end = 0;
+
+    for span in spans {
+        masked.push_str(&text[end..span.start]);
+        masked.push(PLACEHOLDER);
+        end = span.end;
+    }
+
+    masked.push_str(&text[end..]);
+
+    let formatted = format(&masked);
+    let mut parts = formatted.split(PLACEHOLDER);
+    let mut restored = parts.next().unwrap_or_default().to_owned();
+
+    for (part, span) in parts.zip(spans) {
+        restored.push_str(&text[span.clone()]);
+        restored.push_str(part);
+    }
+
+    restored
+}
+
+fn alternation<S: AsRef<str>>(terms: &[S]) -> Option<String> {
+    let terms = terms
+        .iter()
+        .map(|term| term.as_ref().trim())
+        .filter(|term| !term.is_empty())
+        .map(regex::escape)
+        .collect::<Vec<_>>();
+
+    (!terms.is_empty()).then(|| terms.join("|"))
+}
+
+fn superscript(digits: &str) -> String {
+    digits
+        .chars()
+        .map(|char| match char.to_digit(10) {
+            Some(digit) => SUPERSCRIPT_DIGITS[digit as usize],
+            None => '⁻',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::format_text_with;
+
+    #[test]
+    fn test_format() {
+        let formatter = UnitFormatter::new(&UnitRules {
+            do_not_touch: vec!["Vitamina B12 1000mcg".into()],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let formatted = format_text_with(
+            "Glucemia  126mg/dL, PA 120 mmHg, plaquetas 150x10^9/L, SC 1.73 m2, 5 cm2 y \
+            5 mgs de $x^2$. Vitamina B12 1000mcg",
+            &formatter,
+        );
+
+        assert_eq!(
+            formatted,
+            "Glucemia 126\u{2009}mg/dL, PA 120\u{2009}mmHg, plaquetas 150x10⁹/L, \
+            SC 1.73 m², 5\u{2009}cm² y 5 mgs de $x^2$. Vitamina B12 1000mcg"
+        );
+        assert_eq!(format_text_with(&formatted, &formatter), formatted);
+        assert_eq!(
+            formatter.format("Dosis:\n\n- 10mg/kg\n- 10^-3 M"),
+            "Dosis:\n\n- 10\u{2009}mg/kg\n- 10⁻³ M"
+        );
+    }
+}
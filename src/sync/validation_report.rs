@@ -0,0 +1,86 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ValidationIssue {
+    pub entity: String,
+    pub key: String,
+    pub message: String,
+}
+
+/// Content rejected by a check or an edit. Its message is about the content
+/// itself, so unlike other errors it can be shown to clients.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ValidationError(pub String);
+
+impl ValidationError {
+    /// Marks a failed check as a validation error, keeping its source so
+    /// more specific errors can still be downcast.
+    pub fn wrap(error: anyhow::Error) -> anyhow::Error {
+        if error.is::<Self>() {
+            return error;
+        }
+
+        let message = format!("{error:#}");
+        error.context(Self(message))
+    }
+}
+
+impl ValidationReport {
+    pub fn push(&mut self, entity: &str, key: &str, message: String) {
+        self.issues.push(ValidationIssue {
+            entity: entity.into(),
+            key: key.into(),
+            message,
+        });
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.issues.extend(other.issues);
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<()> {
+        if !self.is_ok() {
+            bail!(ValidationError(format!("validation failed:\n{self}")));
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .issues
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>();
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}: {}", self.entity, self.key, self.message)
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ValidationError {}
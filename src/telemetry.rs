@@ -0,0 +1,163 @@
+#[cfg(feature = "telemetry")]
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "telemetry")]
+use tracing_subscriber::layer::SubscriberExt;
+#[cfg(feature = "telemetry")]
+use tracing_subscriber::util::SubscriberInitExt;
+#[cfg(feature = "telemetry")]
+use tracing_subscriber::{fmt, EnvFilter, Layer};
+
+use crate::config::from_env_str;
+use crate::sync::SyncReport;
+
+pub const DEFAULT_FILTER: &str = "info";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TelemetryConfig {
+    /// Used when `RUST_LOG` isn't set.
+    pub filter: String,
+    #[serde(deserialize_with = "from_env_str")]
+    pub json: bool,
+    pub otlp_endpoint: Option<String>,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            filter: DEFAULT_FILTER.into(),
+            json: true,
+            otlp_endpoint: None,
+        }
+    }
+}
+
+/// Flushes pending spans when dropped; keep it alive for the whole process.
+#[cfg(feature = "telemetry")]
+#[must_use]
+pub struct TelemetryGuard {
+    #[cfg(feature = "otlp")]
+    otlp: bool,
+}
+
+#[cfg(feature = "telemetry")]
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otlp")]
+        if self.otlp {
+            opentelemetry::global::shutdown_tracer_provider();
+        }
+    }
+}
+
+#[cfg(feature = "telemetry")]
+pub fn init(service_name: &str, config: &TelemetryConfig) -> Result<TelemetryGuard> {
+    let filter =
+        EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new(&config.filter))?;
+
+    let fmt_layer = if config.json {
+        fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .boxed()
+    } else {
+        fmt::layer().boxed()
+    };
+
+    #[cfg(feature = "otlp")]
+    let otlp_layer = config
+        .otlp_endpoint
+        .as_deref()
+        .map(|endpoint| otlp_layer(service_name, endpoint))
+        .transpose()?;
+    #[cfg(not(feature = "otlp"))]
+    if config.otlp_endpoint.is_some() {
+        return Err(anyhow!("OTLP export requires the otlp feature"));
+    }
+
+    let registry = tracing_subscriber::registry().with(filter).with(fmt_layer);
+
+    #[cfg(feature = "otlp")]
+    let registry = registry.with(otlp_layer);
+
+    registry.try_init()?;
+
+    tracing::info!(service = service_name, "telemetry initialized");
+
+    Ok(TelemetryGuard {
+        #[cfg(feature = "otlp")]
+        otlp: config.otlp_endpoint.is_some(),
+    })
+}
+
+#[cfg(feature = "otlp")]
+fn otlp_layer<S>(service_name: &str, endpoint: &str) -> Result<impl Layer<S>>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::{runtime, trace::TracerProvider, Resource};
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|error| anyhow!("failed to build OTLP exporter: {error}"))?;
+
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            service_name.to_string(),
+        )]))
+        .build();
+    let tracer = provider.tracer(service_name.to_string());
+
+    opentelemetry::global::set_tracer_provider(provider);
+
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// Records the report on the current span (for fields declared on it) and as an event.
+pub fn record_sync_result(report: &SyncReport) {
+    let span = tracing::Span::current();
+
+    span.record("synced", report.synced_count());
+    span.record("deleted", report.deleted_count());
+    span.record("duration_ms", report.duration_ms);
+
+    tracing::info!(
+        synced = report.synced_count(),
+        deleted = report.deleted_count(),
+        duration_ms = report.duration_ms,
+        "sync applied: {report}"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use fake::{Fake, Faker};
+
+    use super::*;
+    use crate::sync::{QuestionTopicData, SyncData};
+
+    #[test]
+    fn test_sync_report() {
+        let mut sync_data = SyncData::default();
+        sync_data.add_for_sync(Faker.fake::<QuestionTopicData>());
+        sync_data.add_for_deletion(Faker.fake::<QuestionTopicData>());
+
+        let report = SyncReport::new(&sync_data, Duration::from_millis(1500));
+
+        assert_eq!(report.synced_count(), 1);
+        assert_eq!(report.deleted_count(), 1);
+        assert_eq!(report.to_string(), "question_topic: +1 -1 in 1500 ms");
+
+        record_sync_result(&report);
+    }
+}
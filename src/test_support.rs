@@ -0,0 +1,292 @@
+//! In-memory implementations of the external integrations, for tests that
+//! shouldn't need Valkey, SES, OpenAI or S3 credentials.
+
+use std::collections::HashMap;
+#[cfg(feature = "openai")]
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "openai")]
+use anyhow::anyhow;
+use anyhow::Result;
+#[cfg(feature = "openai")]
+use async_openai::types::{CreateChatCompletionRequest, CreateTranscriptionRequest};
+
+use crate::cache::Cache;
+use crate::email::EmailSender;
+#[cfg(feature = "openai")]
+use crate::helpers::{LlmClient, TranscriptionClient};
+use crate::images::ImageStorage;
+use crate::redact::Email;
+use crate::traits::EmailTemplate;
+
+#[derive(Default, Debug)]
+pub struct FakeCache {
+    entries: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl FakeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keys of the entries that haven't expired.
+    pub fn keys(&self) -> Vec<String> {
+        let now = Instant::now();
+        let mut keys = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, (_, expires_at))| *expires_at > now)
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+
+        keys.sort();
+        keys
+    }
+}
+
+impl Cache for FakeCache {
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let mut entries = self.entries.lock().unwrap();
+
+        match entries.get(key) {
+            Some((_, expires_at)) if *expires_at <= Instant::now() => {
+                entries.remove(key);
+                Ok(None)
+            }
+            entry => Ok(entry.map(|(value, _)| value.clone())),
+        }
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Duration) -> Result<()> {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key.into(), (value, Instant::now() + ttl));
+
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.entries.lock().unwrap().remove(key);
+
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SentEmail {
+    pub to: String,
+    pub template_name: &'static str,
+    pub data: serde_json::Value,
+}
+
+#[derive(Default, Debug)]
+pub struct FakeEmailSender {
+    sent: Mutex<Vec<SentEmail>>,
+}
+
+impl FakeEmailSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sent(&self) -> Vec<SentEmail> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+impl EmailSender for FakeEmailSender {
+    async fn send<T: EmailTemplate + Send>(&self, to: &Email, template: T) -> Result<()> {
+        self.sent.lock().unwrap().push(SentEmail {
+            to: to.expose().into(),
+            template_name: T::TEMPLATE_NAME,
+            data: serde_json::to_value(&template)?,
+        });
+
+        Ok(())
+    }
+}
+
+/// Replies with the canned responses in order, failing once they run out.
+#[cfg(feature = "openai")]
+#[derive(Default, Debug)]
+pub struct FakeLlmClient {
+    responses: Mutex<VecDeque<String>>,
+    requests: Mutex<Vec<CreateChatCompletionRequest>>,
+}
+
+#[cfg(feature = "openai")]
+impl FakeLlmClient {
+    pub fn new(responses: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            responses: Mutex::new(responses.into_iter().map(Into::into).collect()),
+            requests: Mutex::default(),
+        }
+    }
+
+    pub fn push_response(&self, response: impl Into<String>) {
+        self.responses.lock().unwrap().push_back(response.into());
+    }
+
+    pub fn requests(&self) -> Vec<CreateChatCompletionRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[cfg(feature = "openai")]
+impl LlmClient for FakeLlmClient {
+    async fn chat_completion(&self, request: CreateChatCompletionRequest) -> Result<String> {
+        self.requests.lock().unwrap().push(request);
+
+        self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| anyhow!("no canned LLM response left"))
+    }
+}
+
+/// Replies with the canned transcripts in order, failing once they run out.
+#[cfg(feature = "openai")]
+#[derive(Default, Debug)]
+pub struct FakeTranscriptionClient {
+    responses: Mutex<VecDeque<String>>,
+    requests: Mutex<Vec<CreateTranscriptionRequest>>,
+}
+
+#[cfg(feature = "openai")]
+impl FakeTranscriptionClient {
+    pub fn new(responses: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            responses: Mutex::new(responses.into_iter().map(Into::into).collect()),
+            requests: Mutex::default(),
+        }
+    }
+
+    pub fn requests(&self) -> Vec<CreateTranscriptionRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[cfg(feature = "openai")]
+impl TranscriptionClient for FakeTranscriptionClient {
+    async fn transcription(&self, request: CreateTranscriptionRequest) -> Result<String> {
+        self.requests.lock().unwrap().push(request);
+
+        self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| anyhow!("no canned transcription left"))
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct FakeImageStorage {
+    objects: Mutex<HashMap<String, (Vec<u8>, String)>>,
+}
+
+impl FakeImageStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn content_type(&self, path: &str) -> Option<String> {
+        self.objects
+            .lock()
+            .unwrap()
+            .get(path)
+            .map(|(_, content_type)| content_type.clone())
+    }
+
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths = self
+            .objects
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        paths.sort();
+        paths
+    }
+}
+
+impl ImageStorage for FakeImageStorage {
+    async fn put(&self, path: &str, bytes: Vec<u8>, content_type: &str) -> Result<()> {
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(path.into(), (bytes, content_type.into()));
+
+        Ok(())
+    }
+
+    async fn get(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .objects
+            .lock()
+            .unwrap()
+            .get(path)
+            .map(|(bytes, _)| bytes.clone()))
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        self.objects.lock().unwrap().remove(path);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fake_cache_get_or_compute() {
+        let cache = FakeCache::new();
+        let calls = AtomicUsize::new(0);
+
+        for _ in 0..2 {
+            let value: Vec<u16> = cache
+                .get_or_compute("key", Duration::from_secs(60), || async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Ok(vec![1, 2])
+                })
+                .await
+                .unwrap();
+
+            assert_eq!(value, [1, 2]);
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.keys(), ["key"]);
+    }
+
+    #[cfg(feature = "openai")]
+    #[tokio::test]
+    async fn test_fake_llm_client() {
+        use crate::images::generate_alt_text;
+        use crate::sync::LanguageTag;
+
+        let client = FakeLlmClient::new(["A chest X-ray."]);
+        let png = [0x89, b'P', b'N', b'G', 0x0D];
+
+        let alt_text = generate_alt_text(&png, &LanguageTag::default(), &client)
+            .await
+            .unwrap();
+
+        assert_eq!(alt_text, "A chest X-ray.");
+        assert_eq!(client.requests().len(), 1);
+        assert!(generate_alt_text(&png, &LanguageTag::default(), &client)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::Result;
+use fake::{Dummy, Fake, Faker};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::sync::{
+    AchievementData, BundleData, CaseData, CourseData, ExplanationData, FlashcardData,
+    GlossaryTermData, IconData, LearningPathData, QuestionData, QuestionOptionData,
+    QuestionSourceData, QuestionTopicData,
+};
+use crate::traits::Syncable;
+
+pub const ROUNDTRIP_ATTEMPTS: usize = 100;
+
+pub trait Processable {
+    fn run_process(&mut self) -> Result<()>;
+
+    /// Copies what serde skips, like the children of a parent entity, from `original`.
+    fn restore_skipped(&mut self, _original: &Self) {}
+}
+
+macro_rules! impl_processable {
+    ($($data:ty),* $(,)?) => {
+        $(
+            impl Processable for $data {
+                fn run_process(&mut self) -> Result<()> {
+                    <$data>::process(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_processable! {
+    AchievementData,
+    BundleData,
+    CaseData,
+    ExplanationData,
+    FlashcardData,
+    GlossaryTermData,
+    IconData,
+    LearningPathData,
+    QuestionOptionData,
+    QuestionSourceData,
+    QuestionTopicData,
+}
+
+impl Processable for CourseData {
+    fn run_process(&mut self) -> Result<()> {
+        self.process()
+    }
+
+    fn restore_skipped(&mut self, original: &Self) {
+        self.questions = original.questions.clone();
+        self.valid_topics = original.valid_topics.clone();
+    }
+}
+
+impl Processable for QuestionData {
+    fn run_process(&mut self) -> Result<()> {
+        self.prepare_for_test()
+    }
+
+    fn restore_skipped(&mut self, original: &Self) {
+        self.question_options = original.question_options.clone();
+    }
+}
+
+/// Fakes a processed `T` and checks that serde JSON round-trips it, and that the
+/// hash stored before serializing matches the one recomputed after deserializing
+/// and processing again, which must change nothing else. Fields serde skips are
+/// restored before processing again.
+pub fn assert_roundtrip<T>()
+where
+    T: Dummy<Faker> + Processable + Syncable + Serialize + DeserializeOwned,
+{
+    let data = (0..ROUNDTRIP_ATTEMPTS)
+        .find_map(|_| {
+            let mut data: T = Faker.fake();
+
+            data.run_process().ok().map(|_| data)
+        })
+        .unwrap_or_else(|| {
+            panic!(
+                "no valid {} in {ROUNDTRIP_ATTEMPTS} attempts",
+                std::any::type_name::<T>()
+            )
+        });
+
+    let stored_hash = data.content_hash().into_owned();
+    let json = serde_json::to_value(&data).expect("failed to serialize");
+    let mut deserialized: T = serde_json::from_value(json.clone()).expect("failed to deserialize");
+
+    // Compared as JSON since serde skipped fields don't round-trip.
+    assert_eq!(serde_json::to_value(&deserialized).unwrap(), json);
+    assert_eq!(deserialized.content_hash(), stored_hash);
+
+    deserialized.restore_skipped(&data);
+    deserialized.run_process().expect("processing again failed");
+
+    assert_eq!(serde_json::to_value(&deserialized).unwrap(), json);
+    assert_eq!(deserialized.compute_hash(), stored_hash);
+    assert_eq!(deserialized.content_hash(), stored_hash);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assert_roundtrip() {
+        assert_roundtrip::<AchievementData>();
+        assert_roundtrip::<BundleData>();
+        assert_roundtrip::<CaseData>();
+        assert_roundtrip::<CourseData>();
+        assert_roundtrip::<FlashcardData>();
+        assert_roundtrip::<GlossaryTermData>();
+        assert_roundtrip::<IconData>();
+        assert_roundtrip::<LearningPathData>();
+        assert_roundtrip::<QuestionData>();
+        assert_roundtrip::<QuestionSourceData>();
+        assert_roundtrip::<QuestionTopicData>();
+    }
+}
@@ -1,3 +1,6 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::hash::Hash;
 use std::path::PathBuf;
 
 use chrono::{DateTime, Utc};
@@ -25,6 +28,19 @@ pub trait Hashable {
     }
 }
 
+pub trait Syncable: Hashable + Eq + Hash + Clone {
+    type Key: Eq + Hash + Clone;
+
+    fn sync_key(&self) -> Self::Key;
+
+    fn content_hash(&self) -> Cow<'_, str> {
+        match self.stored_hash() {
+            Some(hash) => Cow::Borrowed(hash),
+            None => Cow::Owned(self.compute_hash()),
+        }
+    }
+}
+
 impl Hashable for String {
     fn to_bytes(&self) -> Vec<u8> {
         self.as_bytes().into()
@@ -55,6 +71,20 @@ impl<T: Hashable> Hashable for Vec<T> {
     }
 }
 
+impl<A: Hashable, B: Hashable> Hashable for (A, B) {
+    fn to_bytes(&self) -> Vec<u8> {
+        [self.0.to_bytes(), self.1.to_bytes()].concat()
+    }
+}
+
+impl<K: Hashable, V: Hashable> Hashable for BTreeMap<K, V> {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.iter()
+            .flat_map(|(key, value)| [key.to_bytes(), value.to_bytes()].concat())
+            .collect()
+    }
+}
+
 impl Hashable for Decimal {
     fn to_bytes(&self) -> Vec<u8> {
         self.to_string().to_bytes()
@@ -67,6 +97,12 @@ impl Hashable for u16 {
     }
 }
 
+impl Hashable for u32 {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_le_bytes().into()
+    }
+}
+
 impl Hashable for bool {
     fn to_bytes(&self) -> Vec<u8> {
         vec![*self as u8]
@@ -86,6 +122,7 @@ pub trait EmailTemplate: Serialize + Sized {
         serde_json::to_string(self).expect("failed to serialize template data")
     }
 
+    #[cfg(feature = "aws")]
     fn email_content(self) -> aws_sdk_sesv2::types::EmailContent {
         let template = aws_sdk_sesv2::types::Template::builder()
             .template_name(Self::TEMPLATE_NAME)
@@ -97,3 +134,41 @@ pub trait EmailTemplate: Serialize + Sized {
             .build()
     }
 }
+
+/// Row type of a table, implemented with `medici_macros::Table`.
+#[cfg(feature = "db")]
+pub trait Table {
+    type PrimaryKey;
+
+    const TABLE_NAME: &'static str;
+    const PRIMARY_KEY_COLUMN: &'static str;
+
+    fn primary_key(&self) -> &Self::PrimaryKey;
+}
+
+/// Values for a new row of `T`, implemented with `medici_macros::Insertable`.
+#[cfg(feature = "db")]
+pub trait Insertable<const N: usize> {
+    type T: Table;
+
+    const COLUMNS: [&'static str; N];
+
+    fn bind(
+        self,
+        separated: &mut sqlx::query_builder::Separated<'_, '_, sqlx::Postgres, &'static str>,
+    );
+}
+
+/// Partial update of a row of `T`, implemented with `medici_macros::Changeset`.
+/// Only the fields that are `Some` are bound.
+#[cfg(feature = "db")]
+pub trait Changeset<const N: usize> {
+    type T: Table;
+
+    const COLUMNS: [&'static str; N];
+
+    fn bind(
+        self,
+        separated: &mut sqlx::query_builder::Separated<'_, '_, sqlx::Postgres, &'static str>,
+    );
+}
@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Result};
+use async_openai::types::{
+    ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
+    CreateChatCompletionRequestArgs, ResponseFormat,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::LlmClient;
+use crate::sync::{LanguageTag, QuestionData, TranslatedQuestion};
+
+pub const TRANSLATION_MODEL: &str = "gpt-4o";
+
+pub type Glossary = BTreeMap<String, String>;
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct TranslationCache(BTreeMap<LanguageTag, HashMap<String, TranslatedQuestion>>);
+
+impl TranslationCache {
+    pub fn get(&self, source_hash: &str, locale: &LanguageTag) -> Option<&TranslatedQuestion> {
+        self.0.get(locale)?.get(source_hash)
+    }
+
+    pub fn insert(
+        &mut self,
+        source_hash: String,
+        locale: LanguageTag,
+        translation: TranslatedQuestion,
+    ) {
+        self.0
+            .entry(locale)
+            .or_default()
+            .insert(source_hash, translation);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.values().map(HashMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct TranslationPayload {
+    text: String,
+    question_options: Vec<String>,
+    explanation: Option<String>,
+}
+
+impl TranslationPayload {
+    fn from_question(question: &QuestionData) -> Self {
+        let mut question_options = question.question_options.iter().collect::<Vec<_>>();
+        question_options.sort_by_key(|question_option| question_option.reference);
+
+        Self {
+            text: question.text.clone(),
+            question_options: question_options
+                .into_iter()
+                .map(|question_option| question_option.text.clone())
+                .collect(),
+            explanation: question
+                .explanation
+                .as_ref()
+                .map(|explanation| explanation.text.clone()),
+        }
+    }
+}
+
+pub fn source_hash(question: &QuestionData) -> String {
+    let payload = serde_json::to_vec(&TranslationPayload::from_question(question))
+        .expect("failed to serialize translation payload");
+
+    blake3::hash(&payload).to_string()
+}
+
+pub async fn translate_question(
+    question: &QuestionData,
+    target_locale: &LanguageTag,
+    glossary: &Glossary,
+    client: &impl LlmClient,
+) -> Result<TranslatedQuestion> {
+    let payload = serde_json::to_string(&TranslationPayload::from_question(question))?;
+
+    let request = CreateChatCompletionRequestArgs::default()
+        .model(TRANSLATION_MODEL)
+        .response_format(ResponseFormat::JsonObject)
+        .messages([
+            ChatCompletionRequestSystemMessageArgs::default()
+                .content(system_prompt(target_locale, glossary))
+                .build()?
+                .into(),
+            ChatCompletionRequestUserMessageArgs::default()
+                .content(payload)
+                .build()?
+                .into(),
+        ])
+        .build()?;
+
+    let response = client.chat_completion(request).await?;
+    let translated: TranslationPayload = serde_json::from_str(&response)?;
+
+    if translated.question_options.len() != question.question_options.len() {
+        bail!(
+            "translation of question with ID {} has {} option(s)",
+            question.id,
+            translated.question_options.len()
+        );
+    }
+
+    TranslatedQuestion::new(
+        translated.text,
+        translated.question_options,
+        translated.explanation,
+    )
+}
+
+pub async fn translate_questions(
+    questions: &mut [QuestionData],
+    target_locale: &LanguageTag,
+    glossary: &Glossary,
+    cache: &mut TranslationCache,
+    client: &impl LlmClient,
+) -> Result<usize> {
+    let mut translated_count = 0;
+
+    for question in questions.iter_mut().filter(|question| !question.is_blank()) {
+        let source_hash = source_hash(question);
+
+        let translation = match cache.get(&source_hash, target_locale) {
+            Some(translation) => translation.clone(),
+            None => {
+                let translation =
+                    translate_question(question, target_locale, glossary, client).await?;
+
+                cache.insert(source_hash, target_locale.clone(), translation.clone());
+                translated_count += 1;
+
+                translation
+            }
+        };
+
+        question
+            .translations
+            .insert(target_locale.clone(), translation);
+        question.process()?;
+    }
+
+    Ok(translated_count)
+}
+
+fn system_prompt(target_locale: &LanguageTag, glossary: &Glossary) -> String {
+    let glossary_lines = glossary
+        .iter()
+        .fold(String::new(), |acc, (term, translation)| {
+            format!("{acc}\n- {term}: {translation}")
+        });
+
+    let mut prompt = format!(
+        "Translate the medical exam question in the JSON object sent by the user to the \
+        language with tag {target_locale}. Reply with a JSON object with the same keys, keeping \
+        the order of question_options and any null values."
+    );
+
+    if !glossary_lines.is_empty() {
+        prompt.push_str(&format!(
+            "\n\nAlways translate these terms as indicated:{glossary_lines}"
+        ));
+    }
+
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+
+    use super::*;
+
+    #[test]
+    fn test_source_hash() {
+        let mut question: QuestionData = Faker.fake();
+        question.prepare_for_test().unwrap();
+
+        let hash = source_hash(&question);
+
+        question.translations.insert(
+            LanguageTag::new("pt").unwrap(),
+            TranslatedQuestion::new(
+                "Texto".into(),
+                vec!["A".into(); question.question_options.len()],
+                None,
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(source_hash(&question), hash);
+
+        question.text.push_str(" changed");
+
+        assert_ne!(source_hash(&question), hash);
+    }
+
+    #[test]
+    fn test_cache() {
+        let mut cache = TranslationCache::default();
+        let locale = LanguageTag::new("pt").unwrap();
+        let translation = TranslatedQuestion::new("Texto".into(), vec![], None).unwrap();
+
+        cache.insert("hash".into(), locale.clone(), translation.clone());
+
+        assert_eq!(cache.get("hash", &locale), Some(&translation));
+        assert_eq!(cache.get("hash", &LanguageTag::default()), None);
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -0,0 +1,177 @@
+use anyhow::Result;
+use async_openai::types::{
+    ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
+    CreateChatCompletionRequestArgs, ResponseFormat,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::helpers::LlmClient;
+use crate::render::{self, OptionLabels, RenderOptions};
+use crate::sync::QuestionData;
+
+pub const VERIFICATION_MODEL: &str = "gpt-4o";
+
+const LABELS: OptionLabels = OptionLabels::LowerAlpha;
+
+/// Questions whose marked answer the model disagrees with.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+pub struct VerificationReport {
+    pub checked_count: usize,
+    pub disagreements: Vec<AnswerDisagreement>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct AnswerDisagreement {
+    pub question_id: Uuid,
+    /// Label of the option marked as correct, e.g. `b`.
+    pub marked_option: String,
+    /// Label of the model's answer, or `None` if it didn't match any option.
+    pub model_option: Option<String>,
+    /// From 0 to 100, as reported by the model.
+    pub confidence: u8,
+    pub reasoning: String,
+}
+
+impl VerificationReport {
+    pub fn is_clean(&self) -> bool {
+        self.disagreements.is_empty()
+    }
+
+    /// Disagreements the model is at least `min_confidence` sure about, to review first.
+    pub fn confident(&self, min_confidence: u8) -> Vec<&AnswerDisagreement> {
+        self.disagreements
+            .iter()
+            .filter(|disagreement| disagreement.confidence >= min_confidence)
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct VerificationPayload {
+    answer: String,
+    confidence: u8,
+    reasoning: String,
+}
+
+/// Asks the model to answer each question without seeing the marked answer.
+/// Blank questions and questions without a correct option are skipped.
+pub async fn verify_answers(
+    questions: &[QuestionData],
+    client: &impl LlmClient,
+) -> Result<VerificationReport> {
+    let mut report = VerificationReport::default();
+
+    for question in questions.iter().filter(|question| !question.is_blank()) {
+        let Some(correct_option) = question
+            .question_options
+            .iter()
+            .find(|question_option| question_option.is_correct)
+        else {
+            continue;
+        };
+
+        let payload = verify_answer(question, client).await?;
+        let answer = payload.answer.trim().trim_end_matches(['.', ')']);
+        let model_option = question
+            .question_options
+            .iter()
+            .map(|question_option| LABELS.label(question_option.reference))
+            .find(|label| label.eq_ignore_ascii_case(answer));
+        let marked_option = LABELS.label(correct_option.reference);
+
+        report.checked_count += 1;
+
+        if model_option.as_ref() != Some(&marked_option) {
+            report.disagreements.push(AnswerDisagreement {
+                question_id: question.id,
+                marked_option,
+                model_option,
+                confidence: payload.confidence.min(100),
+                reasoning: payload.reasoning,
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+async fn verify_answer(
+    question: &QuestionData,
+    client: &impl LlmClient,
+) -> Result<VerificationPayload> {
+    let request = CreateChatCompletionRequestArgs::default()
+        .model(VERIFICATION_MODEL)
+        .response_format(ResponseFormat::JsonObject)
+        .messages([
+            ChatCompletionRequestSystemMessageArgs::default()
+                .content(
+                    "Answer the multiple choice medical exam question sent by the user. Reply \
+                    with a JSON object with the keys answer (the letter of the single best \
+                    option), confidence (an integer from 0 to 100) and reasoning (a brief \
+                    justification in the language of the question).",
+                )
+                .build()?
+                .into(),
+            ChatCompletionRequestUserMessageArgs::default()
+                .content(render::to_text(
+                    question,
+                    &RenderOptions {
+                        labels: LABELS,
+                        ..Default::default()
+                    },
+                ))
+                .build()?
+                .into(),
+        ])
+        .build()?;
+
+    let response = client.chat_completion(request).await?;
+
+    Ok(serde_json::from_str(&response)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FakeLlmClient;
+
+    #[tokio::test]
+    async fn test_verify_answers() {
+        let mut questions = fake::vec![QuestionData; 2];
+
+        for question in &mut questions {
+            question.prepare_for_test().unwrap();
+        }
+
+        let marked = |question: &QuestionData| {
+            let correct_option = question
+                .question_options
+                .iter()
+                .find(|question_option| question_option.is_correct)
+                .unwrap();
+
+            LABELS.label(correct_option.reference)
+        };
+        let response = |answer: &str| {
+            serde_json::json!({
+                "answer": answer,
+                "confidence": 90,
+                "reasoning": "Por descarte."
+            })
+            .to_string()
+        };
+        let client = FakeLlmClient::new([
+            response(&marked(&questions[0]).to_uppercase()),
+            response("z"),
+        ]);
+
+        let report = verify_answers(&questions, &client).await.unwrap();
+
+        assert_eq!(report.checked_count, 2);
+        assert_eq!(report.disagreements.len(), 1);
+        assert_eq!(report.disagreements[0].question_id, questions[1].id);
+        assert_eq!(report.disagreements[0].model_option, None);
+        assert_eq!(report.confident(95).len(), 0);
+    }
+}
@@ -0,0 +1,343 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use hmac::{Hmac, Mac};
+use http::HeaderMap;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use sha2::Sha256;
+
+pub const MERCADOPAGO_SIGNATURE_HEADER: &str = "x-signature";
+pub const MERCADOPAGO_REQUEST_ID_HEADER: &str = "x-request-id";
+pub const SIGNATURE_TOLERANCE: TimeDelta = TimeDelta::minutes(5);
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum WebhookError {
+    MissingHeader(&'static str),
+    InvalidSignatureHeader,
+    InvalidSignature,
+    Expired,
+    InvalidPayload(String),
+}
+
+impl std::fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingHeader(header) => write!(f, "missing webhook header {header}"),
+            Self::InvalidSignatureHeader => write!(f, "malformed webhook signature header"),
+            Self::InvalidSignature => write!(f, "invalid webhook signature"),
+            Self::Expired => write!(f, "webhook signature timestamp out of tolerance"),
+            Self::InvalidPayload(error) => write!(f, "invalid webhook payload: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for WebhookError {}
+
+/// Checks a hex encoded HMAC-SHA256 signature of `message` in constant time.
+pub fn verify_hmac_sha256(
+    secret: &[u8],
+    message: &[u8],
+    signature_hex: &str,
+) -> Result<(), WebhookError> {
+    let signature =
+        hex::decode(signature_hex.trim()).map_err(|_| WebhookError::InvalidSignatureHeader)?;
+
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(message);
+
+    mac.verify_slice(&signature)
+        .map_err(|_| WebhookError::InvalidSignature)
+}
+
+pub fn sign_hmac_sha256(secret: &[u8], message: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(message);
+
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Verifies a body signed with HMAC-SHA256 in `signature_header`, optionally
+/// prefixed with `sha256=`, and deserializes it into the event type.
+pub fn verify_hmac<T: DeserializeOwned>(
+    headers: &HeaderMap,
+    signature_header: &'static str,
+    body: &[u8],
+    secret: &str,
+) -> Result<T, WebhookError> {
+    let signature = header_str(headers, signature_header)?;
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+
+    verify_hmac_sha256(secret.as_bytes(), body, signature)?;
+
+    parse_payload(body)
+}
+
+pub fn verify_mercadopago(
+    headers: &HeaderMap,
+    body: &[u8],
+    secret: &str,
+) -> Result<MercadoPagoNotification, WebhookError> {
+    verify_mercadopago_at(headers, body, secret, Utc::now())
+}
+
+/// Verifies the `x-signature` header against the manifest Mercado Pago signs,
+/// `id:{data.id};request-id:{x-request-id};ts:{ts};`, and rejects signatures
+/// older than [`SIGNATURE_TOLERANCE`] to prevent replays.
+pub fn verify_mercadopago_at(
+    headers: &HeaderMap,
+    body: &[u8],
+    secret: &str,
+    now: DateTime<Utc>,
+) -> Result<MercadoPagoNotification, WebhookError> {
+    let signature =
+        MercadoPagoSignature::parse(header_str(headers, MERCADOPAGO_SIGNATURE_HEADER)?)?;
+    let request_id = header_str(headers, MERCADOPAGO_REQUEST_ID_HEADER)?;
+
+    let notification: MercadoPagoNotification = parse_payload(body)?;
+
+    verify_hmac_sha256(
+        secret.as_bytes(),
+        notification.manifest(request_id, &signature.ts).as_bytes(),
+        &signature.v1,
+    )?;
+
+    let signed_at = signature
+        .signed_at()
+        .ok_or(WebhookError::InvalidSignatureHeader)?;
+
+    if (now - signed_at).abs() > SIGNATURE_TOLERANCE {
+        return Err(WebhookError::Expired);
+    }
+
+    Ok(notification)
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &'static str) -> Result<&'a str, WebhookError> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .ok_or(WebhookError::MissingHeader(name))
+}
+
+fn parse_payload<T: DeserializeOwned>(body: &[u8]) -> Result<T, WebhookError> {
+    serde_json::from_slice(body).map_err(|error| WebhookError::InvalidPayload(error.to_string()))
+}
+
+struct MercadoPagoSignature {
+    ts: String,
+    v1: String,
+}
+
+impl MercadoPagoSignature {
+    fn parse(header: &str) -> Result<Self, WebhookError> {
+        let mut ts = None;
+        let mut v1 = None;
+
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("ts", value)) => ts = Some(value.trim().to_string()),
+                Some(("v1", value)) => v1 = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+
+        match (ts, v1) {
+            (Some(ts), Some(v1)) if !ts.is_empty() && !v1.is_empty() => Ok(Self { ts, v1 }),
+            _ => Err(WebhookError::InvalidSignatureHeader),
+        }
+    }
+
+    /// Mercado Pago has sent the timestamp both in seconds and in milliseconds.
+    fn signed_at(&self) -> Option<DateTime<Utc>> {
+        let ts = self.ts.parse::<i64>().ok()?;
+
+        if ts > 10_000_000_000 {
+            DateTime::from_timestamp_millis(ts)
+        } else {
+            DateTime::from_timestamp(ts, 0)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct MercadoPagoNotification {
+    #[serde(deserialize_with = "deserialize_id")]
+    pub id: String,
+    #[serde(default)]
+    pub live_mode: bool,
+    pub r#type: MercadoPagoTopic,
+    pub action: String,
+    pub date_created: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_optional_id")]
+    pub user_id: Option<String>,
+    pub api_version: Option<String>,
+    pub data: MercadoPagoResource,
+}
+
+impl MercadoPagoNotification {
+    /// Alphanumeric resource IDs are signed in lowercase.
+    fn manifest(&self, request_id: &str, ts: &str) -> String {
+        let data_id = if self.data.id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            self.data.id.to_ascii_lowercase()
+        } else {
+            self.data.id.clone()
+        };
+
+        format!("id:{data_id};request-id:{request_id};ts:{ts};")
+    }
+
+    pub fn payment_id(&self) -> Option<&str> {
+        (self.r#type == MercadoPagoTopic::Payment).then_some(self.data.id.as_str())
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct MercadoPagoResource {
+    #[serde(deserialize_with = "deserialize_id")]
+    pub id: String,
+}
+
+#[derive(strum::Display, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum MercadoPagoTopic {
+    Payment,
+    MerchantOrder,
+    SubscriptionPreapproval,
+    SubscriptionAuthorizedPayment,
+    #[serde(other)]
+    Other,
+}
+
+/// Payment resource fetched from `/v1/payments/{id}` after a notification.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct MercadoPagoPayment {
+    pub id: u64,
+    pub status: MercadoPagoPaymentStatus,
+    pub status_detail: Option<String>,
+    pub transaction_amount: Decimal,
+    pub currency_id: String,
+    pub external_reference: Option<String>,
+    pub date_created: Option<DateTime<Utc>>,
+    pub date_approved: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub live_mode: bool,
+    pub payer: Option<MercadoPagoPayer>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct MercadoPagoPayer {
+    pub email: Option<String>,
+}
+
+#[derive(strum::Display, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum MercadoPagoPaymentStatus {
+    Pending,
+    Approved,
+    Authorized,
+    InProcess,
+    InMediation,
+    Rejected,
+    Cancelled,
+    Refunded,
+    ChargedBack,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Id {
+    Number(u64),
+    Text(String),
+}
+
+impl From<Id> for String {
+    fn from(value: Id) -> Self {
+        match value {
+            Id::Number(id) => id.to_string(),
+            Id::Text(id) => id,
+        }
+    }
+}
+
+/// Mercado Pago sends some IDs as numbers and others as strings.
+fn deserialize_id<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Id::deserialize(deserializer).map(String::from)
+}
+
+fn deserialize_optional_id<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<Id>::deserialize(deserializer)?.map(String::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = r#"{"action":"payment.updated","api_version":"v1","data":{"id":"123456"},"date_created":"2024-01-10T17:33:30Z","id":12345,"live_mode":true,"type":"payment","user_id":"44332211"}"#;
+
+    fn headers(signature: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(MERCADOPAGO_SIGNATURE_HEADER, signature.parse().unwrap());
+        headers.insert(MERCADOPAGO_REQUEST_ID_HEADER, "req-1".parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_verify_mercadopago() {
+        let now = DateTime::from_timestamp(1_704_908_010, 0).unwrap();
+        let v1 = sign_hmac_sha256(b"secret", b"id:123456;request-id:req-1;ts:1704908010;");
+        let headers = headers(&format!("ts=1704908010,v1={v1}"));
+
+        let notification = verify_mercadopago_at(&headers, BODY.as_bytes(), "secret", now).unwrap();
+
+        assert_eq!(notification.id, "12345");
+        assert_eq!(notification.payment_id(), Some("123456"));
+
+        assert_eq!(
+            verify_mercadopago_at(&headers, BODY.as_bytes(), "other", now),
+            Err(WebhookError::InvalidSignature)
+        );
+        assert_eq!(
+            verify_mercadopago_at(
+                &headers,
+                BODY.as_bytes(),
+                "secret",
+                now + TimeDelta::hours(1)
+            ),
+            Err(WebhookError::Expired)
+        );
+    }
+
+    #[test]
+    fn test_verify_hmac() {
+        let signature = sign_hmac_sha256(b"secret", BODY.as_bytes());
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-hub-signature-256",
+            format!("sha256={signature}").parse().unwrap(),
+        );
+
+        let notification: MercadoPagoNotification =
+            verify_hmac(&headers, "x-hub-signature-256", BODY.as_bytes(), "secret").unwrap();
+
+        assert_eq!(notification.r#type, MercadoPagoTopic::Payment);
+        assert_eq!(
+            verify_hmac::<MercadoPagoNotification>(
+                &headers,
+                "x-signature",
+                BODY.as_bytes(),
+                "secret"
+            ),
+            Err(WebhookError::MissingHeader("x-signature"))
+        );
+    }
+}